@@ -0,0 +1,228 @@
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Column, Line, Point};
+use alacritty_terminal::term::cell::{Cell, Flags, LineLength};
+use alacritty_terminal::term::Term;
+use alacritty_terminal::vte::ansi::{Color, NamedColor};
+
+/// Rows of the terminal to capture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureRegion {
+    /// The rows currently on screen, following the scrollback position
+    Screen,
+    /// The whole scrollback history plus the screen
+    Scrollback,
+}
+
+/// First and last line of `region`.
+fn region_lines<T>(term: &Term<T>, region: CaptureRegion) -> (Line, Line) {
+    match region {
+        CaptureRegion::Screen => {
+            let offset = term.grid().display_offset() as i32;
+            (Line(-offset), Line(term.screen_lines() as i32 - 1 - offset))
+        },
+        CaptureRegion::Scrollback => (term.topmost_line(), term.bottommost_line()),
+    }
+}
+
+/// Text of `region`, with SGR sequences when `ansi` is set.
+pub(super) fn text<T>(term: &Term<T>, region: CaptureRegion, ansi: bool) -> String {
+    if ansi {
+        ansi_text(term, region)
+    } else {
+        plain_text(term, region)
+    }
+}
+
+/// Plain text of `region`, wrapped lines joined.
+fn plain_text<T>(term: &Term<T>, region: CaptureRegion) -> String {
+    let (top, bottom) = region_lines(term, region);
+    term.bounds_to_string(
+        Point::new(top, Column(0)),
+        Point::new(bottom, term.last_column()),
+    )
+}
+
+/// Text of each row of the live screen (not following the scrollback position), top
+/// to bottom. Unlike captures, wrapped lines stay split into their rows.
+pub(super) fn screen_rows<T>(term: &Term<T>) -> Vec<String> {
+    (0..term.screen_lines() as i32)
+        .map(|line| {
+            let text = term.bounds_to_string(
+                Point::new(Line(line), Column(0)),
+                Point::new(Line(line), term.last_column()),
+            );
+            text.trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Cursor position on the live screen as (line, column).
+pub(super) fn cursor_position<T>(term: &Term<T>) -> (usize, usize) {
+    let point = term.grid().cursor.point;
+    (point.line.0.max(0) as usize, point.column.0)
+}
+
+/// Text of `region` with SGR escape sequences reproducing colors and attributes.
+fn ansi_text<T>(term: &Term<T>, region: CaptureRegion) -> String {
+    let (top, bottom) = region_lines(term, region);
+    let grid = term.grid();
+    let mut text = String::new();
+
+    for line in (top.0..=bottom.0).map(Line) {
+        let row = &grid[line];
+        let mut style: Option<Style> = None;
+
+        for column in (0..row.line_length().0).map(Column) {
+            let cell = &row[column];
+            if cell
+                .flags
+                .intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+            {
+                continue;
+            }
+
+            let cell_style = Style::of(cell);
+            if style != Some(cell_style) {
+                text.push_str(&cell_style.sgr());
+                style = Some(cell_style);
+            }
+
+            text.push(cell.c);
+            for c in cell.zerowidth().into_iter().flatten() {
+                text.push(*c);
+            }
+        }
+
+        if style.is_some_and(|s| s != Style::default()) {
+            text.push_str("\x1b[0m");
+        }
+        let wrapped = row[term.last_column()].flags.contains(Flags::WRAPLINE);
+        if !wrapped && line < bottom {
+            text.push('\n');
+        }
+    }
+
+    text
+}
+
+/// Visual attributes of a cell that map to SGR parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Style {
+    fg: Color,
+    bg: Color,
+    flags: Flags,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            fg: Color::Named(NamedColor::Foreground),
+            bg: Color::Named(NamedColor::Background),
+            flags: Flags::empty(),
+        }
+    }
+}
+
+impl Style {
+    /// Flags that affect rendering (wide-char and wrap markers excluded).
+    const VISUAL_FLAGS: Flags = Flags::INVERSE
+        .union(Flags::BOLD)
+        .union(Flags::ITALIC)
+        .union(Flags::DIM)
+        .union(Flags::HIDDEN)
+        .union(Flags::STRIKEOUT)
+        .union(Flags::ALL_UNDERLINES);
+
+    fn of(cell: &Cell) -> Self {
+        Self {
+            fg: cell.fg,
+            bg: cell.bg,
+            flags: cell.flags & Self::VISUAL_FLAGS,
+        }
+    }
+
+    /// Escape sequence switching from any state to this style.
+    fn sgr(&self) -> String {
+        let mut params = vec!["0".to_string()];
+        for (flag, param) in [
+            (Flags::BOLD, "1"),
+            (Flags::DIM, "2"),
+            (Flags::ITALIC, "3"),
+            (Flags::INVERSE, "7"),
+            (Flags::HIDDEN, "8"),
+            (Flags::STRIKEOUT, "9"),
+        ] {
+            if self.flags.contains(flag) {
+                params.push(param.to_string());
+            }
+        }
+        if self.flags.intersects(Flags::ALL_UNDERLINES) {
+            params.push("4".to_string());
+        }
+        params.extend(color_param(self.fg, 30));
+        params.extend(color_param(self.bg, 40));
+        format!("\x1b[{}m", params.join(";"))
+    }
+}
+
+/// SGR parameter for `color` (`base` is 30 for foreground, 40 for background).
+/// Returns `None` for the default color.
+fn color_param(color: Color, base: u8) -> Option<String> {
+    match color {
+        Color::Spec(rgb) => Some(format!("{};2;{};{};{}", base + 8, rgb.r, rgb.g, rgb.b)),
+        Color::Indexed(index) => Some(format!("{};5;{}", base + 8, index)),
+        Color::Named(named) => {
+            let index = match named {
+                n if (n as usize) < 8 => n as u8,
+                n if (n as usize) < 16 => return Some((base + 60 + n as u8 - 8).to_string()),
+                NamedColor::DimBlack => 0,
+                NamedColor::DimRed => 1,
+                NamedColor::DimGreen => 2,
+                NamedColor::DimYellow => 3,
+                NamedColor::DimBlue => 4,
+                NamedColor::DimMagenta => 5,
+                NamedColor::DimCyan => 6,
+                NamedColor::DimWhite => 7,
+                _ => return None,
+            };
+            Some((base + index).to_string())
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{color_param, Style};
+    use alacritty_terminal::term::cell::Flags;
+    use alacritty_terminal::vte::ansi::{Color, NamedColor, Rgb};
+
+    #[test]
+    fn default_style_resets_only() {
+        assert_eq!(Style::default().sgr(), "\x1b[0m");
+    }
+
+    #[test]
+    fn named_colors() {
+        assert_eq!(color_param(Color::Named(NamedColor::Red), 30), Some("31".into()));
+        assert_eq!(color_param(Color::Named(NamedColor::BrightBlue), 40), Some("104".into()));
+        assert_eq!(color_param(Color::Named(NamedColor::DimGreen), 30), Some("32".into()));
+        assert_eq!(color_param(Color::Named(NamedColor::Foreground), 30), None);
+    }
+
+    #[test]
+    fn indexed_and_rgb_colors() {
+        assert_eq!(color_param(Color::Indexed(208), 30), Some("38;5;208".into()));
+        let rgb = Rgb { r: 1, g: 2, b: 3 };
+        assert_eq!(color_param(Color::Spec(rgb), 40), Some("48;2;1;2;3".into()));
+    }
+
+    #[test]
+    fn attributes_and_colors_combined() {
+        let style = Style {
+            fg: Color::Named(NamedColor::Green),
+            bg: Color::Named(NamedColor::Background),
+            flags: Flags::BOLD | Flags::DOUBLE_UNDERLINE,
+        };
+        assert_eq!(style.sgr(), "\x1b[0;1;4;32m");
+    }
+}
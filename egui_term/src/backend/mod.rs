@@ -1,5 +1,11 @@
+mod capture;
+mod repaint;
+mod search;
 pub mod settings;
+mod stats;
+mod stub;
 
+use crate::theme::TerminalTheme;
 use crate::types::Size;
 use alacritty_terminal::event::{
     Event, EventListener, Notify, OnResize, WindowSize,
@@ -15,15 +21,26 @@ use alacritty_terminal::term::search::{Match, RegexIter, RegexSearch};
 use alacritty_terminal::term::{
     self, cell::Cell, test::TermSize, viewport_to_point, Term, TermMode,
 };
+use alacritty_terminal::vte::ansi::Rgb;
 use alacritty_terminal::{tty, Grid};
 use egui::Modifiers;
+pub use capture::CaptureRegion;
+use repaint::RepaintThrottle;
+use search::Search;
 use settings::BackendSettings;
+use stats::IoCounters;
+pub use stats::IoStats;
+pub use stub::StubBackend;
 use std::borrow::Cow;
 use std::cmp::min;
 use std::io::Result;
 use std::ops::{Index, RangeInclusive};
+#[cfg(not(windows))]
+use std::os::fd::{BorrowedFd, OwnedFd};
 use std::sync::mpsc::Sender;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{mpsc, Arc};
+use std::time::Duration;
 
 pub type TerminalMode = TermMode;
 pub type PtyEvent = Event;
@@ -40,6 +57,16 @@ pub enum BackendCommand {
     MouseReport(MouseButton, Modifiers, Point, bool),
 }
 
+/// Where the view is in the scrollback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrollbackPosition {
+    /// Lines scrolled up from the bottom (0 = live output)
+    pub offset: usize,
+    /// Lines of scrollback above the screen
+    pub history: usize,
+    pub screen_lines: usize,
+}
+
 #[derive(Debug, Clone)]
 pub enum MouseMode {
     Sgr,
@@ -100,6 +127,29 @@ impl Default for TerminalSize {
     }
 }
 
+impl TerminalSize {
+    /// Size with cells of `font_size` fitted into `layout_size`, if that differs
+    /// from this one and at least one cell fits.
+    fn resized(&self, layout_size: Size, font_size: Size) -> Option<Self> {
+        if layout_size == self.layout_size
+            && font_size.width as u16 == self.cell_width
+            && font_size.height as u16 == self.cell_height
+        {
+            return None;
+        }
+
+        let lines = (layout_size.height / font_size.height.floor()) as u16;
+        let cols = (layout_size.width / font_size.width.floor()) as u16;
+        (lines > 0 && cols > 0).then_some(Self {
+            layout_size,
+            cell_height: font_size.height as u16,
+            cell_width: font_size.width as u16,
+            num_lines: lines,
+            num_cols: cols,
+        })
+    }
+}
+
 impl Dimensions for TerminalSize {
     fn total_lines(&self) -> usize {
         self.screen_lines()
@@ -133,6 +183,120 @@ impl From<TerminalSize> for WindowSize {
     }
 }
 
+/// What the view and the app need from a terminal engine. `TerminalBackend`
+/// runs a shell on a PTY; `StubBackend` plays scripted output without one.
+///
+/// Engines take the app's `Sender<(u64, PtyEvent)>` when created and send
+/// `(id(), event)` on it: `Wakeup` when there is new output to `sync()`, plus
+/// title, bell, clipboard and exit events as they happen.
+pub trait TerminalBackendTrait {
+    fn id(&self) -> u64;
+
+    fn process_command(&mut self, cmd: BackendCommand);
+
+    /// Copy the terminal's current state into the content drawn by the view.
+    fn sync(&mut self) -> &RenderableContent;
+
+    fn last_content(&self) -> &RenderableContent;
+
+    fn selectable_content(&self) -> String {
+        let content = self.last_content();
+        let mut result = String::new();
+        if let Some(range) = content.selectable_range {
+            for indexed in content.grid.display_iter() {
+                if range.contains(indexed.point) {
+                    result.push(indexed.c);
+                }
+            }
+        }
+        result
+    }
+
+    /// Text of the active selection, if any.
+    fn selection_text(&self) -> Option<String>;
+
+    /// Scrollback position of the content last synced.
+    fn scrollback_position(&self) -> ScrollbackPosition {
+        let grid = &self.last_content().grid;
+        ScrollbackPosition {
+            offset: grid.display_offset(),
+            history: grid.history_size(),
+            screen_lines: grid.screen_lines(),
+        }
+    }
+
+    /// Text of `region`, with SGR sequences for colors and attributes when `ansi` is set.
+    fn capture_text(&self, region: CaptureRegion, ansi: bool) -> String;
+
+    /// Text of each row of the live screen, top to bottom, ignoring the scrollback
+    /// position (wrapped lines are not joined).
+    fn screen_rows(&self) -> Vec<String>;
+
+    /// Cursor position on the live screen as (line, column), from the top left.
+    fn cursor_position(&self) -> (usize, usize);
+
+    /// Search the scrollback for `query` (plain text, case-insensitive unless it
+    /// has an uppercase letter) and scroll to the nearest match above the viewport
+    /// bottom. Matches are highlighted until the search is cleared; an empty
+    /// query clears it. Returns whether anything matched.
+    fn search(&mut self, query: &str) -> bool;
+
+    /// Jump to the next search match towards older (`older`) or newer output,
+    /// wrapping around. Returns whether anything matched.
+    fn search_next(&mut self, older: bool) -> bool;
+
+    /// End the scrollback search and remove its highlights.
+    fn clear_search(&mut self);
+
+    /// Enable or disable URL detection under the pointer (disabled, nothing is scanned
+    /// and no link is highlighted or opened).
+    fn set_link_detection(&mut self, _enabled: bool) {}
+
+    /// Answer a color query (OSC 4/10/11/12) with the color `theme` draws for `index`.
+    /// `format` is the reply builder carried by the `ColorRequest` event.
+    fn answer_color_query(
+        &self,
+        _theme: &TerminalTheme,
+        _index: usize,
+        _format: &dyn Fn(Rgb) -> String,
+    ) {
+    }
+
+    /// Process ID of the child running in the terminal, if there is one.
+    fn pty_id(&self) -> Option<u32> {
+        None
+    }
+
+    /// PTY master file descriptor, if there is one.
+    /// Used for session persistence.
+    #[cfg(not(windows))]
+    fn pty_fd(&self) -> Option<i32> {
+        None
+    }
+
+    /// Stop driving the PTY without hanging up its child, handing back a descriptor for
+    /// its master and the child's pid so another terminal can take it over.
+    #[cfg(not(windows))]
+    fn detach(&mut self) -> Option<(OwnedFd, u32)> {
+        None
+    }
+
+    /// Stop or resume reading output.
+    /// While paused, output is left in the kernel buffer and the producer eventually blocks.
+    fn set_paused(&self, _paused: bool) {}
+
+    /// Limit repaints caused by output to one per `interval` (zero repaints on every update).
+    fn set_repaint_interval(&self, _interval: Duration) {}
+
+    /// Keep `lines` lines of scrollback, dropping the oldest when shrinking.
+    fn set_scrollback_lines(&self, _lines: usize) {}
+
+    /// Bytes read and estimated input-to-echo latency, for diagnostics.
+    fn io_stats(&self) -> IoStats {
+        IoStats::default()
+    }
+}
+
 pub struct TerminalBackend {
     id: u64,
     pty_id: u32,
@@ -142,6 +306,14 @@ pub struct TerminalBackend {
     size: TerminalSize,
     notifier: Notifier,
     last_content: RenderableContent,
+    /// Minimum time between output-driven repaints, shared with the event thread
+    repaint_interval_ms: Arc<AtomicU64>,
+    /// Read and echo-latency counters, shared with the PTY threads
+    io: IoCounters,
+    /// Whether hovering with the command modifier scans for URLs
+    link_detection: bool,
+    /// Active scrollback search, if any
+    search: Option<Search>,
 }
 
 impl TerminalBackend {
@@ -157,7 +329,10 @@ impl TerminalBackend {
             env: settings.env,
             ..tty::Options::default()
         };
-        let config = term::Config::default();
+        let config = term::Config {
+            scrolling_history: settings.scrollback_lines,
+            ..term::Config::default()
+        };
         let terminal_size = TerminalSize::default();
         let pty = tty::new(&pty_config, terminal_size.into(), id)?;
         #[cfg(not(windows))]
@@ -185,6 +360,8 @@ impl TerminalBackend {
             terminal_size,
             cursor: term.grid_mut().cursor_cell().clone(),
             hovered_hyperlink: None,
+            search_matches: Vec::new(),
+            focused_search_match: None,
         };
         let term = Arc::new(FairMutex::new(term));
         let pty_event_loop =
@@ -192,7 +369,11 @@ impl TerminalBackend {
         let notifier = Notifier(pty_event_loop.channel());
         let pty_notifier = Notifier(pty_event_loop.channel());
         let url_regex = RegexSearch::new(r#"(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|https://|http://|news:|file://|git://|ssh:|ftp://)[^\u{0000}-\u{001F}\u{007F}-\u{009F}<>"\s{-}\^⟨⟩`]+"#).unwrap();
+        let io = IoCounters::new(pty_event_loop.bytes_read());
+        let echo = io.echo.clone();
         let _pty_event_loop_thread = pty_event_loop.spawn();
+        let repaint_interval_ms = Arc::new(AtomicU64::new(0));
+        let mut repaint = RepaintThrottle::new(app_context, repaint_interval_ms.clone());
         let _pty_event_subscription = std::thread::Builder::new()
             .name(format!("pty_event_subscription_{}", id))
             .spawn(move || loop {
@@ -205,7 +386,8 @@ impl TerminalBackend {
                     // Only request repaint for visual events (Wakeup = grid changed)
                     // Non-visual events (Title, WorkingDirectory, etc.) don't need immediate repaint
                     if matches!(event, Event::Wakeup) {
-                        app_context.request_repaint();
+                        echo.on_output();
+                        repaint.on_wakeup();
                     }
                     match event {
                         Event::Exit => break,
@@ -220,42 +402,17 @@ impl TerminalBackend {
             pty_id,
             pty_fd,
             url_regex,
+            link_detection: true,
+            search: None,
             term: term.clone(),
             size: terminal_size,
             notifier,
             last_content: initial_content,
+            repaint_interval_ms,
+            io,
         })
     }
 
-    pub fn process_command(&mut self, cmd: BackendCommand) {
-        let term = self.term.clone();
-        let mut term = term.lock();
-        match cmd {
-            BackendCommand::Write(input) => {
-                self.write(input);
-                term.scroll_display(Scroll::Bottom);
-            },
-            BackendCommand::Scroll(delta) => {
-                self.scroll(&mut term, delta);
-            },
-            BackendCommand::Resize(layout_size, font_size) => {
-                self.resize(&mut term, layout_size, font_size);
-            },
-            BackendCommand::SelectStart(selection_type, x, y) => {
-                self.start_selection(&mut term, selection_type, x, y);
-            },
-            BackendCommand::SelectUpdate(x, y) => {
-                self.update_selection(&mut term, x, y);
-            },
-            BackendCommand::ProcessLink(link_action, point) => {
-                self.process_link_action(&term, link_action, point);
-            },
-            BackendCommand::MouseReport(button, modifiers, point, pressed) => {
-                self.process_mouse_report(button, modifiers, point, pressed);
-            },
-        };
-    }
-
     pub fn selection_point(
         x: f32,
         y: f32,
@@ -271,55 +428,6 @@ impl TerminalBackend {
         viewport_to_point(display_offset, Point::new(line, col))
     }
 
-    pub fn selectable_content(&self) -> String {
-        let content = self.last_content();
-        let mut result = String::new();
-        if let Some(range) = content.selectable_range {
-            for indexed in content.grid.display_iter() {
-                if range.contains(indexed.point) {
-                    result.push(indexed.c);
-                }
-            }
-        }
-        result
-    }
-
-    pub fn sync(&mut self) -> &RenderableContent {
-        let term = self.term.clone();
-        let mut terminal = term.lock();
-        let selectable_range = match &terminal.selection {
-            Some(s) => s.to_range(&terminal),
-            None => None,
-        };
-
-        let cursor = terminal.grid_mut().cursor_cell().clone();
-        self.last_content.grid = terminal.grid().clone();
-        self.last_content.selectable_range = selectable_range;
-        self.last_content.cursor = cursor.clone();
-        self.last_content.terminal_mode = *terminal.mode();
-        self.last_content.terminal_size = self.size;
-        self.last_content()
-    }
-
-    pub fn last_content(&self) -> &RenderableContent {
-        &self.last_content
-    }
-
-    pub fn id(&self) -> u64 {
-        self.id
-    }
-
-    pub fn pty_id(&self) -> u32 {
-        self.pty_id
-    }
-
-    /// Get the PTY master file descriptor.
-    /// Used for session persistence.
-    #[cfg(not(windows))]
-    pub fn pty_fd(&self) -> i32 {
-        self.pty_fd
-    }
-
     /// Restore a terminal backend from an existing PTY file descriptor.
     /// Used for session restore after exec.
     ///
@@ -333,9 +441,13 @@ impl TerminalBackend {
         pty_id: u32,
         app_context: egui::Context,
         pty_event_proxy_sender: Sender<(u64, PtyEvent)>,
+        scrollback_lines: usize,
     ) -> Result<Self> {
         let pty = unsafe { tty::from_raw_fd(pty_fd, pty_id)? };
-        let config = term::Config::default();
+        let config = term::Config {
+            scrolling_history: scrollback_lines,
+            ..term::Config::default()
+        };
         let terminal_size = TerminalSize::default();
         let (event_sender, event_receiver) = mpsc::channel();
         let event_proxy = EventProxy(event_sender);
@@ -347,6 +459,8 @@ impl TerminalBackend {
             terminal_size,
             cursor: term.grid_mut().cursor_cell().clone(),
             hovered_hyperlink: None,
+            search_matches: Vec::new(),
+            focused_search_match: None,
         };
         let term = Arc::new(FairMutex::new(term));
         let pty_event_loop =
@@ -354,7 +468,11 @@ impl TerminalBackend {
         let notifier = Notifier(pty_event_loop.channel());
         let pty_notifier = Notifier(pty_event_loop.channel());
         let url_regex = RegexSearch::new(r#"(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|https://|http://|news:|file://|git://|ssh:|ftp://)[^\u{0000}-\u{001F}\u{007F}-\u{009F}<>"\s{-}\^⟨⟩`]+"#).unwrap();
+        let io = IoCounters::new(pty_event_loop.bytes_read());
+        let echo = io.echo.clone();
         let _pty_event_loop_thread = pty_event_loop.spawn();
+        let repaint_interval_ms = Arc::new(AtomicU64::new(0));
+        let mut repaint = RepaintThrottle::new(app_context, repaint_interval_ms.clone());
         let _pty_event_subscription = std::thread::Builder::new()
             .name(format!("pty_event_subscription_{}", id))
             .spawn(move || loop {
@@ -365,7 +483,8 @@ impl TerminalBackend {
                             panic!("pty_event_subscription_{}: sending PtyEvent is failed", id)
                         });
                     if matches!(event, Event::Wakeup) {
-                        app_context.request_repaint();
+                        echo.on_output();
+                        repaint.on_wakeup();
                     }
                     match event {
                         Event::Exit => break,
@@ -380,10 +499,14 @@ impl TerminalBackend {
             pty_id,
             pty_fd,
             url_regex,
+            link_detection: true,
+            search: None,
             term: term.clone(),
             size: terminal_size,
             notifier,
             last_content: initial_content,
+            repaint_interval_ms,
+            io,
         })
     }
 
@@ -394,6 +517,9 @@ impl TerminalBackend {
         point: Point,
     ) {
         match link_action {
+            LinkAction::Hover if !self.link_detection => {
+                self.last_content.hovered_hyperlink = None;
+            },
             LinkAction::Hover => {
                 self.last_content.hovered_hyperlink = self.regex_match_at(
                     terminal,
@@ -511,79 +637,18 @@ impl TerminalBackend {
         self.notifier.notify(msg);
     }
 
-    fn start_selection(
-        &mut self,
-        terminal: &mut Term<EventProxy>,
-        selection_type: SelectionType,
-        x: f32,
-        y: f32,
-    ) {
-        let location = Self::selection_point(
-            x,
-            y,
-            &self.size,
-            terminal.grid().display_offset(),
-        );
-        terminal.selection = Some(Selection::new(
-            selection_type,
-            location,
-            self.selection_side(x),
-        ));
-    }
-
-    fn update_selection(
-        &mut self,
-        terminal: &mut Term<EventProxy>,
-        x: f32,
-        y: f32,
-    ) {
-        let display_offset = terminal.grid().display_offset();
-        if let Some(ref mut selection) = terminal.selection {
-            let location =
-                Self::selection_point(x, y, &self.size, display_offset);
-            selection.update(location, self.selection_side(x));
-        }
-    }
-
-    fn selection_side(&self, x: f32) -> Side {
-        let cell_x = x as usize % self.size.cell_width as usize;
-        let half_cell_width = (self.size.cell_width as f32 / 2.0) as usize;
-
-        if cell_x > half_cell_width {
-            Side::Right
-        } else {
-            Side::Left
-        }
-    }
-
     fn resize(
         &mut self,
         terminal: &mut Term<EventProxy>,
         layout_size: Size,
         font_size: Size,
     ) {
-        if layout_size == self.size.layout_size
-            && font_size.width as u16 == self.size.cell_width
-            && font_size.height as u16 == self.size.cell_height
-        {
-            return;
-        }
-
-        let lines = (layout_size.height / font_size.height.floor()) as u16;
-        let cols = (layout_size.width / font_size.width.floor()) as u16;
-        if lines > 0 && cols > 0 {
-            self.size = TerminalSize {
-                layout_size,
-                cell_height: font_size.height as u16,
-                cell_width: font_size.width as u16,
-                num_lines: lines,
-                num_cols: cols,
-            };
-
-            self.notifier.on_resize(self.size.into());
+        if let Some(size) = self.size.resized(layout_size, font_size) {
+            self.size = size;
+            self.notifier.on_resize(size.into());
             terminal.resize(TermSize::new(
-                self.size.num_cols as usize,
-                self.size.num_lines as usize,
+                size.num_cols as usize,
+                size.num_lines as usize,
             ));
         }
     }
@@ -629,6 +694,226 @@ impl TerminalBackend {
     }
 }
 
+impl TerminalBackendTrait for TerminalBackend {
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn process_command(&mut self, cmd: BackendCommand) {
+        let term = self.term.clone();
+        let mut term = term.lock();
+        match cmd {
+            BackendCommand::Write(input) => {
+                self.io.echo.on_write();
+                self.write(input);
+                term.scroll_display(Scroll::Bottom);
+            },
+            BackendCommand::Scroll(delta) => {
+                self.scroll(&mut term, delta);
+            },
+            BackendCommand::Resize(layout_size, font_size) => {
+                self.resize(&mut term, layout_size, font_size);
+            },
+            BackendCommand::SelectStart(selection_type, x, y) => {
+                start_selection(&mut term, &self.size, selection_type, x, y);
+            },
+            BackendCommand::SelectUpdate(x, y) => {
+                update_selection(&mut term, &self.size, x, y);
+            },
+            BackendCommand::ProcessLink(link_action, point) => {
+                self.process_link_action(&term, link_action, point);
+            },
+            BackendCommand::MouseReport(button, modifiers, point, pressed) => {
+                self.process_mouse_report(button, modifiers, point, pressed);
+            },
+        };
+    }
+
+    fn sync(&mut self) -> &RenderableContent {
+        let term = self.term.clone();
+        sync_content(
+            &mut self.last_content,
+            &mut term.lock(),
+            self.size,
+            &mut self.search,
+        );
+        &self.last_content
+    }
+
+    fn last_content(&self) -> &RenderableContent {
+        &self.last_content
+    }
+
+    fn selection_text(&self) -> Option<String> {
+        self.term
+            .lock()
+            .selection_to_string()
+            .filter(|text| !text.is_empty())
+    }
+
+    fn capture_text(&self, region: CaptureRegion, ansi: bool) -> String {
+        capture::text(&self.term.lock(), region, ansi)
+    }
+
+    fn screen_rows(&self) -> Vec<String> {
+        capture::screen_rows(&self.term.lock())
+    }
+
+    fn cursor_position(&self) -> (usize, usize) {
+        capture::cursor_position(&self.term.lock())
+    }
+
+    fn search(&mut self, query: &str) -> bool {
+        search::set_query(&mut self.search, &mut self.term.lock(), query)
+    }
+
+    fn search_next(&mut self, older: bool) -> bool {
+        let Some(search) = &mut self.search else {
+            return false;
+        };
+        search.next(&mut self.term.lock(), older)
+    }
+
+    fn clear_search(&mut self) {
+        self.search = None;
+    }
+
+    fn set_link_detection(&mut self, enabled: bool) {
+        self.link_detection = enabled;
+        if !enabled {
+            self.last_content.hovered_hyperlink = None;
+        }
+    }
+
+    fn answer_color_query(
+        &self,
+        theme: &TerminalTheme,
+        index: usize,
+        format: &dyn Fn(Rgb) -> String,
+    ) {
+        if let Some(color) = theme.query_color(index) {
+            let reply = format(Rgb {
+                r: color.r(),
+                g: color.g(),
+                b: color.b(),
+            });
+            self.write(reply.into_bytes());
+        }
+    }
+
+    fn pty_id(&self) -> Option<u32> {
+        Some(self.pty_id)
+    }
+
+    #[cfg(not(windows))]
+    fn pty_fd(&self) -> Option<i32> {
+        Some(self.pty_fd)
+    }
+
+    #[cfg(not(windows))]
+    fn detach(&mut self) -> Option<(OwnedFd, u32)> {
+        // Our own descriptor closes with the event loop's PTY
+        let fd = unsafe { BorrowedFd::borrow_raw(self.pty_fd) }
+            .try_clone_to_owned()
+            .ok()?;
+        let _ = self.notifier.0.send(Msg::Detach);
+        Some((fd, self.pty_id))
+    }
+
+    fn set_paused(&self, paused: bool) {
+        let _ = self.notifier.0.send(Msg::SetPaused(paused));
+    }
+
+    fn set_repaint_interval(&self, interval: Duration) {
+        self.repaint_interval_ms
+            .store(interval.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    fn set_scrollback_lines(&self, lines: usize) {
+        self.term.lock().set_options(term::Config {
+            scrolling_history: lines,
+            ..term::Config::default()
+        });
+    }
+
+    fn io_stats(&self) -> IoStats {
+        self.io.snapshot()
+    }
+}
+
+/// Copy `terminal`'s state into `content`, with the visible matches of `search`.
+fn sync_content(
+    content: &mut RenderableContent,
+    terminal: &mut Term<EventProxy>,
+    size: TerminalSize,
+    search: &mut Option<Search>,
+) {
+    content.selectable_range = match &terminal.selection {
+        Some(s) => s.to_range(terminal),
+        None => None,
+    };
+    content.cursor = terminal.grid_mut().cursor_cell().clone();
+    content.grid = terminal.grid().clone();
+    content.terminal_mode = *terminal.mode();
+    content.terminal_size = size;
+    match search {
+        Some(search) => {
+            content.search_matches =
+                visible_regex_match_iter(terminal, &mut search.regex).collect();
+            content.focused_search_match = search.focused.clone();
+        },
+        None => {
+            content.search_matches.clear();
+            content.focused_search_match = None;
+        },
+    }
+}
+
+fn start_selection(
+    terminal: &mut Term<EventProxy>,
+    size: &TerminalSize,
+    selection_type: SelectionType,
+    x: f32,
+    y: f32,
+) {
+    let location = TerminalBackend::selection_point(
+        x,
+        y,
+        size,
+        terminal.grid().display_offset(),
+    );
+    terminal.selection = Some(Selection::new(
+        selection_type,
+        location,
+        selection_side(size, x),
+    ));
+}
+
+fn update_selection(
+    terminal: &mut Term<EventProxy>,
+    size: &TerminalSize,
+    x: f32,
+    y: f32,
+) {
+    let display_offset = terminal.grid().display_offset();
+    if let Some(ref mut selection) = terminal.selection {
+        let location =
+            TerminalBackend::selection_point(x, y, size, display_offset);
+        selection.update(location, selection_side(size, x));
+    }
+}
+
+fn selection_side(size: &TerminalSize, x: f32) -> Side {
+    let cell_x = x as usize % size.cell_width as usize;
+    let half_cell_width = (size.cell_width as f32 / 2.0) as usize;
+
+    if cell_x > half_cell_width {
+        Side::Right
+    } else {
+        Side::Left
+    }
+}
+
 /// Copied from alacritty/src/display/hint.rs:
 /// Iterate over all visible regex matches.
 fn visible_regex_match_iter<'a>(
@@ -651,6 +936,10 @@ fn visible_regex_match_iter<'a>(
 pub struct RenderableContent {
     pub grid: Grid<Cell>,
     pub hovered_hyperlink: Option<RangeInclusive<Point>>,
+    /// Search matches on screen (or just off it)
+    pub search_matches: Vec<RangeInclusive<Point>>,
+    /// The match the search last jumped to
+    pub focused_search_match: Option<RangeInclusive<Point>>,
     pub selectable_range: Option<SelectionRange>,
     pub cursor: Cell,
     pub terminal_mode: TermMode,
@@ -662,6 +951,8 @@ impl Default for RenderableContent {
         Self {
             grid: Grid::new(0, 0, 0),
             hovered_hyperlink: None,
+            search_matches: Vec::new(),
+            focused_search_match: None,
             selectable_range: None,
             cursor: Cell::default(),
             terminal_mode: TermMode::empty(),
@@ -0,0 +1,40 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Coalesces the repaints requested for PTY output.
+///
+/// Output bursts produce a `Wakeup` per read; repainting on every one of them
+/// burns CPU without making anything visible sooner. At most one immediate
+/// repaint is requested per interval; wakeups inside the interval schedule a
+/// repaint at its end so the final output is always drawn.
+pub(super) struct RepaintThrottle {
+    ctx: egui::Context,
+    interval_ms: Arc<AtomicU64>,
+    last_repaint: Option<Instant>,
+}
+
+impl RepaintThrottle {
+    pub(super) fn new(ctx: egui::Context, interval_ms: Arc<AtomicU64>) -> Self {
+        Self {
+            ctx,
+            interval_ms,
+            last_repaint: None,
+        }
+    }
+
+    pub(super) fn on_wakeup(&mut self) {
+        let interval =
+            Duration::from_millis(self.interval_ms.load(Ordering::Relaxed));
+        let now = Instant::now();
+        match self.last_repaint.map(|last| now.duration_since(last)) {
+            Some(elapsed) if elapsed < interval => {
+                self.ctx.request_repaint_after(interval - elapsed);
+            },
+            _ => {
+                self.ctx.request_repaint();
+                self.last_repaint = Some(now);
+            },
+        }
+    }
+}
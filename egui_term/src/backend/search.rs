@@ -0,0 +1,163 @@
+use alacritty_terminal::event::EventListener;
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Boundary, Direction, Line, Point, Side};
+use alacritty_terminal::term::search::{Match, RegexSearch};
+use alacritty_terminal::term::Term;
+
+/// An active scrollback search.
+pub(super) struct Search {
+    pub(super) regex: RegexSearch,
+    /// Where the search started (bottom right of the viewport), kept while the
+    /// query is edited so refining it stays near the same spot
+    origin: Point,
+    /// Match last jumped to
+    pub(super) focused: Option<Match>,
+}
+
+impl Search {
+    /// Search for `query` as plain text, case-insensitive unless it contains an
+    /// uppercase letter. `None` for an empty query.
+    pub(super) fn new(query: &str, origin: Point) -> Option<Self> {
+        if query.is_empty() {
+            return None;
+        }
+        let regex = RegexSearch::new(&literal_pattern(query)).ok()?;
+        Some(Self {
+            regex,
+            origin,
+            focused: None,
+        })
+    }
+
+    /// Jump to the next match towards older output (`older`) or newer output,
+    /// wrapping around the scrollback. The first jump goes to the nearest match
+    /// above the origin. Returns whether anything matched.
+    pub(super) fn next<T: EventListener>(
+        &mut self,
+        term: &mut Term<T>,
+        older: bool,
+    ) -> bool {
+        let direction = if older {
+            Direction::Left
+        } else {
+            Direction::Right
+        };
+        let origin = match &self.focused {
+            None => self.origin.grid_clamp(term, Boundary::Grid),
+            Some(focused) if older => focused
+                .start()
+                .grid_clamp(term, Boundary::Grid)
+                .sub(term, Boundary::None, 1),
+            Some(focused) => focused
+                .end()
+                .grid_clamp(term, Boundary::Grid)
+                .add(term, Boundary::None, 1),
+        };
+        let side = if older { Side::Right } else { Side::Left };
+
+        self.focused =
+            term.search_next(&mut self.regex, origin, direction, side, None);
+        if let Some(focused) = &self.focused {
+            term.scroll_to_point(*focused.start());
+        }
+        self.focused.is_some()
+    }
+}
+
+/// Replace the query of `search` (keeping its origin) and jump to the nearest
+/// match. An empty query ends the search. Returns whether anything matched.
+pub(super) fn set_query<T: EventListener>(
+    search: &mut Option<Search>,
+    term: &mut Term<T>,
+    query: &str,
+) -> bool {
+    let origin = search
+        .as_ref()
+        .map(|search| search.origin)
+        .unwrap_or_else(|| viewport_end(term));
+    *search = Search::new(query, origin);
+    match search {
+        Some(search) => search.next(term, true),
+        None => false,
+    }
+}
+
+/// Bottom right cell of the viewport.
+fn viewport_end<T>(term: &Term<T>) -> Point {
+    let offset = term.grid().display_offset() as i32;
+    Point::new(
+        Line(term.screen_lines() as i32 - 1 - offset),
+        term.last_column(),
+    )
+}
+
+/// Regex pattern matching `query` literally.
+fn literal_pattern(query: &str) -> String {
+    let mut pattern = String::with_capacity(query.len());
+    for c in query.chars() {
+        if r"\.+*?()|[]{}^$#&-~".contains(c) {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alacritty_terminal::event::VoidListener;
+    use alacritty_terminal::index::Column;
+    use alacritty_terminal::term::test::TermSize;
+    use alacritty_terminal::term::Config;
+    use alacritty_terminal::vte::ansi::Processor;
+
+    fn term(text: &str) -> Term<VoidListener> {
+        let size = TermSize::new(20, 4);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser: Processor = Processor::new();
+        parser.advance(&mut term, text.replace('\n', "\r\n").as_bytes());
+        term
+    }
+
+    #[test]
+    fn escapes_regex_syntax() {
+        assert_eq!(literal_pattern("a.b"), r"a\.b");
+        assert_eq!(literal_pattern("f(x) [1]"), r"f\(x\) \[1\]");
+        assert!(RegexSearch::new(&literal_pattern("(*+?")).is_ok());
+    }
+
+    #[test]
+    fn walks_matches_from_newest_and_wraps() {
+        let mut term = term("foo 1\nbar\nfoo 2\nbaz\nfoo 3\n");
+        let mut search = Search::new("foo", viewport_end(&term)).unwrap();
+        let lines =
+            |search: &Search| search.focused.as_ref().map(|m| m.start().line);
+
+        assert!(search.next(&mut term, true));
+        let newest = lines(&search).unwrap();
+        assert!(search.next(&mut term, true));
+        assert_eq!(lines(&search), Some(newest - 2));
+        assert!(search.next(&mut term, false));
+        assert_eq!(lines(&search), Some(newest));
+        assert!(search.next(&mut term, false));
+        assert_eq!(lines(&search), Some(newest - 4));
+        assert_eq!(search.focused.unwrap().start().column, Column(0));
+    }
+
+    #[test]
+    fn scrolls_to_match_in_history() {
+        let mut term = term("needle\n1\n2\n3\n4\n5\n");
+        let mut search = Search::new("NEEDLE", viewport_end(&term)).unwrap();
+        assert!(!search.next(&mut term, true));
+
+        let mut search = Search::new("needle", viewport_end(&term)).unwrap();
+        assert!(search.next(&mut term, true));
+        assert!(term.grid().display_offset() > 0);
+    }
+
+    #[test]
+    fn empty_query_is_no_search() {
+        assert!(Search::new("", Point::default()).is_none());
+    }
+}
@@ -3,6 +3,9 @@ use std::path::PathBuf;
 
 const DEFAULT_SHELL: &str = "/bin/bash";
 
+/// Lines of scrollback kept by default (alacritty's default)
+pub const DEFAULT_SCROLLBACK_LINES: usize = 10_000;
+
 #[derive(Debug, Clone)]
 pub struct BackendSettings {
     pub shell: String,
@@ -10,6 +13,8 @@ pub struct BackendSettings {
     pub working_directory: Option<PathBuf>,
     /// Extra environment variables to set in the shell
     pub env: HashMap<String, String>,
+    /// Lines of output kept above the screen
+    pub scrollback_lines: usize,
 }
 
 impl Default for BackendSettings {
@@ -19,6 +24,7 @@ impl Default for BackendSettings {
             args: vec![],
             working_directory: None,
             env: HashMap::new(),
+            scrollback_lines: DEFAULT_SCROLLBACK_LINES,
         }
     }
 }
@@ -0,0 +1,131 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Echo latency samples kept for the median.
+const MAX_SAMPLES: usize = 32;
+
+/// Snapshot of a terminal's I/O counters, for diagnostics.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IoStats {
+    /// Total bytes read from the PTY
+    pub bytes_read: u64,
+    /// Median input-to-echo latency over recent writes
+    pub echo_latency: Option<Duration>,
+    /// Number of samples behind `echo_latency`
+    pub echo_samples: usize,
+    /// Time since the oldest write that no output has followed yet
+    pub unanswered_for: Option<Duration>,
+}
+
+/// Heuristic input-to-echo latency: the time from a write to the PTY until the
+/// next output wakeup. Writes made while one is still unanswered are folded
+/// into it, so a burst of keys yields one sample.
+#[derive(Default)]
+pub(super) struct EchoProbe {
+    state: Mutex<ProbeState>,
+}
+
+#[derive(Default)]
+struct ProbeState {
+    pending: Option<Instant>,
+    samples: VecDeque<Duration>,
+}
+
+impl EchoProbe {
+    pub(super) fn on_write(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.pending.get_or_insert_with(Instant::now);
+    }
+
+    pub(super) fn on_output(&self) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(since) = state.pending.take() {
+            if state.samples.len() == MAX_SAMPLES {
+                state.samples.pop_front();
+            }
+            state.samples.push_back(since.elapsed());
+        }
+    }
+
+    fn unanswered_for(&self) -> Option<Duration> {
+        let state = self.state.lock().unwrap();
+        state.pending.map(|since| since.elapsed())
+    }
+
+    fn median(&self) -> (Option<Duration>, usize) {
+        let state = self.state.lock().unwrap();
+        let mut samples: Vec<Duration> = state.samples.iter().copied().collect();
+        (median(&mut samples), samples.len())
+    }
+}
+
+/// Counters shared between the backend and its PTY threads.
+#[derive(Clone)]
+pub(super) struct IoCounters {
+    pub(super) bytes_read: Arc<AtomicU64>,
+    pub(super) echo: Arc<EchoProbe>,
+}
+
+impl IoCounters {
+    pub(super) fn new(bytes_read: Arc<AtomicU64>) -> Self {
+        Self {
+            bytes_read,
+            echo: Arc::new(EchoProbe::default()),
+        }
+    }
+
+    pub(super) fn snapshot(&self) -> IoStats {
+        let (echo_latency, echo_samples) = self.echo.median();
+        IoStats {
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            echo_latency,
+            echo_samples,
+            unanswered_for: self.echo.unanswered_for(),
+        }
+    }
+}
+
+fn median(samples: &mut [Duration]) -> Option<Duration> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_unstable();
+    Some(samples[samples.len() / 2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{median, EchoProbe};
+    use std::time::Duration;
+
+    #[test]
+    fn median_of_samples() {
+        let ms = Duration::from_millis;
+        assert_eq!(median(&mut []), None);
+        assert_eq!(median(&mut [ms(9), ms(1), ms(4)]), Some(ms(4)));
+    }
+
+    #[test]
+    fn output_without_write_is_not_a_sample() {
+        let probe = EchoProbe::default();
+        probe.on_output();
+        assert_eq!(probe.median().1, 0);
+        probe.on_write();
+        probe.on_write();
+        probe.on_output();
+        probe.on_output();
+        assert_eq!(probe.median().1, 1);
+    }
+
+    #[test]
+    fn write_is_unanswered_until_output() {
+        let probe = EchoProbe::default();
+        assert_eq!(probe.unanswered_for(), None);
+        probe.on_write();
+        assert!(probe.unanswered_for().is_some());
+        probe.on_output();
+        assert_eq!(probe.unanswered_for(), None);
+    }
+}
@@ -0,0 +1,226 @@
+use super::search::{self, Search};
+use super::{
+    capture, start_selection, sync_content, update_selection, BackendCommand,
+    CaptureRegion, EventProxy, PtyEvent, RenderableContent,
+    TerminalBackendTrait, TerminalSize,
+};
+use alacritty_terminal::event::Event;
+use alacritty_terminal::grid::Scroll;
+use alacritty_terminal::term::{self, test::TermSize, Term};
+use alacritty_terminal::vte::ansi::Processor;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// A terminal without a PTY or child process: it plays scripted output and
+/// records what is written to it, so UI and IPC tests don't spawn shells.
+/// Written input is echoed back, like a terminal in cooked mode.
+pub struct StubBackend {
+    id: u64,
+    term: Term<EventProxy>,
+    parser: Processor,
+    size: TerminalSize,
+    last_content: RenderableContent,
+    search: Option<Search>,
+    /// Events raised by the terminal while parsing (title, bell, ...)
+    term_events: Receiver<Event>,
+    pty_event_proxy_sender: Sender<(u64, PtyEvent)>,
+    app_context: egui::Context,
+    /// Everything written to the terminal, in order
+    input: Vec<u8>,
+}
+
+impl StubBackend {
+    /// A stub terminal that starts by printing `script`.
+    pub fn new(
+        id: u64,
+        app_context: egui::Context,
+        pty_event_proxy_sender: Sender<(u64, PtyEvent)>,
+        script: &[u8],
+    ) -> Self {
+        let (event_sender, term_events) = mpsc::channel();
+        let size = TerminalSize::default();
+        let term =
+            Term::new(term::Config::default(), &size, EventProxy(event_sender));
+        let mut backend = Self {
+            id,
+            term,
+            parser: Processor::new(),
+            size,
+            last_content: RenderableContent::default(),
+            search: None,
+            term_events,
+            pty_event_proxy_sender,
+            app_context,
+            input: Vec::new(),
+        };
+        backend.feed(script);
+        backend
+    }
+
+    /// Print `output` as if the program in the terminal had written it.
+    pub fn feed(&mut self, output: &[u8]) {
+        self.parser.advance(&mut self.term, output);
+        let _ = self.pty_event_proxy_sender.send((self.id, Event::Wakeup));
+        for event in self.term_events.try_iter() {
+            let _ = self.pty_event_proxy_sender.send((self.id, event));
+        }
+        self.app_context.request_repaint();
+    }
+
+    /// Everything written to the terminal so far.
+    pub fn input(&self) -> &[u8] {
+        &self.input
+    }
+}
+
+impl TerminalBackendTrait for StubBackend {
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn process_command(&mut self, cmd: BackendCommand) {
+        match cmd {
+            BackendCommand::Write(input) => {
+                self.input.extend_from_slice(&input);
+                let echo: Vec<u8> = input
+                    .iter()
+                    .flat_map(|&b| match b {
+                        b'\r' | b'\n' => b"\r\n".to_vec(),
+                        _ => vec![b],
+                    })
+                    .collect();
+                self.feed(&echo);
+                self.term.scroll_display(Scroll::Bottom);
+            },
+            BackendCommand::Scroll(delta) => {
+                self.term.scroll_display(Scroll::Delta(delta));
+            },
+            BackendCommand::Resize(layout_size, font_size) => {
+                if let Some(size) = self.size.resized(layout_size, font_size) {
+                    self.size = size;
+                    self.term.resize(TermSize::new(
+                        size.num_cols as usize,
+                        size.num_lines as usize,
+                    ));
+                }
+            },
+            BackendCommand::SelectStart(selection_type, x, y) => {
+                start_selection(
+                    &mut self.term,
+                    &self.size,
+                    selection_type,
+                    x,
+                    y,
+                );
+            },
+            BackendCommand::SelectUpdate(x, y) => {
+                update_selection(&mut self.term, &self.size, x, y);
+            },
+            // No links are detected and no program asks for mouse reports
+            BackendCommand::ProcessLink(..)
+            | BackendCommand::MouseReport(..) => {},
+        }
+    }
+
+    fn sync(&mut self) -> &RenderableContent {
+        sync_content(
+            &mut self.last_content,
+            &mut self.term,
+            self.size,
+            &mut self.search,
+        );
+        &self.last_content
+    }
+
+    fn last_content(&self) -> &RenderableContent {
+        &self.last_content
+    }
+
+    fn selection_text(&self) -> Option<String> {
+        self.term
+            .selection_to_string()
+            .filter(|text| !text.is_empty())
+    }
+
+    fn capture_text(&self, region: CaptureRegion, ansi: bool) -> String {
+        capture::text(&self.term, region, ansi)
+    }
+
+    fn screen_rows(&self) -> Vec<String> {
+        capture::screen_rows(&self.term)
+    }
+
+    fn cursor_position(&self) -> (usize, usize) {
+        capture::cursor_position(&self.term)
+    }
+
+    fn search(&mut self, query: &str) -> bool {
+        search::set_query(&mut self.search, &mut self.term, query)
+    }
+
+    fn search_next(&mut self, older: bool) -> bool {
+        let Some(search) = &mut self.search else {
+            return false;
+        };
+        search.next(&mut self.term, older)
+    }
+
+    fn clear_search(&mut self) {
+        self.search = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stub(script: &str) -> (StubBackend, Receiver<(u64, PtyEvent)>) {
+        let (sender, receiver) = mpsc::channel();
+        let backend = StubBackend::new(
+            7,
+            egui::Context::default(),
+            sender,
+            script.as_bytes(),
+        );
+        (backend, receiver)
+    }
+
+    #[test]
+    fn plays_script_and_forwards_events() {
+        let (backend, events) = stub("\x1b]0;stub title\x07hello\r\n$ ");
+        let text = backend.capture_text(CaptureRegion::Screen, false);
+        assert!(text.starts_with("hello\n$"));
+
+        let titles: Vec<_> = events
+            .try_iter()
+            .filter_map(|(id, event)| match event {
+                Event::Title(title) => Some((id, title)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(titles, vec![(7, "stub title".to_string())]);
+    }
+
+    #[test]
+    fn records_and_echoes_input() {
+        let (mut backend, _events) = stub("$ ");
+        backend.process_command(BackendCommand::Write(b"ls\r".to_vec()));
+        backend.process_command(BackendCommand::Write(b"pwd".to_vec()));
+
+        assert_eq!(backend.input(), b"ls\rpwd");
+        let text = backend.capture_text(CaptureRegion::Screen, false);
+        assert!(text.starts_with("$ ls\npwd"));
+        assert_eq!(backend.cursor_position(), (1, 3));
+        assert_eq!(backend.screen_rows()[..3], ["$ ls", "pwd", ""]);
+    }
+
+    #[test]
+    fn searches_output() {
+        let (mut backend, _events) = stub("one\r\ntwo\r\n");
+        assert!(backend.search("two"));
+        assert!(!backend.search("three"));
+        backend.search("o");
+        assert!(!backend.sync().search_matches.is_empty());
+        backend.clear_search();
+        assert!(backend.sync().search_matches.is_empty());
+    }
+}
@@ -1,13 +1,18 @@
 mod backend;
 mod bindings;
 mod font;
+mod paste;
 mod theme;
 mod types;
 mod view;
 
-pub use backend::settings::BackendSettings;
-pub use backend::{BackendCommand, PtyEvent, TerminalBackend, TerminalMode};
+pub use backend::settings::{BackendSettings, DEFAULT_SCROLLBACK_LINES};
+pub use backend::{
+    BackendCommand, CaptureRegion, IoStats, PtyEvent, ScrollbackPosition, StubBackend,
+    TerminalBackend, TerminalBackendTrait, TerminalMode,
+};
 pub use bindings::{Binding, BindingAction, InputKind, KeyboardBinding};
 pub use font::{FontSettings, TerminalFont};
+pub use paste::paste_bytes;
 pub use theme::{ColorPalette, TerminalTheme};
 pub use view::TerminalView;
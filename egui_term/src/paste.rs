@@ -0,0 +1,43 @@
+/// Start and end markers of a bracketed paste
+const PASTE_START: &[u8] = b"\x1b[200~";
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+/// Bytes written to the terminal for pasted `text`.
+///
+/// With bracketed paste (`bracketed`, DECSET 2004) the text is wrapped in the
+/// paste markers, with escapes and ^C removed so it can't end the paste early;
+/// otherwise line breaks are sent as carriage returns, like Enter.
+pub fn paste_bytes(text: &str, bracketed: bool) -> Vec<u8> {
+    if bracketed {
+        let text = text.replace(['\x1b', '\x03'], "");
+        [PASTE_START, text.as_bytes(), PASTE_END].concat()
+    } else {
+        text.replace("\r\n", "\r").replace('\n', "\r").into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_paste_sends_carriage_returns() {
+        assert_eq!(paste_bytes("ls\necho a\r\n", false), b"ls\recho a\r");
+    }
+
+    #[test]
+    fn bracketed_paste_wraps_text() {
+        assert_eq!(
+            paste_bytes("ls\nrm", true),
+            b"\x1b[200~ls\nrm\x1b[201~".to_vec()
+        );
+    }
+
+    #[test]
+    fn bracketed_paste_cannot_end_early() {
+        assert_eq!(
+            paste_bytes("a\x1b[201~b\x03", true),
+            b"\x1b[200~a[201~b\x1b[201~".to_vec()
+        );
+    }
+}
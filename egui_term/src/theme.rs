@@ -120,6 +120,26 @@ impl TerminalTheme {
         ansi256_colors
     }
 
+    /// Color reported for an OSC 4/10/11/12 query, by alacritty's color index
+    /// (0-255 for the indexed colors, then `NamedColor`).
+    pub fn query_color(&self, index: usize) -> Option<Color32> {
+        let color = match index {
+            0..=255 => ansi::Color::Indexed(index as u8),
+            i if i == NamedColor::Foreground as usize => {
+                ansi::Color::Named(NamedColor::Foreground)
+            },
+            i if i == NamedColor::Background as usize => {
+                ansi::Color::Named(NamedColor::Background)
+            },
+            // The cursor is drawn in the foreground color
+            i if i == NamedColor::Cursor as usize => {
+                ansi::Color::Named(NamedColor::Foreground)
+            },
+            _ => return None,
+        };
+        Some(self.get_color(color))
+    }
+
     pub fn get_color(&self, c: ansi::Color) -> Color32 {
         match c {
             ansi::Color::Spec(rgb) => Color32::from_rgb(rgb.r, rgb.g, rgb.b),
@@ -216,3 +236,25 @@ fn hex_to_color(hex: &str) -> anyhow::Result<Color32> {
 
     Ok(Color32::from_rgb(r, g, b))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_color_uses_palette() {
+        let theme = TerminalTheme::default();
+        let rgb = |c: Option<Color32>| c.map(|c| (c.r(), c.g(), c.b()));
+        assert_eq!(
+            rgb(theme.query_color(NamedColor::Background as usize)),
+            Some((0x18, 0x18, 0x18))
+        );
+        assert_eq!(
+            rgb(theme.query_color(NamedColor::Cursor as usize)),
+            Some((0xd8, 0xd8, 0xd8))
+        );
+        assert_eq!(rgb(theme.query_color(1)), Some((0xac, 0x42, 0x42)));
+        assert_eq!(rgb(theme.query_color(231)), Some((255, 255, 255)));
+        assert_eq!(theme.query_color(NamedColor::DimBlack as usize), None);
+    }
+}
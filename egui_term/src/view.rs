@@ -1,3 +1,4 @@
+use alacritty_terminal::grid::Dimensions;
 use alacritty_terminal::index::Point as TerminalGridPoint;
 use alacritty_terminal::term::cell;
 use alacritty_terminal::term::TermMode;
@@ -7,21 +8,27 @@ use egui::Modifiers;
 use egui::MouseWheelUnit;
 use egui::Shape;
 use egui::Widget;
-use egui::{Align2, Painter, Pos2, Rect, Response, Stroke, Vec2};
+use egui::{Align2, Color32, Painter, Pos2, Rect, Response, Stroke, Vec2};
 use egui::{CornerRadius, Key};
 use egui::{Id, PointerButton};
 
 use crate::backend::BackendCommand;
-use crate::backend::TerminalBackend;
+use crate::backend::{TerminalBackend, TerminalBackendTrait};
 use crate::backend::{LinkAction, MouseButton, SelectionType};
 use crate::bindings::Binding;
 use crate::bindings::{BindingAction, BindingsLayout, InputKind};
 use crate::font::TerminalFont;
+use crate::paste::paste_bytes;
 use crate::theme::TerminalTheme;
 use crate::types::Size;
 
 const EGUI_TERM_WIDGET_ID_PREFIX: &str = "egui_term::instance::";
 
+/// Search highlight colors (alacritty's defaults)
+const SEARCH_MATCH_FG: Color32 = Color32::from_rgb(0x18, 0x18, 0x18);
+const SEARCH_MATCH_BG: Color32 = Color32::from_rgb(0xac, 0x42, 0x42);
+const FOCUSED_SEARCH_MATCH_BG: Color32 = Color32::from_rgb(0xf4, 0xbf, 0x75);
+
 #[derive(Debug, Clone)]
 enum InputAction {
     BackendCall(BackendCommand),
@@ -41,10 +48,19 @@ pub struct TerminalView<'a> {
     widget_id: Id,
     has_focus: bool,
     size: Vec2,
-    backend: &'a mut TerminalBackend,
+    backend: &'a mut dyn TerminalBackendTrait,
     font: TerminalFont,
     theme: TerminalTheme,
     bindings_layout: BindingsLayout,
+    /// Receives a copy of the bytes typed or pasted into the terminal
+    input_mirror: Option<&'a mut Vec<u8>>,
+    /// Copy the selection to the clipboard when the mouse button is released
+    copy_on_select: bool,
+    /// Lines scrolled per wheel line (or per line height of touchpad motion)
+    scroll_multiplier: f32,
+    /// Send the wheel as arrow keys to full-screen programs (alternate screen)
+    /// that don't ask for mouse reports
+    alternate_scroll: bool,
 }
 
 impl Widget for TerminalView<'_> {
@@ -70,7 +86,10 @@ impl Widget for TerminalView<'_> {
 }
 
 impl<'a> TerminalView<'a> {
-    pub fn new(ui: &mut egui::Ui, backend: &'a mut TerminalBackend) -> Self {
+    pub fn new(
+        ui: &mut egui::Ui,
+        backend: &'a mut dyn TerminalBackendTrait,
+    ) -> Self {
         let widget_id = ui.make_persistent_id(format!(
             "{}{}",
             EGUI_TERM_WIDGET_ID_PREFIX,
@@ -85,6 +104,10 @@ impl<'a> TerminalView<'a> {
             font: TerminalFont::default(),
             theme: TerminalTheme::default(),
             bindings_layout: BindingsLayout::new(),
+            input_mirror: None,
+            copy_on_select: false,
+            scroll_multiplier: 1.0,
+            alternate_scroll: true,
         }
     }
 
@@ -112,6 +135,40 @@ impl<'a> TerminalView<'a> {
         self
     }
 
+    /// Append everything typed or pasted into the terminal to `mirror` as
+    /// well, e.g. to send the same input to other terminals. Mouse reports
+    /// and scrolling are not mirrored.
+    #[inline]
+    pub fn mirror_input(mut self, mirror: &'a mut Vec<u8>) -> Self {
+        self.input_mirror = Some(mirror);
+        self
+    }
+
+    /// Copy text to the clipboard as soon as it is selected with the mouse,
+    /// instead of waiting for a copy shortcut.
+    #[inline]
+    pub fn copy_on_select(mut self, enabled: bool) -> Self {
+        self.copy_on_select = enabled;
+        self
+    }
+
+    /// Scale mouse wheel and touchpad scrolling, in the scrollback and in
+    /// the scroll events sent to programs.
+    #[inline]
+    pub fn scroll_multiplier(mut self, multiplier: f32) -> Self {
+        self.scroll_multiplier = multiplier;
+        self
+    }
+
+    /// Whether the wheel sends arrow keys on the alternate screen (`less`,
+    /// `man`, editors without mouse support), unless the program turned it
+    /// off (DECRST 1007). On by default, like alacritty and xterm.
+    #[inline]
+    pub fn alternate_scroll(mut self, enabled: bool) -> Self {
+        self.alternate_scroll = enabled;
+        self
+    }
+
     #[inline]
     pub fn add_bindings(
         mut self,
@@ -141,7 +198,7 @@ impl<'a> TerminalView<'a> {
     }
 
     fn process_input(
-        self,
+        mut self,
         layout: &Response,
         state: &mut TerminalViewState,
     ) -> Self {
@@ -153,6 +210,26 @@ impl<'a> TerminalView<'a> {
         let events = layout.ctx.input(|i| i.events.clone());
         for event in events {
             let mut input_actions = vec![];
+            let is_keyboard = matches!(
+                event,
+                egui::Event::Text(_)
+                    | egui::Event::Key { .. }
+                    | egui::Event::Paste(_)
+            );
+            let selection_done = self.copy_on_select
+                && matches!(
+                    event,
+                    egui::Event::PointerButton {
+                        button: PointerButton::Primary,
+                        pressed: false,
+                        ..
+                    }
+                )
+                && !self
+                    .backend
+                    .last_content()
+                    .terminal_mode
+                    .intersects(TermMode::MOUSE_MODE);
 
             match event {
                 egui::Event::Text(_)
@@ -171,6 +248,10 @@ impl<'a> TerminalView<'a> {
                         state,
                         self.backend,
                         self.font.font_type().size,
+                        WheelSettings {
+                            multiplier: self.scroll_multiplier,
+                            alternate_scroll: self.alternate_scroll,
+                        },
                         unit,
                         delta,
                         modifiers,
@@ -206,10 +287,16 @@ impl<'a> TerminalView<'a> {
             for action in input_actions {
                 match action {
                     InputAction::BackendCall(cmd) => {
+                        if is_keyboard {
+                            self.mirror(&cmd);
+                        }
                         self.backend.process_command(cmd);
                     },
                     InputAction::BackendCalls(cmds) => {
                         for cmd in cmds {
+                            if is_keyboard {
+                                self.mirror(&cmd);
+                            }
                             self.backend.process_command(cmd);
                         }
                     },
@@ -219,11 +306,26 @@ impl<'a> TerminalView<'a> {
                     InputAction::Ignore => {},
                 }
             }
+
+            // A plain click leaves an empty selection, which keeps the clipboard
+            if selection_done {
+                if let Some(text) = self.backend.selection_text() {
+                    layout.ctx.copy_text(text);
+                }
+            }
         }
 
         self
     }
 
+    fn mirror(&mut self, cmd: &BackendCommand) {
+        if let (Some(mirror), BackendCommand::Write(bytes)) =
+            (self.input_mirror.as_mut(), cmd)
+        {
+            mirror.extend_from_slice(bytes);
+        }
+    }
+
     fn show(
         self,
         state: &mut TerminalViewState,
@@ -266,6 +368,15 @@ impl<'a> TerminalView<'a> {
                     r.contains(&indexed.point)
                         && r.contains(&state.current_mouse_position_on_grid)
                 });
+            let is_focused_search_match = content
+                .focused_search_match
+                .as_ref()
+                .is_some_and(|r| r.contains(&indexed.point));
+            let is_search_match = is_focused_search_match
+                || content
+                    .search_matches
+                    .iter()
+                    .any(|r| r.contains(&indexed.point));
 
             let x = layout_min.x + (cell_width * indexed.point.column.0 as f32);
             let line_num =
@@ -288,6 +399,15 @@ impl<'a> TerminalView<'a> {
                 std::mem::swap(&mut fg, &mut bg);
             }
 
+            if is_search_match {
+                fg = SEARCH_MATCH_FG;
+                bg = if is_focused_search_match {
+                    FOCUSED_SEARCH_MATCH_BG
+                } else {
+                    SEARCH_MATCH_BG
+                };
+            }
+
             if global_bg != bg {
                 shapes.push(Shape::Rect(RectShape::filled(
                     Rect::from_min_size(
@@ -355,7 +475,7 @@ impl<'a> TerminalView<'a> {
 
 fn process_keyboard_event(
     event: egui::Event,
-    backend: &TerminalBackend,
+    backend: &dyn TerminalBackendTrait,
     bindings_layout: &BindingsLayout,
     modifiers: Modifiers,
 ) -> InputAction {
@@ -363,19 +483,25 @@ fn process_keyboard_event(
         egui::Event::Text(text) => {
             process_text_event(&text, modifiers, backend, bindings_layout)
         },
-        egui::Event::Paste(text) => InputAction::BackendCall(
-            #[cfg(not(any(target_os = "ios", target_os = "macos")))]
-            if modifiers.contains(Modifiers::COMMAND | Modifiers::SHIFT) {
-                BackendCommand::Write(text.as_bytes().to_vec())
-            } else {
-                // Hotfix - Send ^V when there's not selection on view.
-                BackendCommand::Write([0x16].to_vec())
-            },
-            #[cfg(any(target_os = "ios", target_os = "macos"))]
-            {
-                BackendCommand::Write(text.as_bytes().to_vec())
-            },
-        ),
+        egui::Event::Paste(text) => {
+            let bracketed = backend
+                .last_content()
+                .terminal_mode
+                .contains(TermMode::BRACKETED_PASTE);
+            InputAction::BackendCall(
+                #[cfg(not(any(target_os = "ios", target_os = "macos")))]
+                if modifiers.contains(Modifiers::COMMAND | Modifiers::SHIFT) {
+                    BackendCommand::Write(paste_bytes(&text, bracketed))
+                } else {
+                    // Hotfix - Send ^V when there's not selection on view.
+                    BackendCommand::Write([0x16].to_vec())
+                },
+                #[cfg(any(target_os = "ios", target_os = "macos"))]
+                {
+                    BackendCommand::Write(paste_bytes(&text, bracketed))
+                },
+            )
+        },
         egui::Event::Copy => {
             #[cfg(not(any(target_os = "ios", target_os = "macos")))]
             if modifiers.contains(Modifiers::COMMAND | Modifiers::SHIFT) {
@@ -410,7 +536,7 @@ fn process_keyboard_event(
 fn process_text_event(
     text: &str,
     modifiers: Modifiers,
-    backend: &TerminalBackend,
+    backend: &dyn TerminalBackendTrait,
     bindings_layout: &BindingsLayout,
 ) -> InputAction {
     if let Some(key) = Key::from_name(text) {
@@ -434,7 +560,7 @@ fn process_text_event(
 }
 
 fn process_keyboard_key(
-    backend: &TerminalBackend,
+    backend: &dyn TerminalBackendTrait,
     bindings_layout: &BindingsLayout,
     key: Key,
     modifiers: Modifiers,
@@ -462,38 +588,62 @@ fn process_keyboard_key(
         BindingAction::Esc(seq) => InputAction::BackendCall(
             BackendCommand::Write(seq.as_bytes().to_vec()),
         ),
+        BindingAction::ScrollPageUp => {
+            InputAction::BackendCall(BackendCommand::Scroll(page_lines(backend)))
+        },
+        BindingAction::ScrollPageDown => InputAction::BackendCall(
+            BackendCommand::Scroll(-page_lines(backend)),
+        ),
         _ => InputAction::Ignore,
     }
 }
 
+/// Lines on the terminal's screen, scrolled by Shift+PageUp / Shift+PageDown.
+fn page_lines(backend: &dyn TerminalBackendTrait) -> i32 {
+    backend.last_content().terminal_size.screen_lines() as i32
+}
+
+/// How the wheel scrolls (see `TerminalView::scroll_multiplier` and
+/// `TerminalView::alternate_scroll`)
+#[derive(Clone, Copy)]
+struct WheelSettings {
+    multiplier: f32,
+    alternate_scroll: bool,
+}
+
 fn process_mouse_wheel(
     state: &mut TerminalViewState,
-    backend: &TerminalBackend,
+    backend: &dyn TerminalBackendTrait,
     font_size: f32,
+    settings: WheelSettings,
     unit: MouseWheelUnit,
     delta: Vec2,
     modifiers: Modifiers,
 ) -> InputAction {
     let terminal_mode = backend.last_content().terminal_mode;
 
+    // Lines to scroll, positive towards older output
+    let lines = match unit {
+        MouseWheelUnit::Line => {
+            let lines = delta.y * settings.multiplier;
+            (lines.signum() * lines.abs().ceil()) as i32
+        },
+        MouseWheelUnit::Point => {
+            state.scroll_pixels -= delta.y * settings.multiplier;
+            let lines = (state.scroll_pixels / font_size).trunc() as i32;
+            state.scroll_pixels %= font_size;
+            -lines
+        },
+        MouseWheelUnit::Page => return InputAction::Ignore,
+    };
+
+    if lines == 0 {
+        return InputAction::Ignore;
+    }
+
     // When mouse mode is active, report scroll events as mouse button presses
     // so applications like tmux can handle scrolling themselves.
     if terminal_mode.intersects(TermMode::MOUSE_MODE) {
-        let lines = match unit {
-            MouseWheelUnit::Line => (delta.y.signum() * delta.y.abs().ceil()) as i32,
-            MouseWheelUnit::Point => {
-                state.scroll_pixels -= delta.y;
-                let l = (state.scroll_pixels / font_size).trunc() as i32;
-                state.scroll_pixels %= font_size;
-                -l
-            },
-            MouseWheelUnit::Page => return InputAction::Ignore,
-        };
-
-        if lines == 0 {
-            return InputAction::Ignore;
-        }
-
         let button = if lines > 0 {
             MouseButton::ScrollUp
         } else {
@@ -507,29 +657,29 @@ fn process_mouse_wheel(
         return InputAction::BackendCalls(commands);
     }
 
-    match unit {
-        MouseWheelUnit::Line => {
-            let lines = delta.y.signum() * delta.y.abs().ceil();
-            InputAction::BackendCall(BackendCommand::Scroll(lines as i32))
-        },
-        MouseWheelUnit::Point => {
-            state.scroll_pixels -= delta.y;
-            let lines = (state.scroll_pixels / font_size).trunc();
-            state.scroll_pixels %= font_size;
-            if lines != 0.0 {
-                InputAction::BackendCall(BackendCommand::Scroll(-lines as i32))
-            } else {
-                InputAction::Ignore
-            }
-        },
-        MouseWheelUnit::Page => InputAction::Ignore,
+    // The alternate screen has no scrollback: move through the program's
+    // content with the arrow keys instead
+    if settings.alternate_scroll
+        && terminal_mode.contains(TermMode::ALT_SCREEN | TermMode::ALTERNATE_SCROLL)
+    {
+        let arrow = match (lines > 0, terminal_mode.contains(TermMode::APP_CURSOR)) {
+            (true, true) => "\x1bOA",
+            (true, false) => "\x1b[A",
+            (false, true) => "\x1bOB",
+            (false, false) => "\x1b[B",
+        };
+        return InputAction::BackendCall(BackendCommand::Write(
+            arrow.repeat(lines.unsigned_abs() as usize).into_bytes(),
+        ));
     }
+
+    InputAction::BackendCall(BackendCommand::Scroll(lines))
 }
 
 fn process_button_click(
     state: &mut TerminalViewState,
     layout: &Response,
-    backend: &TerminalBackend,
+    backend: &dyn TerminalBackendTrait,
     bindings_layout: &BindingsLayout,
     button: PointerButton,
     position: Pos2,
@@ -553,7 +703,7 @@ fn process_button_click(
 fn process_left_button(
     state: &mut TerminalViewState,
     layout: &Response,
-    backend: &TerminalBackend,
+    backend: &dyn TerminalBackendTrait,
     bindings_layout: &BindingsLayout,
     position: Pos2,
     modifiers: &Modifiers,
@@ -593,7 +743,7 @@ fn process_left_button_pressed(
 fn process_left_button_released(
     state: &mut TerminalViewState,
     layout: &Response,
-    backend: &TerminalBackend,
+    backend: &dyn TerminalBackendTrait,
     bindings_layout: &BindingsLayout,
     position: Pos2,
     modifiers: &Modifiers,
@@ -642,7 +792,7 @@ fn build_start_select_command(
 fn process_mouse_move(
     state: &mut TerminalViewState,
     layout: &Response,
-    backend: &TerminalBackend,
+    backend: &dyn TerminalBackendTrait,
     position: Pos2,
     modifiers: &Modifiers,
 ) -> Vec<InputAction> {
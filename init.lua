@@ -6,6 +6,15 @@ print('hi!')
 -- Performance logging: set to > 0 to log frame/event stats every N seconds
 -- Run with RUST_LOG=info to see output
 config.perf_log_interval = 5
+-- Also append each window to a file (CSV, or JSON lines for .json/.jsonl),
+-- rotated to <file>.1 once it reaches perf_log_max_kb
+-- config.perf_log_file = "~/.local/state/manse/perf.csv"
+-- config.perf_log_max_kb = 1024
+
+-- Repaints caused by terminal output are coalesced to at most one per N ms
+-- (0 = repaint on every update). The focused terminal has its own interval.
+config.output_repaint_ms = 8
+config.focused_output_repaint_ms = 0
 
 -- Icon aliases
 local TERMINAL = "🖥️"
@@ -73,4 +82,13 @@ config.ui_colors = {
   status_bar_background = "#161821",
   status_bar_text = "#6b7089",
   focused_border = "#84a0c6",
+  notified_background = "#3c1919",
+  activity_dot = "#89b8c2",
+  bell_dot = "#e2a478",
 }
+
+-- Shape cues for focus/notifications and a deuteranopia-safe palette
+config.accessible_indicators = false
+
+-- No ⌘-hover URL detection while these full-screen apps are in the foreground
+config.link_exclusions = { "htop", "btop" }
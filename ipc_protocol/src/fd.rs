@@ -0,0 +1,111 @@
+//! File descriptors passed along with a response line (`SCM_RIGHTS`), for handing a
+//! terminal's PTY to another instance (`term_hand_off`).
+
+use std::io::{self, Write};
+use std::mem;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::net::UnixStream;
+
+/// Room for the control message of one descriptor, aligned for `cmsghdr`
+type ControlBuffer = [u64; 4];
+
+/// Write `data` with `fd` attached to its first byte. The receiver gets its own
+/// descriptor for the same open file.
+#[cfg_attr(not(feature = "server"), allow(dead_code))]
+pub(crate) fn send_with_fd(stream: &UnixStream, data: &[u8], fd: RawFd) -> io::Result<()> {
+    let mut iov = libc::iovec {
+        iov_base: data.as_ptr() as *mut libc::c_void,
+        iov_len: data.len(),
+    };
+    let mut control: ControlBuffer = [0; 4];
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+    msg.msg_controllen = unsafe { libc::CMSG_SPACE(mem::size_of::<RawFd>() as u32) } as _;
+
+    let sent = unsafe {
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(mem::size_of::<RawFd>() as u32) as _;
+        std::ptr::write_unaligned(libc::CMSG_DATA(cmsg) as *mut RawFd, fd);
+        libc::sendmsg(stream.as_raw_fd(), &msg, 0)
+    };
+    if sent < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut writer = stream;
+    writer.write_all(&data[sent as usize..])?;
+    writer.flush()
+}
+
+/// Read up to the end of the next line, with the descriptor sent along with it (if
+/// any). Reads straight from the socket: nothing may be buffered from it elsewhere.
+pub(crate) fn recv_line_with_fd(stream: &UnixStream) -> io::Result<(String, Option<OwnedFd>)> {
+    let mut line = Vec::new();
+    let mut fd = None;
+    let mut buf = [0u8; 4096];
+    while !line.ends_with(b"\n") {
+        let mut iov = libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+            iov_len: buf.len(),
+        };
+        let mut control: ControlBuffer = [0; 4];
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+        msg.msg_controllen = mem::size_of::<ControlBuffer>() as _;
+
+        let got = unsafe { libc::recvmsg(stream.as_raw_fd(), &mut msg, 0) };
+        if got < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+        if got == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        unsafe {
+            let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+            while !cmsg.is_null() {
+                if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_RIGHTS {
+                    let raw = std::ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const RawFd);
+                    fd = Some(OwnedFd::from_raw_fd(raw));
+                }
+                cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+            }
+        }
+        line.extend_from_slice(&buf[..got as usize]);
+    }
+    let line = String::from_utf8(line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok((line, fd))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn passes_descriptor_with_line() {
+        let (sender, receiver) = UnixStream::pair().unwrap();
+        let (mut passed, mut other_end) = UnixStream::pair().unwrap();
+
+        send_with_fd(&sender, b"{\"ok\": true}\n", passed.as_raw_fd()).unwrap();
+        let (line, fd) = recv_line_with_fd(&receiver).unwrap();
+        assert_eq!(line, "{\"ok\": true}\n");
+
+        // The received descriptor is another handle on the same socket
+        let mut received = UnixStream::from(fd.unwrap());
+        received.write_all(b"hi").unwrap();
+        passed.write_all(b"!").unwrap();
+        let mut buf = [0u8; 3];
+        other_end.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"hi!");
+    }
+}
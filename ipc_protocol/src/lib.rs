@@ -0,0 +1,629 @@
+//! Protocol of the manse control socket: requests, responses and events as
+//! newline-delimited JSON over a Unix socket, plus a blocking client.
+//!
+//! Tools written in Rust (editor plugins, bots, scripts) can use [`IpcClient`] to
+//! control a running manse with typed [`Request`]s instead of writing JSON by hand.
+//! A terminal's shell has the socket in `$MANSE_SOCKET` and its own ID in
+//! `$MANSE_TERMINAL`:
+//!
+//! ```no_run
+//! use ipc_protocol::{Event, IpcClient, Request};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let socket = std::env::var("MANSE_SOCKET").unwrap_or_else(|_| "/tmp/manse.sock".into());
+//! let mut client = IpcClient::connect(&socket)?;
+//! client
+//!     .request(&Request::TermNotify {
+//!         terminal: std::env::var("MANSE_TERMINAL")?,
+//!         message: Some("build finished".into()),
+//!     })?
+//!     .into_result()?;
+//!
+//! for event in IpcClient::connect(&socket)?.subscribe(vec!["focus_changed".into()])? {
+//!     if let Event::FocusChanged { terminal, workspace } = event {
+//!         println!("{:?} in {}", terminal, workspace);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Compatibility
+//!
+//! [`Request`], [`Event`] and [`Error`] are `#[non_exhaustive]`: new requests and
+//! events are added in minor versions, so matches need a wildcard arm. New fields of
+//! a request are optional on the wire (`#[serde(default)]`), so an older client's
+//! JSON keeps working with a newer manse. A manse too old for a request answers it
+//! with an error response.
+//!
+//! # Features
+//!
+//! - `server`: `start_ipc_server` and the types the app uses to answer requests.
+//!   It wakes the egui event loop, so it pulls in egui; clients leave it off.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Lines, Write};
+use std::os::fd::OwnedFd;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+mod fd;
+#[cfg(feature = "server")]
+mod server;
+
+#[cfg(feature = "server")]
+pub use server::{start_ipc_server, IpcHandle, PendingRequest};
+
+/// Request sent from client to server, as `{"cmd": "term_rename", ...}`.
+///
+/// Terminals are named by their ID (`$MANSE_TERMINAL`), workspaces by name unless
+/// noted otherwise.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Request {
+    /// Check if server is alive
+    Ping,
+    /// Trigger a restart (exec with state preservation)
+    Restart,
+    /// Quit the application (`force` skips the confirmation dialog)
+    Quit {
+        #[serde(default)]
+        force: bool,
+    },
+    /// Rename a terminal by ID
+    TermRename { terminal: String, title: String },
+    /// Set terminal description by ID
+    TermDesc { terminal: String, description: String },
+    /// Set terminal icon (Nerd Font codepoint) by ID
+    TermIcon { terminal: String, icon: String },
+    /// Apply a named color scheme to a terminal (empty scheme resets to the base theme)
+    TermTheme { terminal: String, scheme: String },
+    /// Set a terminal's accent color ("#rrggbb", empty to clear)
+    TermColor { terminal: String, color: String },
+    /// Set a terminal's width as a fraction of the viewport (0.0-1.0], no narrower than
+    /// the smallest of `width_ratios`
+    TermResize { terminal: String, ratio: f32 },
+    /// Pin a terminal to the left edge of its strip, where it stays while the others
+    /// scroll (`pinned: false` unpins it)
+    TermPin { terminal: String, pinned: bool },
+    /// Move a terminal to a workspace (creates workspace if needed)
+    TermToWorkspace { terminal: String, workspace_name: String },
+    /// Set notification on a terminal (cleared when focused), with an optional history message
+    TermNotify {
+        terminal: String,
+        #[serde(default)]
+        message: Option<String>,
+    },
+    /// Close a terminal by ID (`force` skips the confirmation dialog)
+    TermClose {
+        terminal: String,
+        #[serde(default)]
+        force: bool,
+    },
+    /// Write text to a terminal's PTY as if it were typed
+    TermSendText { terminal: String, text: String },
+    /// A terminal's text (scrollback and screen, wrapped lines joined) as `{"text": ...}`,
+    /// limited to the last `lines` lines when given
+    TermDump {
+        terminal: String,
+        #[serde(default)]
+        lines: Option<usize>,
+    },
+    /// Set notification on every terminal in a workspace
+    WorkspaceNotifyAll {
+        workspace: String,
+        #[serde(default)]
+        message: Option<String>,
+    },
+    /// Write text to every terminal in a workspace
+    WorkspaceSendText { workspace: String, text: String },
+    /// List all terminals with their workspace and focus state
+    TermList,
+    /// Spawn a terminal, optionally running `command`, and reply with its ID.
+    /// Without `workspace` it opens in the active workspace; `focus` switches to it.
+    TermSpawn {
+        #[serde(default)]
+        command: Option<String>,
+        #[serde(default)]
+        cwd: Option<PathBuf>,
+        #[serde(default)]
+        workspace: Option<String>,
+        #[serde(default)]
+        title: Option<String>,
+        #[serde(default)]
+        focus: bool,
+        /// Where the terminal goes in its workspace (default: `new_terminal_position`)
+        #[serde(default)]
+        placement: Option<TerminalPlacement>,
+    },
+    /// Create a workspace with one new terminal and switch to it
+    WorkspaceCreate { name: String },
+    /// Rename a workspace, given by name or 1-based index
+    WorkspaceRename { workspace: String, name: String },
+    /// Delete a workspace, given by name or 1-based index
+    /// (`force` closes its terminals, otherwise it must be empty)
+    WorkspaceDelete {
+        workspace: String,
+        #[serde(default)]
+        force: bool,
+    },
+    /// List all workspaces
+    WorkspaceList,
+    /// Put a workspace, given by name or 1-based index, in a sidebar group
+    /// (`group: None` takes it out of its group)
+    WorkspaceGroup {
+        workspace: String,
+        #[serde(default)]
+        group: Option<String>,
+    },
+    /// Delete every workspace in a sidebar group
+    /// (`force` closes their terminals, otherwise they must be empty)
+    GroupClose {
+        group: String,
+        #[serde(default)]
+        force: bool,
+    },
+    /// Give every terminal in a workspace (name or 1-based index, the active one when
+    /// omitted) the same width
+    LayoutEqualize {
+        #[serde(default)]
+        workspace: Option<String>,
+    },
+    /// Re-read init.lua and apply it (colors, fonts, sidebar, ...)
+    ConfigReload,
+    /// Bug report: recent logs, scrubbed init.lua, perf counters, instance details and
+    /// workspace/terminal counts, as one JSON object
+    DebugReport,
+    /// A terminal as a session profile entry (directory, title, description, icon,
+    /// scheme, width, foreground command), with the name of its workspace
+    TermExport { terminal: String },
+    /// Start a new shell from a `term_export` entry in `workspace` (created if needed),
+    /// switch to it and reply with its ID
+    TermImport {
+        workspace: String,
+        terminal: serde_json::Value,
+    },
+    /// Take a terminal out of the layout without ending its shell and hold its PTY
+    /// until an instance adopts it with `term_attach`
+    TermDetach { terminal: String },
+    /// Give up a detached terminal: replied to with its state, and its PTY passed along
+    /// (SCM_RIGHTS). Sent by the instance handling `term_attach`.
+    TermHandOff { terminal: String },
+    /// Adopt the terminal detached in the instance on socket `from`, at the end of
+    /// `workspace` (the active one when omitted), and switch to it
+    TermAttach {
+        terminal: String,
+        from: PathBuf,
+        #[serde(default)]
+        workspace: Option<String>,
+    },
+    /// Save the layout as the named session profile
+    SessionSave { name: String },
+    /// Recreate the named session profile with new shells
+    SessionLoad { name: String },
+    /// Keep the connection open and stream the named events (all if empty) as
+    /// newline-delimited JSON after the response. With `terminals`, events about a
+    /// terminal are limited to those terminals, and `grid_changed` (only sent for
+    /// terminals named here) is streamed for them.
+    Subscribe {
+        #[serde(default)]
+        events: Vec<String>,
+        #[serde(default)]
+        terminals: Vec<String>,
+    },
+}
+
+/// Where a newly created terminal is inserted in the workspace strip. On the wire a
+/// name (`"before"`, `"after"`, `"start"`, `"end"`) or an index (`2`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "PlacementWire", into = "PlacementWire")]
+pub enum TerminalPlacement {
+    /// Immediately left of the focused terminal
+    BeforeFocused,
+    /// Immediately right of the focused terminal
+    AfterFocused,
+    /// At the left end of the strip
+    Start,
+    /// At the right end of the strip
+    End,
+    /// At a specific index (clamped to the strip length)
+    Index(usize),
+}
+
+impl TerminalPlacement {
+    /// Parse a placement name ("before", "after", "start", "end") or a numeric index.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "before" => Some(Self::BeforeFocused),
+            "after" => Some(Self::AfterFocused),
+            "start" => Some(Self::Start),
+            "end" => Some(Self::End),
+            other => other.parse().ok().map(Self::Index),
+        }
+    }
+
+    /// Compute the insertion index for a strip of `len` terminals with `focused` focused.
+    pub fn insert_index(self, focused: usize, len: usize) -> usize {
+        if len == 0 {
+            return 0;
+        }
+        let focused = focused.min(len - 1);
+        match self {
+            Self::BeforeFocused => focused,
+            Self::AfterFocused => focused + 1,
+            Self::Start => 0,
+            Self::End => len,
+            Self::Index(idx) => idx.min(len),
+        }
+    }
+}
+
+/// [`TerminalPlacement`] as sent over the socket
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum PlacementWire {
+    Index(usize),
+    Name(String),
+}
+
+impl TryFrom<PlacementWire> for TerminalPlacement {
+    type Error = String;
+
+    fn try_from(wire: PlacementWire) -> Result<Self, String> {
+        match wire {
+            PlacementWire::Index(index) => Ok(Self::Index(index)),
+            PlacementWire::Name(name) => Self::parse(&name).ok_or_else(|| {
+                format!("Unknown placement '{}' (before, after, start, end or an index)", name)
+            }),
+        }
+    }
+}
+
+impl From<TerminalPlacement> for PlacementWire {
+    fn from(placement: TerminalPlacement) -> Self {
+        let name = match placement {
+            TerminalPlacement::BeforeFocused => "before",
+            TerminalPlacement::AfterFocused => "after",
+            TerminalPlacement::Start => "start",
+            TerminalPlacement::End => "end",
+            TerminalPlacement::Index(index) => return Self::Index(index),
+        };
+        Self::Name(name.to_string())
+    }
+}
+
+/// Event pushed to subscribed IPC connections, as `{"event": "title_changed", ...}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Event {
+    /// A terminal was opened (spawned, restored or reconnected)
+    TerminalCreated { terminal: String, workspace: String },
+    /// A terminal was closed or its shell exited
+    TerminalClosed { terminal: String },
+    /// The focused terminal changed (`None` in an empty workspace)
+    FocusChanged { terminal: Option<String>, workspace: String },
+    /// A terminal's displayed title changed
+    TitleChanged { terminal: String, title: String },
+    /// Another workspace became active
+    WorkspaceSwitched { workspace: String },
+    /// A `term-notify` or bell, as added to the notification history
+    Notification {
+        terminal: String,
+        /// "notify" or "bell"
+        kind: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    /// Rows of a terminal's screen changed or its cursor moved, at most once a frame.
+    /// Only sent to subscriptions naming the terminal in `terminals`; once a terminal
+    /// is watched, its first event lists every row.
+    GridChanged {
+        terminal: String,
+        /// Changed rows, counted from the top of the screen (0)
+        lines: Vec<usize>,
+        cursor_line: usize,
+        cursor_column: usize,
+    },
+}
+
+impl Event {
+    /// Names accepted by `Request::Subscribe`
+    pub const NAMES: [&'static str; 7] = [
+        "terminal_created",
+        "terminal_closed",
+        "focus_changed",
+        "title_changed",
+        "workspace_switched",
+        "notification",
+        "grid_changed",
+    ];
+
+    /// Name of this kind of event, as in [`Event::NAMES`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::TerminalCreated { .. } => "terminal_created",
+            Event::TerminalClosed { .. } => "terminal_closed",
+            Event::FocusChanged { .. } => "focus_changed",
+            Event::TitleChanged { .. } => "title_changed",
+            Event::WorkspaceSwitched { .. } => "workspace_switched",
+            Event::Notification { .. } => "notification",
+            Event::GridChanged { .. } => "grid_changed",
+        }
+    }
+
+    /// The terminal this event is about, for subscriptions limited to some terminals.
+    /// `None` for events about the whole window (focus and workspace changes).
+    pub fn terminal(&self) -> Option<&str> {
+        match self {
+            Event::TerminalCreated { terminal, .. }
+            | Event::TerminalClosed { terminal }
+            | Event::TitleChanged { terminal, .. }
+            | Event::Notification { terminal, .. }
+            | Event::GridChanged { terminal, .. } => Some(terminal),
+            Event::FocusChanged { .. } | Event::WorkspaceSwitched { .. } => None,
+        }
+    }
+}
+
+/// Response sent from server to client, one per request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Response {
+    /// Whether the request succeeded
+    pub ok: bool,
+    /// Why it failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Data returned by the request (`term-list` rows, a spawned terminal's ID, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+}
+
+impl Response {
+    /// Success without data
+    pub fn ok() -> Self {
+        Self {
+            ok: true,
+            error: None,
+            result: None,
+        }
+    }
+
+    /// Success returning `result`
+    pub fn ok_with(result: serde_json::Value) -> Self {
+        Self {
+            ok: true,
+            error: None,
+            result: Some(result),
+        }
+    }
+
+    /// Failure with the reason shown to the user
+    pub fn error(msg: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            error: Some(msg.into()),
+            result: None,
+        }
+    }
+
+    /// The returned data of a successful response, or its error as [`Error::Server`]
+    pub fn into_result(self) -> Result<Option<serde_json::Value>, Error> {
+        if self.ok {
+            Ok(self.result)
+        } else {
+            Err(Error::Server(
+                self.error.unwrap_or_else(|| "Unknown error".into()),
+            ))
+        }
+    }
+}
+
+/// Error talking to a running instance
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// No instance is listening on the socket
+    Connect { path: PathBuf, source: io::Error },
+    /// Sending or receiving failed
+    Io(io::Error),
+    /// A message could not be encoded or decoded
+    Json(serde_json::Error),
+    /// The instance answered with an error
+    Server(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Connect { path, source } => {
+                write!(f, "Failed to connect to socket {}: {}", path.display(), source)
+            }
+            Error::Io(e) => write!(f, "Connection error: {}", e),
+            Error::Json(e) => write!(f, "Invalid message: {}", e),
+            Error::Server(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Connect { source, .. } => Some(source),
+            Error::Io(e) => Some(e),
+            Error::Json(e) => Some(e),
+            Error::Server(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+/// Client for sending commands to a running instance.
+///
+/// Requests are answered in order on one connection; [`IpcClient::subscribe`]
+/// turns the connection into an event stream.
+pub struct IpcClient {
+    stream: UnixStream,
+    reader: BufReader<UnixStream>,
+}
+
+impl IpcClient {
+    /// Connect to a running instance
+    pub fn connect(socket_path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = socket_path.as_ref();
+        let stream = UnixStream::connect(path).map_err(|source| Error::Connect {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let reader = BufReader::new(stream.try_clone()?);
+        Ok(Self { stream, reader })
+    }
+
+    /// Give up on sending or waiting for a response after `timeout` (`None`, the
+    /// default, waits as long as it takes). A timed out request fails with an
+    /// [`Error::Io`] of kind `WouldBlock` or `TimedOut`.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        self.stream.set_read_timeout(timeout)?;
+        self.stream.set_write_timeout(timeout)?;
+        Ok(())
+    }
+
+    /// Send a request and wait for response. An error response is still `Ok`; see
+    /// [`Response::into_result`].
+    pub fn request(&mut self, req: &Request) -> Result<Response, Error> {
+        let json = serde_json::to_string(req)?;
+        writeln!(self.stream, "{}", json)?;
+        self.stream.flush()?;
+
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(Error::Io(io::ErrorKind::UnexpectedEof.into()));
+        }
+        Ok(serde_json::from_str(&line)?)
+    }
+
+    /// Like [`IpcClient::request`], for a request answered with a file descriptor
+    /// (`term_hand_off`): the response, and the descriptor if one came with it.
+    pub fn request_fd(&mut self, req: &Request) -> Result<(Response, Option<OwnedFd>), Error> {
+        let json = serde_json::to_string(req)?;
+        writeln!(self.stream, "{}", json)?;
+        self.stream.flush()?;
+
+        // Responses come one per request, so nothing is buffered in `reader` here
+        let (line, fd) = fd::recv_line_with_fd(&self.stream)?;
+        Ok((serde_json::from_str(&line)?, fd))
+    }
+
+    /// Subscribe to `events` (names from [`Event::NAMES`], all if empty) and return
+    /// the events as they arrive, until the instance quits.
+    pub fn subscribe(self, events: Vec<String>) -> Result<Subscription, Error> {
+        self.subscribe_terminals(events, Vec::new())
+    }
+
+    /// Like [`IpcClient::subscribe`], limiting events about terminals to `terminals`
+    /// and streaming their `grid_changed` events.
+    pub fn subscribe_terminals(
+        mut self,
+        events: Vec<String>,
+        terminals: Vec<String>,
+    ) -> Result<Subscription, Error> {
+        self.request(&Request::Subscribe { events, terminals })?.into_result()?;
+        Ok(Subscription {
+            lines: self.reader.lines(),
+        })
+    }
+
+    /// Send a ping and check if server is alive
+    pub fn ping(&mut self) -> Result<(), Error> {
+        self.request(&Request::Ping)?.into_result().map(|_| ())
+    }
+}
+
+/// Events of a subscribed connection, from [`IpcClient::subscribe`]. Events added
+/// by a newer manse than this crate knows are skipped.
+pub struct Subscription {
+    lines: Lines<BufReader<UnixStream>>,
+}
+
+impl Iterator for Subscription {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        loop {
+            let line = self.lines.next()?.ok()?;
+            if let Ok(event) = serde_json::from_str(&line) {
+                return Some(event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placement_parse_names() {
+        assert_eq!(TerminalPlacement::parse("before"), Some(TerminalPlacement::BeforeFocused));
+        assert_eq!(TerminalPlacement::parse("After"), Some(TerminalPlacement::AfterFocused));
+        assert_eq!(TerminalPlacement::parse("start"), Some(TerminalPlacement::Start));
+        assert_eq!(TerminalPlacement::parse(" end "), Some(TerminalPlacement::End));
+        assert_eq!(TerminalPlacement::parse("3"), Some(TerminalPlacement::Index(3)));
+        assert_eq!(TerminalPlacement::parse("middle"), None);
+    }
+
+    #[test]
+    fn placement_insert_index_relative_to_focus() {
+        assert_eq!(TerminalPlacement::BeforeFocused.insert_index(2, 4), 2);
+        assert_eq!(TerminalPlacement::AfterFocused.insert_index(2, 4), 3);
+        assert_eq!(TerminalPlacement::AfterFocused.insert_index(3, 4), 4);
+    }
+
+    #[test]
+    fn placement_insert_index_absolute() {
+        assert_eq!(TerminalPlacement::Start.insert_index(2, 4), 0);
+        assert_eq!(TerminalPlacement::End.insert_index(0, 4), 4);
+        assert_eq!(TerminalPlacement::Index(1).insert_index(3, 4), 1);
+        assert_eq!(TerminalPlacement::Index(10).insert_index(3, 4), 4);
+    }
+
+    #[test]
+    fn placement_insert_index_empty_strip() {
+        assert_eq!(TerminalPlacement::AfterFocused.insert_index(0, 0), 0);
+        assert_eq!(TerminalPlacement::End.insert_index(5, 0), 0);
+        assert_eq!(TerminalPlacement::Index(7).insert_index(0, 0), 0);
+    }
+
+    #[test]
+    fn term_spawn_placement_on_the_wire() {
+        let spawn = |json: &str| match serde_json::from_str(json).unwrap() {
+            Request::TermSpawn { placement, .. } => placement,
+            other => panic!("not a spawn: {:?}", other),
+        };
+        assert_eq!(spawn(r#"{"cmd": "term_spawn"}"#), None);
+        assert_eq!(spawn(r#"{"cmd": "term_spawn", "placement": "Before"}"#), Some(TerminalPlacement::BeforeFocused));
+        assert_eq!(spawn(r#"{"cmd": "term_spawn", "placement": 2}"#), Some(TerminalPlacement::Index(2)));
+        assert!(serde_json::from_str::<Request>(r#"{"cmd": "term_spawn", "placement": "middle"}"#).is_err());
+
+        for placement in [TerminalPlacement::AfterFocused, TerminalPlacement::End, TerminalPlacement::Index(0)] {
+            let json = serde_json::to_string(&placement).unwrap();
+            assert_eq!(serde_json::from_str::<TerminalPlacement>(&json).unwrap(), placement);
+        }
+        assert_eq!(serde_json::to_string(&TerminalPlacement::Start).unwrap(), r#""start""#);
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{fd, Event, Request, Response};
+use std::io::{BufRead, BufReader, Write};
+use std::os::fd::{AsRawFd, OwnedFd};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A connection streaming events: the names it asked for (all if empty), the
+/// terminals it is limited to (all if empty) and the channel to its thread
+struct Subscriber {
+    events: Vec<String>,
+    terminals: Vec<String>,
+    tx: Sender<String>,
+}
+
+impl Subscriber {
+    /// Whether `event` goes to this connection. `grid_changed` only goes to the
+    /// terminals it names.
+    fn wants(&self, event: &Event) -> bool {
+        let name = event.name();
+        let named = self.events.is_empty() || self.events.iter().any(|e| e == name);
+        let about = match event.terminal() {
+            Some(_) if self.terminals.is_empty() => name != "grid_changed",
+            Some(terminal) => self.terminals.iter().any(|t| t == terminal),
+            None => true,
+        };
+        named && about
+    }
+
+    fn watches_grid(&self, terminal: &str) -> bool {
+        (self.events.is_empty() || self.events.iter().any(|e| e == "grid_changed"))
+            && self.terminals.iter().any(|t| t == terminal)
+    }
+}
+
+type Subscribers = Arc<Mutex<Vec<Subscriber>>>;
+
+/// A pending IPC request with a channel to send the response back
+pub struct PendingRequest {
+    pub request: Request,
+    response_tx: Sender<(Response, Option<OwnedFd>)>,
+}
+
+impl PendingRequest {
+    /// Send a response back to the client
+    pub fn respond(self, response: Response) {
+        let _ = self.response_tx.send((response, None));
+    }
+
+    /// Send a response back with `fd` passed along (SCM_RIGHTS). It is closed here
+    /// once sent.
+    pub fn respond_with_fd(self, response: Response, fd: OwnedFd) {
+        let _ = self.response_tx.send((response, Some(fd)));
+    }
+}
+
+/// Handle for the main thread to receive IPC requests and publish events
+pub struct IpcHandle {
+    request_rx: Receiver<PendingRequest>,
+    subscribers: Subscribers,
+    _socket_path: PathBuf,
+}
+
+impl IpcHandle {
+    /// Poll for pending requests (non-blocking)
+    pub fn poll(&self) -> Vec<PendingRequest> {
+        let mut requests = Vec::new();
+        while let Ok(req) = self.request_rx.try_recv() {
+            requests.push(req);
+        }
+        requests
+    }
+
+    /// Whether any connection is subscribed to events
+    pub fn has_subscribers(&self) -> bool {
+        !self.subscribers.lock().unwrap().is_empty()
+    }
+
+    /// Whether any connection streams `grid_changed` events of `terminal` (screens are
+    /// only diffed for those)
+    pub fn watches_grid(&self, terminal: &str) -> bool {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|subscriber| subscriber.watches_grid(terminal))
+    }
+
+    /// Send `event` to every connection subscribed to it, dropping closed ones.
+    pub fn publish(&self, event: &Event) {
+        let Ok(json) = serde_json::to_string(event) else {
+            return;
+        };
+        self.subscribers.lock().unwrap().retain(|subscriber| {
+            !subscriber.wants(event) || subscriber.tx.send(json.clone()).is_ok()
+        });
+    }
+}
+
+/// Start the IPC server in a background thread.
+/// Returns a handle for the main thread to receive requests.
+pub fn start_ipc_server(
+    socket_path: impl AsRef<Path>,
+    ctx: egui::Context,
+) -> Result<IpcHandle, String> {
+    let socket_path = socket_path.as_ref().to_path_buf();
+
+    // Check if socket already exists
+    if socket_path.exists() {
+        // Try to connect - if successful, another instance is running
+        match UnixStream::connect(&socket_path) {
+            Ok(_) => {
+                return Err(format!(
+                    "Another instance is already running on socket: {}",
+                    socket_path.display()
+                ));
+            }
+            Err(_) => {
+                // Stale socket file, remove it
+                std::fs::remove_file(&socket_path).map_err(|e| {
+                    format!(
+                        "Failed to remove stale socket {}: {}",
+                        socket_path.display(),
+                        e
+                    )
+                })?;
+            }
+        }
+    }
+
+    // Create the listener (blocking mode for the background thread)
+    let listener = UnixListener::bind(&socket_path)
+        .map_err(|e| format!("Failed to bind socket {}: {}", socket_path.display(), e))?;
+
+    log::info!("IPC server listening on: {}", socket_path.display());
+
+    let (request_tx, request_rx) = mpsc::channel();
+    let subscribers = Subscribers::default();
+    let subscribers_clone = subscribers.clone();
+    let socket_path_clone = socket_path.clone();
+
+    thread::spawn(move || {
+        // Handle cleanup on thread exit
+        struct Cleanup(PathBuf);
+        impl Drop for Cleanup {
+            fn drop(&mut self) {
+                let _ = std::fs::remove_file(&self.0);
+            }
+        }
+        let _cleanup = Cleanup(socket_path_clone);
+
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let request_tx = request_tx.clone();
+                    let subscribers = subscribers_clone.clone();
+                    let ctx = ctx.clone();
+
+                    // Handle each client in its own thread for concurrent connections
+                    thread::spawn(move || {
+                        handle_client(stream, request_tx, subscribers, ctx);
+                    });
+                }
+                Err(e) => {
+                    log::error!("IPC accept error: {}", e);
+                }
+            }
+        }
+    });
+
+    Ok(IpcHandle {
+        request_rx,
+        subscribers,
+        _socket_path: socket_path,
+    })
+}
+
+/// Write `json` as one line, flushed. Returns false once the client is gone.
+fn write_line(writer: &mut UnixStream, json: &str) -> bool {
+    writeln!(writer, "{}", json).is_ok() && writer.flush().is_ok()
+}
+
+/// Answer a subscription, then forward published events to the client until it
+/// disconnects. Handled here rather than on the main thread, which only publishes.
+fn stream_events(
+    mut writer: UnixStream,
+    events: Vec<String>,
+    terminals: Vec<String>,
+    subscribers: &Subscribers,
+) {
+    let error = if let Some(unknown) = events.iter().find(|name| !Event::NAMES.contains(&name.as_str())) {
+        Some(format!(
+            "Unknown event '{}' (expected one of: {})",
+            unknown,
+            Event::NAMES.join(", ")
+        ))
+    } else if terminals.is_empty() && events.iter().any(|name| name == "grid_changed") {
+        Some("grid_changed needs the terminals to watch".to_string())
+    } else {
+        None
+    };
+    if let Some(error) = error {
+        if let Ok(json) = serde_json::to_string(&Response::error(error)) {
+            write_line(&mut writer, &json);
+        }
+        return;
+    }
+
+    let (tx, rx) = mpsc::channel();
+    subscribers.lock().unwrap().push(Subscriber { events, terminals, tx });
+    let Ok(json) = serde_json::to_string(&Response::ok()) else {
+        return;
+    };
+    if !write_line(&mut writer, &json) {
+        return;
+    }
+    // Dropping `rx` on a failed write makes the next publish drop the subscriber
+    for event in rx {
+        if !write_line(&mut writer, &event) {
+            break;
+        }
+    }
+}
+
+fn handle_client(
+    stream: UnixStream,
+    request_tx: Sender<PendingRequest>,
+    subscribers: Subscribers,
+    ctx: egui::Context,
+) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) => break, // Connection closed
+            Ok(_) => {
+                let request = match serde_json::from_str::<Request>(&line) {
+                    Ok(request) => request,
+                    Err(e) => {
+                        // Also what a client newer than this instance gets for new requests
+                        let response = Response::error(format!("Invalid request: {}", e));
+                        match serde_json::to_string(&response) {
+                            Ok(json) if write_line(&mut writer, &json) => continue,
+                            _ => break,
+                        }
+                    }
+                };
+                if let Request::Subscribe { events, terminals } = request {
+                    stream_events(writer, events, terminals, &subscribers);
+                    break;
+                }
+
+                // Create a oneshot-style channel for the response
+                let (response_tx, response_rx) = mpsc::channel();
+
+                let pending = PendingRequest {
+                    request,
+                    response_tx,
+                };
+
+                // Send to main thread and request repaint
+                if request_tx.send(pending).is_err() {
+                    break; // Main thread gone
+                }
+                ctx.request_repaint();
+
+                // Wait for response from main thread
+                match response_rx.recv() {
+                    Ok((response, fd)) => {
+                        if let Ok(json) = serde_json::to_string(&response) {
+                            if let Some(fd) = fd {
+                                let line = json + "\n";
+                                if fd::send_with_fd(&writer, line.as_bytes(), fd.as_raw_fd()).is_err() {
+                                    break;
+                                }
+                                continue;
+                            }
+                            if writeln!(writer, "{}", json).is_err() {
+                                break;
+                            }
+                            if writer.flush().is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    Err(_) => break, // Main thread dropped the sender
+                }
+            }
+            Err(_) => break,
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Registry of running instances, so client commands can address one by name
+//! (`manse --instance webapp term-list`) instead of by socket path, and `manse instances`
+//! can list them. Kept as a JSON file in the XDG runtime directory, written by
+//! `manse run`; instances started without `--instance` are registered under their PID.
+
+use ipc_protocol::IpcClient;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Socket of instances started without `--instance` or `--socket`
+pub const DEFAULT_SOCKET: &str = "/tmp/manse.sock";
+
+/// A running instance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Instance {
+    pub socket: PathBuf,
+    pub pid: u32,
+}
+
+/// Running instances by name
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Registry {
+    instances: BTreeMap<String, Instance>,
+}
+
+/// Directory for the registry and the sockets of named instances:
+/// `$XDG_RUNTIME_DIR/manse`, or `manse` in the temp directory without one.
+pub fn runtime_dir() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("manse")
+}
+
+/// Socket of the instance `name` when `run` isn't given one
+pub fn default_socket(name: &str) -> PathBuf {
+    runtime_dir().join(format!("{}.sock", name))
+}
+
+/// Socket of an unnamed instance started while another one serves the default socket
+pub fn auto_socket() -> PathBuf {
+    runtime_dir().join(format!("manse-{}.sock", std::process::id()))
+}
+
+/// Whether an instance answers on `socket`
+pub fn is_running(socket: &Path) -> bool {
+    IpcClient::connect(socket).is_ok()
+}
+
+fn registry_path() -> PathBuf {
+    runtime_dir().join("instances.json")
+}
+
+impl Registry {
+    /// Read the registry. A missing or unreadable file starts empty.
+    pub fn load() -> Self {
+        Self::load_from(&registry_path())
+    }
+
+    fn load_from(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the registry, creating the runtime directory if needed.
+    pub fn save(&self) -> Result<(), String> {
+        let path = registry_path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Record `name` as served on `socket`, replacing an earlier entry of that name.
+    /// Entries whose socket is gone (instances that exited or crashed) are dropped.
+    pub fn insert(&mut self, name: &str, instance: Instance) {
+        self.instances.retain(|_, instance| instance.socket.exists());
+        self.instances.insert(name.to_string(), instance);
+    }
+
+    /// Forget every instance served on `socket`. Returns whether any was registered.
+    pub fn remove_socket(&mut self, socket: &Path) -> bool {
+        let before = self.instances.len();
+        self.instances.retain(|_, instance| instance.socket != socket);
+        self.instances.len() != before
+    }
+
+    /// Registered instances whose socket still exists, sorted by name
+    pub fn running(&self) -> impl Iterator<Item = (&str, &Instance)> {
+        self.instances
+            .iter()
+            .filter(|(_, instance)| instance.socket.exists())
+            .map(|(name, instance)| (name.as_str(), instance))
+    }
+
+    /// Socket of the instance `name`
+    pub fn resolve(&self, name: &str) -> Result<&Path, String> {
+        let Some(instance) = self.instances.get(name) else {
+            let names: Vec<&str> = self.instances.keys().map(String::as_str).collect();
+            return Err(match names.as_slice() {
+                [] => format!("No instance named '{}' (none are running)", name),
+                _ => format!("No instance named '{}' (running: {})", name, names.join(", ")),
+            });
+        };
+        if !instance.socket.exists() {
+            return Err(format!("Instance '{}' is not running", name));
+        }
+        Ok(&instance.socket)
+    }
+}
+
+/// Register this process as the instance `name` on `socket`.
+pub fn register(name: &str, socket: &Path) -> Result<(), String> {
+    let mut registry = Registry::load();
+    let instance = Instance {
+        socket: socket.to_path_buf(),
+        pid: std::process::id(),
+    };
+    registry.insert(name, instance);
+    registry.save()
+}
+
+/// Drop the registry entry of the instance on `socket` as it exits (if it has one).
+pub fn unregister(socket: &Path) {
+    let mut registry = Registry::load();
+    if !registry.remove_socket(socket) {
+        return;
+    }
+    if let Err(e) = registry.save() {
+        eprintln!("Failed to update the instance registry: {}", e);
+    }
+}
+
+/// Socket of the instance `name`, exiting with an error if it isn't running.
+pub fn resolve_or_exit(name: &str) -> PathBuf {
+    match Registry::load().resolve(name) {
+        Ok(socket) => socket.to_path_buf(),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(socket: &Path) -> Instance {
+        Instance {
+            socket: socket.to_path_buf(),
+            pid: 1,
+        }
+    }
+
+    #[test]
+    fn resolves_registered_instances() {
+        let dir = std::env::temp_dir().join(format!("manse-instances-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let socket = dir.join("webapp.sock");
+        std::fs::write(&socket, "").unwrap();
+
+        let mut registry = Registry::default();
+        registry.insert("webapp", instance(&socket));
+        assert_eq!(registry.resolve("webapp"), Ok(socket.as_path()));
+        assert_eq!(registry.running().map(|(name, _)| name).collect::<Vec<_>>(), ["webapp"]);
+        assert_eq!(
+            registry.resolve("api"),
+            Err("No instance named 'api' (running: webapp)".to_string())
+        );
+
+        std::fs::remove_file(&socket).unwrap();
+        assert_eq!(registry.resolve("webapp"), Err("Instance 'webapp' is not running".to_string()));
+        assert_eq!(registry.running().count(), 0);
+        std::fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn drops_exited_instances() {
+        let mut registry = Registry::default();
+        registry.insert("gone", instance(Path::new("/nonexistent/gone.sock")));
+        registry.insert("api", instance(Path::new("/nonexistent/api.sock")));
+        assert_eq!(registry.instances.keys().collect::<Vec<_>>(), ["api"]);
+
+        assert!(registry.remove_socket(Path::new("/nonexistent/api.sock")));
+        assert!(!registry.remove_socket(Path::new("/nonexistent/api.sock")));
+        assert_eq!(registry, Registry::default());
+    }
+}
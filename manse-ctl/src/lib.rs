@@ -0,0 +1,1353 @@
+//! Commands that only talk to a running instance over its control socket. Shared by
+//! the `manse` binary and the lightweight `manse-ctl`, which links neither egui nor
+//! alacritty.
+
+use clap::Subcommand;
+use ipc_protocol::{IpcClient, Request, Response, TerminalPlacement};
+use std::path::PathBuf;
+
+pub mod instances;
+pub mod shell;
+
+fn parse_placement(value: &str) -> Result<TerminalPlacement, String> {
+    TerminalPlacement::parse(value).ok_or_else(|| "expected before, after, start, end or an index".to_string())
+}
+
+/// Commands sent to a running instance
+#[derive(Subcommand)]
+pub enum Command {
+    /// Trigger restart of running instance
+    Restart {
+        /// Path to IPC socket
+        #[arg(short, long, default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+    },
+    /// Quit a running instance
+    Quit {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Skip the confirmation dialog
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Ping a running instance
+    Ping {
+        /// Path to IPC socket
+        #[arg(short, long, default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+    },
+    /// Rename a terminal
+    TermRename {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Terminal ID (defaults to $MANSE_TERMINAL)
+        #[arg(short, long, env = "MANSE_TERMINAL")]
+        terminal: String,
+        /// New title for the terminal
+        title: String,
+    },
+    /// Close a terminal
+    TermClose {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Terminal ID (defaults to $MANSE_TERMINAL)
+        #[arg(short, long, env = "MANSE_TERMINAL")]
+        terminal: String,
+        /// Skip the confirmation dialog
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Set terminal description
+    TermDesc {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Terminal ID (defaults to $MANSE_TERMINAL)
+        #[arg(short, long, env = "MANSE_TERMINAL")]
+        terminal: String,
+        /// Description for the terminal
+        description: String,
+    },
+    /// Set terminal icon (Nerd Font codepoint)
+    TermIcon {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Terminal ID (defaults to $MANSE_TERMINAL)
+        #[arg(short, long, env = "MANSE_TERMINAL")]
+        terminal: String,
+        /// Icon for the terminal (Nerd Font codepoint, empty string to clear)
+        icon: String,
+    },
+    /// Apply a named color scheme (from config.schemes) to a terminal
+    TermTheme {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Terminal ID (defaults to $MANSE_TERMINAL)
+        #[arg(short, long, env = "MANSE_TERMINAL")]
+        terminal: String,
+        /// Scheme name (empty string to reset to the base theme)
+        scheme: String,
+    },
+    /// Give a terminal an accent color, tinting its border, sidebar entry and minimap
+    /// rectangle
+    TermColor {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Terminal ID (defaults to $MANSE_TERMINAL)
+        #[arg(short, long, env = "MANSE_TERMINAL")]
+        terminal: String,
+        /// Color as "#rrggbb" (empty string to clear)
+        color: String,
+    },
+    /// Set a terminal's width as a fraction of the viewport (e.g. 0.333)
+    TermResize {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Terminal ID (defaults to $MANSE_TERMINAL)
+        #[arg(short, long, env = "MANSE_TERMINAL")]
+        terminal: String,
+        /// Width ratio, greater than 0 and at most 1
+        ratio: f32,
+    },
+    /// Pin a terminal to the left edge of the strip, where it stays while the others
+    /// scroll
+    TermPin {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Terminal ID (defaults to $MANSE_TERMINAL)
+        #[arg(short, long, env = "MANSE_TERMINAL")]
+        terminal: String,
+        /// Unpin it instead
+        #[arg(long)]
+        unpin: bool,
+    },
+    /// Move a terminal to a workspace (creates workspace if needed)
+    TermToWorkspace {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Terminal ID (defaults to $MANSE_TERMINAL)
+        #[arg(short, long, env = "MANSE_TERMINAL")]
+        terminal: String,
+        /// Name of the workspace to move to
+        #[arg(short, long)]
+        workspace_name: String,
+    },
+    /// Move a terminal to another running instance (given with --instance): a new shell
+    /// starts there in its directory, with its title, description, icon and color scheme,
+    /// typing its foreground command again. The original is closed.
+    TermToInstance {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Terminal ID (defaults to $MANSE_TERMINAL)
+        #[arg(short, long, env = "MANSE_TERMINAL")]
+        terminal: String,
+        /// Socket of the instance to move to, instead of naming it with --instance
+        #[arg(long)]
+        to_socket: Option<PathBuf>,
+    },
+    /// Take a terminal out of the layout with its shell still running, to be adopted by
+    /// another instance with term-attach. It is hung up if this instance quits first.
+    TermDetach {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Terminal ID (defaults to $MANSE_TERMINAL)
+        #[arg(short, long, env = "MANSE_TERMINAL")]
+        terminal: String,
+    },
+    /// Adopt a terminal detached with term-detach: its shell carries on in this instance
+    /// (given with --socket or --instance), with an empty scrollback
+    TermAttach {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// ID of the detached terminal
+        terminal: String,
+        /// Socket of the instance the terminal was detached in
+        #[arg(long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        from: PathBuf,
+        /// Workspace to open it in (defaults to the active one)
+        #[arg(short, long)]
+        workspace: Option<String>,
+    },
+    /// Notify a terminal (shows indicator until focused)
+    TermNotify {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Terminal ID (defaults to $MANSE_TERMINAL)
+        #[arg(short, long, env = "MANSE_TERMINAL")]
+        terminal: Option<String>,
+        /// Notify every terminal in this workspace instead of a single terminal
+        #[arg(short, long)]
+        workspace: Option<String>,
+        /// Message recorded in the notification history (e.g. "build finished")
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+    /// Print a terminal's text, including scrollback
+    TermDump {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Terminal ID (defaults to $MANSE_TERMINAL)
+        #[arg(short, long, env = "MANSE_TERMINAL")]
+        terminal: String,
+        /// Only print the last N lines
+        #[arg(short = 'n', long)]
+        lines: Option<usize>,
+    },
+    /// Send text to a terminal as if it were typed
+    TermSendText {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Terminal ID (defaults to $MANSE_TERMINAL)
+        #[arg(short, long, env = "MANSE_TERMINAL")]
+        terminal: Option<String>,
+        /// Send to every terminal in this workspace instead of a single terminal
+        #[arg(short, long)]
+        workspace: Option<String>,
+        /// Text to send (include a trailing newline to submit a command)
+        text: String,
+    },
+    /// List all terminals with their IDs, titles, descriptions and workspaces
+    TermList {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Print the raw JSON result
+        #[arg(long)]
+        json: bool,
+    },
+    /// Open a new terminal, optionally running a command (prints the new terminal's ID)
+    TermSpawn {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Workspace to open it in (created if needed; defaults to the active workspace)
+        #[arg(short, long)]
+        workspace: Option<String>,
+        /// Working directory (defaults to the current directory)
+        #[arg(short, long)]
+        cwd: Option<PathBuf>,
+        /// Title for the new terminal
+        #[arg(short, long)]
+        title: Option<String>,
+        /// Switch to the new terminal
+        #[arg(short, long)]
+        focus: bool,
+        /// Where it goes in the workspace: before, after, start, end, or an index
+        /// (defaults to `new_terminal_position`)
+        #[arg(short, long, value_parser = parse_placement)]
+        placement: Option<TerminalPlacement>,
+        /// Command to run in the new shell (e.g. `-- cargo watch`)
+        #[arg(last = true)]
+        command: Vec<String>,
+    },
+    /// Create a workspace with a new terminal and switch to it
+    WsCreate {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Name of the workspace
+        name: String,
+    },
+    /// Rename a workspace
+    WsRename {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Workspace name or 1-based index
+        workspace: String,
+        /// New name
+        name: String,
+    },
+    /// Delete a workspace
+    WsDelete {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Workspace name or 1-based index
+        workspace: String,
+        /// Close the workspace's terminals instead of refusing when it has any
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Put a workspace in a sidebar group, or take it out of its group
+    WsGroup {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Workspace name or 1-based index
+        workspace: String,
+        /// Group name (omit to ungroup the workspace)
+        group: Option<String>,
+    },
+    /// Delete every workspace in a sidebar group
+    GroupClose {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Group name
+        group: String,
+        /// Close the group's terminals instead of refusing when it has any
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// List all workspaces
+    WsList {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Print the raw JSON result
+        #[arg(long)]
+        json: bool,
+    },
+    /// Give every terminal in a workspace the same width
+    LayoutEqualize {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Workspace name or 1-based index (defaults to the active workspace)
+        #[arg(short, long)]
+        workspace: Option<String>,
+    },
+    /// Re-read init.lua in a running instance
+    ConfigReload {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+    },
+    /// Collect recent logs, the scrubbed config, perf counters and layout counts of a
+    /// running instance as JSON, for attaching to a bug report
+    DebugReport {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Write the report to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print events from a running instance as JSON lines until it exits
+    /// (terminal_created, terminal_closed, focus_changed, title_changed,
+    /// workspace_switched, notification, grid_changed)
+    Subscribe {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Only print events about this terminal, including its grid_changed events
+        /// (repeatable)
+        #[arg(short, long = "terminal")]
+        terminals: Vec<String>,
+        /// Events to print (all if none are given)
+        events: Vec<String>,
+    },
+}
+
+/// `session` commands sent to a running instance
+#[derive(Subcommand)]
+pub enum SessionCommand {
+    /// Save the workspaces and terminals of a running instance as a profile
+    Save {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Profile name
+        name: String,
+    },
+    /// Recreate a profile in a running instance, starting new shells
+    Load {
+        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
+        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
+        socket: PathBuf,
+        /// Profile name
+        name: String,
+    },
+}
+
+impl Command {
+    /// Socket the command is sent to
+    pub fn socket_mut(&mut self) -> &mut PathBuf {
+        match self {
+            Command::Restart { socket, .. }
+            | Command::Quit { socket, .. }
+            | Command::Ping { socket, .. }
+            | Command::TermRename { socket, .. }
+            | Command::TermClose { socket, .. }
+            | Command::TermDesc { socket, .. }
+            | Command::TermIcon { socket, .. }
+            | Command::TermTheme { socket, .. }
+            | Command::TermColor { socket, .. }
+            | Command::TermResize { socket, .. }
+            | Command::TermPin { socket, .. }
+            | Command::TermToWorkspace { socket, .. }
+            | Command::TermToInstance { socket, .. }
+            | Command::TermDetach { socket, .. }
+            | Command::TermAttach { socket, .. }
+            | Command::TermNotify { socket, .. }
+            | Command::TermSendText { socket, .. }
+            | Command::TermDump { socket, .. }
+            | Command::TermList { socket, .. }
+            | Command::TermSpawn { socket, .. }
+            | Command::WsCreate { socket, .. }
+            | Command::WsRename { socket, .. }
+            | Command::WsDelete { socket, .. }
+            | Command::WsGroup { socket, .. }
+            | Command::GroupClose { socket, .. }
+            | Command::WsList { socket, .. }
+            | Command::LayoutEqualize { socket, .. }
+            | Command::ConfigReload { socket, .. }
+            | Command::DebugReport { socket, .. }
+            | Command::Subscribe { socket, .. } => socket,
+        }
+    }
+}
+
+impl Command {
+    /// Address the instance on `socket` named with `--instance`: the destination of
+    /// `term-to-instance`, the instance any other command is sent to.
+    pub fn set_instance(&mut self, socket: PathBuf) {
+        match self {
+            Command::TermToInstance { to_socket, .. } => *to_socket = Some(socket),
+            _ => *self.socket_mut() = socket,
+        }
+    }
+}
+
+impl SessionCommand {
+    /// Socket the command is sent to
+    pub fn socket_mut(&mut self) -> &mut PathBuf {
+        match self {
+            SessionCommand::Save { socket, .. } | SessionCommand::Load { socket, .. } => socket,
+        }
+    }
+}
+
+/// Whether a response says the action is waiting on a confirmation dialog.
+fn pending_confirmation(response: &Response) -> bool {
+    response
+        .result
+        .as_ref()
+        .and_then(|r| r.get("pending_confirmation"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// Print a `term-list` result as one line per terminal (`*` marks the focused one).
+fn print_terminals(result: &serde_json::Value) {
+    let terminals = result["terminals"].as_array().map(Vec::as_slice).unwrap_or_default();
+    for terminal in terminals {
+        let marker = if terminal["focused"].as_bool().unwrap_or(false) { '*' } else { ' ' };
+        let mut line = format!(
+            "{} {}  [{}]  {}",
+            marker,
+            terminal["id"].as_str().unwrap_or_default(),
+            terminal["workspace"].as_str().unwrap_or_default(),
+            terminal["title"].as_str().unwrap_or_default(),
+        );
+        for key in ["description", "cli_description"] {
+            if let Some(text) = terminal[key].as_str().filter(|t| !t.is_empty()) {
+                line.push_str(" — ");
+                line.push_str(text);
+            }
+        }
+        println!("{}", line);
+    }
+}
+
+/// Print a `ws-list` result as one line per workspace (`*` marks the active one).
+fn print_workspaces(result: &serde_json::Value) {
+    let workspaces = result["workspaces"].as_array().map(Vec::as_slice).unwrap_or_default();
+    for workspace in workspaces {
+        let marker = if workspace["active"].as_bool().unwrap_or(false) { '*' } else { ' ' };
+        let group = workspace["group"].as_str().map(|group| format!("  [{}]", group)).unwrap_or_default();
+        println!(
+            "{} {}  {}  ({} terminals){}",
+            marker,
+            workspace["index"],
+            workspace["name"].as_str().unwrap_or_default(),
+            workspace["terminals"],
+            group,
+        );
+    }
+}
+
+/// One line about a running instance's workspaces, from its `workspace_list` result.
+fn workspace_summary(result: &serde_json::Value) -> String {
+    let workspaces = result["workspaces"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let terminals: u64 = workspaces.iter().filter_map(|ws| ws["terminals"].as_u64()).sum();
+    let active = workspaces
+        .iter()
+        .find(|ws| ws["active"].as_bool().unwrap_or(false))
+        .and_then(|ws| ws["name"].as_str())
+        .unwrap_or_default();
+    format!(
+        "{} workspaces, {} terminals, active: {}",
+        workspaces.len(),
+        terminals,
+        active
+    )
+}
+
+/// List the registered instances that are running, with their workspaces.
+pub fn run_instances(json: bool) {
+    let registry = instances::Registry::load();
+    let mut listed = Vec::new();
+    for (name, instance) in registry.running() {
+        let workspaces = IpcClient::connect(&instance.socket)
+            .ok()
+            .and_then(|mut client| client.request(&Request::WorkspaceList).ok())
+            .and_then(|response| response.result);
+        listed.push((name, instance, workspaces));
+    }
+
+    if json {
+        let entries: Vec<_> = listed
+            .iter()
+            .map(|(name, instance, workspaces)| {
+                serde_json::json!({
+                    "name": name,
+                    "pid": instance.pid,
+                    "socket": instance.socket,
+                    "workspaces": workspaces.as_ref().map(|result| &result["workspaces"]),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&entries).unwrap_or_default());
+        return;
+    }
+
+    if listed.is_empty() {
+        println!("No instances running");
+    }
+    for (name, instance, workspaces) in &listed {
+        let summary = match workspaces {
+            Some(result) => workspace_summary(result),
+            None => "not responding".to_string(),
+        };
+        println!(
+            "{}  pid {}  {}  {}",
+            name,
+            instance.pid,
+            instance.socket.display(),
+            summary
+        );
+    }
+}
+
+/// Run `command` against the instance on its socket, printing the outcome.
+pub fn run(command: Command) {
+    match command {
+        Command::Restart { socket } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::Restart)
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Restart initiated");
+            } else {
+                eprintln!(
+                    "Failed to restart: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::Quit { socket, force } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::Quit { force })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                if pending_confirmation(&response) {
+                    println!("Quit awaiting confirmation");
+                } else {
+                    println!("Quit initiated");
+                }
+            } else {
+                eprintln!(
+                    "Failed to quit: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::Ping { socket } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            match client.ping() {
+                Ok(()) => println!("Pong!"),
+                Err(e) => eprintln!("Ping failed: {}", e),
+            }
+        }
+        Command::TermRename {
+            socket,
+            terminal,
+            title,
+        } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::TermRename { terminal, title })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Terminal renamed");
+            } else {
+                eprintln!(
+                    "Failed to rename: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::TermClose {
+            socket,
+            terminal,
+            force,
+        } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::TermClose { terminal, force })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                if pending_confirmation(&response) {
+                    println!("Close awaiting confirmation");
+                } else {
+                    println!("Terminal closed");
+                }
+            } else {
+                eprintln!(
+                    "Failed to close: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::TermDesc {
+            socket,
+            terminal,
+            description,
+        } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::TermDesc { terminal, description })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Terminal description set");
+            } else {
+                eprintln!(
+                    "Failed to set description: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::TermIcon {
+            socket,
+            terminal,
+            icon,
+        } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::TermIcon { terminal, icon })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Terminal icon set");
+            } else {
+                eprintln!(
+                    "Failed to set icon: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::TermTheme {
+            socket,
+            terminal,
+            scheme,
+        } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::TermTheme { terminal, scheme })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Terminal color scheme set");
+            } else {
+                eprintln!(
+                    "Failed to set color scheme: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::TermColor {
+            socket,
+            terminal,
+            color,
+        } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::TermColor { terminal, color })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Terminal accent set");
+            } else {
+                eprintln!(
+                    "Failed to set accent: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::TermResize {
+            socket,
+            terminal,
+            ratio,
+        } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::TermResize { terminal, ratio })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Terminal resized");
+            } else {
+                eprintln!(
+                    "Failed to resize terminal: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::TermPin {
+            socket,
+            terminal,
+            unpin,
+        } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::TermPin {
+                    terminal,
+                    pinned: !unpin,
+                })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Terminal {}", if unpin { "unpinned" } else { "pinned" });
+            } else {
+                eprintln!(
+                    "Failed to pin terminal: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::TermToWorkspace {
+            socket,
+            terminal,
+            workspace_name,
+        } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::TermToWorkspace {
+                    terminal,
+                    workspace_name: workspace_name.clone(),
+                })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Terminal moved to workspace '{}'", workspace_name);
+            } else {
+                eprintln!(
+                    "Failed to move terminal: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::TermNotify {
+            socket,
+            terminal,
+            workspace,
+            message,
+        } => {
+            let request = match (workspace, terminal) {
+                (Some(workspace), _) => {
+                    Request::WorkspaceNotifyAll { workspace, message }
+                }
+                (None, Some(terminal)) => Request::TermNotify { terminal, message },
+                (None, None) => {
+                    eprintln!("No terminal specified (use --terminal, --workspace, or $MANSE_TERMINAL)");
+                    return;
+                }
+            };
+
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&request)
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Terminal notified");
+            } else {
+                eprintln!(
+                    "Failed to notify: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::TermToInstance {
+            socket,
+            terminal,
+            to_socket,
+        } => {
+            let Some(to_socket) = to_socket else {
+                eprintln!("No instance to move to (use --instance or --to-socket)");
+                return;
+            };
+            if to_socket == socket {
+                eprintln!("The terminal already runs in that instance");
+                return;
+            }
+
+            let mut source = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+            let exported = source
+                .request(&Request::TermExport { terminal: terminal.clone() })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+            if !exported.ok {
+                eprintln!(
+                    "Failed to read terminal: {}",
+                    exported.error.unwrap_or_else(|| "Unknown error".into())
+                );
+                return;
+            }
+            let exported = exported.result.unwrap_or_default();
+
+            let mut target = IpcClient::connect(&to_socket)
+                .map_err(|e| eprintln!("Failed to connect to {}: {}", to_socket.display(), e))
+                .unwrap();
+            let imported = target
+                .request(&Request::TermImport {
+                    workspace: exported["workspace"].as_str().unwrap_or("default").to_string(),
+                    terminal: exported["terminal"].clone(),
+                })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+            if !imported.ok {
+                eprintln!(
+                    "Failed to start the terminal there: {}",
+                    imported.error.unwrap_or_else(|| "Unknown error".into())
+                );
+                return;
+            }
+            let moved = imported.result.unwrap_or_default();
+
+            // Only close the original once its replacement runs
+            let closed = source
+                .request(&Request::TermClose { terminal, force: true })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+            if !closed.ok {
+                eprintln!(
+                    "Started a copy, but failed to close the original: {}",
+                    closed.error.unwrap_or_else(|| "Unknown error".into())
+                );
+                return;
+            }
+            println!("Terminal moved (now {})", moved["terminal"].as_str().unwrap_or_default());
+        }
+        Command::TermDetach { socket, terminal } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::TermDetach { terminal: terminal.clone() })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!(
+                    "Terminal detached, attach it with: manse term-attach {} --from {}",
+                    terminal,
+                    socket.display()
+                );
+            } else {
+                eprintln!(
+                    "Failed to detach terminal: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::TermAttach {
+            socket,
+            terminal,
+            from,
+            workspace,
+        } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::TermAttach { terminal, from, workspace })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Terminal attached");
+            } else {
+                eprintln!(
+                    "Failed to attach terminal: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::TermSendText {
+            socket,
+            terminal,
+            workspace,
+            text,
+        } => {
+            let request = match (workspace, terminal) {
+                (Some(workspace), _) => {
+                    Request::WorkspaceSendText { workspace, text }
+                }
+                (None, Some(terminal)) => Request::TermSendText { terminal, text },
+                (None, None) => {
+                    eprintln!("No terminal specified (use --terminal, --workspace, or $MANSE_TERMINAL)");
+                    return;
+                }
+            };
+
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&request)
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Text sent");
+            } else {
+                eprintln!(
+                    "Failed to send text: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::TermDump {
+            socket,
+            terminal,
+            lines,
+        } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::TermDump { terminal, lines })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                let result = response.result.unwrap_or_default();
+                println!("{}", result["text"].as_str().unwrap_or_default());
+            } else {
+                eprintln!(
+                    "Failed to read terminal: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::TermList { socket, json } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::TermList)
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                let result = response.result.unwrap_or_default();
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&result).unwrap_or_default());
+                } else {
+                    print_terminals(&result);
+                }
+            } else {
+                eprintln!(
+                    "Failed to list terminals: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::TermSpawn {
+            socket,
+            workspace,
+            cwd,
+            title,
+            focus,
+            placement,
+            command,
+        } => {
+            let request = Request::TermSpawn {
+                command: (!command.is_empty()).then(|| shell::command_line(&command)),
+                cwd: cwd
+                    .or_else(|| std::env::current_dir().ok())
+                    .map(|dir| std::fs::canonicalize(&dir).unwrap_or(dir)),
+                workspace,
+                title,
+                focus,
+                placement,
+            };
+
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&request)
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                let terminal = response.result.as_ref().and_then(|r| r["terminal"].as_str());
+                println!("{}", terminal.unwrap_or_default());
+            } else {
+                eprintln!(
+                    "Failed to spawn terminal: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::WsCreate { socket, name } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::WorkspaceCreate { name: name.clone() })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                let terminal = response.result.as_ref().and_then(|r| r["terminal"].as_str());
+                println!("Workspace {} created with terminal {}", name, terminal.unwrap_or("?"));
+            } else {
+                eprintln!(
+                    "Failed to create workspace: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::WsRename {
+            socket,
+            workspace,
+            name,
+        } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::WorkspaceRename { workspace, name })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Workspace renamed");
+            } else {
+                eprintln!(
+                    "Failed to rename workspace: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::WsDelete {
+            socket,
+            workspace,
+            force,
+        } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::WorkspaceDelete { workspace, force })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Workspace deleted");
+            } else {
+                eprintln!(
+                    "Failed to delete workspace: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::LayoutEqualize { socket, workspace } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::LayoutEqualize { workspace })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Widths equalized");
+            } else {
+                eprintln!(
+                    "Failed to equalize widths: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::WsGroup {
+            socket,
+            workspace,
+            group,
+        } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::WorkspaceGroup { workspace, group })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Workspace group set");
+            } else {
+                eprintln!(
+                    "Failed to set workspace group: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::GroupClose { socket, group, force } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::GroupClose { group, force })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                let closed = response.result.as_ref().and_then(|r| r["closed"].as_u64()).unwrap_or(0);
+                println!("Group closed ({} terminals)", closed);
+            } else {
+                eprintln!(
+                    "Failed to close group: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::WsList { socket, json } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::WorkspaceList)
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                let result = response.result.unwrap_or_default();
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&result).unwrap_or_default());
+                } else {
+                    print_workspaces(&result);
+                }
+            } else {
+                eprintln!(
+                    "Failed to list workspaces: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::ConfigReload { socket } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::ConfigReload)
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                println!("Config reloaded");
+            } else {
+                eprintln!(
+                    "Failed to reload config: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::DebugReport { socket, output } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::DebugReport)
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                let report =
+                    serde_json::to_string_pretty(&response.result.unwrap_or_default()).unwrap_or_default();
+                match output {
+                    Some(path) => match std::fs::write(&path, report + "\n") {
+                        Ok(()) => println!("Report written to {}", path.display()),
+                        Err(e) => eprintln!("Failed to write {}: {}", path.display(), e),
+                    },
+                    None => println!("{}", report),
+                }
+            } else {
+                eprintln!(
+                    "Failed to collect debug report: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        Command::Subscribe {
+            socket,
+            terminals,
+            events,
+        } => {
+            let client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            match client.subscribe_terminals(events, terminals) {
+                Ok(events) => {
+                    for event in events {
+                        if let Ok(json) = serde_json::to_string(&event) {
+                            println!("{}", json);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Failed to subscribe: {}", e),
+            }
+        }
+    }
+}
+
+/// Run a `session` command against the instance on its socket, printing the outcome.
+pub fn run_session(command: SessionCommand) {
+    match command {
+        SessionCommand::Save { socket, name } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::SessionSave { name })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                let result = response.result.unwrap_or_default();
+                println!("Session saved to {}", result["path"].as_str().unwrap_or_default());
+            } else {
+                eprintln!(
+                    "Failed to save session: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+        SessionCommand::Load { socket, name } => {
+            let mut client = IpcClient::connect(&socket)
+                .map_err(|e| eprintln!("Failed to connect: {}", e))
+                .unwrap();
+
+            let response = client
+                .request(&Request::SessionLoad { name })
+                .map_err(|e| eprintln!("Request failed: {}", e))
+                .unwrap();
+
+            if response.ok {
+                let result = response.result.unwrap_or_default();
+                println!("Session loaded ({} terminals)", result["terminals"]);
+            } else {
+                eprintln!(
+                    "Failed to load session: {}",
+                    response.error.unwrap_or_else(|| "Unknown error".into())
+                );
+            }
+        }
+    }
+}
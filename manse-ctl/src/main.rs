@@ -0,0 +1,51 @@
+use clap::{Parser, Subcommand};
+use manse_ctl::{instances, Command, SessionCommand};
+
+#[derive(Parser)]
+#[command(name = "manse-ctl")]
+#[command(about = "Control a running manse instance (the client commands of `manse`, without the GUI)")]
+struct Cli {
+    /// Send the command to the instance started with `manse --instance <NAME> run`
+    /// instead of the one on --socket (for term-to-instance: the instance to move to)
+    #[arg(long, global = true)]
+    instance: Option<String>,
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    #[command(flatten)]
+    Client(Command),
+    /// Save or load named layouts (stored in ~/.config/manse/sessions/)
+    Session {
+        #[command(subcommand)]
+        command: SessionCommand,
+    },
+    /// List running instances with their sockets and workspaces
+    Instances {
+        /// Print the raw JSON list
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let instance = cli.instance.as_deref().map(instances::resolve_or_exit);
+    match cli.command {
+        Commands::Client(mut command) => {
+            if let Some(socket) = instance {
+                command.set_instance(socket);
+            }
+            manse_ctl::run(command)
+        }
+        Commands::Session { mut command } => {
+            if let Some(socket) = instance {
+                *command.socket_mut() = socket;
+            }
+            manse_ctl::run_session(command)
+        }
+        Commands::Instances { json } => manse_ctl::run_instances(json),
+    }
+}
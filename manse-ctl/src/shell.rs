@@ -0,0 +1,51 @@
+//! Shell lines for commands passed on the command line, and POSIX quoting (also used by
+//! the app).
+
+/// Shell line for a command given as separate arguments. A single argument is taken as a
+/// shell line already (`"cargo watch -x test"`); otherwise words are quoted where needed.
+pub fn command_line(args: &[String]) -> String {
+    match args {
+        [line] => line.clone(),
+        _ => args
+            .iter()
+            .map(|arg| if is_plain_word(arg) { arg.clone() } else { quote(arg) })
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+/// Quote `s` as a single POSIX shell word.
+pub fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Whether `s` means the same to the shell with or without quotes.
+fn is_plain_word(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,@%+".contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_plain() {
+        assert_eq!(quote("/tmp/a b"), "'/tmp/a b'");
+    }
+
+    #[test]
+    fn quote_single_quotes() {
+        assert_eq!(quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn command_line_from_args() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(command_line(&args(&["cargo watch -x test"])), "cargo watch -x test");
+        assert_eq!(command_line(&args(&["cargo", "watch", "-x", "test"])), "cargo watch -x test");
+        assert_eq!(command_line(&args(&["echo", "a b", "it's", "$HOME"])), r"echo 'a b' 'it'\''s' '$HOME'");
+        assert_eq!(command_line(&[]), "");
+    }
+}
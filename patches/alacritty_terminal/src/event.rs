@@ -20,6 +20,9 @@ pub enum Event {
     /// Working directory change (OSC 7).
     WorkingDirectory(String),
 
+    /// Desktop notification (OSC 9 / OSC 777), with an optional title and the body.
+    Notification(Option<String>, String),
+
     /// Reset to the default window title.
     ResetTitle,
 
@@ -70,6 +73,7 @@ impl Debug for Event {
             Event::PtyWrite(text) => write!(f, "PtyWrite({text})"),
             Event::Title(title) => write!(f, "Title({title})"),
             Event::WorkingDirectory(path) => write!(f, "WorkingDirectory({path})"),
+            Event::Notification(title, body) => write!(f, "Notification({title:?}, {body})"),
             Event::CursorBlinkingChange => write!(f, "CursorBlinkingChange"),
             Event::MouseCursorDirty => write!(f, "MouseCursorDirty"),
             Event::ResetTitle => write!(f, "ResetTitle"),
@@ -6,6 +6,7 @@ use std::fmt::{self, Display, Formatter};
 use std::fs::File;
 use std::io::{self, ErrorKind, Read, Write};
 use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
 use std::thread::JoinHandle;
@@ -37,6 +38,14 @@ pub enum Msg {
 
     /// Instruction to resize the PTY.
     Resize(WindowSize),
+
+    /// Stop (`true`) or resume (`false`) reading from the PTY.
+    ///
+    /// While paused, output stays in the kernel buffer until the producer is flow-controlled.
+    SetPaused(bool),
+
+    /// Shut down without hanging up the child, whose PTY is taken over elsewhere.
+    Detach,
 }
 
 /// The main event loop.
@@ -52,6 +61,8 @@ pub struct EventLoop<T: tty::EventedPty, U: EventListener> {
     event_proxy: U,
     drain_on_exit: bool,
     ref_test: bool,
+    paused: bool,
+    bytes_read: Arc<AtomicU64>,
 }
 
 impl<T, U> EventLoop<T, U>
@@ -78,6 +89,8 @@ where
             event_proxy,
             drain_on_exit,
             ref_test,
+            paused: false,
+            bytes_read: Arc::new(AtomicU64::new(0)),
         })
     }
 
@@ -85,6 +98,11 @@ where
         EventLoopSender { sender: self.tx.clone(), poller: self.poll.clone() }
     }
 
+    /// Total bytes read from the PTY, updated after every read batch.
+    pub fn bytes_read(&self) -> Arc<AtomicU64> {
+        self.bytes_read.clone()
+    }
+
     /// Drain the channel.
     ///
     /// Returns `false` when a shutdown message was received.
@@ -93,7 +111,12 @@ where
             match msg {
                 Msg::Input(input) => state.write_list.push_back(input),
                 Msg::Resize(window_size) => self.pty.on_resize(window_size),
+                Msg::SetPaused(paused) => self.paused = paused,
                 Msg::Shutdown => return false,
+                Msg::Detach => {
+                    self.pty.detach();
+                    return false;
+                },
             }
         }
 
@@ -162,6 +185,8 @@ where
             }
         }
 
+        self.bytes_read.fetch_add(processed as u64, Ordering::Relaxed);
+
         // Queue terminal redraw unless all processed bytes were synchronized.
         if state.parser.sync_bytes_count() < processed && processed > 0 {
             self.event_proxy.send_event(Event::Wakeup);
@@ -170,6 +195,15 @@ where
         Ok(())
     }
 
+    /// Report the child's exit and mark the terminal as exited.
+    fn child_exited(&mut self, code: Option<i32>) {
+        if let Some(code) = code {
+            self.event_proxy.send_event(Event::ChildExit(code));
+        }
+        self.terminal.lock().exit();
+        self.event_proxy.send_event(Event::Wakeup);
+    }
+
     #[inline]
     fn pty_write(&mut self, state: &mut State) -> io::Result<()> {
         state.ensure_next();
@@ -258,25 +292,27 @@ where
                         tty::PTY_CHILD_EVENT_TOKEN => {
                             if let Some(tty::ChildEvent::Exited(code)) = self.pty.next_child_event()
                             {
-                                if let Some(code) = code {
-                                    self.event_proxy.send_event(Event::ChildExit(code));
-                                }
                                 if self.drain_on_exit {
                                     let _ = self.pty_read(&mut state, &mut buf, pipe.as_mut());
                                 }
-                                self.terminal.lock().exit();
-                                self.event_proxy.send_event(Event::Wakeup);
+                                self.child_exited(code);
                                 break 'event_loop;
                             }
                         },
 
                         tty::PTY_READ_WRITE_TOKEN => {
                             if event.is_interrupt() {
+                                if let Some(tty::ChildEvent::Exited(code)) =
+                                    self.pty.hangup_event()
+                                {
+                                    self.child_exited(code);
+                                    break 'event_loop;
+                                }
                                 // Don't try to do I/O on a dead PTY.
                                 continue;
                             }
 
-                            if event.readable {
+                            if event.readable && !self.paused {
                                 if let Err(err) = self.pty_read(&mut state, &mut buf, pipe.as_mut())
                                 {
                                     // On Linux, a `read` on the master side of a PTY can fail
@@ -286,6 +322,12 @@ where
                                     // blocking.
                                     #[cfg(target_os = "linux")]
                                     if err.raw_os_error() == Some(libc::EIO) {
+                                        if let Some(tty::ChildEvent::Exited(code)) =
+                                            self.pty.hangup_event()
+                                        {
+                                            self.child_exited(code);
+                                            break 'event_loop;
+                                        }
                                         continue;
                                     }
 
@@ -305,10 +347,12 @@ where
                     }
                 }
 
-                // Register write interest if necessary.
+                // Register write interest if necessary, and read interest unless paused.
                 let needs_write = state.needs_write();
-                if needs_write != interest.writable {
+                let wants_read = !self.paused;
+                if needs_write != interest.writable || wants_read != interest.readable {
                     interest.writable = needs_write;
+                    interest.readable = wants_read;
 
                     // Re-register with new interest.
                     self.pty.reregister(&self.poll, interest, poll_opts).unwrap();
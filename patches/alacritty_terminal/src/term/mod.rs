@@ -2240,6 +2240,12 @@ impl<T: EventListener> Handler for Term<T> {
         }
     }
 
+    #[inline]
+    fn desktop_notification(&mut self, title: Option<String>, body: String) {
+        trace!("Desktop notification {title:?}: '{body}'");
+        self.event_proxy.send_event(Event::Notification(title, body));
+    }
+
     #[inline]
     fn push_title(&mut self) {
         trace!("Pushing '{:?}' onto title stack", self.title);
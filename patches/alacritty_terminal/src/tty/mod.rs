@@ -96,6 +96,16 @@ pub trait EventedPty: EventedReadWrite {
     ///
     /// Returns `Some(event)` on success, or `None` if there are no events to retrieve.
     fn next_child_event(&mut self) -> Option<ChildEvent>;
+
+    /// Exit of the child once the PTY hung up, for a child no event will be seen for
+    /// (one adopted from another process). `None` while events are still to come.
+    fn hangup_event(&mut self) -> Option<ChildEvent> {
+        None
+    }
+
+    /// Let go of the child: dropping the PTY no longer hangs it up, for a PTY whose
+    /// master lives on in another descriptor or process.
+    fn detach(&mut self) {}
 }
 
 /// Setup environment variables.
@@ -463,6 +463,29 @@ impl EventedReadWrite for Pty {
 }
 
 impl EventedPty for Pty {
+    fn hangup_event(&mut self) -> Option<ChildEvent> {
+        if self.child.is_some() {
+            return None;
+        }
+        // ECHILD: not our child, so no SIGCHLD comes for it. WNOWAIT leaves our own
+        // children to be reaped by `next_child_event`.
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        let result = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                self.child_pid as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOHANG | libc::WNOWAIT,
+            )
+        };
+        let adopted = result < 0 && Error::last_os_error().raw_os_error() == Some(libc::ECHILD);
+        adopted.then_some(ChildEvent::Exited(None))
+    }
+
+    fn detach(&mut self) {
+        self.child = None;
+    }
+
     #[inline]
     fn next_child_event(&mut self) -> Option<ChildEvent> {
         // See if there has been a SIGCHLD.
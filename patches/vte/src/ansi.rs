@@ -499,6 +499,9 @@ pub trait Handler {
     /// OSC 7 to set working directory.
     fn set_working_directory(&mut self, _: Option<String>) {}
 
+    /// OSC 9 or OSC 777;notify desktop notification, with an optional title.
+    fn desktop_notification(&mut self, _title: Option<String>, _body: String) {}
+
     /// Set the cursor style.
     fn set_cursor_style(&mut self, _: Option<CursorStyle>) {}
 
@@ -1387,6 +1390,19 @@ where
                 unhandled(params);
             },
 
+            // Desktop notification (OSC 9 from iTerm2). `9;4;...` is ConEmu's progress report.
+            b"9" if params.len() >= 2 && params[1] != b"4" => {
+                let body = String::from_utf8_lossy(&params[1..].join(&b';')).into_owned();
+                self.handler.desktop_notification(None, body);
+            },
+
+            // Desktop notification with a title (OSC 777;notify;title;body from urxvt).
+            b"777" if params.len() >= 3 && params[1] == b"notify" => {
+                let title = String::from_utf8_lossy(params[2]).into_owned();
+                let body = String::from_utf8_lossy(&params[3..].join(&b';')).into_owned();
+                self.handler.desktop_notification(Some(title), body);
+            },
+
             // Set color index.
             b"4" => {
                 if params.len() <= 1 || params.len() % 2 == 0 {
@@ -2071,6 +2087,7 @@ mod tests {
         identity_reported: bool,
         color: Option<Rgb>,
         reset_colors: Vec<usize>,
+        notifications: Vec<(Option<String>, String)>,
     }
 
     impl Handler for MockHandler {
@@ -2102,6 +2119,10 @@ mod tests {
         fn reset_color(&mut self, index: usize) {
             self.reset_colors.push(index)
         }
+
+        fn desktop_notification(&mut self, title: Option<String>, body: String) {
+            self.notifications.push((title, body));
+        }
     }
 
     impl Default for MockHandler {
@@ -2113,6 +2134,7 @@ mod tests {
                 identity_reported: false,
                 color: None,
                 reset_colors: Vec::new(),
+                notifications: Vec::new(),
             }
         }
     }
@@ -2316,6 +2338,30 @@ mod tests {
         assert_eq!(handler.reset_colors, vec![1]);
     }
 
+    #[test]
+    fn parse_osc9_notification() {
+        let bytes: &[u8] = b"\x1b]9;build done; 0 errors\x07\x1b]9;4;1;50\x07";
+
+        let mut parser = Processor::<TestSyncHandler>::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, bytes);
+
+        assert_eq!(handler.notifications, vec![(None, "build done; 0 errors".into())]);
+    }
+
+    #[test]
+    fn parse_osc777_notification() {
+        let bytes: &[u8] = b"\x1b]777;notify;make;done\x1b\\\x1b]777;preexec\x1b\\";
+
+        let mut parser = Processor::<TestSyncHandler>::new();
+        let mut handler = MockHandler::default();
+
+        parser.advance(&mut handler, bytes);
+
+        assert_eq!(handler.notifications, vec![(Some("make".into()), "done".into())]);
+    }
+
     #[test]
     fn parse_osc104_reset_all_colors() {
         let bytes: &[u8] = b"\x1b]104;\x1b\\";
@@ -0,0 +1,295 @@
+//! Single entry point for user actions, with macro recording and playback.
+
+use crate::ui::{ActiveDialog, Command};
+use eframe::egui;
+
+use super::App;
+
+/// Something the user (or a script) asked the window manager to do.
+///
+/// Keybindings, the command palette, the sidebar, follow/move-to-spot picks,
+/// dialogs and IPC all go through `App::dispatch`, so they can be recorded.
+#[derive(Clone, PartialEq)]
+pub enum Action {
+    /// Palette command or keybinding
+    Command(Command),
+    /// Make the workspace at this index active
+    SwitchWorkspace(usize),
+    /// Focus a terminal by position
+    FocusTerminal { workspace: usize, terminal: usize },
+    /// Move the focused terminal before the terminal at this position
+    MoveFocusedTo { workspace: usize, terminal: usize },
+    /// Set the focused terminal's in-app description
+    DescribeFocused(String),
+    /// Set a terminal's custom title (empty clears it)
+    RenameTerminal { panel_id: u64, title: String },
+    /// Move a terminal to the named workspace, creating it if needed
+    MoveToWorkspace { panel_id: u64, workspace: String },
+    /// Add a terminal to or remove it from the broadcast set
+    ToggleBroadcast { panel_id: u64 },
+    /// Set a terminal's accent color ("#rrggbb", `None` clears it)
+    SetAccent { panel_id: u64, accent: Option<String> },
+    /// Fold a sidebar group to its header, or unfold it
+    ToggleGroup { group: String },
+    /// Ask to close every terminal in a group
+    CloseGroup { group: String },
+}
+
+impl Action {
+    /// Whether replaying this action later does the same thing.
+    ///
+    /// Commands that only open a prompt are skipped (their outcome is recorded
+    /// instead), as are actions that target a terminal by its internal id.
+    pub fn is_recordable(&self) -> bool {
+        match self {
+            Action::Command(cmd) => !matches!(
+                cmd,
+                Command::RunInNewTerminal
+                    | Command::FollowMode
+                    | Command::MoveToSpot
+                    | Command::SetDescription
+                    | Command::RenameTerminal
+                    | Command::RenameWorkspace
+                    | Command::PipeSelection
+                    | Command::SaveScreen
+                    | Command::SaveScrollback
+                    | Command::RecordMacro
+                    | Command::PlayMacro
+                    | Command::UndoWorkspaceRemoval
+            ),
+            Action::SwitchWorkspace(_)
+            | Action::FocusTerminal { .. }
+            | Action::MoveFocusedTo { .. }
+            | Action::DescribeFocused(_) => true,
+            Action::RenameTerminal { .. }
+            | Action::MoveToWorkspace { .. }
+            | Action::ToggleBroadcast { .. }
+            | Action::SetAccent { .. }
+            | Action::ToggleGroup { .. }
+            | Action::CloseGroup { .. } => false,
+        }
+    }
+}
+
+impl App {
+    /// Perform `action`, recording it if a macro is being recorded.
+    pub(crate) fn dispatch(&mut self, ctx: &egui::Context, action: Action) -> Result<(), String> {
+        self.macros.record(&action);
+
+        match action {
+            Action::Command(cmd) => self.execute_command(cmd, ctx),
+            Action::SwitchWorkspace(workspace) => {
+                if workspace >= self.workspaces.len() {
+                    return Err(format!("No workspace at index {}", workspace));
+                }
+                self.active_workspace = workspace;
+            }
+            Action::FocusTerminal { workspace, terminal } => {
+                let ws = self
+                    .workspaces
+                    .get_mut(workspace)
+                    .filter(|ws| terminal < ws.panel_order.len())
+                    .ok_or_else(|| format!("No terminal at {}:{}", workspace, terminal))?;
+                ws.focused_index = terminal;
+                self.active_workspace = workspace;
+            }
+            Action::MoveFocusedTo { workspace, terminal } => {
+                if workspace >= self.workspaces.len() {
+                    return Err(format!("No workspace at index {}", workspace));
+                }
+                let ws = self.active_workspace();
+                let source = ws.name.clone();
+                let moved = ws.panel_order.get(ws.focused_index).copied();
+                self.move_focused_to_spot(workspace, terminal);
+                if let Some(panel_id) = moved {
+                    self.hold_emptied_workspace(ctx, &source, panel_id);
+                }
+            }
+            Action::DescribeFocused(description) => {
+                if let Some(panel) = self.focused_panel_mut() {
+                    panel.description = description;
+                }
+            }
+            Action::RenameTerminal { panel_id, title } => {
+                let panel = self.panels.get_mut(&panel_id).ok_or("Terminal not found")?;
+                panel.custom_title = if title.is_empty() { None } else { Some(title) };
+            }
+            Action::MoveToWorkspace { panel_id, workspace } => {
+                let source = self
+                    .workspaces
+                    .iter()
+                    .find(|ws| ws.panel_order.contains(&panel_id))
+                    .map(|ws| ws.name.clone());
+                self.move_to_workspace(panel_id, &workspace);
+                match source {
+                    Some(source) => self.hold_emptied_workspace(ctx, &source, panel_id),
+                    None => self.cleanup_empty_workspaces(),
+                }
+            }
+            Action::ToggleBroadcast { panel_id } => {
+                let panel = self.panels.get_mut(&panel_id).ok_or("Terminal not found")?;
+                panel.broadcast = !panel.broadcast;
+            }
+            Action::SetAccent { panel_id, accent } => {
+                let panel = self.panels.get_mut(&panel_id).ok_or("Terminal not found")?;
+                panel.accent = accent;
+            }
+            Action::ToggleGroup { group } => {
+                if !self.collapsed_groups.remove(&group) {
+                    self.collapsed_groups.insert(group);
+                }
+            }
+            Action::CloseGroup { group } => {
+                self.active_dialog = ActiveDialog::ConfirmCloseGroup { group };
+            }
+        }
+        Ok(())
+    }
+
+    /// Dispatch a palette command or keybinding (commands themselves never fail).
+    pub(crate) fn dispatch_command(&mut self, ctx: &egui::Context, cmd: Command) {
+        let _ = self.dispatch(ctx, Action::Command(cmd));
+    }
+
+    /// Start recording a macro, or stop and keep the recorded one.
+    pub(crate) fn toggle_macro_recording(&mut self) {
+        if let Some(count) = self.macros.toggle_recording() {
+            log::info!("Recorded macro with {} actions", count);
+        }
+    }
+
+    /// Replay the last recorded macro.
+    pub(crate) fn play_macro(&mut self, ctx: &egui::Context) {
+        let actions = match self.macros.playback() {
+            Ok(actions) => actions,
+            Err(e) => {
+                log::warn!("{}", e);
+                return;
+            }
+        };
+        if let Err(e) = play(actions, |action| self.dispatch(ctx, action)) {
+            log::warn!("Macro stopped: {}", e);
+        }
+    }
+}
+
+/// The macro being recorded and the last recorded one. There is a single macro: a new
+/// recording replaces it, and it is not kept across restarts.
+#[derive(Default)]
+pub(crate) struct Macros {
+    /// Actions recorded so far while a macro is being recorded
+    recording: Option<Vec<Action>>,
+    /// Last recorded macro
+    recorded: Vec<Action>,
+}
+
+impl Macros {
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Note `action` being performed: kept if a macro is being recorded and replaying
+    /// it does the same thing.
+    pub fn record(&mut self, action: &Action) {
+        if let Some(recording) = &mut self.recording {
+            if action.is_recordable() {
+                recording.push(action.clone());
+            }
+        }
+    }
+
+    /// Start recording, or stop and keep the recording as the macro (returning how
+    /// many actions it has).
+    pub fn toggle_recording(&mut self) -> Option<usize> {
+        match self.recording.take() {
+            Some(actions) => {
+                self.recorded = actions;
+                Some(self.recorded.len())
+            }
+            None => {
+                self.recording = Some(Vec::new());
+                None
+            }
+        }
+    }
+
+    /// The actions of the macro, to replay. Not while recording: the macro would
+    /// replay into the new one.
+    pub fn playback(&self) -> Result<Vec<Action>, String> {
+        if self.is_recording() {
+            return Err("Not playing macro while recording one".to_string());
+        }
+        Ok(self.recorded.clone())
+    }
+}
+
+/// Perform `actions` in order with `perform`, stopping at the first that fails.
+fn play(actions: Vec<Action>, perform: impl FnMut(Action) -> Result<(), String>) -> Result<(), String> {
+    actions.into_iter().try_for_each(perform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_only_while_recording() {
+        let mut macros = Macros::default();
+        macros.record(&Action::SwitchWorkspace(1));
+        assert_eq!(macros.toggle_recording(), None);
+        assert!(macros.is_recording());
+        macros.record(&Action::SwitchWorkspace(2));
+        assert_eq!(macros.toggle_recording(), Some(1));
+        assert!(macros.playback().unwrap() == vec![Action::SwitchWorkspace(2)]);
+    }
+
+    #[test]
+    fn skips_prompt_only_commands() {
+        let mut macros = Macros::default();
+        macros.toggle_recording();
+        macros.record(&Action::Command(Command::NewTerminal));
+        macros.record(&Action::Command(Command::SetDescription));
+        macros.record(&Action::DescribeFocused("api".to_string()));
+        macros.record(&Action::Command(Command::RenameWorkspace));
+        macros.record(&Action::ToggleBroadcast { panel_id: 3 });
+        macros.toggle_recording();
+        let recorded = macros.playback().unwrap();
+        assert!(recorded == vec![Action::Command(Command::NewTerminal), Action::DescribeFocused("api".to_string())]);
+    }
+
+    #[test]
+    fn no_playback_while_recording() {
+        let mut macros = Macros::default();
+        macros.toggle_recording();
+        macros.record(&Action::SwitchWorkspace(0));
+        macros.toggle_recording();
+        macros.toggle_recording();
+        // Playing is refused, and the play command is not recorded either
+        assert!(macros.playback().is_err());
+        macros.record(&Action::Command(Command::PlayMacro));
+        macros.record(&Action::Command(Command::RecordMacro));
+        assert_eq!(macros.toggle_recording(), Some(0));
+    }
+
+    #[test]
+    fn playback_stops_at_a_failing_action() {
+        let actions = vec![
+            Action::SwitchWorkspace(0),
+            Action::SwitchWorkspace(9),
+            Action::SwitchWorkspace(1),
+        ];
+        let mut performed = Vec::new();
+        let result = play(actions, |action| {
+            let Action::SwitchWorkspace(index) = action else {
+                unreachable!();
+            };
+            if index >= 2 {
+                return Err(format!("No workspace at index {}", index));
+            }
+            performed.push(index);
+            Ok(())
+        });
+        assert_eq!(result, Err("No workspace at index 9".to_string()));
+        assert_eq!(performed, vec![0]);
+    }
+}
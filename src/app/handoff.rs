@@ -0,0 +1,199 @@
+//! Terminals moved between instances with `manse term-detach` / `manse term-attach`.
+//! The detaching instance holds the PTY master until another instance asks for it
+//! (`term_hand_off`), which passes it over the IPC socket (SCM_RIGHTS) along with the
+//! terminal's state. The shell keeps running throughout.
+
+use crate::persist::PersistedTerminal;
+use eframe::egui;
+use ipc_protocol::{IpcClient, PendingRequest, Request, Response};
+use std::io::ErrorKind;
+use std::os::fd::{AsRawFd, IntoRawFd, OwnedFd};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use super::restore;
+use super::App;
+
+/// A terminal taken out of the layout, waiting to be adopted
+pub(crate) struct DetachedTerminal {
+    /// Title, description, width and the like; `pty_fd` is stale
+    state: PersistedTerminal,
+    /// Our descriptor for the PTY master. Closing it hangs up the shell.
+    fd: OwnedFd,
+}
+
+/// How long to wait for the detaching instance to hand a terminal over
+const HAND_OFF_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A `term_attach` waiting on the detaching instance's reply
+pub(crate) struct PendingAttach {
+    /// The `term_attach` request, answered once the terminal is in
+    request: PendingRequest,
+    terminal: String,
+    workspace: Option<String>,
+    reply: Receiver<Result<(serde_json::Value, OwnedFd), String>>,
+}
+
+impl App {
+    /// Take `terminal` out of its workspace without ending its shell, and hold it for
+    /// `term_attach`.
+    pub(super) fn detach_terminal(&mut self, terminal: &str) -> Result<(), String> {
+        let panel_id = self
+            .panels
+            .iter()
+            .find(|(_, p)| p.id == terminal)
+            .map(|(&id, _)| id)
+            .ok_or_else(|| format!("Terminal not found: {}", terminal))?;
+        let state = self.panels[&panel_id]
+            .to_persisted(panel_id)
+            .ok_or("Only terminals running on a PTY can be detached")?;
+
+        let mut panel = self.remove_panel(panel_id)?;
+        let Some((fd, _)) = panel.backend.detach() else {
+            // Put it back rather than hang up its shell
+            self.panels.insert(panel_id, panel);
+            let ws = self.active_workspace_mut();
+            ws.panel_order.push(panel_id);
+            ws.invalidate_positions();
+            return Err("Failed to detach the terminal's PTY".to_string());
+        };
+        self.detached.push(DetachedTerminal { state, fd });
+        Ok(())
+    }
+
+    /// Give up detached `terminal`: its state as JSON and its PTY master.
+    pub(super) fn hand_off(&mut self, terminal: &str) -> Result<(serde_json::Value, OwnedFd), String> {
+        let index = self
+            .detached
+            .iter()
+            .position(|d| d.state.external_id == terminal)
+            .ok_or_else(|| format!("No detached terminal: {}", terminal))?;
+        let state = serde_json::to_value(&self.detached[index].state).map_err(|e| e.to_string())?;
+        Ok((state, self.detached.remove(index).fd))
+    }
+
+    /// Adopt `terminal`, detached in the instance on socket `from` (possibly this one),
+    /// at the end of `workspace` (the active one when `None`) and focus it, answering
+    /// `request` once it is in. Another instance is asked from a worker thread, since it
+    /// answers from its own frame (or not at all, if it hangs); `finish_attaches` adopts
+    /// the terminal when the reply arrives.
+    pub(super) fn attach_terminal(
+        &mut self,
+        ctx: &egui::Context,
+        request: PendingRequest,
+        terminal: String,
+        from: PathBuf,
+        workspace: Option<String>,
+    ) {
+        if self.panels.values().any(|p| p.id == terminal)
+            || self.attaching.iter().any(|a| a.terminal == terminal)
+        {
+            request.respond(Response::error(format!("Terminal already exists: {}", terminal)));
+            return;
+        }
+
+        // Asking ourselves over the socket would wait on this very frame
+        if self.socket_path.as_deref() == Some(from.as_path()) {
+            let result = self
+                .hand_off(&terminal)
+                .and_then(|(state, fd)| self.adopt_terminal(ctx, state, fd, workspace.as_deref()));
+            request.respond(attach_response(&terminal, result));
+            return;
+        }
+
+        let (reply_tx, reply) = mpsc::channel();
+        let repaint = ctx.clone();
+        let asked = terminal.clone();
+        thread::spawn(move || {
+            let _ = reply_tx.send(request_hand_off(&from, &asked));
+            repaint.request_repaint();
+        });
+        self.attaching.push(PendingAttach {
+            request,
+            terminal,
+            workspace,
+            reply,
+        });
+    }
+
+    /// Adopt the terminals of `attach_terminal` whose detaching instance has replied.
+    pub(super) fn finish_attaches(&mut self, ctx: &egui::Context) {
+        let mut index = 0;
+        while index < self.attaching.len() {
+            let reply = match self.attaching[index].reply.try_recv() {
+                Ok(reply) => reply,
+                Err(TryRecvError::Empty) => {
+                    index += 1;
+                    continue;
+                }
+                Err(TryRecvError::Disconnected) => Err("The hand-off was interrupted".to_string()),
+            };
+            let attach = self.attaching.remove(index);
+            let result = reply
+                .and_then(|(state, fd)| self.adopt_terminal(ctx, state, fd, attach.workspace.as_deref()));
+            attach.request.respond(attach_response(&attach.terminal, result));
+        }
+    }
+
+    /// Put the terminal handed off as `state` and PTY master `fd` at the end of
+    /// `workspace` (the active one when `None`) and focus it.
+    fn adopt_terminal(
+        &mut self,
+        ctx: &egui::Context,
+        state: serde_json::Value,
+        fd: OwnedFd,
+        workspace: Option<&str>,
+    ) -> Result<(), String> {
+        let mut state: PersistedTerminal =
+            serde_json::from_value(state).map_err(|e| format!("Invalid terminal: {}", e))?;
+        let id = self.next_id;
+        self.next_id += 1;
+        state.internal_id = id;
+        state.pty_fd = fd.as_raw_fd();
+        let panel = restore::restore_terminal(&state, ctx, self.event_tx.clone(), self.config.scrollback_lines)?;
+        // The terminal's backend owns the descriptor now
+        let _ = fd.into_raw_fd();
+
+        let name = workspace.map_or_else(|| self.active_workspace().name.clone(), str::to_string);
+        self.switch_to_workspace(&name);
+        self.panels.insert(id, panel);
+        let ws = self.active_workspace_mut();
+        ws.panel_order.push(id);
+        ws.focused_index = ws.panel_order.len() - 1;
+        ws.invalidate_positions();
+        Ok(())
+    }
+}
+
+/// Ask the instance on socket `from` for its detached `terminal`: the terminal's state
+/// and its PTY master. Blocks for up to `HAND_OFF_TIMEOUT`.
+fn request_hand_off(from: &Path, terminal: &str) -> Result<(serde_json::Value, OwnedFd), String> {
+    let request = Request::TermHandOff {
+        terminal: terminal.to_string(),
+    };
+    let (response, fd) = IpcClient::connect(from)
+        .and_then(|mut client| {
+            client.set_timeout(Some(HAND_OFF_TIMEOUT))?;
+            client.request_fd(&request)
+        })
+        .map_err(|e| match e {
+            ipc_protocol::Error::Io(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => format!(
+                "No answer from {} within {}s",
+                from.display(),
+                HAND_OFF_TIMEOUT.as_secs()
+            ),
+            e => e.to_string(),
+        })?;
+    let state = response.into_result().map_err(|e| e.to_string())?;
+    let fd = fd.ok_or("The terminal's PTY did not come with it")?;
+    Ok((state.unwrap_or_default(), fd))
+}
+
+fn attach_response(terminal: &str, result: Result<(), String>) -> Response {
+    match result {
+        Ok(()) => Response::ok_with(serde_json::json!({ "terminal": terminal })),
+        Err(e) => Response::error(e),
+    }
+}
@@ -1,20 +1,49 @@
+use crate::ui::search::{SearchAction, SearchBar};
+use crate::ui::sidebar::TitleEdit;
+use crate::ui::command_palette::CommandPalette;
 use crate::ui::{ActiveDialog, Command};
+use crate::util::keystrokes::{self, ChordModifiers, KeystrokeMode};
 use crate::util::layout;
+use crate::util::toasts::ToastLevel;
 use eframe::egui;
+use egui_term::CaptureRegion;
 
-use super::App;
+use super::actions::Action;
+use super::perf::DebugHud;
+use super::{App, CommandSource};
+
+/// Keys of ⌘1..⌘9, which switch to the first nine workspaces
+const WORKSPACE_KEYS: [egui::Key; 9] = [
+    egui::Key::Num1,
+    egui::Key::Num2,
+    egui::Key::Num3,
+    egui::Key::Num4,
+    egui::Key::Num5,
+    egui::Key::Num6,
+    egui::Key::Num7,
+    egui::Key::Num8,
+    egui::Key::Num9,
+];
 
 impl App {
     pub(crate) fn execute_command(&mut self, cmd: Command, ctx: &egui::Context) {
         match cmd {
             Command::NewTerminal => {
-                let ws = self.active_workspace_mut();
-                let new_index = ws.focused_index + 1;
-                self.create_terminal(ctx);
-                self.active_workspace_mut().focused_index = new_index;
+                if let Ok(new_index) = self.create_terminal(ctx, self.config.new_terminal_position) {
+                    self.active_workspace_mut().focused_index = new_index;
+                }
             }
+            Command::RunInNewTerminal => {
+                self.active_dialog = ActiveDialog::NewTerminalWithCommand { input: String::new() };
+            }
+            Command::NewScratchTerminal => self.open_scratch_terminal(ctx),
             Command::CloseTerminal => {
-                self.active_dialog = ActiveDialog::ConfirmClose;
+                let ws = self.active_workspace();
+                if let Some(&id) = ws.panel_order.get(ws.focused_index) {
+                    if let Err(e) = self.request_close(id, CommandSource::Interactive, false) {
+                        log::debug!("Not closing terminal: {}", e);
+                    }
+                }
             }
             Command::FocusPrevious => self.focus_prev(),
             Command::FocusNext => self.focus_next(),
@@ -22,6 +51,15 @@ impl App {
             Command::SwapWithNext => self.swap_with_next(),
             Command::ShrinkTerminal => self.shrink_focused(),
             Command::GrowTerminal => self.grow_focused(),
+            Command::ToggleZoom => self.toggle_zoom_focused(),
+            Command::TogglePin => {
+                let ws = self.active_workspace();
+                if let Some(&id) = ws.panel_order.get(ws.focused_index) {
+                    let pinned = self.panels.get(&id).is_some_and(|p| p.pinned);
+                    self.set_pinned(id, !pinned);
+                }
+            }
+            Command::EqualizeWidths => self.equalize_widths(self.active_workspace),
             Command::FollowMode => self.follow_mode = true,
             Command::MoveToSpot => self.move_to_spot_mode = true,
             Command::SetDescription => {
@@ -31,16 +69,167 @@ impl App {
                     .unwrap_or_default();
                 self.active_dialog = ActiveDialog::SetDescription { input: current };
             }
+            Command::RenameTerminal => {
+                let ws = self.active_workspace();
+                if let Some(&id) = ws.panel_order.get(ws.focused_index) {
+                    if let Some(panel) = self.panels.get(&id) {
+                        self.sidebar_visible = true;
+                        self.title_edit = Some(TitleEdit::new(id, panel.display_title()));
+                    }
+                }
+            }
+            Command::RenameWorkspace => {
+                if !self.active_workspace().is_scratch() {
+                    let name = self.active_workspace().name.clone();
+                    self.active_dialog = ActiveDialog::RenameWorkspace { input: name };
+                }
+            }
+            Command::NextWorkspace => self.cycle_workspace(1),
+            Command::PreviousWorkspace => self.cycle_workspace(-1),
+            Command::CycleColorScheme => self.cycle_color_scheme(),
             Command::ToggleSidebar => {
                 self.sidebar_visible = !self.sidebar_visible;
             }
+            Command::ToggleOverlayTerminal => self.toggle_overlay_terminal(ctx),
+            Command::NotificationHistory => {
+                self.notification_history_open = !self.notification_history_open;
+            }
+            Command::TogglePause => self.toggle_pause_focused(),
+            Command::TogglePassthrough => self.toggle_passthrough_focused(),
+            Command::ReconnectSsh => self.reconnect_focused(ctx),
+            Command::UndoWorkspaceRemoval => {
+                if let Err(e) = self.undo_workspace_removal() {
+                    self.show_toast(ToastLevel::Warning, e);
+                }
+            }
+            Command::ToggleBroadcast => self.toggle_broadcast_mode(),
+            Command::FindInScrollback => {
+                let ws = self.active_workspace();
+                if let Some(&id) = ws.panel_order.get(ws.focused_index) {
+                    match &mut self.search {
+                        Some(bar) if bar.panel_id == id => bar.request_focus = true,
+                        _ => {
+                            self.close_search();
+                            self.search = Some(SearchBar::new(id));
+                        }
+                    }
+                }
+            }
+            Command::PipeSelection => {
+                // Capture now so the text matches what was on screen when the action ran
+                if let Some(panel) = self.focused_panel() {
+                    let text = panel
+                        .backend
+                        .selection_text()
+                        .unwrap_or_else(|| panel.backend.capture_text(CaptureRegion::Screen, false));
+                    self.active_dialog = ActiveDialog::PipeToCommand {
+                        input: String::new(),
+                        text,
+                    };
+                }
+            }
+            Command::SaveScreen => self.save_capture(CaptureRegion::Screen),
+            Command::SaveScrollback => self.save_capture(CaptureRegion::Scrollback),
+            Command::ZoomIn(scope) => self.zoom_font(scope, 1),
+            Command::ZoomOut(scope) => self.zoom_font(scope, -1),
+            Command::ZoomReset(scope) => self.zoom_font(scope, 0),
+            Command::UiScaleUp => self.zoom_ui(ctx, 1),
+            Command::UiScaleDown => self.zoom_ui(ctx, -1),
+            Command::UiScaleReset => self.zoom_ui(ctx, 0),
+            Command::Custom(index) => self.run_custom_command(ctx, index),
+            Command::ToggleDebugHud => {
+                self.debug_hud = match self.debug_hud {
+                    Some(_) => None,
+                    None => Some(DebugHud::default()),
+                };
+            }
+            Command::RecordMacro => self.toggle_macro_recording(),
+            Command::PlayMacro => self.play_macro(ctx),
+            Command::CycleKeystrokeOverlay => {
+                self.keystroke_mode = self.keystroke_mode.next();
+                self.keystrokes.clear();
+            }
+            Command::ToggleHighContrast => {
+                let on = !self.config.ui_colors.high_contrast;
+                self.config.set_high_contrast(on);
+            }
+            Command::ReloadConfig => {
+                if let Err(e) = self.reload_config(ctx) {
+                    self.show_toast(ToastLevel::Error, e);
+                }
+            }
+        }
+    }
+
+    /// Close the scrollback search bar and remove its highlights.
+    pub(crate) fn close_search(&mut self) {
+        if let Some(bar) = self.search.take() {
+            if let Some(panel) = self.panels.get_mut(&bar.panel_id) {
+                panel.backend.clear_search();
+            }
+        }
+    }
+
+    pub(crate) fn handle_search_action(&mut self, action: SearchAction) {
+        let Some(bar) = &mut self.search else {
+            return;
+        };
+        let Some(panel) = self.panels.get_mut(&bar.panel_id) else {
+            return;
+        };
+        match action {
+            SearchAction::Search(query) => {
+                let found = panel.backend.search(&query);
+                bar.found = (!query.is_empty()).then_some(found);
+            }
+            SearchAction::Next { older } => {
+                if !bar.query.is_empty() {
+                    bar.found = Some(panel.backend.search_next(older));
+                }
+            }
+            SearchAction::Close => self.close_search(),
+        }
+    }
+
+    /// Log key presses for the keystroke overlay. Must run before anything consumes the events.
+    pub(crate) fn record_keystrokes(&mut self, ctx: &egui::Context) {
+        let mode = self.keystroke_mode;
+        if mode == KeystrokeMode::Off {
+            return;
+        }
+
+        let (now, chords) = ctx.input(|i| {
+            let chords: Vec<String> = i
+                .events
+                .iter()
+                .filter_map(|event| match event {
+                    egui::Event::Key { key, pressed: true, repeat: false, modifiers, .. } => {
+                        let is_shortcut =
+                            modifiers.command || keystrokes::is_function_key(key.name());
+                        let chord_modifiers = ChordModifiers {
+                            ctrl: modifiers.ctrl,
+                            alt: modifiers.alt,
+                            shift: modifiers.shift,
+                            command: modifiers.mac_cmd,
+                        };
+                        keystrokes::is_shown(mode, is_shortcut).then(|| {
+                            keystrokes::format_chord(chord_modifiers, key.symbol_or_name())
+                        })
+                    }
+                    _ => None,
+                })
+                .collect();
+            (i.time, chords)
+        });
+
+        for chord in chords {
+            self.keystrokes.record(chord, now);
         }
     }
 
     /// Build a mapping of letter index (0-25) to (workspace_idx, terminal_idx)
     fn build_follow_targets(&self) -> Vec<(usize, usize)> {
-        let counts: Vec<usize> = self.workspaces.iter().map(|ws| ws.panel_order.len()).collect();
-        layout::build_follow_targets(&counts)
+        layout::build_follow_targets(self.workspaces.iter().map(|ws| ws.panel_order.len()))
     }
 
     pub(crate) fn handle_keyboard_shortcuts(&mut self, ctx: &egui::Context) {
@@ -48,13 +237,18 @@ impl App {
             return;
         }
 
-        if self.command_palette_open {
+        if self.command_palette.is_some() {
             if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
-                self.command_palette_open = false;
+                self.command_palette = None;
                 return;
             }
         }
 
+        if self.notification_history_open && ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.notification_history_open = false;
+            return;
+        }
+
         if self.follow_mode {
             if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
                 self.follow_mode = false;
@@ -93,9 +287,11 @@ impl App {
             for (idx, &key) in letter_keys.iter().enumerate() {
                 if ctx.input(|i| i.key_pressed(key)) {
                     let targets = self.build_follow_targets();
-                    if let Some(&(ws_idx, term_idx)) = targets.get(idx) {
-                        self.active_workspace = ws_idx;
-                        self.workspaces[ws_idx].focused_index = term_idx;
+                    if let Some(&(workspace, terminal)) = targets.get(idx) {
+                        let action = Action::FocusTerminal { workspace, terminal };
+                        if let Err(e) = self.dispatch(ctx, action) {
+                            log::warn!("Follow mode: {}", e);
+                        }
                     }
                     self.follow_mode = false;
                     return;
@@ -143,8 +339,11 @@ impl App {
             for (idx, &key) in letter_keys.iter().enumerate() {
                 if ctx.input(|i| i.key_pressed(key)) {
                     let targets = self.build_follow_targets();
-                    if let Some(&(target_ws, target_idx)) = targets.get(idx) {
-                        self.move_focused_to_spot(target_ws, target_idx);
+                    if let Some(&(workspace, terminal)) = targets.get(idx) {
+                        let action = Action::MoveFocusedTo { workspace, terminal };
+                        if let Err(e) = self.dispatch(ctx, action) {
+                            log::warn!("Move to spot: {}", e);
+                        }
                     }
                     self.move_to_spot_mode = false;
                     return;
@@ -156,12 +355,59 @@ impl App {
 
         let modifiers = ctx.input(|i| i.modifiers);
 
+        // ⌘⇧\ is the way out of raw passthrough, so it is never passed on. Shift+\ arrives
+        // as Pipe on most layouts.
+        let passthrough_toggle = ctx.input_mut(|i| {
+            let command_shift = egui::Modifiers::COMMAND | egui::Modifiers::SHIFT;
+            i.consume_key(command_shift, egui::Key::Backslash) || i.consume_key(command_shift, egui::Key::Pipe)
+        });
+        if passthrough_toggle {
+            self.dispatch_command(ctx, Command::TogglePassthrough);
+            return;
+        }
+        // Every other shortcut goes to the program while it gets raw keys
+        if self
+            .focused_panel()
+            .is_some_and(|panel| panel.passthrough_active(self.config.auto_passthrough))
+        {
+            return;
+        }
+
         if modifiers.command && ctx.input(|i| i.key_pressed(egui::Key::P)) {
-            self.command_palette_open = !self.command_palette_open;
+            self.command_palette = match self.command_palette {
+                Some(_) => None,
+                None => Some(CommandPalette::new()),
+            };
             return;
         }
 
-        if self.command_palette_open {
+        if self.command_palette.is_some() {
+            return;
+        }
+
+        // Keys typed into the inline title editor or the search bar belong to them
+        if self.title_edit.is_some() || self.search.is_some() {
+            return;
+        }
+
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F2)) {
+            self.dispatch_command(ctx, Command::RenameTerminal);
+            return;
+        }
+
+        // ⌃Tab / ⌃⇧Tab walk the workspace's terminals by recent focus while ⌃ is held
+        // (⌘` being the drop-down terminal)
+        let mru_step = ctx.input_mut(|i| {
+            if i.consume_key(egui::Modifiers::CTRL | egui::Modifiers::SHIFT, egui::Key::Tab) {
+                Some(-1)
+            } else if i.consume_key(egui::Modifiers::CTRL, egui::Key::Tab) {
+                Some(1)
+            } else {
+                None
+            }
+        });
+        if let Some(step) = mru_step {
+            self.step_mru_switch(step);
             return;
         }
 
@@ -169,50 +415,159 @@ impl App {
             return;
         }
 
+        // ⌘⌥ sizes the UI chrome. Checked before the ⌘ bindings below, which ignore ⌥,
+        // and dispatched outside input_mut since it restyles the context.
+        if modifiers.alt {
+            // ⌘⌥↓/↑ and ⌘⌥1..9 show another workspace for as long as they are held
+            let count = self.workspaces.len();
+            let active = self.active_workspace;
+            let peek = ctx.input_mut(|i| {
+                let command_alt = egui::Modifiers::COMMAND | egui::Modifiers::ALT;
+                [(egui::Key::ArrowDown, (active + 1) % count), (egui::Key::ArrowUp, (active + count - 1) % count)]
+                    .into_iter()
+                    .chain(WORKSPACE_KEYS.into_iter().enumerate().map(|(index, key)| (key, index)))
+                    .find(|&(key, index)| index < count && i.consume_key(command_alt, key))
+            });
+            if let Some((key, index)) = peek {
+                self.start_peek(index, key);
+                return;
+            }
+
+            let ui_zoom = ctx.input_mut(|i| {
+                let command_alt = egui::Modifiers::COMMAND | egui::Modifiers::ALT;
+                [
+                    (egui::Key::Equals, Command::UiScaleUp),
+                    (egui::Key::Plus, Command::UiScaleUp),
+                    (egui::Key::Minus, Command::UiScaleDown),
+                    (egui::Key::Num0, Command::UiScaleReset),
+                ]
+                .into_iter()
+                .find(|&(key, _)| i.consume_key(command_alt, key))
+                .map(|(_, command)| command)
+            });
+            if let Some(command) = ui_zoom {
+                self.dispatch_command(ctx, command);
+                return;
+            }
+        }
+
+        // Collected here and dispatched once input_mut has returned: commands may show a
+        // toast (e.g. a workspace full or a failed spawn), which reads the context
+        let mut actions = Vec::new();
         ctx.input_mut(|i| {
-            if i.consume_key(egui::Modifiers::COMMAND, egui::Key::T) {
-                self.execute_command(Command::NewTerminal, ctx);
+            if i.key_pressed(egui::Key::T) && i.modifiers.command && i.modifiers.shift {
+                actions.push(Action::Command(Command::NewScratchTerminal));
+            } else if i.consume_key(egui::Modifiers::COMMAND, egui::Key::T) {
+                actions.push(Action::Command(Command::NewTerminal));
             }
 
             if i.consume_key(egui::Modifiers::COMMAND, egui::Key::W) {
-                self.execute_command(Command::CloseTerminal, ctx);
+                actions.push(Action::Command(Command::CloseTerminal));
             }
 
             if i.key_pressed(egui::Key::OpenCurlyBracket) && i.modifiers.command {
-                self.execute_command(Command::SwapWithPrevious, ctx);
+                actions.push(Action::Command(Command::SwapWithPrevious));
             }
             if i.consume_key(egui::Modifiers::COMMAND, egui::Key::OpenBracket) {
-                self.execute_command(Command::FocusPrevious, ctx);
+                actions.push(Action::Command(Command::FocusPrevious));
             }
 
             if i.key_pressed(egui::Key::CloseCurlyBracket) && i.modifiers.command {
-                self.execute_command(Command::SwapWithNext, ctx);
+                actions.push(Action::Command(Command::SwapWithNext));
             }
             if i.consume_key(egui::Modifiers::COMMAND, egui::Key::CloseBracket) {
-                self.execute_command(Command::FocusNext, ctx);
+                actions.push(Action::Command(Command::FocusNext));
+            }
+
+            // Shift+= arrives as Plus on most layouts
+            let zoom_in = i.key_pressed(egui::Key::Plus) || i.key_pressed(egui::Key::Equals);
+            if zoom_in && i.modifiers.command && i.modifiers.shift {
+                actions.push(Action::Command(Command::ZoomIn(self.config.font_zoom)));
+            } else if i.consume_key(egui::Modifiers::COMMAND, egui::Key::Equals) {
+                actions.push(Action::Command(Command::GrowTerminal));
             }
 
-            if i.consume_key(egui::Modifiers::COMMAND, egui::Key::Minus) {
-                self.execute_command(Command::ShrinkTerminal, ctx);
+            if i.key_pressed(egui::Key::Minus) && i.modifiers.command && i.modifiers.shift {
+                actions.push(Action::Command(Command::ZoomOut(self.config.font_zoom)));
+            } else if i.consume_key(egui::Modifiers::COMMAND, egui::Key::Minus) {
+                actions.push(Action::Command(Command::ShrinkTerminal));
             }
 
-            if i.consume_key(egui::Modifiers::COMMAND, egui::Key::Equals) {
-                self.execute_command(Command::GrowTerminal, ctx);
+            if i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Num0) {
+                actions.push(Action::Command(Command::ZoomReset(self.config.font_zoom)));
+            }
+
+            if i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Enter) {
+                actions.push(Action::Command(Command::ToggleZoom));
             }
 
             if i.key_pressed(egui::Key::J) && i.modifiers.command && i.modifiers.shift {
-                self.execute_command(Command::MoveToSpot, ctx);
+                actions.push(Action::Command(Command::MoveToSpot));
             } else if i.consume_key(egui::Modifiers::COMMAND, egui::Key::J) {
-                self.execute_command(Command::FollowMode, ctx);
+                actions.push(Action::Command(Command::FollowMode));
             }
 
             if i.consume_key(egui::Modifiers::COMMAND, egui::Key::D) {
-                self.execute_command(Command::SetDescription, ctx);
+                actions.push(Action::Command(Command::SetDescription));
             }
 
             if i.consume_key(egui::Modifiers::COMMAND, egui::Key::B) {
-                self.execute_command(Command::ToggleSidebar, ctx);
+                actions.push(Action::Command(Command::ToggleSidebar));
+            }
+
+            if i.consume_key(egui::Modifiers::COMMAND, egui::Key::Backtick) {
+                actions.push(Action::Command(Command::ToggleOverlayTerminal));
+            }
+
+            if i.consume_key(egui::Modifiers::COMMAND, egui::Key::I) {
+                actions.push(Action::Command(Command::NotificationHistory));
+            }
+
+            if i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::F) {
+                actions.push(Action::Command(Command::TogglePause));
+            } else if i.consume_key(egui::Modifiers::COMMAND, egui::Key::F) {
+                actions.push(Action::Command(Command::FindInScrollback));
+            }
+
+            if i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::R) {
+                actions.push(Action::Command(Command::RecordMacro));
+            }
+
+            if i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::M) {
+                actions.push(Action::Command(Command::PlayMacro));
+            }
+
+            if i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::ArrowDown) {
+                actions.push(Action::Command(Command::NextWorkspace));
+            }
+            if i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::ArrowUp) {
+                actions.push(Action::Command(Command::PreviousWorkspace));
+            }
+
+            // ⌘1..⌘9 jump to the workspace at that position in the sidebar
+            for (index, key) in WORKSPACE_KEYS.into_iter().enumerate() {
+                if index < self.workspaces.len() && i.consume_key(egui::Modifiers::COMMAND, key) {
+                    actions.push(Action::SwitchWorkspace(index));
+                }
             }
         });
+        for action in actions {
+            if let Err(e) = self.dispatch(ctx, action) {
+                log::warn!("Shortcut: {}", e);
+            }
+        }
+
+        // Outside input_mut: reloading reads the context (fonts, system theme)
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::L)) {
+            self.dispatch_command(ctx, Command::ReloadConfig);
+        }
+
+        // Only while a removal can be undone, so ⌘Z reaches the terminal otherwise. Outside
+        // input_mut: a failed undo shows a toast, which reads the time from the context.
+        if self.emptied_workspace.is_some()
+            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z))
+        {
+            self.dispatch_command(ctx, Command::UndoWorkspaceRemoval);
+        }
     }
 }
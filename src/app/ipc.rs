@@ -1,11 +1,25 @@
-use crate::ipc_protocol::{Request, Response};
-use crate::workspace::Workspace;
+use ipc_protocol::{Event, Request, Response};
+use crate::config::{self, hex_to_color32};
+use crate::debug_report::{self, ReportSources};
+use crate::log_buffer;
+use crate::notifications::NotificationKind;
+use crate::session::{self, SessionProfile, SessionTerminal};
+use crate::terminal::SpawnOptions;
+use crate::util::screen_diff::ScreenDiff;
+use crate::util::text;
+use crate::workspace::find_workspace;
 use eframe::egui;
+use egui_term::{BackendCommand, CaptureRegion};
+use std::collections::HashMap;
 
-use super::App;
+use super::actions::Action;
+use super::sessions;
+use super::{App, CommandSource};
 
 impl App {
     pub(crate) fn process_ipc(&mut self, ctx: &egui::Context) {
+        #[cfg(unix)]
+        self.finish_attaches(ctx);
         let Some(handle) = &self.ipc_handle else {
             return;
         };
@@ -17,6 +31,17 @@ impl App {
                 Request::Restart => {
                     pending.respond(Response::ok());
 
+                    // Placeholders have no PTY to hand over to the new process
+                    let deferred: Vec<u64> = self
+                        .panels
+                        .iter()
+                        .filter(|(_, panel)| panel.deferred.is_some())
+                        .map(|(&id, _)| id)
+                        .collect();
+                    for id in deferred {
+                        self.start_deferred(ctx, id);
+                    }
+
                     #[cfg(unix)]
                     if let Err(e) = self.trigger_restart() {
                         log::error!("Restart failed: {}", e);
@@ -25,14 +50,45 @@ impl App {
 
                     continue;
                 }
+                Request::Quit { force } => {
+                    let pending_confirmation = self.request_quit(ctx, CommandSource::Ipc, force);
+                    Response::ok_with(serde_json::json!({
+                        "pending_confirmation": pending_confirmation
+                    }))
+                }
+                Request::TermClose { ref terminal, force } => {
+                    let panel_id = self
+                        .panels
+                        .iter()
+                        .find(|(_, p)| p.id == *terminal)
+                        .map(|(&id, _)| id);
+
+                    match panel_id {
+                        Some(id) => match self.request_close(id, CommandSource::Ipc, force) {
+                            Ok(pending_confirmation) => Response::ok_with(serde_json::json!({
+                                "pending_confirmation": pending_confirmation
+                            })),
+                            Err(e) => Response::error(e),
+                        },
+                        None => Response::error(format!("Terminal not found: {}", terminal)),
+                    }
+                }
                 Request::TermRename { ref terminal, ref title } => {
-                    let panel = self.panels.values_mut().find(|p| p.id == *terminal);
+                    let panel_id = self
+                        .panels
+                        .iter()
+                        .find(|(_, p)| p.id == *terminal)
+                        .map(|(&id, _)| id);
 
-                    if let Some(panel) = panel {
-                        panel.custom_title = Some(title.clone());
-                        Response::ok()
-                    } else {
-                        Response::error(format!("Terminal not found: {}", terminal))
+                    match panel_id {
+                        Some(panel_id) => {
+                            let action = Action::RenameTerminal { panel_id, title: title.clone() };
+                            match self.dispatch(ctx, action) {
+                                Ok(()) => Response::ok(),
+                                Err(e) => Response::error(e),
+                            }
+                        }
+                        None => Response::error(format!("Terminal not found: {}", terminal)),
                     }
                 }
                 Request::TermDesc {
@@ -66,16 +122,158 @@ impl App {
                         Response::error(format!("Terminal not found: {}", terminal))
                     }
                 }
-                Request::TermNotify { ref terminal } => {
+                Request::TermTheme { ref terminal, ref scheme } => {
+                    let known = scheme.is_empty() || self.themes.schemes.contains_key(scheme);
                     let panel = self.panels.values_mut().find(|p| p.id == *terminal);
 
-                    if let Some(panel) = panel {
+                    match panel {
+                        Some(_) if !known => {
+                            Response::error(format!("Unknown color scheme: {}", scheme))
+                        }
+                        Some(panel) => {
+                            if scheme.is_empty() {
+                                panel.color_scheme = None;
+                            } else {
+                                panel.color_scheme = Some(scheme.clone());
+                            }
+                            Response::ok()
+                        }
+                        None => Response::error(format!("Terminal not found: {}", terminal)),
+                    }
+                }
+                Request::TermColor { ref terminal, ref color } => {
+                    let valid = color.is_empty() || hex_to_color32(color).is_some();
+                    let panel = self.panels.values_mut().find(|p| p.id == *terminal);
+
+                    match panel {
+                        Some(_) if !valid => {
+                            Response::error(format!("Invalid color (expected #rrggbb): {}", color))
+                        }
+                        Some(panel) => {
+                            panel.accent = Some(color.clone()).filter(|color| !color.is_empty());
+                            Response::ok()
+                        }
+                        None => Response::error(format!("Terminal not found: {}", terminal)),
+                    }
+                }
+                Request::TermResize { ref terminal, ratio } => {
+                    let panel_id = self
+                        .panels
+                        .iter()
+                        .find(|(_, p)| p.id == *terminal)
+                        .map(|(&id, _)| id);
+
+                    match panel_id {
+                        Some(_) if !(ratio > 0.0 && ratio <= 1.0) => {
+                            Response::error(format!("Width ratio must be in (0, 1]: {}", ratio))
+                        }
+                        Some(panel_id) => {
+                            self.set_width_ratio(panel_id, ratio);
+                            Response::ok()
+                        }
+                        None => Response::error(format!("Terminal not found: {}", terminal)),
+                    }
+                }
+                Request::TermPin { ref terminal, pinned } => {
+                    let panel_id = self
+                        .panels
+                        .iter()
+                        .find(|(_, p)| p.id == *terminal)
+                        .map(|(&id, _)| id);
+
+                    match panel_id {
+                        Some(panel_id) => {
+                            self.set_pinned(panel_id, pinned);
+                            Response::ok()
+                        }
+                        None => Response::error(format!("Terminal not found: {}", terminal)),
+                    }
+                }
+                Request::TermNotify { ref terminal, ref message } => {
+                    let panel = self.panels.iter_mut().find(|(_, p)| p.id == *terminal);
+
+                    if let Some((&id, panel)) = panel {
                         panel.notified = true;
+                        self.notifications
+                            .push(id, NotificationKind::Notify, message.clone());
+                        self.notify_desktop(ctx, id, None, message.as_deref());
                         Response::ok()
                     } else {
                         Response::error(format!("Terminal not found: {}", terminal))
                     }
                 }
+                Request::TermSendText { ref terminal, ref text } => {
+                    let panel_id = self.panels.iter().find(|(_, p)| p.id == *terminal).map(|(&id, _)| id);
+                    if let Some(id) = panel_id {
+                        self.start_deferred(ctx, id);
+                    }
+                    let panel = panel_id.and_then(|id| self.panels.get_mut(&id));
+
+                    if let Some(panel) = panel {
+                        panel
+                            .backend
+                            .process_command(BackendCommand::Write(text.as_bytes().to_vec()));
+                        Response::ok()
+                    } else {
+                        Response::error(format!("Terminal not found: {}", terminal))
+                    }
+                }
+                Request::TermDump { ref terminal, lines } => {
+                    match self.panels.values().find(|p| p.id == *terminal) {
+                        Some(panel) => {
+                            let text =
+                                panel.backend.capture_text(CaptureRegion::Scrollback, false);
+                            let text = text::tail_lines(&text, lines.unwrap_or(usize::MAX));
+                            Response::ok_with(serde_json::json!({ "text": text }))
+                        }
+                        None => Response::error(format!("Terminal not found: {}", terminal)),
+                    }
+                }
+                Request::WorkspaceNotifyAll {
+                    ref workspace,
+                    ref message,
+                } => {
+                    let ws = self.workspaces.iter().find(|ws| ws.name == *workspace);
+
+                    if let Some(ws) = ws {
+                        let ids = ws.panel_order.clone();
+                        for id in ids {
+                            if let Some(panel) = self.panels.get_mut(&id) {
+                                panel.notified = true;
+                                self.notifications
+                                    .push(id, NotificationKind::Notify, message.clone());
+                                self.notify_desktop(ctx, id, None, message.as_deref());
+                            }
+                        }
+                        Response::ok()
+                    } else {
+                        Response::error(format!("Workspace not found: {}", workspace))
+                    }
+                }
+                Request::WorkspaceSendText {
+                    ref workspace,
+                    ref text,
+                } => {
+                    let panel_order = self
+                        .workspaces
+                        .iter()
+                        .find(|ws| ws.name == *workspace)
+                        .map(|ws| ws.panel_order.clone());
+
+                    if let Some(panel_order) = panel_order {
+                        for id in panel_order {
+                            self.start_deferred(ctx, id);
+                            if let Some(panel) = self.panels.get_mut(&id) {
+                                panel
+                                    .backend
+                                    .process_command(BackendCommand::Write(text.as_bytes().to_vec()));
+                            }
+                        }
+                        Response::ok()
+                    } else {
+                        Response::error(format!("Workspace not found: {}", workspace))
+                    }
+                }
                 Request::TermToWorkspace {
                     ref terminal,
                     ref workspace_name,
@@ -87,61 +285,376 @@ impl App {
                         .map(|(&id, _)| id);
 
                     match panel_id {
-                        Some(id) => {
-                            let current_ws_idx = self
-                                .workspaces
-                                .iter()
-                                .position(|ws| ws.panel_order.contains(&id));
-
-                            if let Some(ws_idx) = current_ws_idx {
-                                if self.workspaces[ws_idx].name == *workspace_name {
-                                    self.active_workspace = ws_idx;
-                                    pending.respond(Response::ok());
-                                    continue;
-                                }
+                        Some(panel_id) => {
+                            let action = Action::MoveToWorkspace {
+                                panel_id,
+                                workspace: workspace_name.clone(),
+                            };
+                            match self.dispatch(ctx, action) {
+                                Ok(()) => Response::ok(),
+                                Err(e) => Response::error(e),
                             }
-
-                            for ws in &mut self.workspaces {
-                                if let Some(pos) = ws.panel_order.iter().position(|&x| x == id) {
-                                    ws.panel_order.remove(pos);
-                                    if ws.focused_index >= ws.panel_order.len()
-                                        && !ws.panel_order.is_empty()
-                                    {
-                                        ws.focused_index = ws.panel_order.len() - 1;
-                                    }
-                                    ws.invalidate_positions();
-                                    break;
+                        }
+                        None => Response::error(format!("Terminal not found: {}", terminal)),
+                    }
+                }
+                Request::TermList => {
+                    let focused = self.focused_panel().map(|p| p.id.clone());
+                    let terminals: Vec<_> = self
+                        .workspaces
+                        .iter()
+                        .flat_map(|ws| ws.panel_order.iter().map(move |id| (ws, id)))
+                        .filter_map(|(ws, id)| {
+                            let panel = self.panels.get(id)?;
+                            Some(serde_json::json!({
+                                "id": panel.id,
+                                "title": panel.display_title(),
+                                "description": panel.description,
+                                "cli_description": panel.cli_description,
+                                "workspace": ws.name,
+                                "focused": focused.as_ref() == Some(&panel.id),
+                                "pinned": panel.pinned,
+                            }))
+                        })
+                        .collect();
+                    Response::ok_with(serde_json::json!({ "terminals": terminals }))
+                }
+                Request::TermSpawn {
+                    ref command,
+                    ref cwd,
+                    ref workspace,
+                    ref title,
+                    focus,
+                    placement,
+                } => {
+                    if let Some(dir) = cwd.as_ref().filter(|dir| !dir.is_dir()) {
+                        Response::error(format!("Not a directory: {}", dir.display()))
+                    } else if workspace.as_ref().is_some_and(|name| name.is_empty()) {
+                        Response::error("Workspace name is empty")
+                    } else {
+                        let options = SpawnOptions {
+                            working_directory: cwd.clone(),
+                            startup_command: command.clone().filter(|c| !c.is_empty()),
+                            ..Default::default()
+                        };
+                        let placement = self.config.placement(placement);
+                        match self.spawn_in_workspace(ctx, workspace.as_deref(), placement, options, focus) {
+                            Ok(terminal) => {
+                                if let Some(panel) = self.panels.values_mut().find(|p| p.id == terminal) {
+                                    panel.custom_title = title.clone().filter(|t| !t.is_empty());
                                 }
+                                Response::ok_with(serde_json::json!({ "terminal": terminal }))
                             }
-
-                            let target_ws_idx = self
-                                .workspaces
-                                .iter()
-                                .position(|ws| ws.name == *workspace_name);
-
-                            let target_ws_idx = match target_ws_idx {
-                                Some(idx) => idx,
-                                None => {
-                                    self.workspaces.push(Workspace::new(workspace_name));
-                                    self.workspaces.len() - 1
-                                }
-                            };
-
-                            self.workspaces[target_ws_idx].panel_order.push(id);
-                            self.workspaces[target_ws_idx].focused_index =
-                                self.workspaces[target_ws_idx].panel_order.len() - 1;
-                            self.workspaces[target_ws_idx].invalidate_positions();
-
-                            self.active_workspace = target_ws_idx;
-                            self.cleanup_empty_workspaces();
-
+                            Err(e) => Response::error(e),
+                        }
+                    }
+                }
+                Request::WorkspaceCreate { ref name } => match self.create_workspace(ctx, name) {
+                    Ok(terminal) => Response::ok_with(serde_json::json!({ "terminal": terminal })),
+                    Err(e) => Response::error(e),
+                },
+                Request::WorkspaceRename {
+                    ref workspace,
+                    ref name,
+                } => match find_workspace(&self.workspaces, workspace) {
+                    Some(index) => match self.request_rename_workspace(index, name, CommandSource::Ipc) {
+                        Ok(()) => Response::ok(),
+                        Err(e) => Response::error(e),
+                    },
+                    None => Response::error(format!("Workspace not found: {}", workspace)),
+                },
+                Request::WorkspaceDelete { ref workspace, force } => {
+                    match find_workspace(&self.workspaces, workspace) {
+                        Some(index) => match self.delete_workspace(index, force) {
+                            Ok(()) => Response::ok(),
+                            Err(e) => Response::error(e),
+                        },
+                        None => Response::error(format!("Workspace not found: {}", workspace)),
+                    }
+                }
+                Request::WorkspaceList => {
+                    let workspaces: Vec<_> = self
+                        .workspaces
+                        .iter()
+                        .enumerate()
+                        .map(|(i, ws)| {
+                            serde_json::json!({
+                                "index": i + 1,
+                                "name": ws.name,
+                                "terminals": ws.panel_order.len(),
+                                "active": i == self.active_workspace,
+                                "group": ws.group,
+                            })
+                        })
+                        .collect();
+                    Response::ok_with(serde_json::json!({ "workspaces": workspaces }))
+                }
+                Request::WorkspaceGroup { ref workspace, ref group } => {
+                    match find_workspace(&self.workspaces, workspace) {
+                        Some(index) => {
+                            let group = group.as_deref().map(str::trim).filter(|g| !g.is_empty());
+                            self.set_workspace_group(index, group.map(str::to_string));
                             Response::ok()
                         }
+                        None => Response::error(format!("Workspace not found: {}", workspace)),
+                    }
+                }
+                Request::GroupClose { ref group, force } => match self.close_group(group, force) {
+                    Ok(closed) => Response::ok_with(serde_json::json!({ "closed": closed })),
+                    Err(e) => Response::error(e),
+                },
+                Request::LayoutEqualize { ref workspace } => {
+                    let index = match workspace {
+                        Some(workspace) => find_workspace(&self.workspaces, workspace),
+                        None => Some(self.active_workspace),
+                    };
+                    match index {
+                        Some(index) => {
+                            self.equalize_widths(index);
+                            Response::ok()
+                        }
+                        None => Response::error(format!(
+                            "Workspace not found: {}",
+                            workspace.as_deref().unwrap_or_default()
+                        )),
+                    }
+                }
+                Request::ConfigReload => match self.reload_config(ctx) {
+                    Ok(()) => Response::ok(),
+                    Err(e) => Response::error(e),
+                },
+                Request::DebugReport => {
+                    let config_path = config::config_path();
+                    let report = debug_report::assemble(ReportSources {
+                        socket: self.socket_path.as_deref(),
+                        uptime_secs: ctx.input(|i| i.time),
+                        perf: self
+                            .perf_stats
+                            .current_window()
+                            .to_json(env!("CARGO_PKG_VERSION")),
+                        terminals_per_workspace: self
+                            .workspaces
+                            .iter()
+                            .map(|ws| ws.panel_order.len())
+                            .collect(),
+                        config_path: config_path.as_deref(),
+                        logs: log_buffer::recent(),
+                    });
+                    Response::ok_with(report)
+                }
+                Request::TermExport { ref terminal } => {
+                    let found = self.workspaces.iter().find_map(|ws| {
+                        let panel = ws
+                            .panel_order
+                            .iter()
+                            .filter_map(|id| self.panels.get(id))
+                            .find(|p| p.id == *terminal)?;
+                        Some((ws, panel))
+                    });
+                    match found {
+                        Some((ws, panel)) => Response::ok_with(serde_json::json!({
+                            "workspace": ws.name,
+                            "terminal": sessions::session_terminal(panel),
+                        })),
                         None => Response::error(format!("Terminal not found: {}", terminal)),
                     }
                 }
+                Request::TermImport {
+                    ref workspace,
+                    ref terminal,
+                } => match serde_json::from_value::<SessionTerminal>(terminal.clone()) {
+                    Err(e) => Response::error(format!("Invalid terminal: {}", e)),
+                    Ok(_) if workspace.is_empty() => Response::error("Workspace name is empty"),
+                    Ok(entry) => match self.spawn_session_terminal(ctx, workspace, &entry, true) {
+                        Ok(terminal) => Response::ok_with(serde_json::json!({ "terminal": terminal })),
+                        Err(e) => Response::error(e),
+                    },
+                },
+                #[cfg(unix)]
+                Request::TermDetach { ref terminal } => match self.detach_terminal(terminal) {
+                    Ok(()) => Response::ok_with(serde_json::json!({ "terminal": terminal })),
+                    Err(e) => Response::error(e),
+                },
+                #[cfg(unix)]
+                Request::TermHandOff { ref terminal } => {
+                    match self.hand_off(terminal) {
+                        Ok((state, fd)) => pending.respond_with_fd(Response::ok_with(state), fd),
+                        Err(e) => pending.respond(Response::error(e)),
+                    }
+                    continue;
+                }
+                #[cfg(unix)]
+                Request::TermAttach {
+                    ref terminal,
+                    ref from,
+                    ref workspace,
+                } => {
+                    let (terminal, from, workspace) = (terminal.clone(), from.clone(), workspace.clone());
+                    self.attach_terminal(ctx, pending, terminal, from, workspace);
+                    continue;
+                }
+                Request::SessionSave { ref name } => {
+                    match session::profile_path(name).and_then(|path| {
+                        self.session_profile().save(&path).map(|()| path)
+                    }) {
+                        Ok(path) => Response::ok_with(serde_json::json!({ "path": path })),
+                        Err(e) => Response::error(e),
+                    }
+                }
+                Request::SessionLoad { ref name } => {
+                    match session::profile_path(name)
+                        .and_then(|path| SessionProfile::load(&path))
+                        .and_then(|profile| self.load_session(ctx, &profile))
+                    {
+                        Ok(terminals) => Response::ok_with(serde_json::json!({ "terminals": terminals })),
+                        Err(e) => Response::error(e),
+                    }
+                }
+                // The connection thread answers subscriptions itself
+                Request::Subscribe { .. } => Response::error("Subscribe is handled by the IPC connection"),
+                _ => Response::error("Unsupported request"),
             };
             pending.respond(response);
         }
     }
+
+    /// Publish what changed since the last frame (terminals, titles, focus, workspace,
+    /// notification history) to subscribed IPC connections. Nothing is tracked without any.
+    pub(crate) fn publish_ipc_events(&mut self) {
+        let Some(handle) = self.ipc_handle.as_ref().filter(|handle| handle.has_subscribers()) else {
+            self.event_snapshot = None;
+            return;
+        };
+
+        let current = EventSnapshot::of(self);
+        let Some(previous) = self.event_snapshot.replace(current) else {
+            return;
+        };
+        let current = self.event_snapshot.as_ref().unwrap();
+
+        for (id, state) in &current.terminals {
+            let Some(old) = previous.terminals.get(id) else {
+                handle.publish(&Event::TerminalCreated {
+                    terminal: state.terminal.clone(),
+                    workspace: state.workspace.clone(),
+                });
+                continue;
+            };
+            if state.title != old.title {
+                handle.publish(&Event::TitleChanged {
+                    terminal: state.terminal.clone(),
+                    title: state.title.clone(),
+                });
+            }
+        }
+        for (id, old) in &previous.terminals {
+            if !current.terminals.contains_key(id) {
+                handle.publish(&Event::TerminalClosed {
+                    terminal: old.terminal.clone(),
+                });
+            }
+        }
+        // New history entries, oldest first (entries already dropped are skipped)
+        let new_notifications = current.notifications.saturating_sub(previous.notifications) as usize;
+        let mut notifications: Vec<_> = self.notifications.newest_first().take(new_notifications).collect();
+        notifications.reverse();
+        for notification in notifications {
+            let Some(state) = current.terminals.get(&notification.panel_id) else {
+                continue;
+            };
+            handle.publish(&Event::Notification {
+                terminal: state.terminal.clone(),
+                kind: notification.kind.label().to_string(),
+                message: notification.message.clone(),
+            });
+        }
+        if current.workspace != previous.workspace {
+            handle.publish(&Event::WorkspaceSwitched {
+                workspace: current.workspace.clone(),
+            });
+        }
+        if current.focused != previous.focused || current.workspace != previous.workspace {
+            handle.publish(&Event::FocusChanged {
+                terminal: current.focused.clone(),
+                workspace: current.workspace.clone(),
+            });
+        }
+
+        // Screen changes of the terminals a subscription watches
+        let mut screens = previous.screens;
+        screens.retain(|id, _| {
+            current
+                .terminals
+                .get(id)
+                .is_some_and(|state| handle.watches_grid(&state.terminal))
+        });
+        for (id, state) in &current.terminals {
+            if !handle.watches_grid(&state.terminal) {
+                continue;
+            }
+            let Some(panel) = self.panels.get(id) else {
+                continue;
+            };
+            let screen = screens.entry(*id).or_default();
+            let rows = panel.backend.screen_rows();
+            if let Some(change) = screen.update(&rows, panel.backend.cursor_position()) {
+                handle.publish(&Event::GridChanged {
+                    terminal: state.terminal.clone(),
+                    lines: change.lines,
+                    cursor_line: change.cursor.0,
+                    cursor_column: change.cursor.1,
+                });
+            }
+        }
+        if let Some(current) = &mut self.event_snapshot {
+            current.screens = screens;
+        }
+    }
+}
+
+/// A terminal as last published to IPC subscribers
+struct TerminalState {
+    /// External ID
+    terminal: String,
+    workspace: String,
+    title: String,
+}
+
+/// State compared between frames to publish IPC events
+pub(crate) struct EventSnapshot {
+    /// By internal ID
+    terminals: HashMap<u64, TerminalState>,
+    /// External ID of the focused terminal
+    focused: Option<String>,
+    /// Name of the active workspace
+    workspace: String,
+    /// `NotificationLog::pushed` count
+    notifications: u64,
+    /// Screens of watched terminals as last published, by internal ID
+    screens: HashMap<u64, ScreenDiff>,
+}
+
+impl EventSnapshot {
+    fn of(app: &App) -> Self {
+        let mut terminals = HashMap::new();
+        for ws in &app.workspaces {
+            for id in &ws.panel_order {
+                if let Some(panel) = app.panels.get(id) {
+                    let state = TerminalState {
+                        terminal: panel.id.clone(),
+                        workspace: ws.name.clone(),
+                        title: panel.display_title().to_string(),
+                    };
+                    terminals.insert(*id, state);
+                }
+            }
+        }
+        Self {
+            terminals,
+            focused: app.focused_panel().map(|panel| panel.id.clone()),
+            workspace: app.active_workspace().name.clone(),
+            notifications: app.notifications.pushed(),
+            screens: HashMap::new(),
+        }
+    }
 }
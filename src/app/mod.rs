@@ -1,35 +1,95 @@
+mod actions;
+#[cfg(unix)]
+mod handoff;
 mod input;
 mod ipc;
 mod perf;
+mod restore;
+mod sessions;
+mod ssh;
 mod terminals;
 
-use crate::config::Config;
-use egui_term::TerminalTheme;
+use crate::config::{self, Appearance, AppearanceMode, Config, ThemeCache};
 use crate::fonts;
-use crate::ipc_protocol::{start_ipc_server, IpcHandle};
+use ipc_protocol::{start_ipc_server, IpcHandle};
+use crate::notifications::NotificationLog;
+use crate::palette_usage::{self, PaletteUsage};
 use crate::persist::{self, PersistedState, PersistedTerminal, PersistedWorkspace};
+#[cfg(unix)]
+use crate::rescue;
+use crate::status_segments::StatusSegments;
 use crate::terminal::TerminalPanel;
 use crate::ui::{
-    command_palette, dialogs_state, sidebar, status_bar, terminal_strip, ActiveDialog, DialogAction,
+    self, command_palette, dialogs_state, empty_state, keystroke_overlay, mru_switcher, notification_history,
+    overlay_terminal, restore_progress, search, sidebar, status_bar, terminal_strip, toasts, ActiveDialog, Command, DialogAction,
 };
-use crate::util::layout;
+use crate::util::keystrokes::{KeystrokeLog, KeystrokeMode};
+use crate::util::toasts::{ToastLevel, Toasts};
+use crate::util::labels::LabelCache;
+use crate::util::layout::{self, TerminalPlacement};
+use crate::watchdog::Watchdog;
 use crate::workspace::Workspace;
 use eframe::egui;
 use egui_term::PtyEvent;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::mpsc::{self, Receiver, Sender};
-use self::perf::PerfStats;
+use self::actions::Action;
+use self::ipc::EventSnapshot;
+use self::perf::{DebugHud, PerfStats};
+use self::restore::PendingRestore;
+use self::ssh::SshScanner;
+use self::terminals::EmptiedWorkspace;
 
-/// Width ratios for terminal panels
-pub const WIDTH_RATIOS: [f32; 4] = [0.333, 0.5, 0.667, 1.0];
+/// Height of the status bar below the terminals, before `ui_scale`
+const STATUS_BAR_HEIGHT: f32 = 28.0;
+
+/// Seconds between snapshots of the state a crash rescue resumes from
+#[cfg(unix)]
+const RESCUE_SNAPSHOT_SECS: f64 = 2.0;
+
+/// Where a command originated, used to decide whether confirmation dialogs apply
+#[derive(Clone, Copy, PartialEq)]
+pub enum CommandSource {
+    /// Keyboard shortcut, command palette, or other in-app interaction
+    Interactive,
+    /// IPC request from an external tool
+    Ipc,
+}
+
+/// Workspace switch animation in progress
+pub(crate) struct WorkspaceTransition {
+    /// Name of the outgoing workspace, drawn until the animation ends
+    pub from: String,
+    /// Whether the incoming workspace comes after the outgoing one in sidebar order
+    pub forward: bool,
+    /// 0.0 (just switched) to 1.0 (done)
+    pub progress: f32,
+}
+
+/// Another workspace shown in place of the active one while a peek chord is held
+pub(crate) struct Peek {
+    /// Name of the peeked workspace
+    pub workspace: String,
+    /// Key of the chord; releasing it (or ⌘) snaps back to the active workspace
+    pub key: egui::Key,
+}
+
+/// ⌃Tab switch in progress: focus moves through `order` while ⌃ is held, and the
+/// focus history is updated once it is released
+pub(crate) struct MruSwitch {
+    /// Terminals of the workspace, most recently focused first (at the first ⌃Tab)
+    pub order: Vec<u64>,
+    /// Position in `order` of the terminal focused now
+    pub selected: usize,
+}
 
 /// The scrolling window manager
 pub struct App {
     /// Application configuration
     config: Config,
-    /// Terminal color theme (cached from config)
-    terminal_theme: TerminalTheme,
+    /// Terminal color themes per scheme (cached from config)
+    themes: ThemeCache,
     /// Terminal panels (global pool)
     panels: HashMap<u64, TerminalPanel>,
     /// Workspaces
@@ -44,20 +104,119 @@ pub struct App {
     event_tx: Sender<(u64, PtyEvent)>,
     /// IPC handle for external control (server runs in background thread)
     ipc_handle: Option<IpcHandle>,
+    /// State last published to IPC event subscribers (None while nobody subscribes)
+    event_snapshot: Option<EventSnapshot>,
     /// Socket path for IPC (passed to terminal env)
     socket_path: Option<PathBuf>,
-    /// Whether the command palette is open
-    command_palette_open: bool,
+    /// Open command palette (None = closed)
+    command_palette: Option<command_palette::CommandPalette>,
+    /// How often and how recently palette commands were run, for ranking them
+    palette_usage: PaletteUsage,
+    /// Whether the notification history overlay is open
+    notification_history_open: bool,
+    /// History of notification events (IPC notifies, bells)
+    notifications: NotificationLog,
     /// Whether follow mode is active (jump to terminal by letter)
     follow_mode: bool,
     /// Whether move-to-spot mode is active (move terminal to position by letter)
     move_to_spot_mode: bool,
     /// Whether the sidebar is visible
     sidebar_visible: bool,
+    /// Inline title edit in progress in the sidebar
+    title_edit: Option<sidebar::TitleEdit>,
+    /// Scrollback search bar over the focused terminal
+    search: Option<search::SearchBar>,
+    /// Sidebar entry text cached per terminal
+    sidebar_labels: LabelCache,
+    /// Sidebar entry heights measured when last drawn, for skipping entries out of view
+    sidebar_row_heights: HashMap<u64, layout::RowHeight>,
     /// Performance tracking stats
     perf_stats: PerfStats,
     /// Active dialog (confirmation, input, etc.)
     active_dialog: ActiveDialog,
+    /// Workspace to return to once the scratch workspace empties
+    scratch_return_workspace: Option<String>,
+    /// Workspace emptied by a move, kept for a few seconds so the move can be undone
+    emptied_workspace: Option<EmptiedWorkspace>,
+    /// Names of workspaces removed after emptying, most recent first
+    recent_workspaces: Vec<String>,
+    /// Working directories reported by terminals, most recent first
+    recent_directories: Vec<PathBuf>,
+    /// Text of the start screen's "Open project" field
+    start_screen_path: String,
+    /// Drop-down terminal with its internal ID (spawned on first toggle, kept while hidden)
+    overlay_terminal: Option<(u64, TerminalPanel)>,
+    /// Whether the drop-down terminal is shown (or sliding in)
+    overlay_visible: bool,
+    /// Drop-down slide progress, 0.0 (hidden) to 1.0 (shown)
+    overlay_progress: f32,
+    /// Name of the workspace shown in the previous frame (detects workspace switches)
+    shown_workspace: String,
+    /// Workspace switch animation in progress
+    workspace_transition: Option<WorkspaceTransition>,
+    /// Workspace shown while ⌘⌥↓/↑ or ⌘⌥1..9 is held (`active_workspace` is unchanged)
+    peek: Option<Peek>,
+    /// ⌃Tab switch in progress (switcher shown)
+    mru_switch: Option<MruSwitch>,
+    /// Sidebar groups shown folded to their header
+    collapsed_groups: HashSet<String>,
+    /// Latency/throughput HUD over the focused terminal (None = hidden)
+    debug_hud: Option<DebugHud>,
+    /// Time of the last `link_exclusions` check (seconds)
+    link_policy_checked: f64,
+    /// Time of the last check of project files for workspace renames (seconds)
+    projects_checked: f64,
+    /// Time of the last SSH health check (seconds)
+    ssh_checked: f64,
+    /// Background lookup of the SSH sessions running in terminals
+    ssh_scanner: SshScanner,
+    /// Cached text of the status bar segments (`status_segments`)
+    status_segments: StatusSegments,
+    /// Status bar text from `init.lua`'s `manse.on_status`
+    script_status: Option<String>,
+    /// Extra sidebar line per terminal from `init.lua`'s `manse.on_sidebar`
+    script_sidebar: HashMap<u64, String>,
+    /// Time the `init.lua` functions were last called (seconds)
+    script_checked: f64,
+    /// Time before which placeholders on screen are not started, after one failed (seconds)
+    deferred_retry_at: f64,
+    /// Time of the last output sample for the minimap heatmap (seconds)
+    activity_checked: f64,
+    /// Time of the last crash rescue snapshot (seconds), and the terminal count and
+    /// next ID it was taken with
+    rescue_snapshot: (f64, usize, u64),
+    /// Restarts manse when a frame hangs (`watchdog_secs`)
+    watchdog: Watchdog,
+    /// Macro being recorded and the last recorded one
+    macros: actions::Macros,
+    /// Mirror input typed into the focused terminal to the terminals marked `broadcast`
+    broadcast_mode: bool,
+    /// Key chords shown in the keystroke overlay (starts from config, cycled from the palette)
+    keystroke_mode: KeystrokeMode,
+    /// Recently pressed chords for the keystroke overlay
+    keystrokes: KeystrokeLog,
+    /// Size of the UI chrome (starts from `ui_scale`, adjusted with ⌘⌥= / ⌘⌥-)
+    ui_scale: f32,
+    /// Terminal font scale of every workspace (global zoom), under workspace and terminal zoom
+    font_scale: f32,
+    /// Warnings and errors shown over the window (e.g. failed terminal spawns)
+    toasts: Toasts,
+    /// Time (egui seconds) the current frame started, for toasts raised during it
+    frame_time: f64,
+    /// Window title last set (`window_title`)
+    window_title: String,
+    /// Light/dark variant the colors were last switched to (`None` until the first switch)
+    appearance: Option<Appearance>,
+    /// Terminals still being re-attached after a restart (progress screen shown)
+    restore: Option<PendingRestore>,
+    /// Signals changes to init.lua (`watch_config`)
+    config_changes: Option<Receiver<()>>,
+    /// Terminals taken out with `term-detach`, held until another instance adopts them
+    #[cfg(unix)]
+    detached: Vec<handoff::DetachedTerminal>,
+    /// Terminals being handed over by another instance (`term_attach`)
+    #[cfg(unix)]
+    attaching: Vec<handoff::PendingAttach>,
 }
 
 impl App {
@@ -78,29 +237,94 @@ impl App {
             }
         });
 
-        let terminal_theme = config.build_theme();
+        let themes = config.build_themes();
+        let keystroke_mode = config.keystroke_overlay;
+        let ui_scale = config.ui_scale;
+        ui::apply_ui_scale(&cc.egui_ctx, ui_scale);
+        let perf_stats = PerfStats::with_log_file(config.perf_log_file.clone(), config.perf_log_max_kb * 1024);
+        let watchdog = Watchdog::start(config.watchdog_secs);
+        let config_changes = if config.watch_config {
+            config::watch_config(cc.egui_ctx.clone())
+        } else {
+            None
+        };
 
         let mut app = Self {
             config,
-            terminal_theme,
+            themes,
             panels: HashMap::new(),
-            workspaces: vec![Workspace::new("default")],
+            workspaces: vec![Workspace::implicit()],
             active_workspace: 0,
             next_id: 0,
             event_rx,
             event_tx,
             ipc_handle,
+            event_snapshot: None,
             socket_path,
-            command_palette_open: false,
+            command_palette: None,
+            palette_usage: PaletteUsage::load(),
+            notification_history_open: false,
+            notifications: NotificationLog::default(),
             follow_mode: false,
             move_to_spot_mode: false,
             sidebar_visible: true,
-            perf_stats: PerfStats::default(),
+            title_edit: None,
+            search: None,
+            sidebar_labels: LabelCache::default(),
+            sidebar_row_heights: HashMap::new(),
+            perf_stats,
             active_dialog: ActiveDialog::None,
+            scratch_return_workspace: None,
+            emptied_workspace: None,
+            recent_workspaces: Vec::new(),
+            recent_directories: Vec::new(),
+            start_screen_path: String::new(),
+            overlay_terminal: None,
+            overlay_visible: false,
+            overlay_progress: 0.0,
+            // Empty so the first frame records the active workspace without animating
+            shown_workspace: String::new(),
+            workspace_transition: None,
+            peek: None,
+            mru_switch: None,
+            collapsed_groups: HashSet::new(),
+            debug_hud: None,
+            link_policy_checked: 0.0,
+            projects_checked: 0.0,
+            ssh_checked: 0.0,
+            ssh_scanner: SshScanner::spawn(cc.egui_ctx.clone()),
+            status_segments: StatusSegments::default(),
+            script_status: None,
+            script_sidebar: HashMap::new(),
+            script_checked: 0.0,
+            deferred_retry_at: 0.0,
+            rescue_snapshot: (f64::NEG_INFINITY, 0, 0),
+            watchdog,
+            activity_checked: 0.0,
+            macros: actions::Macros::default(),
+            broadcast_mode: false,
+            keystroke_mode,
+            ui_scale,
+            font_scale: 1.0,
+            keystrokes: KeystrokeLog::default(),
+            toasts: Toasts::default(),
+            frame_time: 0.0,
+            window_title: String::new(),
+            appearance: None,
+            restore: None,
+            config_changes,
+            #[cfg(unix)]
+            detached: Vec::new(),
+            #[cfg(unix)]
+            attaching: Vec::new(),
         };
 
-        // Create initial terminal
-        app.create_terminal(&cc.egui_ctx);
+        // Recreate the last session's layout, or create the initial terminal (a failure is
+        // shown as a toast over the start screen)
+        let restored = app.config.restore_on_start && app.restore_last_session(&cc.egui_ctx);
+        if !restored {
+            let _ = app.create_terminal(&cc.egui_ctx, TerminalPlacement::End);
+        }
 
         app
     }
@@ -126,89 +350,94 @@ impl App {
             }
         };
 
-        let mut panels = HashMap::new();
-        let mut workspaces = Vec::new();
-
-        // Restore all terminals from all workspaces
-        for persisted_ws in &state.workspaces {
-            let mut ws = Workspace::new(&persisted_ws.name);
-            ws.focused_index = persisted_ws.focused_index;
-
-            for persisted_term in &persisted_ws.terminals {
-                // Try to restore this terminal
-                match unsafe {
-                    TerminalPanel::from_persisted(
-                        persisted_term.internal_id,
-                        persisted_term,
-                        &cc.egui_ctx,
-                        event_tx.clone(),
-                    )
-                } {
-                    Ok(panel) => {
-                        panels.insert(persisted_term.internal_id, panel);
-                        ws.panel_order.push(persisted_term.internal_id);
-
-                        // Force redraw by toggling PTY size
-                        if let Err(e) = persist::force_redraw(
-                            persisted_term.pty_fd,
-                            persisted_term.pty_pid,
-                        ) {
-                            log::warn!(
-                                "Failed to force redraw for terminal {}: {}",
-                                persisted_term.external_id,
-                                e
-                            );
-                        }
-                    }
-                    Err(e) => {
-                        log::warn!(
-                            "Failed to restore terminal {}: {}",
-                            persisted_term.external_id,
-                            e
-                        );
-                    }
-                }
-            }
-
-            // Fix up focused_index if needed
-            if ws.focused_index >= ws.panel_order.len() {
-                ws.focused_index = ws.panel_order.len().saturating_sub(1);
-            }
-
-            workspaces.push(ws);
+        if state.workspaces.iter().all(|ws| ws.terminals.is_empty()) {
+            return Err("No terminals to restore".to_string());
         }
 
-        // If we failed to restore anything, return an error
-        if panels.is_empty() {
-            return Err("No terminals could be restored".to_string());
-        }
-
-        // Remove any empty workspaces (except keep at least one)
-        workspaces.retain(|ws| !ws.panel_order.is_empty());
-        if workspaces.is_empty() {
-            workspaces.push(Workspace::new("default"));
-        }
-
-        let active_workspace = state.active_workspace.min(workspaces.len().saturating_sub(1));
-        let terminal_theme = config.build_theme();
+        // Terminals are re-attached one per frame behind the restore progress screen
+        let next_id = state.next_id;
+        let font_scale = state.font_scale;
+        let (restore, workspaces) = PendingRestore::new(state);
+        let themes = config.build_themes();
+        let keystroke_mode = config.keystroke_overlay;
+        let ui_scale = config.ui_scale;
+        ui::apply_ui_scale(&cc.egui_ctx, ui_scale);
+        let perf_stats = PerfStats::with_log_file(config.perf_log_file.clone(), config.perf_log_max_kb * 1024);
+        let watchdog = Watchdog::start(config.watchdog_secs);
+        let config_changes = if config.watch_config {
+            config::watch_config(cc.egui_ctx.clone())
+        } else {
+            None
+        };
 
         Ok(Self {
             config,
-            terminal_theme,
-            panels,
+            themes,
+            panels: HashMap::new(),
             workspaces,
-            active_workspace,
-            next_id: state.next_id,
+            active_workspace: 0,
+            next_id,
             event_rx,
             event_tx,
             ipc_handle,
+            event_snapshot: None,
             socket_path: Some(socket_path),
-            command_palette_open: false,
+            command_palette: None,
+            palette_usage: PaletteUsage::load(),
+            notification_history_open: false,
+            notifications: NotificationLog::default(),
             follow_mode: false,
             move_to_spot_mode: false,
             sidebar_visible: true,
-            perf_stats: PerfStats::default(),
+            title_edit: None,
+            search: None,
+            sidebar_labels: LabelCache::default(),
+            sidebar_row_heights: HashMap::new(),
+            perf_stats,
             active_dialog: ActiveDialog::None,
+            scratch_return_workspace: None,
+            emptied_workspace: None,
+            recent_workspaces: Vec::new(),
+            recent_directories: Vec::new(),
+            start_screen_path: String::new(),
+            overlay_terminal: None,
+            overlay_visible: false,
+            overlay_progress: 0.0,
+            // Empty so the first frame records the active workspace without animating
+            shown_workspace: String::new(),
+            workspace_transition: None,
+            peek: None,
+            mru_switch: None,
+            collapsed_groups: HashSet::new(),
+            debug_hud: None,
+            link_policy_checked: 0.0,
+            projects_checked: 0.0,
+            ssh_checked: 0.0,
+            ssh_scanner: SshScanner::spawn(cc.egui_ctx.clone()),
+            status_segments: StatusSegments::default(),
+            script_status: None,
+            script_sidebar: HashMap::new(),
+            script_checked: 0.0,
+            deferred_retry_at: 0.0,
+            rescue_snapshot: (f64::NEG_INFINITY, 0, 0),
+            watchdog,
+            activity_checked: 0.0,
+            macros: actions::Macros::default(),
+            broadcast_mode: false,
+            keystroke_mode,
+            ui_scale,
+            font_scale,
+            keystrokes: KeystrokeLog::default(),
+            toasts: Toasts::default(),
+            frame_time: 0.0,
+            window_title: String::new(),
+            appearance: None,
+            restore: Some(restore),
+            config_changes,
+            #[cfg(unix)]
+            detached: Vec::new(),
+            #[cfg(unix)]
+            attaching: Vec::new(),
         })
     }
 
@@ -223,7 +452,7 @@ impl App {
                     .panel_order
                     .iter()
                     .filter_map(|&id| {
-                        self.panels.get(&id).map(|panel| panel.to_persisted(id))
+                        self.panels.get(&id).and_then(|panel| panel.to_persisted(id))
                     })
                     .collect();
 
@@ -231,6 +460,9 @@ impl App {
                     name: ws.name.clone(),
                     panel_order: ws.panel_order.clone(),
                     focused_index: ws.focused_index,
+                    font_scale: ws.font_scale,
+                    project: ws.project.clone(),
+                    group: ws.group.clone(),
                     terminals,
                 }
             })
@@ -241,7 +473,38 @@ impl App {
             workspaces,
             active_workspace: self.active_workspace,
             next_id: self.next_id,
+            font_scale: self.font_scale,
+        }
+    }
+
+    /// Refresh the state a crash rescue resumes from every `RESCUE_SNAPSHOT_SECS`, and
+    /// right away when terminals were opened or closed (their PTY fds must match).
+    #[cfg(unix)]
+    fn update_rescue_snapshot(&mut self, ctx: &egui::Context) {
+        let now = ctx.input(|i| i.time);
+        let (taken, count, next_id) = self.rescue_snapshot;
+        if now - taken < RESCUE_SNAPSHOT_SECS && count == self.panels.len() && next_id == self.next_id {
+            return;
         }
+        self.rescue_snapshot = (now, self.panels.len(), self.next_id);
+        let socket = self.socket_path.clone().unwrap_or_else(|| PathBuf::from("/tmp/manse.sock"));
+        rescue::set_snapshot(&self.to_persisted_state(), &socket);
+    }
+
+    /// What the watchdog logs about the app should a frame hang.
+    fn hang_diagnostics(&self) -> String {
+        let ws = self.active_workspace();
+        let focused = self.focused_panel().map_or("-", |p| p.display_title());
+        format!(
+            "workspace \"{}\" ({} terminals, focused \"{}\"), {} workspaces, restoring: {}, dialog open: {}; {}",
+            ws.name,
+            ws.panel_order.len(),
+            focused,
+            self.workspaces.len(),
+            self.restore.is_some(),
+            !matches!(self.active_dialog, ActiveDialog::None),
+            self.perf_stats.summary(),
+        )
     }
 
     /// Trigger a restart by saving state and exec'ing a new process.
@@ -257,8 +520,7 @@ impl App {
             .map_err(|e| format!("Failed to save state: {}", e))?;
 
         // 2. Clear CLOEXEC on all PTY fds
-        for panel in self.panels.values() {
-            let fd = panel.pty_fd();
+        for fd in self.panels.values().filter_map(|panel| panel.pty_fd()) {
             if let Err(e) = persist::clear_cloexec(fd) {
                 log::warn!("Failed to clear CLOEXEC on fd {}: {}", fd, e);
             }
@@ -286,11 +548,133 @@ impl App {
         // If we get here, exec failed
         Err(format!("exec failed: {}", err))
     }
+
+    /// Re-read init.lua and apply it to the running instance (`manse config-reload`, ⌘⇧L
+    /// or a change on disk with `watch_config`).
+    pub(crate) fn reload_config(&mut self, ctx: &egui::Context) -> Result<(), String> {
+        let config = config::reload_config()?;
+        self.apply_config(ctx, config);
+        log::info!("Config reloaded");
+        Ok(())
+    }
+
+    /// Switch to `config`, refreshing fonts, themes and cached layout. The perf log file
+    /// and the terminal engine of existing terminals stay as they were started.
+    pub(crate) fn apply_config(&mut self, ctx: &egui::Context, config: Config) {
+        if config.font_family != self.config.font_family {
+            fonts::setup_fonts(ctx, config.font_family.as_deref());
+        }
+        if !config.watch_config {
+            self.config_changes = None;
+        } else if self.config_changes.is_none() {
+            self.config_changes = config::watch_config(ctx.clone());
+        }
+        self.keystroke_mode = config.keystroke_overlay;
+        self.watchdog.set_timeout(config.watchdog_secs);
+        if config.ui_scale != self.config.ui_scale {
+            self.ui_scale = config.ui_scale;
+            ui::apply_ui_scale(ctx, self.ui_scale);
+        }
+        if config.scrollback_lines != self.config.scrollback_lines {
+            for panel in self.panels.values() {
+                panel.backend.set_scrollback_lines(config.scrollback_lines);
+            }
+        }
+        self.config = config;
+        self.themes = self.config.build_themes();
+        // Re-apply the light/dark variant to the new colors
+        self.appearance = None;
+        self.update_appearance(ctx);
+        self.update_repaint_intervals();
+        // Re-check foreground processes against the new link_exclusions
+        self.link_policy_checked = 0.0;
+        // Call the new init.lua's functions right away
+        self.script_checked = 0.0;
+        for ws in &mut self.workspaces {
+            ws.invalidate_positions();
+        }
+        ctx.request_repaint();
+    }
+
+    /// Switch to the light or dark colors (`colors_light` / `colors_dark`) when the OS
+    /// appearance or the schedule calls for it, rebuilding the terminal themes.
+    pub(crate) fn update_appearance(&mut self, ctx: &egui::Context) {
+        let appearance = &self.config.appearance;
+        if !appearance.is_dynamic() {
+            return;
+        }
+        let wanted = match appearance.mode {
+            AppearanceMode::Light => Appearance::Light,
+            AppearanceMode::Dark => Appearance::Dark,
+            AppearanceMode::System => match ctx.system_theme() {
+                Some(egui::Theme::Light) => Appearance::Light,
+                _ => Appearance::Dark,
+            },
+            AppearanceMode::Schedule => {
+                let Some(now) = local_minutes() else {
+                    return;
+                };
+                let (wanted, minutes_left) = appearance.scheduled(now);
+                // Wake up for the next switch even when nothing else repaints
+                ctx.request_repaint_after(std::time::Duration::from_secs(minutes_left as u64 * 60));
+                wanted
+            }
+        };
+        if self.appearance == Some(wanted) {
+            return;
+        }
+
+        self.appearance = Some(wanted);
+        if self.config.apply_appearance(wanted) {
+            self.themes = self.config.build_themes();
+            log::info!("Switched to {:?} colors", wanted);
+            ctx.request_repaint();
+        }
+    }
+}
+
+/// Local time of day in minutes since midnight.
+#[cfg(unix)]
+fn local_minutes() -> Option<u32> {
+    let tm = crate::util::time::local_tm()?;
+    Some((tm.tm_hour * 60 + tm.tm_min) as u32)
+}
+
+#[cfg(not(unix))]
+fn local_minutes() -> Option<u32> {
+    None
 }
 
 impl eframe::App for App {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // Counts as hung if this frame is still running after `watchdog_secs`
+        let _watchdog_frame = self.watchdog.frame(self.panels.len());
         self.perf_stats.on_frame(ctx);
+        self.watchdog.note(|| self.hang_diagnostics());
+        self.frame_time = ctx.input(|i| i.time);
+
+        // Re-attach terminals after a restart, one per frame, before anything else runs
+        if self.restore.is_some() {
+            self.step_restore(ctx);
+            let mut continue_clicked = false;
+            if let Some(restore) = &self.restore {
+                egui::CentralPanel::default()
+                    .frame(egui::Frame::NONE.fill(self.config.ui_colors.sidebar_background))
+                    .show(ctx, |ui| {
+                        continue_clicked = restore_progress::render(
+                            ui,
+                            &restore.entries,
+                            restore.is_done(),
+                            &self.config.ui_colors,
+                            self.ui_scale,
+                        );
+                    });
+            }
+            if continue_clicked {
+                self.finish_restore();
+            }
+            return;
+        }
 
         // Skip rendering when minimized (window definitely not visible)
         let is_minimized = ctx.input(|i| i.viewport().minimized.unwrap_or(false));
@@ -299,6 +683,7 @@ impl eframe::App for App {
             // Still process events so terminals don't buffer forever
             self.process_events(ctx);
             self.process_ipc(ctx);
+            self.publish_ipc_events();
             // Use slow refresh rate when minimized to save battery
             ctx.request_repaint_after(std::time::Duration::from_millis(500));
             self.perf_stats.maybe_log(self.config.perf_log_interval);
@@ -307,72 +692,215 @@ impl eframe::App for App {
 
         // Request repaint during scroll animation
         let ws = self.active_workspace();
-        let is_scrolling = layout::is_animating(ws.scroll_offset, ws.target_offset);
+        let is_scrolling = layout::is_animating(ws.scroll_offset, ws.target_offset)
+            || layout::is_animating(ws.overscroll, 0.0);
         if is_scrolling {
             self.perf_stats.on_scroll_anim();
             ctx.request_repaint();
         }
 
+        // Follow the OS appearance or the light/dark schedule
+        self.update_appearance(ctx);
+
         // Process PTY events
         self.process_events(ctx);
 
+        // Keep the buffered-bytes counter on paused terminals current
+        if self.panels.values().any(|p| p.paused) {
+            ctx.request_repaint_after(std::time::Duration::from_millis(250));
+        }
+
         // Process IPC commands (background thread triggers repaint when requests arrive)
         self.process_ipc(ctx);
 
+        // Pick up edits to init.lua (a failed reload keeps the running config)
+        if self.config_changes.as_ref().is_some_and(|rx| rx.try_iter().count() > 0) {
+            if let Err(e) = self.reload_config(ctx) {
+                self.show_toast(ToastLevel::Error, e);
+            }
+        }
+
+        // Log chords for the keystroke overlay before shortcuts and terminals consume them
+        self.record_keystrokes(ctx);
+
         // Handle keyboard shortcuts
         self.handle_keyboard_shortcuts(ctx);
 
-        // Clear notification on focused terminal
+        // Snap back from a peek once its chord is released
+        self.update_peek(ctx);
+
+        // Note focus changes for ⌃Tab, or finish a ⌃Tab switch once ⌃ is released
+        self.update_focus_history(ctx);
+
+        // Clear notification and activity dot on focused terminal
         if let Some(panel) = self.focused_panel_mut() {
             panel.notified = false;
+            panel.marks.clear();
         }
 
+        // Coalesce output-driven repaints, keeping the focused terminal's latency low
+        self.update_repaint_intervals();
+
+        // Skip URL detection in terminals running an excluded full-screen app
+        #[cfg(unix)]
+        self.update_link_detection(ctx);
+
+        // Track SSH sessions and mark the ones that stopped answering
+        #[cfg(unix)]
+        self.update_ssh_health(ctx);
+
+        // Refresh the status bar's clock, battery and command segments
+        self.status_segments.update(ctx, &self.config.status_segments);
+
+        // Sample output for the minimap heatmap
+        self.update_activity(ctx);
+
+        // Type project commands into shells that are ready for them
+        self.send_pending_input(ctx);
+
+        // Follow workspace renames made in project files
+        self.sync_project_names(ctx);
+
+        // Remove a workspace emptied by a move once it can no longer be undone
+        self.expire_emptied_workspace(ctx);
+        self.expire_empty_workspaces(ctx);
+
         // Update scroll animation
         self.update_scroll();
 
+        // Start or advance the workspace switch animation
+        self.update_workspace_transition(ctx);
+
+        // Tell apart terminals sharing a title (cached per workspace)
+        self.refresh_title_suffixes();
+
+        // Name the window after the workspace and focused terminal
+        self.update_window_title(ctx);
+
+        // Text from init.lua's manse.on_status / manse.on_sidebar (rate-limited)
+        self.update_script_text(ctx);
+
+        // Keep the state a panic resumes from current
+        #[cfg(unix)]
+        self.update_rescue_snapshot(ctx);
+
+        // Slide the drop-down terminal toward its shown/hidden state
+        let overlay_target = if self.overlay_visible { 1.0 } else { 0.0 };
+        if self.overlay_progress != overlay_target {
+            let step = ctx.input(|i| i.stable_dt) / layout::OVERLAY_SLIDE_SECS;
+            self.overlay_progress = layout::step_toward(self.overlay_progress, overlay_target, step);
+            ctx.request_repaint();
+        }
+
+        // Drop an inline title edit whose field is no longer on screen
+        let edit_orphaned = self
+            .title_edit
+            .as_ref()
+            .is_some_and(|edit| !self.panels.contains_key(&edit.panel_id));
+        if edit_orphaned || !self.sidebar_visible {
+            self.title_edit = None;
+        }
+
+        // Close the scrollback search once its terminal loses focus or closes
+        let search_orphaned = self.search.as_ref().is_some_and(|bar| {
+            let ws = self.active_workspace();
+            ws.panel_order.get(ws.focused_index) != Some(&bar.panel_id)
+        });
+        if search_orphaned {
+            self.close_search();
+        }
+
         // Sidebar (left)
         if self.sidebar_visible {
             egui::SidePanel::left("sidebar")
                 .resizable(false)
-                .exact_width(self.config.sidebar.width)
+                .exact_width(self.config.sidebar.width * self.ui_scale)
                 .frame(egui::Frame::NONE.fill(self.config.ui_colors.sidebar_background))
                 .show(ctx, |ui| {
+                    let mut sidebar_state = sidebar::SidebarState {
+                        show_jump_letters: self.follow_mode || self.move_to_spot_mode,
+                        broadcast: self.broadcast_mode,
+                        title_edit: &mut self.title_edit,
+                        labels: &mut self.sidebar_labels,
+                        row_heights: &mut self.sidebar_row_heights,
+                        collapsed_groups: &self.collapsed_groups,
+                        script_text: &self.script_sidebar,
+                        now: ctx.input(|i| i.time),
+                    };
                     if let Some(action) =
-                        sidebar::render(ui, &self.workspaces, self.active_workspace, &self.panels, self.follow_mode || self.move_to_spot_mode, &self.config.sidebar, &self.config.icons, &self.config.ui_colors)
+                        sidebar::render(ui, &self.workspaces, self.active_workspace, &self.panels, &mut sidebar_state, &self.config.sidebar.scaled(self.ui_scale), &self.config.icons, &self.config.ui_colors)
                     {
-                        match action {
+                        let action = match action {
                             sidebar::SidebarAction::SwitchWorkspace(ws_idx) => {
-                                self.active_workspace = ws_idx;
+                                Action::SwitchWorkspace(ws_idx)
                             }
                             sidebar::SidebarAction::FocusTerminal { workspace, terminal } => {
-                                self.active_workspace = workspace;
-                                self.workspaces[workspace].focused_index = terminal;
+                                Action::FocusTerminal { workspace, terminal }
                             }
+                            sidebar::SidebarAction::RenameTerminal { panel_id, title } => {
+                                Action::RenameTerminal { panel_id, title }
+                            }
+                            sidebar::SidebarAction::ToggleBroadcast(panel_id) => {
+                                Action::ToggleBroadcast { panel_id }
+                            }
+                            sidebar::SidebarAction::SetAccent { panel_id, accent } => {
+                                Action::SetAccent { panel_id, accent }
+                            }
+                            sidebar::SidebarAction::ToggleGroup(group) => Action::ToggleGroup { group },
+                            sidebar::SidebarAction::CloseGroup(group) => Action::CloseGroup { group },
+                        };
+                        if let Err(e) = self.dispatch(ctx, action) {
+                            log::warn!("Sidebar action failed: {}", e);
                         }
                     }
                 });
         }
 
         // Main terminal area
+        let mut focused_rect = None;
         egui::CentralPanel::default()
             .frame(egui::Frame::NONE.fill(egui::Color32::BLACK))
             .show(ctx, |ui| {
+                // Start screen for an empty workspace (including after the last terminal exits)
+                if self.active_workspace().panel_order.is_empty() {
+                    let mut template_names: Vec<String> =
+                        self.config.templates.keys().cloned().collect();
+                    template_names.sort();
+                    let mut screen = empty_state::StartScreen {
+                        workspace: &self.workspaces[self.active_workspace].name,
+                        recent_workspaces: &self.recent_workspaces,
+                        recent_directories: &self.recent_directories,
+                        templates: &template_names,
+                        project_path: &mut self.start_screen_path,
+                    };
+                    if let Some(action) =
+                        empty_state::render(ui, &mut screen, &self.config.ui_colors, self.ui_scale)
+                    {
+                        self.handle_start_screen_action(ctx, action);
+                    }
+                    return;
+                }
+
                 let total_width = ui.available_width();
 
                 // Calculate viewport dimensions early so we can cache positions
                 // before rendering the status bar minimap
                 let padding = 4.0;
                 let available = ui.available_size();
-                // Reserve 28px for status bar height
-                let padded_height = available.y - padding * 2.0 - 28.0;
+                // Reserve space for the status bar
+                let status_bar_height = STATUS_BAR_HEIGHT * self.ui_scale;
+                let padded_height = available.y - padding * 2.0 - status_bar_height;
                 let viewport_width = available.x - padding * 2.0;
 
                 // Ensure terminal positions are cached before status bar render
-                self.ensure_positions_cached(viewport_width);
+                self.ensure_positions_cached(ctx, viewport_width);
 
                 // Scroll to focused terminal
                 self.scroll_to_focused(viewport_width);
 
+                // Start the shells of placeholder terminals that came into view
+                self.start_visible_terminals(ctx, viewport_width);
+
                 // Build minimap state from cached positions
                 let minimap_state = {
                     let ws = self.active_workspace();
@@ -384,8 +912,30 @@ impl eframe::App for App {
                         .collect();
 
                     if !positions.is_empty() {
+                        let heat = ws
+                            .cached_positions
+                            .positions
+                            .iter()
+                            .map(|(id, _, _)| self.panels.get(id).map_or(0.0, |p| p.activity.heat()))
+                            .collect();
+                        let now = ctx.input(|i| i.time);
+                        let indicators = ws
+                            .cached_positions
+                            .positions
+                            .iter()
+                            .map(|(id, _, _)| self.panels.get(id).and_then(|p| p.marks.indicator(now)))
+                            .collect();
+                        let accents = ws
+                            .cached_positions
+                            .positions
+                            .iter()
+                            .map(|(id, _, _)| self.panels.get(id).and_then(|p| p.accent_color()))
+                            .collect();
                         Some(status_bar::MinimapState {
                             positions,
+                            heat,
+                            indicators,
+                            accents,
                             scroll_offset: ws.scroll_offset,
                             viewport_width,
                         })
@@ -398,66 +948,359 @@ impl eframe::App for App {
                     .fill(self.config.ui_colors.status_bar_background)
                     .show(ui, |ui| {
                         ui.set_min_width(total_width);
-                        ui.set_height(28.0);
-                        ui.horizontal_centered(|ui| {
-                            status_bar::render(
-                                ui,
-                                self.active_workspace(),
-                                self.focused_panel(),
-                                minimap_state.as_ref(),
-                                &self.config.status_bar,
-                                &self.config.ui_colors,
-                            );
-                        });
+                        ui.set_height(status_bar_height);
+                        let segments: Vec<&str> = self
+                            .script_status
+                            .iter()
+                            .map(String::as_str)
+                            .chain(self.status_segments.texts())
+                            .collect();
+                        let status_action = ui
+                            .horizontal_centered(|ui| {
+                                status_bar::render(
+                                    ui,
+                                    self.active_workspace(),
+                                    self.focused_panel(),
+                                    minimap_state.as_ref(),
+                                    status_bar::StatusModes {
+                                        recording_macro: self.macros.is_recording(),
+                                        broadcasting: self.broadcast_mode,
+                                        passthrough: self.focused_panel().is_some_and(|panel| {
+                                            panel.passthrough_active(self.config.auto_passthrough)
+                                        }),
+                                        font_scale: self.font_scale
+                                            * self.active_workspace().font_scale
+                                            * self.focused_panel().map_or(1.0, |panel| panel.font_scale),
+                                        segments: &segments,
+                                    },
+                                    &self.config.status_bar.scaled(self.ui_scale),
+                                    &self.config.ui_colors,
+                                )
+                            })
+                            .inner;
+
+                        match status_action {
+                            Some(status_bar::StatusBarAction::PanBy(delta)) => {
+                                let target = self.active_workspace().target_offset + delta;
+                                self.pan_strip_to(target);
+                            }
+                            Some(status_bar::StatusBarAction::CenterOn(x)) => {
+                                self.pan_strip_to(x - viewport_width / 2.0);
+                            }
+                            None => {}
+                        }
                     });
 
-                let dialog_open = !matches!(self.active_dialog, ActiveDialog::None);
+                // The drop-down terminal, inline title edits and the search bar take keyboard
+                // focus from the strip
+                let dialog_open = !matches!(self.active_dialog, ActiveDialog::None)
+                    || self.command_palette.is_some()
+                    || self.overlay_visible
+                    || self.title_edit.is_some()
+                    || self.search.is_some();
+                let strip_rect = ui.available_rect_before_wrap();
+
+                // While a peek chord is held, the peeked workspace (non-interactive) takes the
+                // strip's place
+                if let Some(index) = self.peek_index() {
+                    self.ensure_workspace_positions_cached(ctx, index, viewport_width);
+                    let ws = &self.workspaces[index];
+                    let peek_state = terminal_strip::TerminalStripState {
+                        scroll_offset: ws.scroll_offset,
+                        focused_index: ws.focused_index,
+                        font_scale: self.font_scale * ws.font_scale,
+                        positions: &ws.cached_positions.positions,
+                        pinned: ws.cached_positions.pinned,
+                        hud: None,
+                        broadcast: false,
+                        jump_letters: None,
+                    };
+                    terminal_strip::render(
+                        ui,
+                        &self.config,
+                        &self.themes,
+                        &peek_state,
+                        &mut self.panels,
+                        true,
+                        viewport_width,
+                        padded_height,
+                        padding,
+                    );
+                    terminal_strip::peek_banner(ui, strip_rect, &ws.name, self.ui_scale);
+                    return;
+                }
+
+                // During a workspace switch, draw the outgoing workspace (non-interactive) first
+                let transition_frame = self.workspace_transition.as_ref().map(|transition| {
+                    let frame = layout::transition_frame(
+                        self.config.animations.workspace_switch,
+                        transition.progress,
+                        transition.forward,
+                        total_width,
+                    );
+                    let outgoing = self
+                        .workspaces
+                        .iter()
+                        .find(|ws| ws.name == transition.from)
+                        .filter(|ws| ws.cached_positions.is_valid_for(viewport_width, ws.panel_order.len()));
+                    if let Some(ws) = outgoing {
+                        let outgoing_state = terminal_strip::TerminalStripState {
+                            scroll_offset: ws.scroll_offset,
+                            focused_index: ws.focused_index,
+                            font_scale: self.font_scale * ws.font_scale,
+                            positions: &ws.cached_positions.positions,
+                            pinned: ws.cached_positions.pinned,
+                            hud: None,
+                            broadcast: false,
+                            jump_letters: None,
+                        };
+                        let mut outgoing_ui = terminal_strip::transition_ui(
+                            ui,
+                            strip_rect,
+                            "outgoing_workspace",
+                            frame.outgoing_x,
+                            frame.outgoing_opacity,
+                        );
+                        terminal_strip::render(
+                            &mut outgoing_ui,
+                            &self.config,
+                            &self.themes,
+                            &outgoing_state,
+                            &mut self.panels,
+                            true,
+                            viewport_width,
+                            padded_height,
+                            padding,
+                        );
+                    }
+                    frame
+                });
+                let mut incoming_ui = transition_frame.map(|frame| {
+                    terminal_strip::transition_ui(
+                        ui,
+                        strip_rect,
+                        "incoming_workspace",
+                        frame.incoming_x,
+                        frame.incoming_opacity,
+                    )
+                });
+                let strip_ui = incoming_ui.as_mut().unwrap_or(ui);
+
+                let hud = self.debug_hud_text(ctx, frame.info().cpu_usage);
+                // Letters run through the workspaces in sidebar order
+                let jump_letters = (self.follow_mode || self.move_to_spot_mode).then(|| {
+                    self.workspaces[..self.active_workspace]
+                        .iter()
+                        .map(|ws| ws.panel_order.len())
+                        .sum()
+                });
+
+                // Borrow the workspace field directly so `self.panels` can still be lent mutably
+                let ws = &self.workspaces[self.active_workspace];
                 let terminal_state = terminal_strip::TerminalStripState {
-                    scroll_offset: self.active_workspace().scroll_offset,
-                    focused_index: self.active_workspace().focused_index,
-                    positions: self.active_workspace().cached_positions.positions.clone(),
+                    scroll_offset: ws.scroll_offset + ws.overscroll,
+                    focused_index: ws.focused_index,
+                    font_scale: self.font_scale * ws.font_scale,
+                    positions: &ws.cached_positions.positions,
+                    pinned: ws.cached_positions.pinned,
+                    hud: hud.as_deref(),
+                    broadcast: self.broadcast_mode,
+                    jump_letters,
                 };
 
-                if let Some(clicked_idx) = terminal_strip::render(
-                    ui,
+                let strip = terminal_strip::render(
+                    strip_ui,
                     &self.config,
-                    &self.terminal_theme,
+                    &self.themes,
                     &terminal_state,
                     &mut self.panels,
                     dialog_open,
                     viewport_width,
                     padded_height,
                     padding,
-                ) {
+                );
+                if let Some(clicked_idx) = strip.clicked_index {
                     self.workspaces[self.active_workspace].focused_index = clicked_idx;
                 }
+                if !strip.typed.is_empty() {
+                    self.broadcast_input(ctx, &strip.typed);
+                }
+                if let Some(resize) = strip.resize {
+                    self.drag_resize(resize.panel_id, resize.ratio, resize.released);
+                }
+                if let Some(panel_id) = strip.reconnect {
+                    if let Err(e) = self.reconnect_ssh(ctx, panel_id) {
+                        self.show_toast(ToastLevel::Warning, e);
+                    }
+                }
+                match strip.title_bar {
+                    Some(terminal_strip::TitleBarAction::ToggleZoom(panel_id)) => {
+                        let ws = self.active_workspace_mut();
+                        if let Some(index) = ws.panel_order.iter().position(|&id| id == panel_id) {
+                            ws.focused_index = index;
+                            self.dispatch_command(ctx, Command::ToggleZoom);
+                        }
+                    }
+                    Some(terminal_strip::TitleBarAction::Close(panel_id)) => {
+                        if let Err(e) = self.request_close(panel_id, CommandSource::Interactive, false) {
+                            self.show_toast(ToastLevel::Warning, e);
+                        }
+                    }
+                    None => {}
+                }
+                focused_rect = strip.focused_rect;
             });
 
+        // Scrollback search bar over the focused terminal
+        if let (Some(bar), Some(rect)) = (&mut self.search, focused_rect) {
+            if let Some(action) = search::render(ctx, bar, rect, self.ui_scale) {
+                self.handle_search_action(action);
+            }
+        }
+
+        // Drop-down terminal overlay
+        if self.overlay_progress > 0.0 {
+            if let Some((_, panel)) = &mut self.overlay_terminal {
+                let has_focus = self.overlay_visible
+                    && self.command_palette.is_none()
+                    && matches!(self.active_dialog, ActiveDialog::None);
+                overlay_terminal::render(
+                    ctx,
+                    &self.config,
+                    &self.themes.default,
+                    panel,
+                    self.overlay_progress,
+                    has_focus,
+                );
+            }
+        }
+
+        // Notification history overlay
+        if self.notification_history_open {
+            let result = notification_history::render(ctx, &self.notifications, &self.panels, self.ui_scale);
+
+            if result.background_clicked {
+                self.notification_history_open = false;
+            }
+            if result.clear_all {
+                self.notifications.clear();
+            }
+            if let Some(panel_id) = result.focus_panel {
+                self.notification_history_open = false;
+                self.focus_panel(panel_id);
+            }
+        }
+
         // Command palette overlay
-        if self.command_palette_open {
-            let result = command_palette::render(ctx);
+        let palette_terminals = match self.command_palette {
+            Some(_) => self.palette_terminals(),
+            None => Vec::new(),
+        };
+        if let Some(palette) = &mut self.command_palette {
+            let result = command_palette::render(
+                ctx,
+                palette,
+                &self.palette_usage,
+                &self.config.commands,
+                self.config.font_zoom,
+                &palette_terminals,
+                self.ui_scale,
+            );
 
             if result.background_clicked {
-                self.command_palette_open = false;
+                self.command_palette = None;
             }
 
-            if let Some(cmd) = result.selected_command {
-                self.command_palette_open = false;
-                self.execute_command(cmd, ctx);
+            match result.selected {
+                Some(command_palette::PaletteSelection::Command(cmd)) => {
+                    self.command_palette = None;
+                    self.palette_usage
+                        .record(cmd.label(&self.config.commands), palette_usage::now_secs());
+                    if let Err(e) = self.palette_usage.save() {
+                        log::warn!("Failed to save palette usage: {}", e);
+                    }
+                    self.dispatch_command(ctx, cmd);
+                }
+                Some(command_palette::PaletteSelection::Terminal(panel_id)) => {
+                    self.command_palette = None;
+                    self.focus_panel(panel_id);
+                }
+                None => {}
             }
         }
 
-        let dialog_action = dialogs_state::render_dialogs(ctx, &mut self.active_dialog);
+        let dialog_action = dialogs_state::render_dialogs(ctx, &mut self.active_dialog, self.ui_scale);
         match dialog_action {
             DialogAction::None => {}
-            DialogAction::ConfirmClose => self.close_focused(),
+            DialogAction::ConfirmClose(panel_id) => {
+                if let Err(e) = self.close_panel(panel_id) {
+                    log::warn!("Failed to close terminal: {}", e);
+                }
+            }
+            DialogAction::ConfirmQuit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
             DialogAction::SaveDescription(description) => {
-                if let Some(panel) = self.focused_panel_mut() {
-                    panel.description = description;
+                if let Err(e) = self.dispatch(ctx, Action::DescribeFocused(description)) {
+                    log::warn!("Failed to set description: {}", e);
+                }
+            }
+            DialogAction::PipeToCommand { command, text } => {
+                self.pipe_to_command(ctx, &command, &text);
+            }
+            DialogAction::RenameWorkspace(name) => {
+                if let Err(e) = self.request_rename_workspace(self.active_workspace, &name, CommandSource::Interactive) {
+                    self.show_toast(ToastLevel::Warning, e);
+                }
+            }
+            DialogAction::NewTerminalWithCommand(command) => {
+                let placement = self.config.new_terminal_position;
+                if let Ok(new_index) = self.create_terminal_running(ctx, placement, Some(command)) {
+                    self.active_workspace_mut().focused_index = new_index;
+                }
+            }
+            DialogAction::RenameProject { root, name } => self.rename_project(&root, &name),
+            DialogAction::CloseGroup(group) => {
+                if let Err(e) = self.close_group(&group, true) {
+                    self.show_toast(ToastLevel::Warning, e);
                 }
             }
         }
 
+        if !self.toasts.is_empty() {
+            let now = ctx.input(|i| i.time);
+            self.toasts.prune(now);
+            toasts::render(ctx, &self.toasts, now);
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+
+        // ⌃Tab switcher, while ⌃ is held
+        if let Some(switch) = &self.mru_switch {
+            let titles: Vec<String> = switch
+                .order
+                .iter()
+                .filter_map(|id| self.panels.get(id))
+                .map(|panel| panel.display_title().to_string())
+                .collect();
+            mru_switcher::render(ctx, &titles, switch.selected, &self.config.ui_colors, self.ui_scale);
+        }
+
+        // Keystroke overlay, drawn last so it stays above dialogs
+        if !self.keystrokes.is_empty() {
+            let now = ctx.input(|i| i.time);
+            self.keystrokes.prune(now);
+            keystroke_overlay::render(ctx, &self.keystrokes, now);
+            ctx.request_repaint();
+        }
+
+        // Tell IPC subscribers what changed this frame
+        self.publish_ipc_events();
+
         self.perf_stats.maybe_log(self.config.perf_log_interval);
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Not while a restart is still re-attaching: the layout would be partial
+        if self.config.restore_on_start && self.restore.is_none() {
+            self.save_last_session();
+        }
+    }
 }
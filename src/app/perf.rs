@@ -1,5 +1,14 @@
+use crate::util::bytes::{self, ByteRate};
+use crate::util::perf_log::{self, PerfLogFormat, PerfWindow};
 use eframe::egui;
-use std::time::{Duration, Instant};
+use egui_term::IoStats;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Seconds between debug HUD refreshes (also the throughput window)
+pub const HUD_REFRESH_SECS: f64 = 0.5;
 
 /// Performance tracking for debugging battery/CPU usage
 #[derive(Default)]
@@ -22,9 +31,19 @@ pub struct PerfStats {
     keyboard_frames: u64,
     /// Frames where window has focus
     focused_frames: u64,
+    /// Where finished windows are appended (`perf_log_file`), kept across windows
+    log_file: Option<PerfLogWriter>,
 }
 
 impl PerfStats {
+    /// Stats that also append each window to `path` (see `PerfLogWriter`).
+    pub fn with_log_file(path: Option<PathBuf>, max_bytes: u64) -> Self {
+        Self {
+            log_file: path.map(|path| PerfLogWriter::new(path, max_bytes)),
+            ..Default::default()
+        }
+    }
+
     pub fn on_frame(&mut self, ctx: &egui::Context) {
         self.frame_count += 1;
 
@@ -62,7 +81,8 @@ impl PerfStats {
         self.ipc_requests += 1;
     }
 
-    /// Log performance stats if enabled and interval has elapsed
+    /// Log performance stats (and append them to the log file) if enabled and
+    /// interval has elapsed
     pub fn maybe_log(&mut self, interval: f32) {
         if interval <= 0.0 {
             return;
@@ -73,34 +93,166 @@ impl PerfStats {
         let elapsed = now.duration_since(*window_start);
 
         if elapsed >= Duration::from_secs_f32(interval) {
-            let secs = elapsed.as_secs_f64();
-            let fps = self.frame_count as f64 / secs;
-            let s = &self;
-
-            let explained = s.pty_events
-                + s.scroll_animation_frames
-                + s.minimized_frames
-                + s.pointer_frames
-                + s.keyboard_frames;
-            let mystery = s.frame_count.saturating_sub(explained);
+            let window = self.window(elapsed);
 
             log::info!(
-                "[perf] {:.1}s: frames={} ({:.1} fps) | pty={} scroll={} pointer={} kbd={} focused={} | mystery={}",
-                secs,
-                s.frame_count,
-                fps,
-                s.pty_events,
-                s.scroll_animation_frames,
-                s.pointer_frames,
-                s.keyboard_frames,
-                s.focused_frames,
-                mystery,
+                "[perf] {:.1}s: frames={} ({:.1} fps) | pty={} scroll={} pointer={} kbd={} focused={} | mystery={}{}",
+                window.secs,
+                window.frames,
+                window.fps(),
+                window.pty_events,
+                window.scroll_frames,
+                window.pointer_frames,
+                window.keyboard_frames,
+                window.focused_frames,
+                window.mystery(),
+                if window.is_idle() { " | idle" } else { "" },
             );
 
+            let mut log_file = self.log_file.take();
+            if let Some(writer) = &log_file {
+                if let Err(e) = writer.append(&window) {
+                    log::warn!("Not writing perf log {}: {}", writer.path.display(), e);
+                    log_file = None;
+                }
+            }
+
             *self = PerfStats {
                 window_start: Some(now),
+                log_file,
                 ..Default::default()
             };
         }
     }
+
+    /// Counters of the window measured so far, in the form of the `[perf]` log line.
+    pub fn summary(&self) -> String {
+        format!(
+            "frames={} pty={} ipc={} scroll={} pointer={} kbd={} focused={}",
+            self.frame_count,
+            self.pty_events,
+            self.ipc_requests,
+            self.scroll_animation_frames,
+            self.pointer_frames,
+            self.keyboard_frames,
+            self.focused_frames,
+        )
+    }
+
+    /// Counters of the window measured so far (empty unless `perf_log_interval` is set).
+    pub fn current_window(&self) -> PerfWindow {
+        let elapsed = self.window_start.map(|start| start.elapsed()).unwrap_or_default();
+        self.window(elapsed)
+    }
+
+    fn window(&self, elapsed: Duration) -> PerfWindow {
+        PerfWindow {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            secs: elapsed.as_secs_f64(),
+            frames: self.frame_count,
+            pty_events: self.pty_events,
+            ipc_requests: self.ipc_requests,
+            scroll_frames: self.scroll_animation_frames,
+            minimized_frames: self.minimized_frames,
+            pointer_frames: self.pointer_frames,
+            keyboard_frames: self.keyboard_frames,
+            focused_frames: self.focused_frames,
+        }
+    }
+}
+
+/// Appends perf windows to a CSV or JSON-lines file, moving a file that reached
+/// `max_bytes` to `<file>.1` (replacing the previous one) and starting a new one.
+struct PerfLogWriter {
+    path: PathBuf,
+    format: PerfLogFormat,
+    /// 0 = never rotate
+    max_bytes: u64,
+}
+
+impl PerfLogWriter {
+    fn new(path: PathBuf, max_bytes: u64) -> Self {
+        Self {
+            format: PerfLogFormat::for_path(&path),
+            path,
+            max_bytes,
+        }
+    }
+
+    fn append(&self, window: &PerfWindow) -> io::Result<()> {
+        let mut size = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if self.max_bytes > 0 && size >= self.max_bytes {
+            let mut rotated = self.path.clone().into_os_string();
+            rotated.push(".1");
+            std::fs::rename(&self.path, rotated)?;
+            size = 0;
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        if size == 0 && self.format == PerfLogFormat::Csv {
+            writeln!(file, "{}", perf_log::CSV_HEADER)?;
+        }
+        writeln!(file, "{}", window.format_row(self.format, env!("CARGO_PKG_VERSION")))
+    }
+}
+
+/// Per-terminal diagnostics shown over the focused terminal
+pub struct DebugHud {
+    /// Terminal the read rate is being measured for
+    panel_id: Option<u64>,
+    read_rate: ByteRate,
+}
+
+impl Default for DebugHud {
+    fn default() -> Self {
+        Self {
+            panel_id: None,
+            read_rate: ByteRate::new(HUD_REFRESH_SECS),
+        }
+    }
+}
+
+impl DebugHud {
+    /// HUD text for terminal `panel_id` with I/O counters `io` at time `now`.
+    ///
+    /// `frame_cpu` is the CPU time of the last frame, `frame_dt` the time between frames.
+    pub fn text(
+        &mut self,
+        panel_id: u64,
+        io: IoStats,
+        now: f64,
+        frame_cpu: Option<f32>,
+        frame_dt: f32,
+    ) -> String {
+        if self.panel_id != Some(panel_id) {
+            self.panel_id = Some(panel_id);
+            self.read_rate.reset();
+        }
+        let read = match self.read_rate.update(now, io.bytes_read) {
+            Some(rate) => format!("{}/s", bytes::format_bytes(rate as usize)),
+            None => "…".to_string(),
+        };
+        let echo = match io.echo_latency {
+            Some(latency) => format!(
+                "{:.1} ms (median of {})",
+                latency.as_secs_f64() * 1000.0,
+                io.echo_samples
+            ),
+            None => "type to measure".to_string(),
+        };
+        let cpu = frame_cpu
+            .map(|cpu| format!("{:.1} ms cpu, ", cpu * 1000.0))
+            .unwrap_or_default();
+        format!(
+            "echo {}\nread {} ({} total)\nframe {}{:.1} ms",
+            echo,
+            read,
+            bytes::format_bytes(io.bytes_read as usize),
+            cpu,
+            frame_dt * 1000.0
+        )
+    }
 }
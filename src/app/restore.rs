@@ -0,0 +1,180 @@
+#[cfg(unix)]
+use crate::persist;
+use crate::persist::{PersistedState, PersistedTerminal};
+use crate::terminal::TerminalPanel;
+use crate::ui::restore_progress::{RestoreEntry, RestoreStatus};
+use crate::workspace::Workspace;
+use eframe::egui;
+use egui_term::PtyEvent;
+use std::collections::VecDeque;
+use std::sync::mpsc::Sender;
+
+use super::App;
+
+/// Terminals still to re-attach after a restart. They are restored one per frame
+/// behind a progress screen, so the window shows up before everything is attached.
+pub(crate) struct PendingRestore {
+    /// Terminals left, with the index of their workspace
+    queue: VecDeque<(usize, PersistedTerminal)>,
+    /// One per persisted terminal, in restore order
+    pub(crate) entries: Vec<RestoreEntry>,
+    /// Name of the workspace that was active before the restart
+    active_workspace: String,
+}
+
+impl PendingRestore {
+    /// Queue every terminal of `state`. Returns the workspaces to restore them into,
+    /// still without panels.
+    pub(crate) fn new(state: PersistedState) -> (Self, Vec<Workspace>) {
+        let active_workspace = state
+            .workspaces
+            .get(state.active_workspace)
+            .map(|ws| ws.name.clone())
+            .unwrap_or_default();
+        let mut queue = VecDeque::new();
+        let mut entries = Vec::new();
+        let mut workspaces = Vec::new();
+
+        for (ws_idx, persisted_ws) in state.workspaces.into_iter().enumerate() {
+            let mut ws = Workspace::new(&persisted_ws.name);
+            ws.focused_index = persisted_ws.focused_index;
+            ws.font_scale = persisted_ws.font_scale;
+            ws.project = persisted_ws.project;
+            ws.group = persisted_ws.group;
+
+            for persisted_term in persisted_ws.terminals {
+                let title = persisted_term
+                    .custom_title
+                    .clone()
+                    .filter(|title| !title.is_empty())
+                    .or_else(|| Some(persisted_term.title.clone()).filter(|title| !title.is_empty()))
+                    .unwrap_or_else(|| persisted_term.external_id.clone());
+                entries.push(RestoreEntry {
+                    workspace: persisted_ws.name.clone(),
+                    title,
+                    status: RestoreStatus::Pending,
+                });
+                queue.push_back((ws_idx, persisted_term));
+            }
+
+            workspaces.push(ws);
+        }
+
+        let restore = Self {
+            queue,
+            entries,
+            active_workspace,
+        };
+        (restore, workspaces)
+    }
+
+    /// Every terminal has been tried
+    pub(crate) fn is_done(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    fn has_failures(&self) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| matches!(entry.status, RestoreStatus::Failed(_)))
+    }
+}
+
+impl App {
+    /// Re-attach the next queued terminal. Once all are tried the restore finishes on
+    /// its own, unless some failed: then it waits for the user to continue.
+    pub(crate) fn step_restore(&mut self, ctx: &egui::Context) {
+        let Some(restore) = &mut self.restore else {
+            return;
+        };
+        let index = restore.entries.len() - restore.queue.len();
+
+        let Some((ws_idx, persisted_term)) = restore.queue.pop_front() else {
+            if !restore.has_failures() {
+                self.finish_restore();
+            }
+            return;
+        };
+
+        restore.entries[index].status = match restore_terminal(
+            &persisted_term,
+            ctx,
+            self.event_tx.clone(),
+            self.config.scrollback_lines,
+        ) {
+            Ok(panel) => {
+                self.panels.insert(persisted_term.internal_id, panel);
+                self.workspaces[ws_idx].panel_order.push(persisted_term.internal_id);
+                RestoreStatus::Restored
+            }
+            Err(e) => {
+                log::warn!("Failed to restore terminal {}: {}", persisted_term.external_id, e);
+                RestoreStatus::Failed(e)
+            }
+        };
+        ctx.request_repaint();
+    }
+
+    /// Leave the progress screen with whatever was restored. Workspaces that lost all
+    /// their terminals are dropped.
+    pub(crate) fn finish_restore(&mut self) {
+        let Some(restore) = self.restore.take() else {
+            return;
+        };
+
+        for ws in &mut self.workspaces {
+            if ws.focused_index >= ws.panel_order.len() {
+                ws.focused_index = ws.panel_order.len().saturating_sub(1);
+            }
+        }
+
+        // Remove any empty workspaces (except keep at least one)
+        self.workspaces.retain(|ws| !ws.panel_order.is_empty());
+        if self.workspaces.is_empty() {
+            self.workspaces.push(Workspace::implicit());
+        }
+
+        self.active_workspace = self
+            .workspaces
+            .iter()
+            .position(|ws| ws.name == restore.active_workspace)
+            .unwrap_or(0);
+    }
+}
+
+/// Validate and re-attach one persisted terminal, then nudge its program to redraw.
+#[cfg(unix)]
+pub(super) fn restore_terminal(
+    persisted_term: &PersistedTerminal,
+    ctx: &egui::Context,
+    event_tx: Sender<(u64, PtyEvent)>,
+    scrollback_lines: usize,
+) -> Result<TerminalPanel, String> {
+    persisted_term.validate().map_err(|e| e.to_string())?;
+
+    let panel = unsafe {
+        TerminalPanel::from_persisted(persisted_term.internal_id, persisted_term, ctx, event_tx, scrollback_lines)
+    }
+    .map_err(|e| e.to_string())?;
+
+    // Force redraw by toggling PTY size
+    if let Err(e) = persist::force_redraw(persisted_term.pty_fd, persisted_term.pty_pid) {
+        log::warn!(
+            "Failed to force redraw for terminal {}: {}",
+            persisted_term.external_id,
+            e
+        );
+    }
+
+    Ok(panel)
+}
+
+#[cfg(not(unix))]
+pub(super) fn restore_terminal(
+    _persisted_term: &PersistedTerminal,
+    _ctx: &egui::Context,
+    _event_tx: Sender<(u64, PtyEvent)>,
+    _scrollback_lines: usize,
+) -> Result<TerminalPanel, String> {
+    Err("Restoring terminals is only supported on unix".to_string())
+}
@@ -0,0 +1,166 @@
+use crate::project::expand_home;
+use crate::session::{SessionProfile, SessionTerminal, SessionWorkspace, LAST_SESSION_FILE};
+use crate::terminal::{SpawnOptions, TerminalPanel};
+use crate::util::layout::TerminalPlacement;
+use crate::util::toasts::ToastLevel;
+use eframe::egui;
+
+use super::App;
+
+impl App {
+    /// The current layout as a session profile (`manse session save`).
+    pub(crate) fn session_profile(&self) -> SessionProfile {
+        let workspaces = self
+            .workspaces
+            .iter()
+            .map(|ws| SessionWorkspace {
+                name: ws.name.clone(),
+                focused_index: ws.focused_index,
+                group: ws.group.clone(),
+                terminals: ws
+                    .panel_order
+                    .iter()
+                    .filter_map(|id| self.panels.get(id))
+                    .map(session_terminal)
+                    .collect(),
+            })
+            .collect();
+
+        SessionProfile {
+            workspaces,
+            active_workspace: self.active_workspace,
+            font_scale: self.font_scale,
+        }
+    }
+
+    /// Recreate `profile` with new shells (`manse session load`). Terminals are added to
+    /// the end of workspaces that already exist under a saved name. Returns how many
+    /// terminals were started.
+    pub(crate) fn load_session(
+        &mut self,
+        ctx: &egui::Context,
+        profile: &SessionProfile,
+    ) -> Result<usize, String> {
+        let mut started = 0;
+        for saved in &profile.workspaces {
+            let existing = self
+                .workspaces
+                .iter()
+                .find(|ws| ws.name == saved.name)
+                .map_or(0, |ws| ws.panel_order.len());
+
+            for terminal in &saved.terminals {
+                self.spawn_session_terminal(ctx, &saved.name, terminal, false)?;
+                started += 1;
+            }
+
+            if let Some(ws) = self.workspaces.iter_mut().find(|ws| ws.name == saved.name) {
+                let last = ws.panel_order.len().saturating_sub(1);
+                ws.focused_index = (existing + saved.focused_index).min(last);
+                ws.invalidate_positions();
+            }
+            if let Some(group) = &saved.group {
+                if let Some(index) = self.workspaces.iter().position(|ws| ws.name == saved.name) {
+                    self.set_workspace_group(index, Some(group.clone()));
+                }
+            }
+        }
+
+        let active = profile.workspaces.get(profile.active_workspace);
+        if let Some(index) = active.and_then(|saved| self.workspaces.iter().position(|ws| ws.name == saved.name)) {
+            self.active_workspace = index;
+        }
+        self.font_scale = profile.font_scale;
+        Ok(started)
+    }
+
+    /// Save the layout for the next start (`restore_on_start`).
+    pub(crate) fn save_last_session(&self) {
+        let path = expand_home(LAST_SESSION_FILE);
+        if let Err(e) = self.session_profile().save(&path) {
+            log::warn!("Failed to save the session for the next start: {}", e);
+        }
+    }
+
+    /// Recreate the layout saved when the last instance quit (`restore_on_start`), in
+    /// place of the initial empty workspace. Returns false if there was nothing to restore.
+    pub(crate) fn restore_last_session(&mut self, ctx: &egui::Context) -> bool {
+        let path = expand_home(LAST_SESSION_FILE);
+        if !path.exists() {
+            return false;
+        }
+        let profile = match SessionProfile::load(&path) {
+            Ok(profile) => profile,
+            Err(e) => {
+                log::warn!("Not restoring the last session: {}", e);
+                return false;
+            }
+        };
+        if profile.workspaces.iter().all(|ws| ws.terminals.is_empty()) {
+            return false;
+        }
+
+        match self.load_session(ctx, &profile) {
+            Ok(started) => log::info!("Restored {} terminals from the last session", started),
+            Err(e) => self.show_toast(ToastLevel::Error, format!("Failed to restore the last session: {}", e)),
+        }
+        if self.workspaces.len() > 1 && self.workspaces[0].panel_order.is_empty() {
+            self.workspaces.remove(0);
+            self.active_workspace = self.active_workspace.saturating_sub(1);
+        }
+        !self.panels.is_empty()
+    }
+
+    /// Start a new shell for the profile entry `terminal` at the end of `workspace`
+    /// (created if needed). Returns its ID.
+    pub(crate) fn spawn_session_terminal(
+        &mut self,
+        ctx: &egui::Context,
+        workspace: &str,
+        terminal: &SessionTerminal,
+        focus: bool,
+    ) -> Result<String, String> {
+        let options = SpawnOptions {
+            working_directory: terminal.cwd.clone().filter(|dir| dir.is_dir()),
+            startup_command: terminal.command.clone().filter(|c| !c.is_empty()),
+            deferred: self.config.lazy_spawn,
+            ..Default::default()
+        };
+        let id = self.spawn_in_workspace(ctx, Some(workspace), TerminalPlacement::End, options, focus)?;
+        let min_ratio = self.config.width_ratios[0];
+        if let Some(panel) = self.panels.values_mut().find(|p| p.id == id) {
+            panel.custom_title = terminal.title.clone();
+            panel.description = terminal.description.clone();
+            panel.icon = terminal.icon.clone();
+            panel.color_scheme = terminal.color_scheme.clone();
+            panel.accent = terminal.accent.clone();
+            panel.pinned = terminal.pinned;
+            panel.font_scale = terminal.font_scale;
+            panel.width_ratio = terminal.width_ratio.clamp(min_ratio, 1.0);
+        }
+        Ok(id)
+    }
+}
+
+/// `panel` as a profile entry. The shell itself isn't kept: its directory and
+/// foreground command are, to start a new one like it.
+pub(crate) fn session_terminal(panel: &TerminalPanel) -> SessionTerminal {
+    SessionTerminal {
+        cwd: panel.current_working_directory.clone(),
+        title: panel.custom_title.clone(),
+        description: panel.description.clone(),
+        icon: panel.icon.clone(),
+        color_scheme: panel.color_scheme.clone(),
+        accent: panel.accent.clone(),
+        pinned: panel.pinned,
+        font_scale: panel.font_scale,
+        width_ratio: panel.zoomed_from.unwrap_or(panel.width_ratio),
+        #[cfg(unix)]
+        command: match &panel.deferred {
+            Some(options) => options.startup_command.clone(),
+            None => panel.foreground_command(),
+        },
+        #[cfg(not(unix))]
+        command: panel.deferred.as_ref().and_then(|options| options.startup_command.clone()),
+    }
+}
@@ -0,0 +1,194 @@
+use crate::terminal::{self, SpawnOptions, SshHealth, SshLink, SshSession};
+use crate::util::toasts::ToastLevel;
+use eframe::egui;
+use egui_term::CaptureRegion;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+use super::App;
+
+/// Seconds between SSH session scans
+const SSH_HEALTH_REFRESH_SECS: f64 = 2.0;
+
+/// Terminals by panel ID and shell PID
+type ScanRequest = Vec<(u64, u32)>;
+/// SSH session found under each scanned terminal, by panel ID
+type ScanResult = Vec<(u64, Option<SshSession>)>;
+
+/// Looks up the SSH sessions of terminals on a background thread, so the UI thread
+/// never waits for `ps`. The thread ends when the scanner is dropped.
+pub(crate) struct SshScanner {
+    requests: Sender<ScanRequest>,
+    results: Receiver<ScanResult>,
+    /// A scan was requested and its result not collected yet
+    scanning: bool,
+}
+
+impl SshScanner {
+    pub(crate) fn spawn(ctx: egui::Context) -> Self {
+        let (requests, request_rx) = mpsc::channel::<ScanRequest>();
+        let (result_tx, results) = mpsc::channel();
+        std::thread::spawn(move || {
+            for terminals in request_rx {
+                let pids: Vec<u32> = terminals.iter().map(|&(_, pid)| pid).collect();
+                let mut sessions = terminal::detect_ssh_sessions(&pids);
+                let found: ScanResult = terminals
+                    .into_iter()
+                    .map(|(id, pid)| (id, sessions.remove(&pid)))
+                    .collect();
+                if result_tx.send(found).is_err() {
+                    return;
+                }
+                ctx.request_repaint();
+            }
+        });
+        Self {
+            requests,
+            results,
+            scanning: false,
+        }
+    }
+
+    /// Ask for a scan of `terminals`, unless one is still running.
+    fn scan(&mut self, terminals: ScanRequest) {
+        if !self.scanning {
+            self.scanning = self.requests.send(terminals).is_ok();
+        }
+    }
+
+    /// Result of the last scan, once it is done.
+    fn poll(&mut self) -> Option<ScanResult> {
+        let found = self.results.try_iter().last()?;
+        self.scanning = false;
+        Some(found)
+    }
+}
+
+impl App {
+    /// Track SSH sessions in every terminal for the status bar and sidebar badges, and
+    /// with `ssh_timeout_secs` set, mark the ones that stopped answering: typed input
+    /// left without output for that long, or an ssh client that exited with a
+    /// connection error. Sessions are scanned about every two seconds.
+    #[cfg(unix)]
+    pub(crate) fn update_ssh_health(&mut self, ctx: &egui::Context) {
+        if let Some(found) = self.ssh_scanner.poll() {
+            self.apply_ssh_scan(found);
+        }
+
+        let now = ctx.input(|i| i.time);
+        if now - self.ssh_checked < SSH_HEALTH_REFRESH_SECS {
+            return;
+        }
+        self.ssh_checked = now;
+        let terminals = self
+            .panels
+            .iter()
+            .filter(|(_, panel)| !panel.paused)
+            .filter_map(|(&id, panel)| Some((id, panel.pty_pid()?)))
+            .collect();
+        self.ssh_scanner.scan(terminals);
+
+        // Keep checking while the window is idle
+        if self.panels.values().any(|panel| panel.ssh.is_some()) {
+            ctx.request_repaint_after(Duration::from_secs_f64(SSH_HEALTH_REFRESH_SECS));
+        }
+    }
+
+    /// Update each scanned terminal's SSH link from the session found under it.
+    #[cfg(unix)]
+    fn apply_ssh_scan(&mut self, found: ScanResult) {
+        let health_checks = self.config.ssh_timeout_secs > 0;
+        let timeout = Duration::from_secs(self.config.ssh_timeout_secs);
+        for (id, session) in found {
+            let Some(panel) = self.panels.get_mut(&id) else {
+                continue;
+            };
+            match (&mut panel.ssh, session) {
+                (Some(link), Some(session)) => {
+                    let unanswered = panel.backend.io_stats().unanswered_for;
+                    link.health = if health_checks && unanswered.is_some_and(|waited| waited >= timeout) {
+                        SshHealth::Disconnected(format!("No response for {}s", timeout.as_secs()))
+                    } else {
+                        SshHealth::Connected
+                    };
+                    link.session = session;
+                }
+                (None, Some(session)) => {
+                    panel.ssh = Some(SshLink {
+                        session,
+                        health: SshHealth::Connected,
+                    });
+                }
+                (Some(link), None) if link.health == SshHealth::Connected => {
+                    let reason = health_checks
+                        .then(|| panel.backend.capture_text(CaptureRegion::Screen, false))
+                        .and_then(|screen| terminal::disconnect_reason(&screen));
+                    match reason {
+                        Some(reason) => link.health = SshHealth::Disconnected(reason.to_string()),
+                        None => panel.ssh = None,
+                    }
+                }
+                // A dead session stays marked until it is reconnected
+                _ => {}
+            }
+        }
+    }
+
+    /// Replace the terminal `panel_id` with a fresh one that runs its SSH command again,
+    /// in the same place and with the same ID, title and settings.
+    pub(crate) fn reconnect_ssh(&mut self, ctx: &egui::Context, panel_id: u64) -> Result<(), String> {
+        let old = self
+            .panels
+            .get(&panel_id)
+            .ok_or_else(|| format!("No terminal with id {}", panel_id))?;
+        let link = old
+            .ssh
+            .as_ref()
+            .ok_or_else(|| format!("Terminal {} has no SSH session", old.id))?;
+        let options = SpawnOptions {
+            working_directory: old.current_working_directory.clone(),
+            startup_command: Some(link.session.full_command.clone()),
+            terminal_id: Some(old.id.clone()),
+            ..Default::default()
+        };
+        let host = link.session.to_string();
+
+        let (new_id, mut panel) = self.new_panel(ctx, options)?;
+        let Some(old) = self.panels.remove(&panel_id) else {
+            return Ok(());
+        };
+        panel.width_ratio = old.width_ratio;
+        panel.zoomed_from = old.zoomed_from;
+        panel.custom_title = old.custom_title;
+        panel.description = old.description;
+        panel.cli_description = old.cli_description;
+        panel.icon = old.icon;
+        panel.color_scheme = old.color_scheme;
+        panel.accent = old.accent;
+        panel.pinned = old.pinned;
+        panel.font_scale = old.font_scale;
+        panel.scroll_multiplier = old.scroll_multiplier;
+        panel.broadcast = old.broadcast;
+        self.panels.insert(new_id, panel);
+
+        for ws in &mut self.workspaces {
+            if let Some(slot) = ws.panel_order.iter_mut().find(|id| **id == panel_id) {
+                *slot = new_id;
+                ws.invalidate_positions();
+            }
+        }
+        log::info!("Reconnecting terminal {} to {}", panel_id, host);
+        Ok(())
+    }
+
+    /// Reconnect the focused terminal's SSH session, reporting failures in a toast.
+    pub(crate) fn reconnect_focused(&mut self, ctx: &egui::Context) {
+        let ws = self.active_workspace();
+        let Some(&panel_id) = ws.panel_order.get(ws.focused_index) else {
+            return;
+        };
+        if let Err(e) = self.reconnect_ssh(ctx, panel_id) {
+            self.show_toast(ToastLevel::Warning, e);
+        }
+    }
+}
@@ -1,12 +1,66 @@
-use crate::terminal::TerminalPanel;
-use crate::util::layout;
-use crate::workspace::Workspace;
+use crate::config::{hex_to_color32, CloseConfirmation, FontZoom, LastTerminalBehavior};
+use crate::notifications::{self, NotificationKind};
+use crate::persist;
+use crate::terminal::{ProcessTable, SpawnOptions, TerminalPanel};
+use crate::project::{self, ProjectFile, PROJECT_FILE};
+use crate::scripting;
+use crate::ui::empty_state::{self, EmptyStateAction};
+use crate::ui::command_palette::PaletteTerminal;
+use crate::ui::{self, ActiveDialog};
+use crate::util::layout::{self, TerminalPlacement, WorkspaceSwitchAnimation};
+use crate::util::limits::{self, FdPressure};
+use crate::util::links;
+use crate::util::toasts::{self, ToastLevel};
+use crate::util::{shell, titles};
+use crate::workspace::{self, overflow_workspace, ProjectLink, Workspace, SCRATCH_WORKSPACE};
 use eframe::egui;
-use egui_term::PtyEvent;
-use std::path::PathBuf;
+use egui_term::{BackendCommand, CaptureRegion, PtyEvent};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use super::App;
-use super::WIDTH_RATIOS;
+use super::CommandSource;
+use super::MruSwitch;
+use super::Peek;
+use super::WorkspaceTransition;
+use super::perf::HUD_REFRESH_SECS;
+
+/// Number of recently removed workspaces offered on the start screen
+const MAX_RECENT_WORKSPACES: usize = 5;
+
+/// Number of recent working directories offered on the start screen
+const MAX_RECENT_DIRECTORIES: usize = 8;
+
+/// Seconds between checks of the foreground processes against `link_exclusions`
+const LINK_POLICY_REFRESH_SECS: f64 = 1.0;
+
+/// Seconds between checks of project files for workspace renames
+const PROJECT_REFRESH_SECS: f64 = 2.0;
+
+/// Seconds between samples of terminal output for the minimap heatmap
+const ACTIVITY_REFRESH_SECS: f64 = 0.25;
+
+/// Seconds between checks of whether a new shell is ready for its `runOnOpen` command
+const PENDING_INPUT_REFRESH_SECS: f64 = 0.1;
+
+/// Seconds a workspace emptied by a move is kept so the move can be undone (as long
+/// as its toast is shown)
+const UNDO_REMOVAL_SECS: f64 = toasts::TOAST_SECS;
+
+/// Seconds before placeholder terminals on screen are started again after one failed
+const DEFERRED_RETRY_SECS: f64 = 5.0;
+
+/// A workspace emptied by moving its last terminal away. It stays in the list, empty,
+/// until `deadline` so ⌘Z can bring the terminal back.
+pub(crate) struct EmptiedWorkspace {
+    name: String,
+    /// Internal ID of the terminal that was moved out
+    panel_id: u64,
+    /// Time (egui seconds) the workspace is removed
+    deadline: f64,
+}
 
 impl App {
     pub(crate) fn active_workspace(&self) -> &Workspace {
@@ -17,12 +71,53 @@ impl App {
         &mut self.workspaces[self.active_workspace]
     }
 
-    /// Remove empty workspaces except "default". Adjusts active_workspace index if needed.
+    /// Remove empty workspaces except "default" and one held for undo. Adjusts
+    /// active_workspace index if needed. When the active scratch workspace empties,
+    /// switches back to where it was opened from.
+    ///
+    /// With `empty_workspace_linger_secs` set, a newly emptied workspace is kept (and
+    /// left for one with terminals if it was active) until that time is up; the active
+    /// workspace is never removed this way.
     pub(crate) fn cleanup_empty_workspaces(&mut self) {
+        let linger = Duration::from_secs(self.config.empty_workspace_linger_secs);
+        let now = Instant::now();
         let mut i = 0;
         while i < self.workspaces.len() {
-            if self.workspaces[i].panel_order.is_empty() && self.workspaces[i].name != "default" {
-                self.workspaces.remove(i);
+            let held = self
+                .emptied_workspace
+                .as_ref()
+                .is_some_and(|emptied| emptied.name == self.workspaces[i].name);
+            let ws = &mut self.workspaces[i];
+            if !ws.panel_order.is_empty() {
+                ws.emptied_at = None;
+            }
+            if ws.panel_order.is_empty() && ws.name != "default" && !held {
+                if !ws.is_scratch() && !linger.is_zero() {
+                    let newly_emptied = ws.emptied_at.is_none();
+                    let emptied_at = *ws.emptied_at.get_or_insert(now);
+                    if newly_emptied && self.active_workspace == i {
+                        self.leave_emptied_workspace(i);
+                    }
+                    if self.active_workspace == i || now.duration_since(emptied_at) < linger {
+                        i += 1;
+                        continue;
+                    }
+                }
+                let removed = self.workspaces.remove(i);
+                if !removed.is_scratch() {
+                    self.recent_workspaces.retain(|name| *name != removed.name);
+                    self.recent_workspaces.insert(0, removed.name.clone());
+                    self.recent_workspaces.truncate(MAX_RECENT_WORKSPACES);
+                }
+                if removed.is_scratch() && self.active_workspace == i {
+                    let return_idx = self.scratch_return_workspace.take().and_then(|name| {
+                        self.workspaces.iter().position(|ws| ws.name == name)
+                    });
+                    if let Some(idx) = return_idx {
+                        self.active_workspace = idx;
+                        continue;
+                    }
+                }
                 if self.active_workspace > i {
                     self.active_workspace -= 1;
                 } else if self.active_workspace == i && self.active_workspace >= self.workspaces.len() {
@@ -34,31 +129,947 @@ impl App {
         }
     }
 
-    pub(crate) fn create_terminal(&mut self, ctx: &egui::Context) {
+    /// Switch from the emptied workspace at `index` to the nearest one with terminals,
+    /// as removing it would have. Stays when there is none.
+    fn leave_emptied_workspace(&mut self, index: usize) {
+        let len = self.workspaces.len();
+        let next = (index + 1..len)
+            .chain((0..index).rev())
+            .find(|&idx| !self.workspaces[idx].panel_order.is_empty());
+        if let Some(idx) = next {
+            self.active_workspace = idx;
+        }
+    }
+
+    /// Remove lingering empty workspaces once `empty_workspace_linger_secs` is up.
+    pub(crate) fn expire_empty_workspaces(&mut self, ctx: &egui::Context) {
+        let linger = Duration::from_secs(self.config.empty_workspace_linger_secs);
+        let deadline = self
+            .workspaces
+            .iter()
+            .enumerate()
+            .filter(|&(idx, ws)| idx != self.active_workspace && ws.is_lingering())
+            .filter_map(|(_, ws)| ws.emptied_at)
+            .min()
+            .map(|emptied_at| emptied_at + linger);
+        let Some(deadline) = deadline else {
+            return;
+        };
+        let now = Instant::now();
+        if deadline > now {
+            ctx.request_repaint_after(deadline - now);
+            return;
+        }
+        self.cleanup_empty_workspaces();
+    }
+
+    /// Spawn a new terminal in the active workspace at `placement` and return its index.
+    /// Context from the focused terminal is inherited according to `config.inherit`.
+    pub(crate) fn create_terminal(
+        &mut self,
+        ctx: &egui::Context,
+        placement: TerminalPlacement,
+    ) -> Result<usize, String> {
+        self.create_terminal_running(ctx, placement, None)
+    }
+
+    /// Like `create_terminal`, typing `command` into the new shell instead of the
+    /// inherited ssh command or `config.startup_command`.
+    pub(crate) fn create_terminal_running(
+        &mut self,
+        ctx: &egui::Context,
+        placement: TerminalPlacement,
+        command: Option<String>,
+    ) -> Result<usize, String> {
+        let inherit = &self.config.inherit;
+        let focused = self.focused_panel();
+
+        let mut options = focused.map(|p| p.inherited_options(inherit)).unwrap_or_default();
+        if inherit.env {
+            options
+                .env
+                .insert("MANSE_WORKSPACE".to_string(), self.active_workspace().name.clone());
+        }
+        options.startup_command = command
+            .or(options.startup_command)
+            .or_else(|| self.config.startup_command.clone());
+        let icon = focused.filter(|_| inherit.icon).and_then(|p| p.icon.clone());
+        let description = focused
+            .filter(|_| inherit.description)
+            .map(|p| crate::terminal::description_prefix(&p.description));
+
+        let index = self.spawn_terminal(ctx, placement, options)?;
+        if let Some(panel) = self.panel_at_mut(index) {
+            panel.icon = icon;
+            panel.description = description.unwrap_or_default();
+        }
+        Ok(index)
+    }
+
+    /// Spawn a terminal with explicit `options` in the active workspace and return its index.
+    /// A workspace at `max_terminals_per_workspace` hands the terminal to its overflow
+    /// workspace, which becomes active. Failures are shown as a toast and returned.
+    pub(crate) fn spawn_terminal(
+        &mut self,
+        ctx: &egui::Context,
+        placement: TerminalPlacement,
+        options: SpawnOptions,
+    ) -> Result<usize, String> {
+        let (id, panel) = self.new_panel(ctx, options)?;
+        self.panels.insert(id, panel);
+
+        let limit = self.config.max_terminals_per_workspace;
+        let overflow = overflow_workspace(&self.workspaces, self.active_workspace, limit)
+            .filter(|_| !self.active_workspace().is_scratch());
+        if let Some(name) = overflow {
+            let message = format!(
+                "Workspace \"{}\" has {} terminals (max_terminals_per_workspace): opened in \"{}\"",
+                self.active_workspace().name,
+                limit,
+                name
+            );
+            self.show_toast(ToastLevel::Warning, message);
+            self.switch_to_workspace(&name);
+        }
+
+        let panels = &self.panels;
+        let ws = &mut self.workspaces[self.active_workspace];
+        // New terminals go beside the docked ones, never among them
+        let pinned = ws.panel_order.iter().take_while(|id| panels.get(id).is_some_and(|p| p.pinned)).count();
+        let insert_pos = placement.insert_index(ws.focused_index, ws.panel_order.len()).max(pinned);
+        ws.panel_order.insert(insert_pos, id);
+        // Keep the previously focused terminal focused when inserting to its left
+        if insert_pos <= ws.focused_index && ws.panel_order.len() > 1 {
+            ws.focused_index += 1;
+        }
+        ws.invalidate_positions();
+        Ok(insert_pos)
+    }
+
+    /// Start a terminal process under a fresh internal ID, unless file descriptors are
+    /// about to run out. Errors come with a remediation hint and are shown as a toast.
+    pub(crate) fn new_panel(
+        &mut self,
+        ctx: &egui::Context,
+        mut options: SpawnOptions,
+    ) -> Result<(u64, TerminalPanel), String> {
+        if options.shell.is_none() {
+            options.shell = self.config.shell.clone();
+        }
+        options.scrollback_lines = Some(self.config.scrollback_lines);
+        if options.deferred {
+            let id = self.next_id;
+            self.next_id += 1;
+            return Ok((id, TerminalPanel::deferred(id, ctx, self.event_tx.clone(), options)));
+        }
+        self.check_fd_pressure()?;
+
         let id = self.next_id;
         self.next_id += 1;
+        let engine = self.config.terminal_engine.with_env_override();
+        TerminalPanel::new(id, ctx, self.event_tx.clone(), &engine, self.socket_path.as_ref(), options)
+            .map(|panel| (id, panel))
+            .map_err(|e| {
+                let message = limits::spawn_error_message(&e);
+                self.show_toast(ToastLevel::Error, message.clone());
+                message
+            })
+    }
 
-        let working_dir = self
-            .focused_panel()
-            .and_then(|p| p.current_working_directory.clone());
+    /// Warn when file descriptors run low, and refuse (with the message) to start another
+    /// shell once they are used up.
+    fn check_fd_pressure(&mut self) -> Result<(), String> {
+        #[cfg(unix)]
+        if let Some((open, limit)) = crate::terminal::fd_usage() {
+            match limits::fd_pressure(open, limit) {
+                FdPressure::Normal => {}
+                FdPressure::High => self.show_toast(
+                    ToastLevel::Warning,
+                    format!(
+                        "{} of {} file descriptors in use: close some terminals or raise the limit (ulimit -n)",
+                        open, limit
+                    ),
+                ),
+                FdPressure::Exhausted => {
+                    let message = format!(
+                        "Not starting a terminal: {} of {} file descriptors in use. Close some terminals or raise the limit (ulimit -n).",
+                        open, limit
+                    );
+                    self.show_toast(ToastLevel::Error, message.clone());
+                    return Err(message);
+                }
+            }
+        }
+        Ok(())
+    }
 
-        let panel = TerminalPanel::new(
-            id,
-            ctx,
-            self.event_tx.clone(),
-            self.socket_path.as_ref(),
-            working_dir,
-        );
-        self.panels.insert(id, panel);
+    /// Start the shell of placeholder terminal `panel_id` (see `lazy_spawn`), if it is one.
+    /// Returns false when the shell failed to start; the terminal stays a placeholder.
+    pub(crate) fn start_deferred(&mut self, ctx: &egui::Context, panel_id: u64) -> bool {
+        if self.panels.get(&panel_id).is_none_or(|panel| panel.deferred.is_none()) {
+            return true;
+        }
+        if let Err(message) = self.check_fd_pressure() {
+            log::warn!("{}", message);
+            return false;
+        }
+        let engine = self.config.terminal_engine.with_env_override();
+        let Some(panel) = self.panels.get_mut(&panel_id) else {
+            return true;
+        };
+        let started = panel.start(panel_id, ctx, self.event_tx.clone(), &engine, self.socket_path.as_ref());
+        if let Err(e) = started {
+            let message = limits::spawn_error_message(&e);
+            self.show_toast(ToastLevel::Error, message);
+            return false;
+        }
+        true
+    }
 
-        let ws = self.active_workspace_mut();
-        if ws.panel_order.is_empty() {
-            ws.panel_order.push(id);
+    /// Start the placeholder terminals of the active workspace that are on screen: the
+    /// pinned ones, those in the scrolled viewport and the focused one. After a failure
+    /// this waits `DEFERRED_RETRY_SECS` before trying again.
+    pub(crate) fn start_visible_terminals(&mut self, ctx: &egui::Context, viewport_width: f32) {
+        if self.frame_time < self.deferred_retry_at {
+            return;
+        }
+        let ws = self.active_workspace();
+        let any_deferred = ws
+            .panel_order
+            .iter()
+            .any(|id| self.panels.get(id).is_some_and(|panel| panel.deferred.is_some()));
+        if !any_deferred {
+            return;
+        }
+
+        let cached = &ws.cached_positions;
+        let positions: Vec<(f32, f32)> = cached.positions.iter().map(|&(_, x, w)| (x, w)).collect();
+        let (docked_width, scrolling) = layout::split_docked(&positions, cached.pinned);
+        let range = layout::visible_range(&scrolling, ws.scroll_offset, viewport_width - docked_width, 0);
+        let on_screen = (0..cached.pinned).chain(range.start + cached.pinned..range.end + cached.pinned);
+        let shown: Vec<u64> = on_screen
+            .filter_map(|index| cached.positions.get(index).map(|&(id, _, _)| id))
+            .chain(ws.panel_order.get(ws.focused_index).copied())
+            .collect();
+        for panel_id in shown {
+            if !self.start_deferred(ctx, panel_id) {
+                // Out of file descriptors, say: not again every frame
+                self.deferred_retry_at = self.frame_time + DEFERRED_RETRY_SECS;
+                ctx.request_repaint_after(Duration::from_secs_f64(DEFERRED_RETRY_SECS));
+                return;
+            }
+        }
+    }
+
+    /// Show `text` in a toast (and the log).
+    /// Timed from the start of the frame rather than read from the context, so this is
+    /// safe from inside `ctx.input_mut` (which holds the context lock).
+    pub(crate) fn show_toast(&mut self, level: ToastLevel, text: String) {
+        match level {
+            ToastLevel::Info => log::info!("{}", text),
+            ToastLevel::Warning => log::warn!("{}", text),
+            ToastLevel::Error => log::error!("{}", text),
+        }
+        self.toasts.push(level, text, self.frame_time);
+    }
+
+    /// Run `command` in a new terminal right of the focused one, with `text` on its stdin.
+    pub(crate) fn pipe_to_command(&mut self, ctx: &egui::Context, command: &str, text: &str) {
+        // The shell reads the text back from a temp file only the user can read, which the
+        // command line removes
+        let path = std::env::temp_dir().join(format!(
+            "manse-pipe-{}-{}.txt",
+            std::process::id(),
+            self.next_id
+        ));
+        if let Err(e) = persist::write_private(&path, text.as_bytes()) {
+            log::warn!("Failed to write {}: {}", path.display(), e);
+            return;
+        }
+
+        let options = SpawnOptions {
+            working_directory: self
+                .focused_panel()
+                .and_then(|p| p.current_working_directory.clone()),
+            startup_command: Some(shell::with_stdin_from(command, &path.to_string_lossy())),
+            ..Default::default()
+        };
+        match self.spawn_terminal(ctx, TerminalPlacement::AfterFocused, options) {
+            Ok(index) => self.active_workspace_mut().focused_index = index,
+            Err(_) => {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+
+    /// Run the `config.commands` entry `index`: type it into the focused terminal, or
+    /// start it in a new terminal (in its workspace, if it names one) from the focused
+    /// terminal's directory.
+    pub(crate) fn run_custom_command(&mut self, ctx: &egui::Context, index: usize) {
+        let Some(command) = self.config.commands.get(index).cloned() else {
+            return;
+        };
+        if command.send {
+            match self.focused_panel_mut() {
+                Some(panel) => panel
+                    .backend
+                    .process_command(BackendCommand::Write(format!("{}\n", command.exec).into_bytes())),
+                None => {
+                    let text = format!("No terminal to send \"{}\" to", command.name);
+                    self.show_toast(ToastLevel::Warning, text);
+                }
+            }
+            return;
+        }
+
+        let options = SpawnOptions {
+            working_directory: self
+                .focused_panel()
+                .and_then(|p| p.current_working_directory.clone()),
+            startup_command: Some(command.exec),
+            ..Default::default()
+        };
+        // Spawn failures are already shown as a toast
+        if let Err(e) = self.spawn_in_workspace(ctx, command.workspace.as_deref(), TerminalPlacement::End, options, true) {
+            log::warn!("Failed to run {}: {}", command.name, e);
+        }
+    }
+
+    /// Ask for a file and write the focused terminal's `region` to it.
+    /// A `.ansi` extension keeps colors as escape sequences; anything else is plain text.
+    pub(crate) fn save_capture(&self, region: CaptureRegion) {
+        let Some(panel) = self.focused_panel() else {
+            return;
+        };
+        let file_name = match region {
+            CaptureRegion::Screen => "screen.txt",
+            CaptureRegion::Scrollback => "scrollback.txt",
+        };
+        let mut dialog = rfd::FileDialog::new()
+            .set_file_name(file_name)
+            .add_filter("Plain text", &["txt"])
+            .add_filter("Text with ANSI colors", &["ansi"]);
+        if let Some(dir) = &panel.current_working_directory {
+            dialog = dialog.set_directory(dir);
+        }
+        // The dialog blocks the frame without the UI having hung
+        let Some(path) = self.watchdog.outside_frame(|| dialog.save_file()) else {
+            return;
+        };
+
+        let ansi = path.extension().is_some_and(|ext| ext == "ansi");
+        let text = panel.backend.capture_text(region, ansi);
+        if let Err(e) = persist::write_private(&path, text.as_bytes()) {
+            log::warn!("Failed to save {}: {}", path.display(), e);
+        }
+    }
+
+    /// Terminal at `index` in the active workspace.
+    fn panel_at_mut(&mut self, index: usize) -> Option<&mut TerminalPanel> {
+        let id = *self.active_workspace().panel_order.get(index)?;
+        self.panels.get_mut(&id)
+    }
+
+    /// Make the workspace named `name` active, creating it if needed.
+    pub(crate) fn switch_to_workspace(&mut self, name: &str) {
+        self.active_workspace = match self.workspaces.iter().position(|ws| ws.name == name) {
+            Some(idx) => idx,
+            None => {
+                self.workspaces.push(Workspace::new(name));
+                self.workspaces.len() - 1
+            }
+        };
+    }
+
+    /// Move terminal `panel_id` to the end of the workspace named `name` (created if needed)
+    /// and focus it there.
+    pub(crate) fn move_to_workspace(&mut self, panel_id: u64, name: &str) {
+        let current_ws_idx = self
+            .workspaces
+            .iter()
+            .position(|ws| ws.panel_order.contains(&panel_id));
+
+        if let Some(ws_idx) = current_ws_idx {
+            if self.workspaces[ws_idx].name == name {
+                self.active_workspace = ws_idx;
+                return;
+            }
+        }
+
+        for ws in &mut self.workspaces {
+            if let Some(pos) = ws.panel_order.iter().position(|&x| x == panel_id) {
+                ws.panel_order.remove(pos);
+                if ws.focused_index >= ws.panel_order.len() && !ws.panel_order.is_empty() {
+                    ws.focused_index = ws.panel_order.len() - 1;
+                }
+                ws.invalidate_positions();
+                break;
+            }
+        }
+
+        let target_ws_idx = match self.workspaces.iter().position(|ws| ws.name == name) {
+            Some(idx) => idx,
+            None => {
+                self.workspaces.push(Workspace::new(name));
+                self.workspaces.len() - 1
+            }
+        };
+
+        let target = &mut self.workspaces[target_ws_idx];
+        target.panel_order.push(panel_id);
+        target.focused_index = target.panel_order.len() - 1;
+        target.invalidate_positions();
+
+        self.active_workspace = target_ws_idx;
+    }
+
+    /// After terminal `panel_id` was moved out of the workspace `source`: if that left it
+    /// empty, keep it for `UNDO_REMOVAL_SECS` and say so in a toast, then drop any other
+    /// empty workspaces. A workspace held for an earlier move is removed right away.
+    pub(crate) fn hold_emptied_workspace(&mut self, ctx: &egui::Context, source: &str, panel_id: u64) {
+        let emptied = self
+            .workspaces
+            .iter()
+            .find(|ws| ws.name == source)
+            .is_some_and(|ws| ws.panel_order.is_empty() && ws.name != "default" && !ws.is_scratch());
+        if emptied {
+            self.emptied_workspace = Some(EmptiedWorkspace {
+                name: source.to_string(),
+                panel_id,
+                deadline: ctx.input(|i| i.time) + UNDO_REMOVAL_SECS,
+            });
+            let message = if self.config.empty_workspace_linger_secs > 0 {
+                format!("Moved the last terminal out of \"{}\" (⌘Z to undo)", source)
+            } else {
+                format!("Workspace \"{}\" removed (⌘Z to undo)", source)
+            };
+            self.show_toast(ToastLevel::Info, message);
+        }
+        self.cleanup_empty_workspaces();
+    }
+
+    /// Move the terminal whose move emptied a workspace back into it, and switch there.
+    pub(crate) fn undo_workspace_removal(&mut self) -> Result<(), String> {
+        let emptied = self
+            .emptied_workspace
+            .take()
+            .ok_or("No workspace removal to undo")?;
+        let Some(ws_idx) = self.workspaces.iter().position(|ws| ws.name == emptied.name) else {
+            return Err(format!("Workspace \"{}\" no longer exists", emptied.name));
+        };
+        if !self.panels.contains_key(&emptied.panel_id) {
+            self.cleanup_empty_workspaces();
+            return Err("The moved terminal was closed".to_string());
+        }
+
+        for ws in &mut self.workspaces {
+            if let Some(pos) = ws.panel_order.iter().position(|&x| x == emptied.panel_id) {
+                ws.panel_order.remove(pos);
+                if ws.focused_index >= ws.panel_order.len() {
+                    ws.focused_index = ws.panel_order.len().saturating_sub(1);
+                }
+                ws.invalidate_positions();
+            }
+        }
+        let ws = &mut self.workspaces[ws_idx];
+        ws.panel_order.push(emptied.panel_id);
+        ws.focused_index = ws.panel_order.len() - 1;
+        ws.invalidate_positions();
+        self.active_workspace = ws_idx;
+
+        // Drops the target workspace again if the move created it
+        self.cleanup_empty_workspaces();
+        Ok(())
+    }
+
+    /// Remove the workspace held for undo once its time is up.
+    pub(crate) fn expire_emptied_workspace(&mut self, ctx: &egui::Context) {
+        let Some(emptied) = &self.emptied_workspace else {
+            return;
+        };
+        let remaining = emptied.deadline - ctx.input(|i| i.time);
+        if remaining > 0.0 {
+            ctx.request_repaint_after(Duration::from_secs_f64(remaining));
+            return;
+        }
+        self.emptied_workspace = None;
+        self.cleanup_empty_workspaces();
+    }
+
+    /// Apply the `.manse.json` project that terminal `panel_id`'s new working directory
+    /// `dir` is in: take the project's icon, color scheme and scroll speed, and move to its workspace
+    /// (switching there only if the terminal was focused). Runs once per project entered,
+    /// so changes made afterwards stick until the terminal leaves the project.
+    fn enter_project(&mut self, panel_id: u64, dir: &Path) {
+        let found = ProjectFile::find(dir);
+        let root = found.as_ref().map(|(root, _)| root.clone());
+        let Some(panel) = self.panels.get_mut(&panel_id) else {
+            return;
+        };
+        if panel.project_root == root {
+            return;
+        }
+        panel.project_root = root;
+        let Some((project_root, project)) = found else {
+            return;
+        };
+
+        if project.icon.is_some() {
+            panel.icon = project.icon;
+        }
+        match project.color_scheme {
+            Some(scheme) if self.themes.schemes.contains_key(&scheme) => panel.color_scheme = Some(scheme),
+            Some(scheme) => log::warn!("Unknown color scheme in {}: {}", PROJECT_FILE, scheme),
+            None => {}
+        }
+        match project.accent {
+            Some(accent) if hex_to_color32(&accent).is_some() => panel.accent = Some(accent),
+            Some(accent) => log::warn!("Invalid accent in {}: {}", PROJECT_FILE, accent),
+            None => {}
+        }
+        match project.scroll_multiplier {
+            Some(multiplier) if multiplier > 0.0 => panel.scroll_multiplier = Some(multiplier),
+            Some(multiplier) => log::warn!("Invalid scrollMultiplier in {}: {}", PROJECT_FILE, multiplier),
+            None => {}
+        }
+
+        let name = project.workspace_name;
+        let Some(current) = self.workspaces.iter().find(|ws| ws.panel_order.contains(&panel_id)) else {
+            return;
+        };
+        if name.is_empty() {
+            return;
+        }
+        if current.name != name {
+            let active = self.active_workspace();
+            let focused = active.panel_order.get(active.focused_index) == Some(&panel_id);
+            let previous = active.name.clone();
+            self.move_to_workspace(panel_id, &name);
+            self.cleanup_empty_workspaces();
+            if !focused {
+                if let Some(index) = self.workspaces.iter().position(|ws| ws.name == previous) {
+                    self.active_workspace = index;
+                }
+            }
+        }
+        self.link_project(&name, &project_root);
+    }
+
+    /// Remember that the workspace `name` was named from the project file in `root`, unless
+    /// it already belongs to a project.
+    fn link_project(&mut self, name: &str, root: &Path) {
+        if let Some(ws) = self.workspaces.iter_mut().find(|ws| ws.name == name) {
+            if ws.project.is_none() {
+                ws.project = Some(ProjectLink {
+                    root: root.to_path_buf(),
+                    file_name: name.to_string(),
+                    modified: None,
+                });
+            }
+        }
+    }
+
+    /// Rename the workspace at `index` and, if it was named from a `.manse.json`, offer to
+    /// rename it in the file too (never asked for IPC with `ipc_non_interactive`).
+    pub(crate) fn request_rename_workspace(
+        &mut self,
+        index: usize,
+        name: &str,
+        source: CommandSource,
+    ) -> Result<(), String> {
+        self.rename_workspace(index, name)?;
+
+        let Some(project) = &self.workspaces[index].project else {
+            return Ok(());
+        };
+        let ask = match source {
+            CommandSource::Interactive => true,
+            CommandSource::Ipc => !self.config.ipc_non_interactive,
+        };
+        if ask && project.file_name != name && matches!(self.active_dialog, ActiveDialog::None) {
+            self.active_dialog = ActiveDialog::ConfirmProjectRename {
+                root: project.root.clone(),
+                name: name.to_string(),
+            };
+        }
+        Ok(())
+    }
+
+    /// Write `name` as the `workspaceName` of the project file in `root`.
+    pub(crate) fn rename_project(&mut self, root: &Path, name: &str) {
+        if let Err(e) = project::set_workspace_name(root, name) {
+            self.show_toast(ToastLevel::Error, e);
+            return;
+        }
+        let links = self.workspaces.iter_mut().filter_map(|ws| ws.project.as_mut());
+        for link in links.filter(|link| link.root == root) {
+            link.file_name = name.to_string();
+        }
+    }
+
+    /// Follow renames made in the `.manse.json` of project workspaces. Checked about
+    /// every two seconds while the window redraws.
+    pub(crate) fn sync_project_names(&mut self, ctx: &egui::Context) {
+        let now = ctx.input(|i| i.time);
+        if now - self.projects_checked < PROJECT_REFRESH_SECS {
+            return;
+        }
+        self.projects_checked = now;
+
+        for index in 0..self.workspaces.len() {
+            let Some(link) = &mut self.workspaces[index].project else {
+                continue;
+            };
+            let modified = std::fs::metadata(link.root.join(PROJECT_FILE))
+                .and_then(|meta| meta.modified())
+                .ok();
+            if modified.is_none() || modified == link.modified {
+                continue;
+            }
+            link.modified = modified;
+            let Some(project) = ProjectFile::load(&link.root) else {
+                continue;
+            };
+            if project.workspace_name.is_empty() || project.workspace_name == link.file_name {
+                continue;
+            }
+            link.file_name = project.workspace_name.clone();
+            match self.rename_workspace(index, &project.workspace_name) {
+                Ok(()) => log::info!("Workspace renamed from {} to {}", PROJECT_FILE, project.workspace_name),
+                Err(e) => log::warn!("Not following rename in {}: {}", PROJECT_FILE, e),
+            }
+        }
+    }
+
+    /// Create the workspace `name` with one new terminal and switch to it.
+    /// Returns the new terminal's external ID.
+    pub(crate) fn create_workspace(
+        &mut self,
+        ctx: &egui::Context,
+        name: &str,
+    ) -> Result<String, String> {
+        if name.is_empty() {
+            return Err("Workspace name is empty".to_string());
+        }
+        if self.workspaces.iter().any(|ws| ws.name == name) {
+            return Err(format!("Workspace already exists: {}", name));
+        }
+
+        self.spawn_in_workspace(ctx, Some(name), TerminalPlacement::End, SpawnOptions::default(), true)
+    }
+
+    /// Spawn a terminal at `placement` in the workspace named `workspace` (created if
+    /// needed; the active one if `None`) and return its external ID. With `focus`, the new
+    /// terminal is focused and its workspace made active; otherwise the active workspace is kept.
+    pub(crate) fn spawn_in_workspace(
+        &mut self,
+        ctx: &egui::Context,
+        workspace: Option<&str>,
+        placement: TerminalPlacement,
+        options: SpawnOptions,
+        focus: bool,
+    ) -> Result<String, String> {
+        let previous = self.active_workspace;
+        let workspace_count = self.workspaces.len();
+        if let Some(name) = workspace {
+            self.switch_to_workspace(name);
+        }
+        let index = match self.spawn_terminal(ctx, placement, options) {
+            Ok(index) => index,
+            Err(e) => {
+                // Drop the workspace again if it was created for this terminal
+                self.workspaces.truncate(workspace_count);
+                self.active_workspace = previous;
+                return Err(e);
+            }
+        };
+
+        let id = self.active_workspace().panel_order[index];
+        if focus {
+            self.active_workspace_mut().focused_index = index;
         } else {
-            let insert_pos = ws.focused_index + 1;
-            ws.panel_order.insert(insert_pos, id);
+            self.active_workspace = previous;
+        }
+        Ok(self.panels[&id].id.clone())
+    }
+
+    /// Name the implicit workspace whose first terminal is `panel_id` after the git
+    /// repository `dir` is in (`workspace_name_from_git`). Decided on the terminal's first
+    /// working directory only: a later `cd` into a repository leaves the name alone.
+    fn name_workspace_from_git(&mut self, panel_id: u64, dir: &Path) {
+        let Some(index) = self
+            .workspaces
+            .iter()
+            .position(|ws| ws.implicit_name && ws.panel_order.first() == Some(&panel_id))
+        else {
+            return;
+        };
+        self.workspaces[index].implicit_name = false;
+        if !self.config.workspace_name_from_git {
+            return;
+        }
+        let Some(name) = project::git_root(dir).and_then(|root| Some(root.file_name()?.to_string_lossy().into_owned())) else {
+            return;
+        };
+        if let Err(e) = self.rename_workspace(index, &name) {
+            log::debug!("Keeping the workspace name: {}", e);
+        }
+    }
+
+    /// Rename the workspace at `index`. The scratch workspace keeps its reserved name.
+    pub(crate) fn rename_workspace(&mut self, index: usize, name: &str) -> Result<(), String> {
+        if name.is_empty() {
+            return Err("Workspace name is empty".to_string());
+        }
+        if name == SCRATCH_WORKSPACE || self.workspaces[index].is_scratch() {
+            return Err("The scratch workspace cannot be renamed".to_string());
+        }
+        if self.workspaces.iter().any(|ws| ws.name == name) {
+            return Err(format!("Workspace already exists: {}", name));
+        }
+
+        self.workspaces[index].implicit_name = false;
+        let old = std::mem::replace(&mut self.workspaces[index].name, name.to_string());
+        if self.scratch_return_workspace.as_ref() == Some(&old) {
+            self.scratch_return_workspace = Some(name.to_string());
+        }
+        // Not a switch, so no transition animation
+        if self.shown_workspace == old {
+            self.shown_workspace = name.to_string();
+        }
+        Ok(())
+    }
+
+    /// Delete the workspace at `index`. It must be empty unless `force`, which closes its
+    /// terminals. The only workspace, or the one holding every terminal, cannot be deleted.
+    pub(crate) fn delete_workspace(&mut self, index: usize, force: bool) -> Result<(), String> {
+        if self.workspaces.len() <= 1 {
+            return Err("Cannot delete the only workspace".to_string());
+        }
+        let count = self.workspaces[index].panel_order.len();
+        if count > 0 && !force {
+            return Err(format!(
+                "Workspace {} has {} terminal(s) (use --force to close them)",
+                self.workspaces[index].name, count
+            ));
+        }
+        let total: usize = self.workspaces.iter().map(|ws| ws.panel_order.len()).sum();
+        if count > 0 && count == total {
+            return Err("Cannot close every terminal".to_string());
+        }
+
+        let removed = self.workspaces.remove(index);
+        for id in &removed.panel_order {
+            if let Some(panel) = self.panels.remove(id) {
+                #[cfg(unix)]
+                panel.terminate();
+            }
+        }
+        if self.active_workspace > index
+            || (self.active_workspace == index && self.active_workspace >= self.workspaces.len())
+        {
+            self.active_workspace -= 1;
+        }
+        Ok(())
+    }
+
+    /// Put the workspace at `index` in `group` (`None` ungroups it), moving it next to
+    /// the group's other members. The same workspace stays active.
+    pub(crate) fn set_workspace_group(&mut self, index: usize, group: Option<String>) {
+        let active = self.workspaces[self.active_workspace].name.clone();
+        workspace::set_group(&mut self.workspaces, index, group);
+        self.active_workspace = self.workspaces.iter().position(|ws| ws.name == active).unwrap_or(0);
+    }
+
+    /// Delete every workspace in `group`, which must hold no terminals unless `force`.
+    /// Refuses to close every terminal or workspace. Returns how many terminals were closed.
+    pub(crate) fn close_group(&mut self, group: &str, force: bool) -> Result<usize, String> {
+        let members: Vec<&Workspace> =
+            self.workspaces.iter().filter(|ws| ws.group.as_deref() == Some(group)).collect();
+        if members.is_empty() {
+            return Err(format!("Group not found: {}", group));
+        }
+        if members.len() == self.workspaces.len() {
+            return Err("Cannot delete every workspace".to_string());
+        }
+        let count: usize = members.iter().map(|ws| ws.panel_order.len()).sum();
+        if count > 0 && !force {
+            return Err(format!("Group {} has {} terminal(s) (use --force to close them)", group, count));
+        }
+        let total: usize = self.workspaces.iter().map(|ws| ws.panel_order.len()).sum();
+        if count > 0 && count == total {
+            return Err("Cannot close every terminal".to_string());
+        }
+
+        while let Some(index) = self.workspaces.iter().position(|ws| ws.group.as_deref() == Some(group)) {
+            self.delete_workspace(index, true)?;
+        }
+        self.collapsed_groups.remove(group);
+        Ok(count)
+    }
+
+    /// Start an animation when the active workspace changed since the last frame,
+    /// and advance a running one.
+    pub(crate) fn update_workspace_transition(&mut self, ctx: &egui::Context) {
+        let active_name = &self.workspaces[self.active_workspace].name;
+        if *active_name != self.shown_workspace {
+            let from = std::mem::replace(&mut self.shown_workspace, active_name.clone());
+            let from_index = self.workspaces.iter().position(|ws| ws.name == from);
+            self.workspace_transition = match (self.config.animations.workspace_switch, from_index) {
+                (WorkspaceSwitchAnimation::Off, _) | (_, None) => None,
+                (_, Some(from_index)) => Some(WorkspaceTransition {
+                    from,
+                    forward: self.active_workspace > from_index,
+                    progress: 0.0,
+                }),
+            };
+        }
+
+        if let Some(transition) = &mut self.workspace_transition {
+            let duration = self.config.animations.workspace_switch_ms as f32 / 1000.0;
+            let step = if duration > 0.0 {
+                ctx.input(|i| i.stable_dt) / duration
+            } else {
+                1.0
+            };
+            transition.progress = layout::step_toward(transition.progress, 1.0, step);
+            if transition.progress >= 1.0 {
+                self.workspace_transition = None;
+            } else {
+                ctx.request_repaint();
+            }
+        }
+    }
+
+    pub(crate) fn handle_start_screen_action(
+        &mut self,
+        ctx: &egui::Context,
+        action: empty_state::EmptyStateAction,
+    ) {
+        let mut project_root = None;
+        let (workspace, spawns) = match action {
+            EmptyStateAction::NewTerminal => (None, vec![SpawnOptions::default()]),
+            EmptyStateAction::OpenWorkspace(name) => (Some(name), vec![SpawnOptions::default()]),
+            EmptyStateAction::OpenDirectory(dir) => (
+                None,
+                vec![SpawnOptions {
+                    working_directory: Some(dir),
+                    ..Default::default()
+                }],
+            ),
+            EmptyStateAction::OpenProject(dir) => {
+                if !dir.is_dir() {
+                    log::warn!("Not a directory: {}", dir.display());
+                    return;
+                }
+                // Name the workspace from .manse.json, falling back to the directory name
+                let project = ProjectFile::load(&dir);
+                if project.is_some() {
+                    project_root = Some(dir.clone());
+                }
+                let name = project
+                    .as_ref()
+                    .map(|project| project.workspace_name.clone())
+                    .or_else(|| dir.file_name().map(|n| n.to_string_lossy().into_owned()))
+                    .unwrap_or_else(|| "project".to_string());
+                self.start_screen_path.clear();
+                let spawns = match project {
+                    Some(project) if !project.terminals.is_empty() => project
+                        .terminals
+                        .into_iter()
+                        .map(|terminal| SpawnOptions {
+                            working_directory: Some(match terminal.dir {
+                                Some(sub) => dir.join(sub),
+                                None => dir.clone(),
+                            }),
+                            title: terminal.title,
+                            run_on_open: terminal.run_on_open.filter(|c| !c.is_empty()),
+                            deferred: self.config.lazy_spawn,
+                            ..Default::default()
+                        })
+                        .collect(),
+                    project => project
+                        .map(|project| project.startup_commands)
+                        .filter(|commands| !commands.is_empty())
+                        .unwrap_or_else(|| vec![String::new()])
+                        .into_iter()
+                        .map(|command| SpawnOptions {
+                            working_directory: Some(dir.clone()),
+                            startup_command: Some(command).filter(|c| !c.is_empty()),
+                            deferred: self.config.lazy_spawn,
+                            ..Default::default()
+                        })
+                        .collect(),
+                };
+                (Some(name), spawns)
+            }
+            EmptyStateAction::ApplyTemplate(name) => {
+                let Some(commands) = self.config.templates.get(&name) else {
+                    return;
+                };
+                let spawns = commands
+                    .iter()
+                    .map(|command| SpawnOptions {
+                        startup_command: Some(command.clone()).filter(|c| !c.is_empty()),
+                        deferred: self.config.lazy_spawn,
+                        ..Default::default()
+                    })
+                    .collect();
+                (None, spawns)
+            }
+        };
+
+        if let Some(name) = &workspace {
+            self.switch_to_workspace(name);
+        }
+        for options in spawns {
+            if self.spawn_terminal(ctx, TerminalPlacement::End, options).is_err() {
+                break;
+            }
+        }
+        self.active_workspace_mut().focused_index = 0;
+        if let (Some(name), Some(root)) = (&workspace, &project_root) {
+            self.link_project(name, root);
+        }
+    }
+
+    /// Spawn a terminal in the scratch workspace (creating it if needed) and switch to it.
+    pub(crate) fn open_scratch_terminal(&mut self, ctx: &egui::Context) {
+        let scratch_idx = match self.workspaces.iter().position(|ws| ws.is_scratch()) {
+            Some(idx) => idx,
+            None => {
+                self.workspaces.push(Workspace::new(SCRATCH_WORKSPACE));
+                self.workspaces.len() - 1
+            }
+        };
+
+        if self.active_workspace != scratch_idx {
+            self.scratch_return_workspace = Some(self.active_workspace().name.clone());
+            self.active_workspace = scratch_idx;
+        }
+
+        match self.create_terminal(ctx, TerminalPlacement::End) {
+            Ok(new_index) => self.active_workspace_mut().focused_index = new_index,
+            // Drops the scratch workspace again if it was created for this terminal
+            Err(_) => self.cleanup_empty_workspaces(),
+        }
+    }
+
+    /// Show or hide the drop-down terminal, spawning its shell on first use.
+    pub(crate) fn toggle_overlay_terminal(&mut self, ctx: &egui::Context) {
+        if self.overlay_terminal.is_none() {
+            let options = SpawnOptions {
+                working_directory: self
+                    .focused_panel()
+                    .and_then(|p| p.current_working_directory.clone()),
+                ..Default::default()
+            };
+            let Ok(overlay) = self.new_panel(ctx, options) else {
+                return;
+            };
+            self.overlay_terminal = Some(overlay);
         }
-        ws.invalidate_positions();
+        self.overlay_visible = !self.overlay_visible;
     }
 
     pub(crate) fn focused_panel(&self) -> Option<&TerminalPanel> {
@@ -77,10 +1088,259 @@ impl App {
         focused_id.and_then(|id| self.panels.get_mut(&id))
     }
 
+    /// Switch to the workspace containing `panel_id` and focus it. Returns false if not found.
+    pub(crate) fn focus_panel(&mut self, panel_id: u64) -> bool {
+        for (ws_idx, ws) in self.workspaces.iter_mut().enumerate() {
+            if let Some(pos) = ws.panel_order.iter().position(|&id| id == panel_id) {
+                ws.focused_index = pos;
+                self.active_workspace = ws_idx;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Every terminal, workspace by workspace, for the command palette to switch to.
+    pub(crate) fn palette_terminals(&self) -> Vec<PaletteTerminal> {
+        self.workspaces
+            .iter()
+            .flat_map(|ws| ws.panel_order.iter().map(move |id| (ws, id)))
+            .filter_map(|(ws, id)| {
+                let panel = self.panels.get(id)?;
+                let description = match panel.description.as_str() {
+                    "" => panel.cli_description.clone().unwrap_or_default(),
+                    description => description.to_string(),
+                };
+                Some(PaletteTerminal {
+                    panel_id: *id,
+                    title: panel.display_title().to_string(),
+                    description,
+                    workspace: ws.name.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Step the focused terminal through the configured color schemes, then back to the base theme.
+    pub(crate) fn cycle_color_scheme(&mut self) {
+        let mut names: Vec<String> = self.themes.schemes.keys().cloned().collect();
+        names.sort();
+        let Some(panel) = self.focused_panel_mut() else {
+            return;
+        };
+        let next = match &panel.color_scheme {
+            None => names.first(),
+            Some(current) => names
+                .iter()
+                .position(|name| name == current)
+                .and_then(|i| names.get(i + 1)),
+        };
+        panel.color_scheme = next.cloned();
+    }
+
+    /// Apply the configured output repaint intervals (the focused terminal gets its own).
+    pub(crate) fn update_repaint_intervals(&self) {
+        let ws = self.active_workspace();
+        let focused_id = ws.panel_order.get(ws.focused_index).copied();
+        let overlay_focused = self.overlay_visible;
+        let focused = Duration::from_millis(self.config.focused_output_repaint_ms);
+        let background = Duration::from_millis(self.config.output_repaint_ms);
+
+        for (&id, panel) in &self.panels {
+            let is_focused = !overlay_focused && Some(id) == focused_id;
+            panel
+                .backend
+                .set_repaint_interval(if is_focused { focused } else { background });
+        }
+        if let Some((_, panel)) = &self.overlay_terminal {
+            panel
+                .backend
+                .set_repaint_interval(if overlay_focused { focused } else { background });
+        }
+    }
+
+    /// Turn broadcast mode on or off. Turned on with no terminal of the active workspace
+    /// marked, it marks all of them.
+    pub(crate) fn toggle_broadcast_mode(&mut self) {
+        self.broadcast_mode = !self.broadcast_mode;
+        if !self.broadcast_mode {
+            return;
+        }
+        let ws = &self.workspaces[self.active_workspace];
+        let marked = |panel: &TerminalPanel| panel.broadcast;
+        if !ws.panel_order.iter().any(|id| self.panels.get(id).is_some_and(marked)) {
+            for id in &ws.panel_order {
+                if let Some(panel) = self.panels.get_mut(id) {
+                    panel.broadcast = true;
+                }
+            }
+        }
+    }
+
+    /// Mirror `input`, typed into the focused terminal, to the other marked terminals of
+    /// the active workspace.
+    pub(crate) fn broadcast_input(&mut self, ctx: &egui::Context, input: &[u8]) {
+        let ws = &self.workspaces[self.active_workspace];
+        let focused = ws.panel_order.get(ws.focused_index);
+        let targets: Vec<u64> = ws
+            .panel_order
+            .iter()
+            .filter(|&id| Some(id) != focused && self.panels.get(id).is_some_and(|panel| panel.broadcast))
+            .copied()
+            .collect();
+        for id in targets {
+            self.start_deferred(ctx, id);
+            if let Some(panel) = self.panels.get_mut(&id) {
+                panel.backend.process_command(BackendCommand::Write(input.to_vec()));
+            }
+        }
+    }
+
+    /// Turn URL detection off in terminals whose foreground process is listed in
+    /// `config.link_exclusions`, and back on once it exits. Checked about once a second,
+    /// for the active workspace and the drop-down terminal.
+    #[cfg(unix)]
+    pub(crate) fn update_link_detection(&mut self, ctx: &egui::Context) {
+        if self.config.link_exclusions.is_empty() {
+            return;
+        }
+        let now = ctx.input(|i| i.time);
+        if now - self.link_policy_checked < LINK_POLICY_REFRESH_SECS {
+            return;
+        }
+        self.link_policy_checked = now;
+
+        let exclusions = &self.config.link_exclusions;
+        let visible = &self.workspaces[self.active_workspace].panel_order;
+        let panels = self
+            .panels
+            .iter_mut()
+            .filter(|(id, _)| visible.contains(id))
+            .map(|(_, panel)| panel);
+        for panel in panels.chain(self.overlay_terminal.as_mut().map(|(_, p)| p)) {
+            let excluded = panel
+                .foreground_process_name()
+                .is_some_and(|name| links::is_excluded(&name, exclusions));
+            panel.backend.set_link_detection(!excluded);
+        }
+    }
+
+    /// Sample every terminal's output counter for the minimap heatmap, keeping the
+    /// window repainting while a terminal is still cooling down or an activity dot
+    /// is still fading.
+    pub(crate) fn update_activity(&mut self, ctx: &egui::Context) {
+        let now = ctx.input(|i| i.time);
+        if now - self.activity_checked < ACTIVITY_REFRESH_SECS {
+            return;
+        }
+        self.activity_checked = now;
+
+        let show_minimap = self.config.status_bar.show_minimap;
+        if show_minimap {
+            for panel in self.panels.values_mut() {
+                panel.activity.update(now, panel.backend.io_stats().bytes_read);
+            }
+        }
+        let warm = self.panels.values().any(|panel| {
+            (show_minimap && panel.activity.heat() > 0.0) || panel.marks.is_fading(now)
+        });
+        if warm {
+            ctx.request_repaint_after(Duration::from_secs_f64(ACTIVITY_REFRESH_SECS));
+        }
+    }
+
+    /// Type the `runOnOpen` commands of new terminals whose prompt is up, checking again
+    /// shortly while any is still waiting.
+    pub(crate) fn send_pending_input(&mut self, ctx: &egui::Context) {
+        let now = ctx.input(|i| i.time);
+        let mut waiting = false;
+        for panel in self.panels.values_mut() {
+            waiting |= panel.send_pending_input(now);
+        }
+        if waiting {
+            ctx.request_repaint_after(Duration::from_secs_f64(PENDING_INPUT_REFRESH_SECS));
+        }
+    }
+
+    /// Text of the debug HUD for the focused terminal, if the HUD is shown.
+    pub(crate) fn debug_hud_text(
+        &mut self,
+        ctx: &egui::Context,
+        frame_cpu: Option<f32>,
+    ) -> Option<String> {
+        let hud = self.debug_hud.as_mut()?;
+        let ws = &self.workspaces[self.active_workspace];
+        let &panel_id = ws.panel_order.get(ws.focused_index)?;
+        let panel = self.panels.get(&panel_id)?;
+        let (now, dt) = ctx.input(|i| (i.time, i.stable_dt));
+        ctx.request_repaint_after(Duration::from_secs_f64(HUD_REFRESH_SECS));
+        Some(hud.text(panel_id, panel.backend.io_stats(), now, frame_cpu, dt))
+    }
+
+    /// Zoom the terminal font of `scope` (the focused terminal, the active workspace or
+    /// every terminal) by `steps`; negative zooms out, 0 resets it. Terminal grids are
+    /// resized to the new cell size on the next frame.
+    pub(crate) fn zoom_font(&mut self, scope: FontZoom, steps: i32) {
+        let step = |scale: f32| match steps {
+            0 => 1.0,
+            _ => layout::step_font_scale(scale, steps),
+        };
+        match scope {
+            FontZoom::Terminal => {
+                if let Some(panel) = self.focused_panel_mut() {
+                    panel.font_scale = step(panel.font_scale);
+                }
+            }
+            FontZoom::Workspace => {
+                let ws = self.active_workspace_mut();
+                ws.font_scale = step(ws.font_scale);
+            }
+            FontZoom::All => self.font_scale = step(self.font_scale),
+        }
+        // Minimum widths (`min_columns`) follow the font size
+        for ws in &mut self.workspaces {
+            ws.invalidate_positions();
+        }
+    }
+
+    /// Resize the UI chrome by `steps`; 0 goes back to the configured `ui_scale`.
+    pub(crate) fn zoom_ui(&mut self, ctx: &egui::Context, steps: i32) {
+        self.ui_scale = match steps {
+            0 => self.config.ui_scale,
+            _ => layout::step_font_scale(self.ui_scale, steps),
+        };
+        ui::apply_ui_scale(ctx, self.ui_scale);
+    }
+
+    pub(crate) fn toggle_pause_focused(&mut self) {
+        if let Some(panel) = self.focused_panel_mut() {
+            let paused = !panel.paused;
+            panel.set_paused(paused);
+        }
+    }
+
+    /// Turn raw key passthrough of the focused terminal on or off, overriding detection.
+    pub(crate) fn toggle_passthrough_focused(&mut self) {
+        let auto = self.config.auto_passthrough;
+        let Some(panel) = self.focused_panel_mut() else {
+            return;
+        };
+        let on = !panel.passthrough_active(auto);
+        panel.passthrough = Some(on);
+        let text = if on {
+            "Raw key passthrough on: shortcuts go to the program until ⌘⇧\\"
+        } else {
+            "Raw key passthrough off"
+        };
+        self.show_toast(ToastLevel::Info, text.to_string());
+    }
+
     pub(crate) fn focus_next(&mut self) {
         let ws = self.active_workspace_mut();
         if ws.focused_index < ws.panel_order.len().saturating_sub(1) {
             ws.focused_index += 1;
+        } else {
+            ws.overscroll = layout::rubber_band(layout::OVERSCROLL_BUMP, layout::OVERSCROLL_MAX);
         }
         self.log_ssh_status();
     }
@@ -89,6 +1349,8 @@ impl App {
         let ws = self.active_workspace_mut();
         if ws.focused_index > 0 {
             ws.focused_index -= 1;
+        } else {
+            ws.overscroll = layout::rubber_band(-layout::OVERSCROLL_BUMP, layout::OVERSCROLL_MAX);
         }
         self.log_ssh_status();
     }
@@ -96,7 +1358,7 @@ impl App {
     /// Log whether the currently focused terminal is running an SSH session.
     pub(crate) fn log_ssh_status(&self) {
         if let Some(panel) = self.focused_panel() {
-            match panel.detect_ssh() {
+            match panel.ssh.as_ref().map(|link| &link.session) {
                 Some(ssh) => {
                     log::info!(
                         "Terminal {} is SSH'd to {} (full cmd: {})",
@@ -116,21 +1378,181 @@ impl App {
     }
 
     pub(crate) fn grow_focused(&mut self) {
-        if let Some(panel) = self.focused_panel_mut() {
-            if let Some(new_ratio) = layout::next_ratio(&WIDTH_RATIOS, panel.width_ratio, 0.01) {
-                panel.width_ratio = new_ratio;
-            }
+        let ws = self.active_workspace();
+        if let Some(&id) = ws.panel_order.get(ws.focused_index) {
+            self.step_width(id, true);
         }
-        self.active_workspace_mut().invalidate_positions();
     }
 
     pub(crate) fn shrink_focused(&mut self) {
+        let ws = self.active_workspace();
+        if let Some(&id) = ws.panel_order.get(ws.focused_index) {
+            self.step_width(id, false);
+        }
+    }
+
+    /// Step a terminal to the next wider (`grow`) or narrower width in `width_ratios`.
+    pub(crate) fn step_width(&mut self, panel_id: u64, grow: bool) {
+        let Some(panel) = self.panels.get_mut(&panel_id) else {
+            return;
+        };
+        panel.step_width(&self.config.width_ratios, grow);
+        self.invalidate_positions_of(panel_id);
+    }
+
+    /// Set a terminal's width as a fraction of the viewport, no narrower than the
+    /// smallest width step.
+    pub(crate) fn set_width_ratio(&mut self, panel_id: u64, ratio: f32) {
+        let min = self.config.width_ratios[0];
+        let Some(panel) = self.panels.get_mut(&panel_id) else {
+            return;
+        };
+        panel.zoomed_from = None;
+        panel.width_ratio = ratio.clamp(min, 1.0);
+        self.invalidate_positions_of(panel_id);
+    }
+
+    /// Give every terminal in the workspace at `ws_idx` the same width, splitting the
+    /// viewport between them when they fit.
+    pub(crate) fn equalize_widths(&mut self, ws_idx: usize) {
+        let ws = &mut self.workspaces[ws_idx];
+        let ratio = layout::equal_ratio(ws.panel_order.len(), self.config.width_ratios[0]);
+        for id in &ws.panel_order {
+            if let Some(panel) = self.panels.get_mut(id) {
+                panel.zoomed_from = None;
+                panel.width_ratio = ratio;
+            }
+        }
+        ws.invalidate_positions();
+    }
+
+    /// Drop the cached positions of the workspace holding `panel_id`.
+    fn invalidate_positions_of(&mut self, panel_id: u64) {
+        if let Some(ws) = self.workspaces.iter_mut().find(|ws| ws.panel_order.contains(&panel_id)) {
+            ws.invalidate_positions();
+        }
+    }
+
+    /// Follow a drag of a terminal's right border, snapping to the nearest configured
+    /// width once the border is released.
+    pub(crate) fn drag_resize(&mut self, panel_id: u64, ratio: f32, released: bool) {
+        let ratio = if released {
+            layout::nearest_ratio(&self.config.width_ratios, ratio)
+        } else {
+            ratio
+        };
+        self.set_width_ratio(panel_id, ratio);
+    }
+
+    /// Expand the focused terminal to the full viewport width, or return it to the width
+    /// it had before, scrolling it into view either way.
+    pub(crate) fn toggle_zoom_focused(&mut self) {
         if let Some(panel) = self.focused_panel_mut() {
-            if let Some(new_ratio) = layout::prev_ratio(&WIDTH_RATIOS, panel.width_ratio, 0.01) {
-                panel.width_ratio = new_ratio;
+            panel.toggle_zoom();
+        }
+        let ws = self.active_workspace_mut();
+        // A manual pan would keep `scroll_to_focused` from following the new width
+        ws.manual_scroll_anchor = None;
+        ws.invalidate_positions();
+    }
+
+    /// Pin a terminal to the left edge of its strip, or unpin it. Pinned terminals are
+    /// docked in the order they were pinned; an unpinned one leads the scrolling ones.
+    pub(crate) fn set_pinned(&mut self, panel_id: u64, pinned: bool) {
+        let Some(panel) = self.panels.get_mut(&panel_id) else {
+            return;
+        };
+        if panel.pinned == pinned {
+            return;
+        }
+        panel.pinned = pinned;
+        let panels = &self.panels;
+        if let Some(ws) = self.workspaces.iter_mut().find(|ws| ws.panel_order.contains(&panel_id)) {
+            let is_pinned = |id: u64| id != panel_id && panels.get(&id).is_some_and(|p| p.pinned);
+            let focused = ws.panel_order.get(ws.focused_index).copied();
+            ws.panel_order.retain(|&id| id != panel_id);
+            let docked = ws.panel_order.iter().take_while(|&&id| is_pinned(id)).count();
+            ws.panel_order.insert(docked, panel_id);
+            if let Some(index) = focused.and_then(|id| ws.panel_order.iter().position(|&other| other == id)) {
+                ws.focused_index = index;
+            }
+            ws.invalidate_positions();
+        }
+    }
+
+    /// Switch to the workspace `step` places below the active one in the sidebar,
+    /// wrapping around at either end.
+    pub(crate) fn cycle_workspace(&mut self, step: isize) {
+        let count = self.workspaces.len() as isize;
+        self.active_workspace = (self.active_workspace as isize + step).rem_euclid(count) as usize;
+    }
+
+    /// Show the workspace at `index` until `key` is released, without switching to it.
+    /// Peeking at the active workspace ends a peek.
+    pub(crate) fn start_peek(&mut self, index: usize, key: egui::Key) {
+        self.peek = self
+            .workspaces
+            .get(index)
+            .filter(|_| index != self.active_workspace)
+            .map(|ws| Peek { workspace: ws.name.clone(), key });
+    }
+
+    /// Index of the workspace being peeked at, if it still exists and isn't the active one
+    pub(crate) fn peek_index(&self) -> Option<usize> {
+        let peek = self.peek.as_ref()?;
+        self.workspaces
+            .iter()
+            .position(|ws| ws.name == peek.workspace)
+            .filter(|&index| index != self.active_workspace)
+    }
+
+    /// End the peek once its key or ⌘ is let go (or the workspace is gone).
+    pub(crate) fn update_peek(&mut self, ctx: &egui::Context) {
+        let Some(peek) = &self.peek else {
+            return;
+        };
+        let held = ctx.input(|i| i.modifiers.command && i.key_down(peek.key));
+        if !held || self.peek_index().is_none() {
+            self.peek = None;
+        }
+    }
+
+    /// Move the ⌃Tab switch `step` terminals along the focus history, starting one if
+    /// none is in progress, and focus the terminal it lands on.
+    pub(crate) fn step_mru_switch(&mut self, step: isize) {
+        if self.mru_switch.is_none() {
+            let order = self.active_workspace().mru_order();
+            self.mru_switch = Some(MruSwitch { order, selected: 0 });
+        }
+        let Some(switch) = &mut self.mru_switch else {
+            return;
+        };
+        if switch.order.is_empty() {
+            self.mru_switch = None;
+            return;
+        }
+        let len = switch.order.len() as isize;
+        switch.selected = (switch.selected as isize + step).rem_euclid(len) as usize;
+        let id = switch.order[switch.selected];
+        self.focus_panel(id);
+    }
+
+    /// Put the focused terminal at the front of its workspace's focus history. During a
+    /// ⌃Tab switch the history stays as it was until ⌃ is released, so repeated presses
+    /// walk further back instead of bouncing between two terminals.
+    pub(crate) fn update_focus_history(&mut self, ctx: &egui::Context) {
+        if self.mru_switch.is_some() {
+            if ctx.input(|i| i.modifiers.ctrl) {
+                return;
+            }
+            self.mru_switch = None;
+        }
+        let ws = self.active_workspace_mut();
+        if let Some(&id) = ws.panel_order.get(ws.focused_index) {
+            if ws.focus_history.first() != Some(&id) {
+                ws.record_focus(id);
             }
         }
-        self.active_workspace_mut().invalidate_positions();
     }
 
     pub(crate) fn swap_with_prev(&mut self) {
@@ -198,37 +1620,279 @@ impl App {
         }
     }
 
-    pub(crate) fn close_focused(&mut self) {
-        let ws = self.active_workspace_mut();
-        if ws.panel_order.len() <= 1 {
-            return;
-        }
+    /// Close a terminal by internal ID. The last terminal in a workspace is never closed,
+    /// except in the scratch workspace, which is removed once empty.
+    pub(crate) fn close_panel(&mut self, panel_id: u64) -> Result<(), String> {
+        let panel = self.remove_panel(panel_id)?;
+        #[cfg(unix)]
+        panel.terminate();
+        Ok(())
+    }
 
-        if let Some(&id) = ws.panel_order.get(ws.focused_index) {
-            self.panels.remove(&id);
-            let ws = self.active_workspace_mut();
-            ws.panel_order.remove(ws.focused_index);
+    /// Take terminal `panel_id` out of its workspace and the panel pool, leaving its
+    /// shell running. Refused for the last terminal of a workspace, except in scratch.
+    pub(crate) fn remove_panel(&mut self, panel_id: u64) -> Result<TerminalPanel, String> {
+        let total_terminals: usize = self.workspaces.iter().map(|ws| ws.panel_order.len()).sum();
+        let Some(ws) = self
+            .workspaces
+            .iter_mut()
+            .find(|ws| ws.panel_order.contains(&panel_id))
+        else {
+            return Err("Terminal is not in any workspace".to_string());
+        };
 
+        let scratch = ws.is_scratch() && total_terminals > 1;
+        if ws.panel_order.len() <= 1 && !scratch {
+            return Err("Cannot close the last terminal in a workspace".to_string());
+        }
+
+        if let Some(pos) = ws.panel_order.iter().position(|&x| x == panel_id) {
+            ws.panel_order.remove(pos);
+            if pos < ws.focused_index {
+                ws.focused_index -= 1;
+            }
             if ws.focused_index >= ws.panel_order.len() {
                 ws.focused_index = ws.panel_order.len().saturating_sub(1);
             }
             ws.invalidate_positions();
         }
+
+        let panel = self.panels.remove(&panel_id).ok_or("Terminal not found")?;
+        if scratch {
+            self.cleanup_empty_workspaces();
+        }
+        Ok(panel)
+    }
+
+    /// Whether a destructive action from `source` on the terminals `panel_ids` should
+    /// ask for confirmation first.
+    fn needs_confirmation(&self, source: CommandSource, force: bool, panel_ids: &[u64]) -> bool {
+        let asks = match source {
+            CommandSource::Interactive => true,
+            CommandSource::Ipc => !self.config.ipc_non_interactive,
+        };
+        if force || !asks {
+            return false;
+        }
+        match self.config.confirm_close {
+            CloseConfirmation::Always => true,
+            CloseConfirmation::WhenBusy => self.any_busy(panel_ids),
+            CloseConfirmation::Never => false,
+        }
+    }
+
+    /// Whether any of `panel_ids` runs something besides an idle shell. Reads the process
+    /// table once, and only when no terminal has a foreground job.
+    #[cfg(unix)]
+    fn any_busy(&self, panel_ids: &[u64]) -> bool {
+        let panels = || panel_ids.iter().filter_map(|id| self.panels.get(id));
+        if panels().any(|panel| panel.running_process().is_some()) {
+            return true;
+        }
+        match ProcessTable::snapshot() {
+            Some(processes) => panels().any(|panel| panel.is_busy(&processes)),
+            // Unknown: better to ask
+            None => true,
+        }
+    }
+
+    /// Close a terminal, showing the confirmation dialog unless bypassed.
+    /// Returns true if a confirmation dialog was opened instead of closing.
+    pub(crate) fn request_close(
+        &mut self,
+        panel_id: u64,
+        source: CommandSource,
+        force: bool,
+    ) -> Result<bool, String> {
+        if self.needs_confirmation(source, force, &[panel_id]) {
+            #[cfg(unix)]
+            let running = self.panels.get(&panel_id).and_then(|panel| panel.running_process());
+            self.active_dialog = ActiveDialog::ConfirmClose { panel_id, running };
+            Ok(true)
+        } else {
+            self.close_panel(panel_id).map(|()| false)
+        }
+    }
+
+    /// Quit the application, showing the confirmation dialog unless bypassed.
+    /// Returns true if a confirmation dialog was opened instead of quitting.
+    pub(crate) fn request_quit(
+        &mut self,
+        ctx: &egui::Context,
+        source: CommandSource,
+        force: bool,
+    ) -> bool {
+        let all: Vec<u64> = self.panels.keys().copied().collect();
+        if self.needs_confirmation(source, force, &all) {
+            self.active_dialog = ActiveDialog::ConfirmQuit;
+            true
+        } else {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            false
+        }
+    }
+
+    /// Set the OS window title from `window_title` when the active workspace, the focused
+    /// terminal or its title changed.
+    pub(crate) fn update_window_title(&mut self, ctx: &egui::Context) {
+        let panel = self.focused_panel();
+        let cwd = panel
+            .and_then(|p| p.current_working_directory.as_ref())
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let title = titles::window_title(
+            &self.config.window_title,
+            &titles::WindowTitleParts {
+                workspace: &self.active_workspace().name,
+                title: panel.map_or("", |p| p.display_title()),
+                description: panel.map_or("", |p| p.cli_description.as_deref().unwrap_or(&p.description)),
+                cwd: &cwd,
+            },
+        );
+        if title != self.window_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.window_title = title;
+        }
+    }
+
+    /// Refresh the text `init.lua`'s `manse.on_status` / `manse.on_sidebar` functions
+    /// give, at most every `HOOK_INTERVAL_SECS`, all the calls sharing one budget.
+    pub(crate) fn update_script_text(&mut self, ctx: &egui::Context) {
+        let Some(hooks) = self.config.lua_hooks.clone() else {
+            self.script_status = None;
+            self.script_sidebar.clear();
+            return;
+        };
+        let now = ctx.input(|i| i.time);
+        if now - self.script_checked < scripting::HOOK_INTERVAL_SECS {
+            return;
+        }
+        self.script_checked = now;
+        ctx.request_repaint_after(Duration::from_secs_f64(scripting::HOOK_INTERVAL_SECS));
+
+        hooks.begin_pass();
+        self.refresh_script_text(&hooks);
+        hooks.end_pass();
+    }
+
+    fn refresh_script_text(&mut self, hooks: &scripting::LuaHooks) {
+        let active = self.active_workspace();
+        self.script_status = hooks.status(&scripting::StatusInfo {
+            workspace: &active.name,
+            workspaces: self.workspaces.len(),
+            terminals: active.panel_order.len(),
+            focused: self.focused_panel().map(|panel| terminal_info(panel, &active.name, true)),
+        });
+
+        self.script_sidebar.clear();
+        if !hooks.has_sidebar() {
+            return;
+        }
+        for (ws_idx, ws) in self.workspaces.iter().enumerate() {
+            for (index, id) in ws.panel_order.iter().enumerate() {
+                let Some(panel) = self.panels.get(id) else {
+                    continue;
+                };
+                let focused = ws_idx == self.active_workspace && index == ws.focused_index;
+                if let Some(text) = hooks.sidebar(&terminal_info(panel, &ws.name, focused)) {
+                    self.script_sidebar.insert(*id, text);
+                }
+            }
+        }
     }
 
     /// Compute and cache terminal positions for the active workspace.
-    pub(crate) fn ensure_positions_cached(&mut self, viewport_width: f32) {
-        let ws = self.active_workspace();
-        if (ws.cached_positions.viewport_width - viewport_width).abs() < 0.1
-            && ws.cached_positions.positions.len() == ws.panel_order.len()
-        {
+    /// Recompute duplicate-title suffixes for workspaces whose titles or cwds changed.
+    pub(crate) fn refresh_title_suffixes(&mut self) {
+        let mode = self.config.title_disambiguation;
+        for ws in &mut self.workspaces {
+            let mut hasher = DefaultHasher::new();
+            (mode as u8).hash(&mut hasher);
+            for id in &ws.panel_order {
+                if let Some(panel) = self.panels.get(id) {
+                    id.hash(&mut hasher);
+                    panel.display_title().hash(&mut hasher);
+                    panel.current_working_directory.hash(&mut hasher);
+                }
+            }
+            let key = hasher.finish();
+            if key == ws.title_suffix_key {
+                continue;
+            }
+
+            let ids: Vec<u64> = ws
+                .panel_order
+                .iter()
+                .copied()
+                .filter(|id| self.panels.contains_key(id))
+                .collect();
+            let cwds: Vec<Option<String>> = ids
+                .iter()
+                .map(|id| {
+                    self.panels[id]
+                        .current_working_directory
+                        .as_ref()
+                        .map(|p| p.to_string_lossy().into_owned())
+                })
+                .collect();
+            let entries: Vec<(&str, Option<&str>)> = ids
+                .iter()
+                .zip(&cwds)
+                .map(|(id, cwd)| (self.panels[id].display_title(), cwd.as_deref()))
+                .collect();
+
+            ws.title_suffixes = ids
+                .iter()
+                .zip(titles::disambiguate(&entries, mode))
+                .filter_map(|(&id, suffix)| suffix.map(|s| (id, s)))
+                .collect();
+            ws.title_suffix_key = key;
+        }
+    }
+
+    pub(crate) fn ensure_positions_cached(&mut self, ctx: &egui::Context, viewport_width: f32) {
+        self.ensure_workspace_positions_cached(ctx, self.active_workspace, viewport_width);
+    }
+
+    /// Narrowest `panel` may get in the workspace at `ws_idx`: `min_columns` columns at
+    /// its font size, plus border and padding.
+    fn min_terminal_width(&self, ctx: &egui::Context, ws_idx: usize, panel: &TerminalPanel) -> f32 {
+        if self.config.min_columns == 0 {
+            return 0.0;
+        }
+        let zoom = self.font_scale * self.workspaces[ws_idx].font_scale * panel.font_scale;
+        let font_size = self.config.font_size_for(panel.width_ratio) * zoom;
+        let font = egui::FontId::monospace(font_size);
+        let cell_width = ctx.fonts_mut(|fonts| fonts.glyph_width(&font, 'm'));
+        let chrome = ui::terminal_strip::horizontal_chrome(&self.config);
+        layout::columns_width(self.config.min_columns, cell_width, chrome)
+    }
+
+    /// Compute the terminal positions of the workspace at `ws_idx` unless they are cached
+    /// for this viewport width. Pinned terminals are moved to the front of the strip first.
+    pub(crate) fn ensure_workspace_positions_cached(
+        &mut self,
+        ctx: &egui::Context,
+        ws_idx: usize,
+        viewport_width: f32,
+    ) {
+        let ws = &self.workspaces[ws_idx];
+        if ws.cached_positions.is_valid_for(viewport_width, ws.panel_order.len()) {
             return;
         }
 
+        let panels = &self.panels;
+        let pinned = self.workspaces[ws_idx].dock_pinned(|id| panels.get(&id).is_some_and(|p| p.pinned));
+        let ws = &self.workspaces[ws_idx];
+
         let panel_order: Vec<u64> = ws.panel_order.clone();
         let widths: Vec<f32> = panel_order
             .iter()
-            .filter_map(|id| self.panels.get(id).map(|p| p.pixel_width(viewport_width)))
+            .filter_map(|id| {
+                let panel = self.panels.get(id)?;
+                let min_width = self.min_terminal_width(ctx, ws_idx, panel);
+                Some(panel.pixel_width(viewport_width, min_width))
+            })
             .collect();
 
         let raw_positions = layout::compute_positions(widths.into_iter());
@@ -239,9 +1903,10 @@ impl App {
             .map(|(id, (x, w))| (id, x, w))
             .collect();
 
-        let ws = self.active_workspace_mut();
+        let ws = &mut self.workspaces[ws_idx];
         ws.cached_positions.positions = positions;
         ws.cached_positions.viewport_width = viewport_width;
+        ws.cached_positions.pinned = pinned;
     }
 
     pub(crate) fn scroll_to_focused(&mut self, viewport_width: f32) {
@@ -253,23 +1918,59 @@ impl App {
         let focused_index = ws.focused_index;
         let current_target = ws.target_offset;
 
+        // Leave a manual pan alone until focus moves to a different terminal
+        let focused_id = ws.panel_order.get(focused_index).copied();
+        if ws.manual_scroll_anchor.is_some() {
+            if ws.manual_scroll_anchor == focused_id {
+                return;
+            }
+            self.active_workspace_mut().manual_scroll_anchor = None;
+        }
+        let ws = self.active_workspace();
+
+        // Pinned terminals are always in view
+        let pinned = ws.cached_positions.pinned;
+        if focused_index < pinned {
+            return;
+        }
         let positions: Vec<(f32, f32)> = ws
             .cached_positions
             .positions
             .iter()
             .map(|&(_, x, w)| (x, w))
             .collect();
+        let (docked_width, scrolling) = layout::split_docked(&positions, pinned);
 
         let new_target = layout::scroll_target_for_visible(
-            &positions,
-            focused_index,
+            &scrolling,
+            focused_index - pinned,
             current_target,
-            viewport_width,
+            viewport_width - docked_width,
         );
 
         self.active_workspace_mut().target_offset = new_target;
     }
 
+    /// Manually scroll the strip to `target` without changing focus.
+    pub(crate) fn pan_strip_to(&mut self, target: f32) {
+        let ws = self.active_workspace_mut();
+        let positions: Vec<(f32, f32)> = ws
+            .cached_positions
+            .positions
+            .iter()
+            .map(|&(_, x, w)| (x, w))
+            .collect();
+        let (docked_width, scrolling) = layout::split_docked(&positions, ws.cached_positions.pinned);
+        ws.target_offset =
+            layout::clamp_scroll(target, &scrolling, ws.cached_positions.viewport_width - docked_width);
+        // Stretch past the edge when the requested target was out of range
+        let excess = target - ws.target_offset;
+        if excess != 0.0 {
+            ws.overscroll = layout::rubber_band(excess, layout::OVERSCROLL_MAX);
+        }
+        ws.manual_scroll_anchor = ws.panel_order.get(ws.focused_index).copied();
+    }
+
     pub(crate) fn update_scroll(&mut self) {
         let ws = self.active_workspace_mut();
         ws.scroll_offset = layout::ease_toward(
@@ -277,12 +1978,66 @@ impl App {
             ws.target_offset,
             layout::SCROLL_EASING,
         );
+        ws.overscroll = layout::ease_toward(ws.overscroll, 0.0, layout::OVERSCROLL_EASING);
+    }
+
+    /// Mark a terminal notified by an OSC 9 / OSC 777 sequence, as `term-notify` does.
+    fn osc_notify(&mut self, ctx: &egui::Context, id: u64, title: Option<String>, body: String) {
+        let Some(panel) = self.panels.get_mut(&id) else {
+            return;
+        };
+        panel.notified = true;
+        let message = match &title {
+            Some(title) if !body.is_empty() => format!("{}: {}", title, body),
+            Some(title) => title.clone(),
+            None => body.clone(),
+        };
+        self.notifications
+            .push(id, NotificationKind::Notify, Some(message).filter(|m| !m.is_empty()));
+        self.notify_desktop(ctx, id, title.as_deref(), Some(body.as_str()).filter(|b| !b.is_empty()));
+    }
+
+    /// Show a notify or bell of terminal `id` as a desktop notification, if enabled by
+    /// `notifications.desktop`. Titled with `title` or the terminal's title; the body is
+    /// `message`, else the terminal's description.
+    pub(crate) fn notify_desktop(
+        &self,
+        ctx: &egui::Context,
+        id: u64,
+        title: Option<&str>,
+        message: Option<&str>,
+    ) {
+        let settings = &self.config.notifications;
+        if !settings.desktop {
+            return;
+        }
+        let Some(panel) = self.panels.get(&id) else {
+            return;
+        };
+        let window_focused = ctx.input(|i| i.viewport().focused).unwrap_or(true);
+        let looking_at = window_focused && self.focused_panel().is_some_and(|p| p.id == panel.id);
+        if settings.only_when_unfocused && looking_at {
+            return;
+        }
+        let description = panel
+            .cli_description
+            .as_deref()
+            .unwrap_or(&panel.description);
+        notifications::show_desktop(
+            title.unwrap_or_else(|| panel.display_title()),
+            message.unwrap_or(description),
+        );
     }
 
     pub(crate) fn process_events(&mut self, ctx: &egui::Context) {
         while let Ok((id, event)) = self.event_rx.try_recv() {
             self.perf_stats.on_pty_event();
             match event {
+                PtyEvent::Exit if self.overlay_terminal.as_ref().is_some_and(|(oid, _)| *oid == id) => {
+                    self.overlay_terminal = None;
+                    self.overlay_visible = false;
+                    self.overlay_progress = 0.0;
+                }
                 PtyEvent::Exit => {
                     for ws in &mut self.workspaces {
                         if let Some(pos) = ws.panel_order.iter().position(|&x| x == id) {
@@ -302,8 +2057,19 @@ impl App {
                     let total_terminals: usize =
                         self.workspaces.iter().map(|ws| ws.panel_order.len()).sum();
                     if total_terminals == 0 {
-                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                        return;
+                        match self.config.on_last_terminal {
+                            LastTerminalBehavior::Quit => {
+                                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                                return;
+                            }
+                            LastTerminalBehavior::NewShell => {
+                                if let Ok(new_index) = self.create_terminal(ctx, TerminalPlacement::End) {
+                                    self.active_workspace_mut().focused_index = new_index;
+                                }
+                            }
+                            // The central panel shows the start screen while no terminals exist
+                            LastTerminalBehavior::EmptyState => {}
+                        }
                     }
                 }
                 PtyEvent::Title(title) => {
@@ -311,9 +2077,45 @@ impl App {
                         panel.title = title;
                     }
                 }
+                PtyEvent::ColorRequest(index, format) => {
+                    // Answer with the scheme the terminal is drawn in (the drop-down uses the base theme)
+                    let target = match self.panels.get(&id) {
+                        Some(panel) => Some((panel, panel.color_scheme.as_deref())),
+                        None => self
+                            .overlay_terminal
+                            .as_ref()
+                            .filter(|(oid, _)| *oid == id)
+                            .map(|(_, panel)| (panel, None)),
+                    };
+                    if let Some((panel, scheme)) = target {
+                        let theme = &self.themes.get(scheme).theme;
+                        panel.backend.answer_color_query(theme, index, &*format);
+                    }
+                }
+                PtyEvent::Wakeup => {
+                    if let Some(panel) = self.panels.get_mut(&id) {
+                        panel.marks.output(ctx.input(|i| i.time));
+                    }
+                }
+                PtyEvent::Bell if self.panels.contains_key(&id) => {
+                    if let Some(panel) = self.panels.get_mut(&id) {
+                        panel.marks.bell(ctx.input(|i| i.time));
+                    }
+                    self.notifications.push(id, NotificationKind::Bell, None);
+                    self.notify_desktop(ctx, id, None, None);
+                }
+                PtyEvent::Notification(title, body) => self.osc_notify(ctx, id, title, body),
                 PtyEvent::WorkingDirectory(path) => {
                     if let Some(panel) = self.panels.get_mut(&id) {
-                        panel.current_working_directory = Some(PathBuf::from(path));
+                        let path = PathBuf::from(path);
+                        self.recent_directories.retain(|dir| *dir != path);
+                        self.recent_directories.insert(0, path.clone());
+                        self.recent_directories.truncate(MAX_RECENT_DIRECTORIES);
+                        panel.current_working_directory = Some(path.clone());
+                        if self.config.project_discovery {
+                            self.enter_project(id, &path);
+                        }
+                        self.name_workspace_from_git(id, &path);
                     }
                 }
                 _ => {}
@@ -321,3 +2123,16 @@ impl App {
         }
     }
 }
+
+/// `panel` in workspace `workspace` as passed to the `init.lua` functions
+fn terminal_info<'a>(panel: &'a TerminalPanel, workspace: &'a str, focused: bool) -> scripting::TerminalInfo<'a> {
+    scripting::TerminalInfo {
+        id: &panel.id,
+        title: panel.display_title(),
+        description: panel.cli_description.as_deref().unwrap_or(&panel.description),
+        cwd: panel.current_working_directory.as_deref(),
+        workspace,
+        focused,
+        notified: panel.notified,
+    }
+}
@@ -2,10 +2,20 @@
 //!
 //! Loads `init.lua` from the project root (found by walking up from the executable).
 
-use eframe::egui::Color32;
+use crate::engine::TerminalEngine;
+use crate::import::theme;
+use crate::scripting::{self, LuaHooks};
+use crate::util::keystrokes::KeystrokeMode;
+use crate::util::layout::{self, TerminalPlacement, WorkspaceSwitchAnimation};
+use crate::util::time;
+use crate::util::titles::TitleDisambiguation;
+use eframe::egui::{self, Color32};
 use egui_term::{ColorPalette, TerminalTheme};
 use mlua::{Lua, Result as LuaResult};
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
 
 /// Parse a hex color string like "#1e2132" to Color32
 pub fn hex_to_color32(hex: &str) -> Option<Color32> {
@@ -28,6 +38,18 @@ pub struct SidebarConfig {
     pub description_font_size: f32,
 }
 
+impl SidebarConfig {
+    /// Width and font sizes multiplied by `scale` (`ui_scale`)
+    pub fn scaled(&self, scale: f32) -> Self {
+        Self {
+            width: self.width * scale,
+            workspace_font_size: self.workspace_font_size * scale,
+            terminal_title_font_size: self.terminal_title_font_size * scale,
+            description_font_size: self.description_font_size * scale,
+        }
+    }
+}
+
 impl Default for SidebarConfig {
     fn default() -> Self {
         Self {
@@ -47,6 +69,17 @@ pub struct StatusBarConfig {
     pub description_font_size: f32,
 }
 
+impl StatusBarConfig {
+    /// Font sizes multiplied by `scale` (`ui_scale`)
+    pub fn scaled(&self, scale: f32) -> Self {
+        Self {
+            show_minimap: self.show_minimap,
+            title_font_size: self.title_font_size * scale,
+            description_font_size: self.description_font_size * scale,
+        }
+    }
+}
+
 impl Default for StatusBarConfig {
     fn default() -> Self {
         Self {
@@ -57,6 +90,76 @@ impl Default for StatusBarConfig {
     }
 }
 
+/// Animation settings
+#[derive(Debug, Clone)]
+pub struct AnimationConfig {
+    /// Animation played when switching workspaces ("off", "crossfade", "slide")
+    pub workspace_switch: WorkspaceSwitchAnimation,
+    /// Duration of the workspace switch animation
+    pub workspace_switch_ms: u64,
+}
+
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        Self {
+            workspace_switch: WorkspaceSwitchAnimation::Off,
+            workspace_switch_ms: 150,
+        }
+    }
+}
+
+/// Mouse wheel and touchpad scrolling (`scroll = { ... }` in init.lua)
+#[derive(Debug, Clone)]
+pub struct ScrollConfig {
+    /// Lines scrolled per wheel line; terminals of a `.manse.json` project with a
+    /// `scrollMultiplier` use that instead
+    pub multiplier: f32,
+    /// Send the wheel as arrow keys to full-screen programs that don't take mouse
+    /// input (less, man, ...)
+    pub alternate_scroll: bool,
+}
+
+impl Default for ScrollConfig {
+    fn default() -> Self {
+        Self {
+            multiplier: 1.0,
+            alternate_scroll: true,
+        }
+    }
+}
+
+/// Desktop notifications for notifies and bells (`notifications = { ... }` in init.lua)
+#[derive(Debug, Clone)]
+pub struct NotificationsConfig {
+    /// Show notifies (IPC, OSC 9 / OSC 777) and bells as native desktop notifications
+    pub desktop: bool,
+    /// Skip the terminal the user is looking at: the focused one while the window has focus
+    pub only_when_unfocused: bool,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            desktop: false,
+            only_when_unfocused: true,
+        }
+    }
+}
+
+/// What a new terminal inherits from the terminal focused when it was created
+#[derive(Debug, Clone, Default)]
+pub struct InheritConfig {
+    /// Copy the icon
+    pub icon: bool,
+    /// Start the description with the focused terminal's description prefix
+    pub description: bool,
+    /// Copy the environment the focused terminal's shell was started with, and set
+    /// MANSE_WORKSPACE to the workspace the terminal is created in
+    pub env: bool,
+    /// Re-run the SSH command if the focused terminal is in an SSH session
+    pub ssh: bool,
+}
+
 /// A pattern for icon detection
 #[derive(Debug, Clone)]
 pub struct IconPattern {
@@ -66,6 +169,35 @@ pub struct IconPattern {
     pub icon: String,
 }
 
+/// A command palette entry declared in `config.commands`
+#[derive(Debug, Clone)]
+pub struct CustomCommand {
+    /// Name shown in the palette
+    pub name: String,
+    /// Shell line to run
+    pub exec: String,
+    /// Workspace to open the new terminal in (created if needed; default: the active one)
+    pub workspace: Option<String>,
+    /// Type `exec` into the focused terminal instead of running it in a new one
+    pub send: bool,
+}
+
+/// A status bar segment declared in `config.status_segments`, shown on the right
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatusSegment {
+    /// Local time in a strftime `format` ("%H:%M")
+    Clock { format: String },
+    /// This machine's host name
+    Hostname,
+    /// Battery charge, left out on machines without a battery
+    Battery,
+    /// First line printed by a shell command, re-run every `interval_secs`
+    Command { exec: String, interval_secs: f64 },
+}
+
+/// Seconds between runs of a `command` status segment without an `interval`
+pub const DEFAULT_SEGMENT_INTERVAL_SECS: f64 = 10.0;
+
 /// Icon configuration for terminal titles
 #[derive(Debug, Clone)]
 pub struct IconConfig {
@@ -132,6 +264,39 @@ pub struct ColorsConfig {
 }
 
 impl ColorsConfig {
+    /// Fill unset colors from `base`.
+    pub fn or(self, base: &ColorsConfig) -> ColorsConfig {
+        ColorsConfig {
+            foreground: self.foreground.or_else(|| base.foreground.clone()),
+            background: self.background.or_else(|| base.background.clone()),
+            black: self.black.or_else(|| base.black.clone()),
+            red: self.red.or_else(|| base.red.clone()),
+            green: self.green.or_else(|| base.green.clone()),
+            yellow: self.yellow.or_else(|| base.yellow.clone()),
+            blue: self.blue.or_else(|| base.blue.clone()),
+            magenta: self.magenta.or_else(|| base.magenta.clone()),
+            cyan: self.cyan.or_else(|| base.cyan.clone()),
+            white: self.white.or_else(|| base.white.clone()),
+            bright_black: self.bright_black.or_else(|| base.bright_black.clone()),
+            bright_red: self.bright_red.or_else(|| base.bright_red.clone()),
+            bright_green: self.bright_green.or_else(|| base.bright_green.clone()),
+            bright_yellow: self.bright_yellow.or_else(|| base.bright_yellow.clone()),
+            bright_blue: self.bright_blue.or_else(|| base.bright_blue.clone()),
+            bright_magenta: self.bright_magenta.or_else(|| base.bright_magenta.clone()),
+            bright_cyan: self.bright_cyan.or_else(|| base.bright_cyan.clone()),
+            bright_white: self.bright_white.or_else(|| base.bright_white.clone()),
+            dim_foreground: self.dim_foreground.or_else(|| base.dim_foreground.clone()),
+            dim_black: self.dim_black.or_else(|| base.dim_black.clone()),
+            dim_red: self.dim_red.or_else(|| base.dim_red.clone()),
+            dim_green: self.dim_green.or_else(|| base.dim_green.clone()),
+            dim_yellow: self.dim_yellow.or_else(|| base.dim_yellow.clone()),
+            dim_blue: self.dim_blue.or_else(|| base.dim_blue.clone()),
+            dim_magenta: self.dim_magenta.or_else(|| base.dim_magenta.clone()),
+            dim_cyan: self.dim_cyan.or_else(|| base.dim_cyan.clone()),
+            dim_white: self.dim_white.or_else(|| base.dim_white.clone()),
+        }
+    }
+
     /// Build a ColorPalette from this config, using defaults for unset values.
     pub fn build_palette(&self) -> ColorPalette {
         let defaults = ColorPalette::default();
@@ -197,6 +362,45 @@ impl ColorsConfig {
             dim_white: self.dim_white.clone().unwrap_or_else(|| derive_dim(&white)),
         }
     }
+
+    /// Resolved background as a Color32.
+    pub fn background_color(&self) -> Color32 {
+        let default_bg = ColorPalette::default().background;
+        let hex = self.background.as_deref().unwrap_or(&default_bg);
+        hex_to_color32(hex).unwrap_or(Color32::from_rgb(0x18, 0x18, 0x18))
+    }
+}
+
+/// A terminal theme built from a color scheme, with its resolved background.
+#[derive(Clone)]
+pub struct SchemeTheme {
+    pub theme: TerminalTheme,
+    pub background: Color32,
+}
+
+impl SchemeTheme {
+    fn new(colors: &ColorsConfig) -> Self {
+        Self {
+            theme: TerminalTheme::new(Box::new(colors.build_palette())),
+            background: colors.background_color(),
+        }
+    }
+}
+
+/// Terminal themes built once at startup, keyed by scheme name.
+#[derive(Clone)]
+pub struct ThemeCache {
+    pub default: SchemeTheme,
+    pub schemes: HashMap<String, SchemeTheme>,
+}
+
+impl ThemeCache {
+    /// Theme for a terminal's scheme override, falling back to the base theme.
+    pub fn get(&self, scheme: Option<&str>) -> &SchemeTheme {
+        scheme
+            .and_then(|name| self.schemes.get(name))
+            .unwrap_or(&self.default)
+    }
 }
 
 /// UI color configuration for Manse's chrome (sidebar, status bar, etc.)
@@ -208,6 +412,16 @@ pub struct UiConfig {
     pub status_bar_background: Color32,
     pub status_bar_text: Color32,
     pub focused_border: Color32,
+    /// Sidebar background of terminals with a pending notification
+    pub notified_background: Color32,
+    /// Dot of terminals with recent output (sidebar and minimap)
+    pub activity_dot: Color32,
+    /// Dot of terminals that recently rang the bell
+    pub bell_dot: Color32,
+    /// Supplement color-only indicators with shapes (`accessible_indicators`)
+    pub shape_cues: bool,
+    /// Thicker double focus outline and larger minimap focus marker (`high_contrast`)
+    pub high_contrast: bool,
 }
 
 impl Default for UiConfig {
@@ -219,6 +433,191 @@ impl Default for UiConfig {
             status_bar_background: Color32::from_rgb(20, 20, 20),
             status_bar_text: Color32::from_rgb(120, 120, 120),
             focused_border: Color32::from_rgb(100, 150, 255),
+            notified_background: Color32::from_rgb(60, 25, 25),
+            activity_dot: Color32::from_rgb(120, 200, 120),
+            bell_dot: Color32::from_rgb(240, 190, 60),
+            shape_cues: false,
+            high_contrast: false,
+        }
+    }
+}
+
+impl UiConfig {
+    /// Defaults used with `accessible_indicators`: shape cues on, and focus/notification
+    /// colors from the Okabe-Ito palette (orange vs. blue, distinguishable with deuteranopia).
+    pub fn accessible() -> Self {
+        Self {
+            focused_border: Color32::from_rgb(230, 159, 0),
+            notified_background: Color32::from_rgb(15, 50, 85),
+            activity_dot: Color32::from_rgb(86, 180, 233),
+            bell_dot: Color32::from_rgb(240, 228, 66),
+            shape_cues: true,
+            ..Self::default()
+        }
+    }
+
+    /// Colors used with `high_contrast`: white and yellow on black, every text color at
+    /// least 7:1 against its background (WCAG AAA).
+    pub fn high_contrast(shape_cues: bool) -> Self {
+        Self {
+            sidebar_background: Color32::BLACK,
+            sidebar_text: Color32::WHITE,
+            sidebar_text_dim: Color32::from_rgb(190, 190, 190),
+            status_bar_background: Color32::BLACK,
+            status_bar_text: Color32::from_rgb(230, 230, 230),
+            focused_border: Color32::from_rgb(255, 220, 0),
+            notified_background: Color32::from_rgb(120, 0, 0),
+            activity_dot: Color32::from_rgb(0, 230, 120),
+            bell_dot: Color32::from_rgb(255, 150, 0),
+            shape_cues,
+            high_contrast: true,
+        }
+    }
+}
+
+/// When closing a terminal or quitting asks for confirmation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CloseConfirmation {
+    Always,
+    /// Only when something besides an idle shell runs (a foreground or background job)
+    WhenBusy,
+    Never,
+}
+
+impl CloseConfirmation {
+    /// Parse a config value ("always", "when_busy", "never").
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "always" => Some(Self::Always),
+            "when_busy" => Some(Self::WhenBusy),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+}
+
+/// What happens when the last terminal exits
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LastTerminalBehavior {
+    /// Close the window
+    Quit,
+    /// Start a fresh shell in the active workspace
+    NewShell,
+    /// Show a start screen with "New terminal" and recent workspaces
+    EmptyState,
+}
+
+impl LastTerminalBehavior {
+    /// Parse a config value ("quit", "new_shell", "empty_state").
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "quit" => Some(Self::Quit),
+            "new_shell" => Some(Self::NewShell),
+            "empty_state" => Some(Self::EmptyState),
+            _ => None,
+        }
+    }
+}
+
+/// Which terminals' font ⌘⇧= / ⌘⇧- / ⌘⇧0 zoom
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FontZoom {
+    /// The focused terminal
+    Terminal,
+    /// Every terminal of the active workspace
+    Workspace,
+    /// Every terminal
+    All,
+}
+
+impl FontZoom {
+    /// Parse a config value ("terminal", "workspace", "all").
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "terminal" => Some(Self::Terminal),
+            "workspace" => Some(Self::Workspace),
+            "all" => Some(Self::All),
+            _ => None,
+        }
+    }
+}
+
+/// Light or dark variant of the colors
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Appearance {
+    Light,
+    Dark,
+}
+
+/// How the appearance is chosen when `colors_light` / `colors_dark` are set
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AppearanceMode {
+    /// Follow the OS light/dark setting
+    System,
+    /// Light from `light_at` until `dark_at`, local time
+    Schedule,
+    Light,
+    Dark,
+}
+
+impl AppearanceMode {
+    /// Parse a config value ("system", "schedule", "light", "dark").
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "system" => Some(Self::System),
+            "schedule" => Some(Self::Schedule),
+            "light" => Some(Self::Light),
+            "dark" => Some(Self::Dark),
+            _ => None,
+        }
+    }
+}
+
+/// Terminal and UI colors of one appearance
+#[derive(Debug, Clone)]
+pub struct AppearanceColors {
+    pub colors: ColorsConfig,
+    pub ui_colors: UiConfig,
+}
+
+/// Automatic switching between light and dark colors
+#[derive(Debug, Clone)]
+pub struct AppearanceConfig {
+    pub mode: AppearanceMode,
+    /// Start of the light period for `Schedule` (minutes since midnight)
+    pub light_at: u32,
+    /// Start of the dark period for `Schedule` (minutes since midnight)
+    pub dark_at: u32,
+    /// Both set when any of `colors_light`, `colors_dark`, `ui_colors_light` or
+    /// `ui_colors_dark` is configured (a missing variant is the base colors)
+    pub light: Option<AppearanceColors>,
+    pub dark: Option<AppearanceColors>,
+}
+
+impl Default for AppearanceConfig {
+    fn default() -> Self {
+        Self {
+            mode: AppearanceMode::System,
+            light_at: 7 * 60,
+            dark_at: 19 * 60,
+            light: None,
+            dark: None,
+        }
+    }
+}
+
+impl AppearanceConfig {
+    /// Whether there are variants to switch between.
+    pub fn is_dynamic(&self) -> bool {
+        self.light.is_some() && self.dark.is_some()
+    }
+
+    /// Scheduled appearance at minute-of-day `now`, and minutes until it next changes.
+    pub fn scheduled(&self, now: u32) -> (Appearance, u32) {
+        if time::in_daily_window(now, self.light_at, self.dark_at) {
+            (Appearance::Light, time::minutes_until(now, self.dark_at))
+        } else {
+            (Appearance::Dark, time::minutes_until(now, self.light_at))
         }
     }
 }
@@ -230,19 +629,121 @@ pub struct Config {
     pub status_bar: StatusBarConfig,
     /// System font family name (e.g. "Iosevka"). None = embedded JetBrains Mono.
     pub font_family: Option<String>,
+    /// Shell new terminals run instead of `$SHELL`, optionally with arguments ("/bin/fish -l")
+    pub shell: Option<String>,
+    /// Command typed into every new terminal (⌘T, the palette, the start screen)
+    pub startup_command: Option<String>,
     pub terminal_font_size: f32,
+    /// Terminal font size per width ratio as (ratio, size), e.g. larger for full-width
+    /// terminals; other widths use `terminal_font_size`
+    pub font_size_by_width: Vec<(f32, f32)>,
+    /// Size of the UI around the terminals (sidebar, status bar, dialogs, palette) relative
+    /// to its defaults, independent of the terminal font size
+    pub ui_scale: f32,
+    /// Lines of output each terminal keeps above its screen
+    pub scrollback_lines: usize,
     /// Horizontal interior padding inside each terminal panel (pixels)
     pub terminal_padding_x: f32,
     /// Vertical interior padding inside each terminal panel (pixels)
     pub terminal_padding_y: f32,
     /// Performance logging interval in seconds (0 = disabled)
     pub perf_log_interval: f32,
+    /// File each perf window is appended to (CSV, or JSON lines for `.json`/`.jsonl`)
+    pub perf_log_file: Option<PathBuf>,
+    /// Size in KiB at which the perf log file is rotated to `<file>.1` (0 = never)
+    pub perf_log_max_kb: u64,
+    /// Minimum milliseconds between repaints caused by output of unfocused terminals
+    pub output_repaint_ms: u64,
+    /// Same for the focused terminal (0 = repaint on every update)
+    pub focused_output_repaint_ms: u64,
+    /// Off-screen terminals on either side of the viewport rendered anyway, so scroll
+    /// animations reveal them without a hitch (0 = only render what is on screen)
+    pub strip_overscan: usize,
+    /// Context copied from the focused terminal into new terminals
+    pub inherit: InheritConfig,
+    /// Where new terminals are inserted ("before", "after", "start", "end", or an index)
+    pub new_terminal_position: TerminalPlacement,
+    /// Terminals a workspace holds before new ones go to an overflow workspace (0 = no limit)
+    pub max_terminals_per_workspace: usize,
+    /// Terminal widths as fractions of the viewport, stepped through by grow/shrink and
+    /// snapped to after dragging a border (sorted, never empty)
+    pub width_ratios: Vec<f32>,
+    /// Columns a terminal keeps at its font size however small its width ratio makes it
+    /// on the window (0 = no minimum)
+    pub min_columns: u32,
+    /// How duplicate titles within a workspace are told apart ("off", "cwd", "index")
+    pub title_disambiguation: TitleDisambiguation,
+    /// What happens when the last terminal exits ("quit", "new_shell", "empty_state")
+    pub on_last_terminal: LastTerminalBehavior,
+    /// What the font zoom keys zoom ("terminal", "workspace", "all")
+    pub font_zoom: FontZoom,
+    /// Engine new terminals run on ("pty", "stub")
+    pub terminal_engine: TerminalEngine,
+    /// Animation settings
+    pub animations: AnimationConfig,
+    /// Key chords shown in the keystroke overlay ("off", "shortcuts", "all")
+    pub keystroke_overlay: KeystrokeMode,
+    /// When to ask for confirmation before closing a terminal or quitting
+    /// ("always", "when_busy", "never")
+    pub confirm_close: CloseConfirmation,
+    /// Move terminals into the workspace of the `.manse.json` project they `cd` into
+    pub project_discovery: bool,
+    /// Name the startup workspace after the git repository its first terminal starts in
+    pub workspace_name_from_git: bool,
+    /// Reload the config when `init.lua` changes on disk
+    pub watch_config: bool,
+    /// Save the layout on quit and recreate it with new shells on the next start
+    pub restore_on_start: bool,
+    /// Start the shells of restored and template terminals when they are first shown
+    pub lazy_spawn: bool,
+    /// Copy text to the clipboard as soon as it is selected in a terminal
+    pub copy_on_select: bool,
+    /// Pass ⌘ shortcuts to programs using the kitty keyboard protocol, as if raw key
+    /// passthrough were toggled on for their terminal
+    pub auto_passthrough: bool,
+    /// Show a title bar with icon, title and close/zoom buttons above each terminal
+    pub title_bars: bool,
+    /// OS window title, with `{workspace}`, `{title}`, `{description}` and `{cwd}` filled in
+    /// from the active workspace and focused terminal
+    pub window_title: String,
+    /// Desktop notifications for terminal notifies and bells
+    pub notifications: NotificationsConfig,
+    /// Wheel scrolling speed and alternate screen handling
+    pub scroll: ScrollConfig,
+    /// Seconds typed input may go unanswered in an SSH terminal before it is marked
+    /// disconnected (0 = no SSH health checks)
+    pub ssh_timeout_secs: u64,
+    /// Seconds a frame may run while terminals are open before the UI counts as hung
+    /// and manse restarts itself, keeping the shells (0 = no watchdog)
+    pub watchdog_secs: u64,
+    /// Seconds an emptied workspace lingers in the sidebar before it is removed
+    /// (0 = remove it right away)
+    pub empty_workspace_linger_secs: u64,
+    /// Skip confirmation dialogs for commands arriving over IPC (for scripted teardown)
+    pub ipc_non_interactive: bool,
     /// Icon detection configuration
     pub icons: IconConfig,
-    /// Terminal color scheme
+    /// Terminal color scheme (the active variant when `appearance` switches colors)
     pub colors: ColorsConfig,
-    /// UI colors (sidebar, status bar, borders)
+    /// Light/dark color variants and when to use them
+    pub appearance: AppearanceConfig,
+    /// Named color schemes that can be applied to individual terminals
+    pub schemes: HashMap<String, ColorsConfig>,
+    /// Workspace templates: name -> startup command per terminal ("" = plain shell)
+    pub templates: HashMap<String, Vec<String>>,
+    /// User-defined command palette entries
+    pub commands: Vec<CustomCommand>,
+    /// Segments on the right of the status bar, left to right
+    pub status_segments: Vec<StatusSegment>,
+    /// Functions `init.lua` registered with `manse.on_status` / `manse.on_sidebar`
+    pub lua_hooks: Option<LuaHooks>,
+    /// Foreground process names for which URL detection is turned off (e.g. "htop")
+    pub link_exclusions: Vec<String>,
+    /// UI colors (sidebar, status bar, borders); the high-contrast palette while
+    /// `high_contrast` is on
     pub ui_colors: UiConfig,
+    /// `ui_colors` as configured, restored when high contrast is turned off
+    pub configured_ui_colors: UiConfig,
 }
 
 impl Default for Config {
@@ -251,28 +752,110 @@ impl Default for Config {
             sidebar: SidebarConfig::default(),
             status_bar: StatusBarConfig::default(),
             font_family: None,
+            shell: None,
+            startup_command: None,
             terminal_font_size: 14.0,
+            font_size_by_width: Vec::new(),
+            ui_scale: 1.0,
+            scrollback_lines: egui_term::DEFAULT_SCROLLBACK_LINES,
             terminal_padding_x: 8.0,
             terminal_padding_y: 4.0,
             perf_log_interval: 0.0,
+            perf_log_file: None,
+            perf_log_max_kb: 1024,
+            output_repaint_ms: 8,
+            focused_output_repaint_ms: 0,
+            strip_overscan: 0,
+            inherit: InheritConfig::default(),
+            new_terminal_position: TerminalPlacement::AfterFocused,
+            max_terminals_per_workspace: 0,
+            width_ratios: vec![0.333, 0.5, 0.667, 1.0],
+            min_columns: 0,
+            title_disambiguation: TitleDisambiguation::Cwd,
+            on_last_terminal: LastTerminalBehavior::Quit,
+            font_zoom: FontZoom::Workspace,
+            terminal_engine: TerminalEngine::Pty,
+            animations: AnimationConfig::default(),
+            keystroke_overlay: KeystrokeMode::Off,
+            confirm_close: CloseConfirmation::Always,
+            project_discovery: true,
+            workspace_name_from_git: true,
+            watch_config: true,
+            restore_on_start: false,
+            lazy_spawn: true,
+            copy_on_select: false,
+            auto_passthrough: false,
+            title_bars: false,
+            window_title: "manse — {workspace}: {title}".to_string(),
+            notifications: NotificationsConfig::default(),
+            scroll: ScrollConfig::default(),
+            ssh_timeout_secs: 0,
+            watchdog_secs: 0,
+            empty_workspace_linger_secs: 300,
+            ipc_non_interactive: false,
             icons: IconConfig::default(),
             colors: ColorsConfig::default(),
+            appearance: AppearanceConfig::default(),
+            schemes: HashMap::new(),
+            templates: HashMap::new(),
+            commands: Vec::new(),
+            status_segments: Vec::new(),
+            lua_hooks: None,
+            link_exclusions: Vec::new(),
             ui_colors: UiConfig::default(),
+            configured_ui_colors: UiConfig::default(),
         }
     }
 }
 
 impl Config {
-    /// Build a terminal theme from the color configuration.
-    pub fn build_theme(&self) -> TerminalTheme {
-        TerminalTheme::new(Box::new(self.colors.build_palette()))
+    /// Where a new terminal goes: `requested`, or `new_terminal_position` without one.
+    pub fn placement(&self, requested: Option<TerminalPlacement>) -> TerminalPlacement {
+        requested.unwrap_or(self.new_terminal_position)
     }
 
-    /// Resolved terminal background as a Color32.
-    pub fn terminal_background(&self) -> Color32 {
-        let default_bg = ColorPalette::default().background;
-        let hex = self.colors.background.as_deref().unwrap_or(&default_bg);
-        hex_to_color32(hex).unwrap_or(Color32::from_rgb(0x18, 0x18, 0x18))
+    /// Font size of terminals `width_ratio` wide, before workspace zoom.
+    pub fn font_size_for(&self, width_ratio: f32) -> f32 {
+        layout::font_size_for_ratio(&self.font_size_by_width, width_ratio, 0.01)
+            .unwrap_or(self.terminal_font_size)
+    }
+
+    /// Build the terminal themes for the base colors and every named scheme
+    /// (unset scheme colors fall back to the base colors).
+    pub fn build_themes(&self) -> ThemeCache {
+        ThemeCache {
+            default: SchemeTheme::new(&self.colors),
+            schemes: self
+                .schemes
+                .iter()
+                .map(|(name, colors)| (name.clone(), SchemeTheme::new(&colors.clone().or(&self.colors))))
+                .collect(),
+        }
+    }
+
+    /// Make the `appearance` variant the active colors. Returns false without variants.
+    pub fn apply_appearance(&mut self, appearance: Appearance) -> bool {
+        let variant = match appearance {
+            Appearance::Light => &self.appearance.light,
+            Appearance::Dark => &self.appearance.dark,
+        };
+        let Some(variant) = variant.clone() else {
+            return false;
+        };
+        self.colors = variant.colors;
+        self.configured_ui_colors = variant.ui_colors;
+        self.set_high_contrast(self.ui_colors.high_contrast);
+        true
+    }
+
+    /// Switch the UI chrome to the high-contrast palette and focus ring, or back to the
+    /// configured colors.
+    pub fn set_high_contrast(&mut self, on: bool) {
+        self.ui_colors = if on {
+            UiConfig::high_contrast(self.configured_ui_colors.shape_cues)
+        } else {
+            self.configured_ui_colors.clone()
+        };
     }
 }
 
@@ -293,15 +876,51 @@ fn find_project_root() -> Option<PathBuf> {
     None
 }
 
+/// Path of `init.lua` in the project root.
+pub fn config_path() -> Option<PathBuf> {
+    find_project_root().map(|root| root.join("init.lua"))
+}
+
+/// Re-read `init.lua` for a running instance. Unlike `load_config`, errors are returned
+/// rather than replaced by the defaults.
+pub fn reload_config() -> Result<Config, String> {
+    let path = config_path()
+        .filter(|path| path.exists())
+        .ok_or("No init.lua found")?;
+    load_config_from_file(&path).map_err(|e| format!("Failed to load {}: {}", path.display(), e))
+}
+
+/// Watch `init.lua` for changes by polling its modification time on a background
+/// thread. Each change is sent on the returned channel and wakes the UI.
+pub fn watch_config(ctx: egui::Context) -> Option<Receiver<()>> {
+    let path = config_path()?;
+    let modified = |path: &PathBuf| std::fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut last = modified(&path);
+        loop {
+            std::thread::sleep(Duration::from_secs(1));
+            let current = modified(&path);
+            if current != last {
+                last = current;
+                if tx.send(()).is_err() {
+                    return;
+                }
+                ctx.request_repaint();
+            }
+        }
+    });
+    Some(rx)
+}
+
 /// Load configuration from `init.lua` in the project root.
 /// Returns default config if no config file exists or on any error.
 pub fn load_config() -> Config {
-    let Some(project_root) = find_project_root() else {
+    let Some(config_path) = config_path() else {
         log::debug!("Could not find project root, using default config");
         return Config::default();
     };
 
-    let config_path = project_root.join("init.lua");
     if !config_path.exists() {
         log::debug!("No init.lua found at {}, using default config", config_path.display());
         return Config::default();
@@ -319,10 +938,76 @@ pub fn load_config() -> Config {
     }
 }
 
+/// Read a UI color table (`config.ui_colors` or a light/dark variant); unset colors come
+/// from `defaults`.
+fn parse_ui_colors(table: &mlua::Table, defaults: &UiConfig) -> UiConfig {
+    let color = |key: &str, default: Color32| {
+        table
+            .get::<String>(key)
+            .ok()
+            .and_then(|s| hex_to_color32(&s))
+            .unwrap_or(default)
+    };
+    UiConfig {
+        sidebar_background: color("sidebar_background", defaults.sidebar_background),
+        sidebar_text: color("sidebar_text", defaults.sidebar_text),
+        sidebar_text_dim: color("sidebar_text_dim", defaults.sidebar_text_dim),
+        status_bar_background: color("status_bar_background", defaults.status_bar_background),
+        status_bar_text: color("status_bar_text", defaults.status_bar_text),
+        focused_border: color("focused_border", defaults.focused_border),
+        notified_background: color("notified_background", defaults.notified_background),
+        activity_dot: color("activity_dot", defaults.activity_dot),
+        bell_dot: color("bell_dot", defaults.bell_dot),
+        shape_cues: defaults.shape_cues,
+        high_contrast: defaults.high_contrast,
+    }
+}
+
+/// Read a color table (`config.colors` or a `config.schemes` entry).
+fn parse_colors(table: &mlua::Table) -> ColorsConfig {
+    ColorsConfig {
+        foreground: table.get("foreground").ok(),
+        background: table.get("background").ok(),
+        black: table.get("black").ok(),
+        red: table.get("red").ok(),
+        green: table.get("green").ok(),
+        yellow: table.get("yellow").ok(),
+        blue: table.get("blue").ok(),
+        magenta: table.get("magenta").ok(),
+        cyan: table.get("cyan").ok(),
+        white: table.get("white").ok(),
+        bright_black: table.get("bright_black").ok(),
+        bright_red: table.get("bright_red").ok(),
+        bright_green: table.get("bright_green").ok(),
+        bright_yellow: table.get("bright_yellow").ok(),
+        bright_blue: table.get("bright_blue").ok(),
+        bright_magenta: table.get("bright_magenta").ok(),
+        bright_cyan: table.get("bright_cyan").ok(),
+        bright_white: table.get("bright_white").ok(),
+        dim_foreground: table.get("dim_foreground").ok(),
+        dim_black: table.get("dim_black").ok(),
+        dim_red: table.get("dim_red").ok(),
+        dim_green: table.get("dim_green").ok(),
+        dim_yellow: table.get("dim_yellow").ok(),
+        dim_blue: table.get("dim_blue").ok(),
+        dim_magenta: table.get("dim_magenta").ok(),
+        dim_cyan: table.get("dim_cyan").ok(),
+        dim_white: table.get("dim_white").ok(),
+    }
+}
+
 /// Load configuration from a specific Lua file.
 fn load_config_from_file(path: &PathBuf) -> LuaResult<Config> {
     let lua = Lua::new();
 
+    // `config.colors = import_theme("~/themes/kanagawa.conf")`: kitty, WezTerm or iTerm2 schemes
+    let import_theme = lua.create_function(|lua, path: String| {
+        let colors = theme::load(&crate::project::expand_home(&path)).map_err(mlua::Error::runtime)?;
+        lua.create_table_from(theme::entries(&colors))
+    })?;
+    lua.globals().set("import_theme", import_theme)?;
+    scripting::install(&lua)?;
+
     // Create config table with defaults
     let sidebar_defaults = SidebarConfig::default();
     let status_bar_defaults = StatusBarConfig::default();
@@ -335,12 +1020,38 @@ fn load_config_from_file(path: &PathBuf) -> LuaResult<Config> {
             terminal_title_font_size = {terminal_title_font_size},
             description_font_size = {description_font_size},
             terminal_font_size = {terminal_font_size},
+            ui_scale = {ui_scale},
+            scrollback_lines = {scrollback_lines},
             terminal_padding_x = {terminal_padding_x},
             terminal_padding_y = {terminal_padding_y},
             perf_log_interval = {perf_log_interval},
+            perf_log_max_kb = {perf_log_max_kb},
+            output_repaint_ms = {output_repaint_ms},
+            focused_output_repaint_ms = {focused_output_repaint_ms},
+            strip_overscan = {strip_overscan},
+            min_columns = {min_columns},
+            max_terminals_per_workspace = {max_terminals_per_workspace},
+            inherit_icon = {inherit_icon},
+            inherit_description = {inherit_description},
+            inherit_env = {inherit_env},
+            inherit_ssh = {inherit_ssh},
+            project_discovery = {project_discovery},
+            workspace_name_from_git = {workspace_name_from_git},
+            watch_config = {watch_config},
+            restore_on_start = {restore_on_start},
+            lazy_spawn = {lazy_spawn},
+            copy_on_select = {copy_on_select},
+            auto_passthrough = {auto_passthrough},
+            title_bars = {title_bars},
+            ssh_timeout_secs = {ssh_timeout_secs},
+            watchdog_secs = {watchdog_secs},
+            empty_workspace_linger_secs = {empty_workspace_linger_secs},
+            ipc_non_interactive = {ipc_non_interactive},
             show_minimap = {show_minimap},
             status_bar_title_font_size = {status_bar_title_font_size},
             status_bar_description_font_size = {status_bar_description_font_size},
+            accessible_indicators = {accessible_indicators},
+            high_contrast = {high_contrast},
         }}
         "#,
         sidebar_width = sidebar_defaults.width,
@@ -348,12 +1059,38 @@ fn load_config_from_file(path: &PathBuf) -> LuaResult<Config> {
         terminal_title_font_size = sidebar_defaults.terminal_title_font_size,
         description_font_size = sidebar_defaults.description_font_size,
         terminal_font_size = config_defaults.terminal_font_size,
+        ui_scale = config_defaults.ui_scale,
+        scrollback_lines = config_defaults.scrollback_lines,
         terminal_padding_x = config_defaults.terminal_padding_x,
         terminal_padding_y = config_defaults.terminal_padding_y,
         perf_log_interval = config_defaults.perf_log_interval,
+        perf_log_max_kb = config_defaults.perf_log_max_kb,
+        output_repaint_ms = config_defaults.output_repaint_ms,
+        focused_output_repaint_ms = config_defaults.focused_output_repaint_ms,
+        strip_overscan = config_defaults.strip_overscan,
+        min_columns = config_defaults.min_columns,
+        max_terminals_per_workspace = config_defaults.max_terminals_per_workspace,
+        inherit_icon = config_defaults.inherit.icon,
+        inherit_description = config_defaults.inherit.description,
+        inherit_env = config_defaults.inherit.env,
+        inherit_ssh = config_defaults.inherit.ssh,
+        project_discovery = config_defaults.project_discovery,
+        workspace_name_from_git = config_defaults.workspace_name_from_git,
+        watch_config = config_defaults.watch_config,
+        restore_on_start = config_defaults.restore_on_start,
+        lazy_spawn = config_defaults.lazy_spawn,
+        copy_on_select = config_defaults.copy_on_select,
+        auto_passthrough = config_defaults.auto_passthrough,
+        title_bars = config_defaults.title_bars,
+        ssh_timeout_secs = config_defaults.ssh_timeout_secs,
+        watchdog_secs = config_defaults.watchdog_secs,
+        empty_workspace_linger_secs = config_defaults.empty_workspace_linger_secs,
+        ipc_non_interactive = config_defaults.ipc_non_interactive,
         show_minimap = status_bar_defaults.show_minimap,
         status_bar_title_font_size = status_bar_defaults.title_font_size,
         status_bar_description_font_size = status_bar_defaults.description_font_size,
+        accessible_indicators = UiConfig::default().shape_cues,
+        high_contrast = UiConfig::default().high_contrast,
     ))
     .exec()?;
 
@@ -392,82 +1129,287 @@ fn load_config_from_file(path: &PathBuf) -> LuaResult<Config> {
 
     // Parse colors config if present
     let colors = if let Ok(colors_table) = config_table.get::<mlua::Table>("colors") {
-        ColorsConfig {
-            foreground: colors_table.get("foreground").ok(),
-            background: colors_table.get("background").ok(),
-            black: colors_table.get("black").ok(),
-            red: colors_table.get("red").ok(),
-            green: colors_table.get("green").ok(),
-            yellow: colors_table.get("yellow").ok(),
-            blue: colors_table.get("blue").ok(),
-            magenta: colors_table.get("magenta").ok(),
-            cyan: colors_table.get("cyan").ok(),
-            white: colors_table.get("white").ok(),
-            bright_black: colors_table.get("bright_black").ok(),
-            bright_red: colors_table.get("bright_red").ok(),
-            bright_green: colors_table.get("bright_green").ok(),
-            bright_yellow: colors_table.get("bright_yellow").ok(),
-            bright_blue: colors_table.get("bright_blue").ok(),
-            bright_magenta: colors_table.get("bright_magenta").ok(),
-            bright_cyan: colors_table.get("bright_cyan").ok(),
-            bright_white: colors_table.get("bright_white").ok(),
-            dim_foreground: colors_table.get("dim_foreground").ok(),
-            dim_black: colors_table.get("dim_black").ok(),
-            dim_red: colors_table.get("dim_red").ok(),
-            dim_green: colors_table.get("dim_green").ok(),
-            dim_yellow: colors_table.get("dim_yellow").ok(),
-            dim_blue: colors_table.get("dim_blue").ok(),
-            dim_magenta: colors_table.get("dim_magenta").ok(),
-            dim_cyan: colors_table.get("dim_cyan").ok(),
-            dim_white: colors_table.get("dim_white").ok(),
-        }
+        parse_colors(&colors_table)
     } else {
         ColorsConfig::default()
     };
 
-    // Parse UI colors config if present
-    let ui_colors = if let Ok(ui_table) = config_table.get::<mlua::Table>("ui_colors") {
-        let defaults = UiConfig::default();
-        UiConfig {
-            sidebar_background: ui_table
-                .get::<String>("sidebar_background")
-                .ok()
-                .and_then(|s| hex_to_color32(&s))
-                .unwrap_or(defaults.sidebar_background),
-            sidebar_text: ui_table
-                .get::<String>("sidebar_text")
-                .ok()
-                .and_then(|s| hex_to_color32(&s))
-                .unwrap_or(defaults.sidebar_text),
-            sidebar_text_dim: ui_table
-                .get::<String>("sidebar_text_dim")
-                .ok()
-                .and_then(|s| hex_to_color32(&s))
-                .unwrap_or(defaults.sidebar_text_dim),
-            status_bar_background: ui_table
-                .get::<String>("status_bar_background")
-                .ok()
-                .and_then(|s| hex_to_color32(&s))
-                .unwrap_or(defaults.status_bar_background),
-            status_bar_text: ui_table
-                .get::<String>("status_bar_text")
-                .ok()
-                .and_then(|s| hex_to_color32(&s))
-                .unwrap_or(defaults.status_bar_text),
-            focused_border: ui_table
-                .get::<String>("focused_border")
-                .ok()
-                .and_then(|s| hex_to_color32(&s))
-                .unwrap_or(defaults.focused_border),
+    // Parse named color schemes; unset colors fall back to the active base colors
+    let mut schemes = HashMap::new();
+    if let Ok(schemes_table) = config_table.get::<mlua::Table>("schemes") {
+        for (name, scheme_table) in schemes_table.pairs::<String, mlua::Table>().flatten() {
+            schemes.insert(name, parse_colors(&scheme_table));
+        }
+    }
+
+    // Parse workspace templates: name -> list of startup commands
+    let mut templates = HashMap::new();
+    if let Ok(templates_table) = config_table.get::<mlua::Table>("templates") {
+        for (name, commands) in templates_table.pairs::<String, Vec<String>>().flatten() {
+            templates.insert(name, commands);
+        }
+    }
+
+    // Custom palette commands: { name = ..., exec = ..., workspace = ..., send = ... }
+    let mut commands = Vec::new();
+    if let Ok(commands_table) = config_table.get::<mlua::Table>("commands") {
+        for pair in commands_table.pairs::<i64, mlua::Table>() {
+            let Ok((_, entry)) = pair else {
+                continue;
+            };
+            match (entry.get::<String>("name"), entry.get::<String>("exec")) {
+                (Ok(name), Ok(exec)) => commands.push(CustomCommand {
+                    name,
+                    exec,
+                    workspace: entry.get::<Option<String>>("workspace")?.filter(|ws| !ws.is_empty()),
+                    send: entry.get::<Option<bool>>("send")?.unwrap_or(false),
+                }),
+                _ => log::warn!("Ignoring a config.commands entry without name and exec"),
+            }
+        }
+    }
+
+    // Status bar segments: { kind = "clock" | "hostname" | "battery" | "command", ... }
+    let mut status_segments = Vec::new();
+    if let Ok(segments_table) = config_table.get::<mlua::Table>("status_segments") {
+        for pair in segments_table.pairs::<i64, mlua::Table>() {
+            let Ok((_, entry)) = pair else {
+                continue;
+            };
+            let kind = entry.get::<Option<String>>("kind")?.unwrap_or_default();
+            match kind.as_str() {
+                "clock" => status_segments.push(StatusSegment::Clock {
+                    format: entry.get::<Option<String>>("format")?.unwrap_or_else(|| "%H:%M".to_string()),
+                }),
+                "hostname" => status_segments.push(StatusSegment::Hostname),
+                "battery" => status_segments.push(StatusSegment::Battery),
+                "command" => match entry.get::<Option<String>>("exec")? {
+                    Some(exec) if !exec.trim().is_empty() => status_segments.push(StatusSegment::Command {
+                        exec,
+                        interval_secs: entry
+                            .get::<Option<f64>>("interval")?
+                            .filter(|secs| *secs > 0.0)
+                            .unwrap_or(DEFAULT_SEGMENT_INTERVAL_SECS),
+                    }),
+                    _ => log::warn!("Ignoring a command status segment without exec"),
+                },
+                other => log::warn!("Ignoring status segment of unknown kind '{}'", other),
+            }
+        }
+    }
+
+    // Width steps: keep ratios in (0, 1], sorted without duplicates
+    let width_ratios = match config_table.get::<Option<Vec<f32>>>("width_ratios")? {
+        Some(mut ratios) => {
+            ratios.retain(|&r| r > 0.0 && r <= 1.0);
+            ratios.sort_by(f32::total_cmp);
+            ratios.dedup();
+            if ratios.is_empty() {
+                log::warn!("width_ratios has no ratio in (0, 1], using default");
+                config_defaults.width_ratios.clone()
+            } else {
+                ratios
+            }
         }
+        None => config_defaults.width_ratios.clone(),
+    };
+
+    // Font sizes per width: { { ratio = 1.0, size = 16 }, ... }
+    let mut font_size_by_width = Vec::new();
+    if let Ok(sizes_table) = config_table.get::<mlua::Table>("font_size_by_width") {
+        for pair in sizes_table.pairs::<i64, mlua::Table>() {
+            let Ok((_, entry)) = pair else {
+                continue;
+            };
+            match (entry.get::<f32>("ratio"), entry.get::<f32>("size")) {
+                (Ok(ratio), Ok(size)) if ratio > 0.0 && ratio <= 1.0 && size > 0.0 => {
+                    font_size_by_width.push((ratio, size));
+                }
+                _ => log::warn!("Ignoring a font_size_by_width entry without a ratio in (0, 1] and a size"),
+            }
+        }
+    }
+
+    // Processes whose terminals skip URL detection (full-screen TUIs)
+    let link_exclusions = config_table
+        .get::<Vec<String>>("link_exclusions")
+        .unwrap_or_default();
+
+    // Parse UI colors config if present, on top of the accessible palette when enabled
+    let ui_defaults = if config_table.get::<bool>("accessible_indicators")? {
+        UiConfig::accessible()
     } else {
         UiConfig::default()
     };
+    let ui_colors = match config_table.get::<mlua::Table>("ui_colors") {
+        Ok(ui_table) => parse_ui_colors(&ui_table, &ui_defaults),
+        Err(_) => ui_defaults,
+    };
+
+    // Light/dark variants on top of the base colors
+    let colors_light = config_table.get::<Option<mlua::Table>>("colors_light")?;
+    let colors_dark = config_table.get::<Option<mlua::Table>>("colors_dark")?;
+    let ui_colors_light = config_table.get::<Option<mlua::Table>>("ui_colors_light")?;
+    let ui_colors_dark = config_table.get::<Option<mlua::Table>>("ui_colors_dark")?;
+    let dynamic = colors_light.is_some()
+        || colors_dark.is_some()
+        || ui_colors_light.is_some()
+        || ui_colors_dark.is_some();
+    let variant = |colors_table: Option<mlua::Table>, ui_table: Option<mlua::Table>| {
+        dynamic.then(|| AppearanceColors {
+            colors: colors_table.map_or_else(|| colors.clone(), |t| parse_colors(&t).or(&colors)),
+            ui_colors: ui_table.map_or_else(|| ui_colors.clone(), |t| parse_ui_colors(&t, &ui_colors)),
+        })
+    };
+    let appearance_defaults = AppearanceConfig::default();
+    let clock = |key: &str, default: u32| -> LuaResult<u32> {
+        Ok(match config_table.get::<Option<String>>(key)? {
+            Some(text) => time::parse_clock(&text).unwrap_or_else(|| {
+                log::warn!("Invalid {} '{}' (expected HH:MM), using default", key, text);
+                default
+            }),
+            None => default,
+        })
+    };
+    let appearance = AppearanceConfig {
+        mode: match config_table.get::<Option<String>>("appearance")? {
+            Some(mode) => AppearanceMode::parse(&mode).unwrap_or_else(|| {
+                log::warn!("Unknown appearance '{}', using default", mode);
+                appearance_defaults.mode
+            }),
+            None => appearance_defaults.mode,
+        },
+        light_at: clock("light_at", appearance_defaults.light_at)?,
+        dark_at: clock("dark_at", appearance_defaults.dark_at)?,
+        light: variant(colors_light, ui_colors_light),
+        dark: variant(colors_dark, ui_colors_dark),
+    };
+
+    // new_terminal_position accepts a placement name or a numeric index
+    let new_terminal_position = match config_table.get::<mlua::Value>("new_terminal_position") {
+        Ok(mlua::Value::String(name)) => {
+            let name = name.to_str()?.to_string();
+            TerminalPlacement::parse(&name).unwrap_or_else(|| {
+                log::warn!("Unknown new_terminal_position '{}', using default", name);
+                config_defaults.new_terminal_position
+            })
+        }
+        Ok(mlua::Value::Integer(idx)) if idx >= 0 => TerminalPlacement::Index(idx as usize),
+        _ => config_defaults.new_terminal_position,
+    };
+
+    let title_disambiguation = match config_table.get::<Option<String>>("disambiguate_titles")? {
+        Some(mode) => TitleDisambiguation::parse(&mode).unwrap_or_else(|| {
+            log::warn!("Unknown disambiguate_titles '{}', using default", mode);
+            config_defaults.title_disambiguation
+        }),
+        None => config_defaults.title_disambiguation,
+    };
+
+    let on_last_terminal = match config_table.get::<Option<String>>("on_last_terminal")? {
+        Some(mode) => LastTerminalBehavior::parse(&mode).unwrap_or_else(|| {
+            log::warn!("Unknown on_last_terminal '{}', using default", mode);
+            config_defaults.on_last_terminal
+        }),
+        None => config_defaults.on_last_terminal,
+    };
+
+    let font_zoom = match config_table.get::<Option<String>>("font_zoom")? {
+        Some(scope) => FontZoom::parse(&scope).unwrap_or_else(|| {
+            log::warn!("Unknown font_zoom '{}', using default", scope);
+            config_defaults.font_zoom
+        }),
+        None => config_defaults.font_zoom,
+    };
+
+    let terminal_engine = match config_table.get::<Option<String>>("terminal_engine")? {
+        Some(name) => TerminalEngine::parse(&name).unwrap_or_else(|| {
+            log::warn!("Unknown terminal_engine '{}', using default", name);
+            config_defaults.terminal_engine.clone()
+        }),
+        None => config_defaults.terminal_engine.clone(),
+    };
+
+    let animation_defaults = AnimationConfig::default();
+    let animations = if let Ok(animations_table) = config_table.get::<mlua::Table>("animations") {
+        AnimationConfig {
+            workspace_switch: match animations_table.get::<Option<String>>("workspace_switch")? {
+                Some(name) => WorkspaceSwitchAnimation::parse(&name).unwrap_or_else(|| {
+                    log::warn!("Unknown animations.workspace_switch '{}', using default", name);
+                    animation_defaults.workspace_switch
+                }),
+                None => animation_defaults.workspace_switch,
+            },
+            workspace_switch_ms: animations_table
+                .get::<Option<u64>>("workspace_switch_ms")?
+                .unwrap_or(animation_defaults.workspace_switch_ms),
+        }
+    } else {
+        animation_defaults
+    };
+
+    let notification_defaults = NotificationsConfig::default();
+    let notifications = if let Ok(notifications_table) = config_table.get::<mlua::Table>("notifications") {
+        NotificationsConfig {
+            desktop: notifications_table
+                .get::<Option<bool>>("desktop")?
+                .unwrap_or(notification_defaults.desktop),
+            only_when_unfocused: notifications_table
+                .get::<Option<bool>>("only_when_unfocused")?
+                .unwrap_or(notification_defaults.only_when_unfocused),
+        }
+    } else {
+        notification_defaults
+    };
+
+    let scroll_defaults = ScrollConfig::default();
+    let scroll = if let Ok(scroll_table) = config_table.get::<mlua::Table>("scroll") {
+        let multiplier = scroll_table
+            .get::<Option<f32>>("multiplier")?
+            .unwrap_or(scroll_defaults.multiplier);
+        ScrollConfig {
+            multiplier: if multiplier > 0.0 {
+                multiplier
+            } else {
+                log::warn!("Ignoring scroll.multiplier {}: it must be above 0", multiplier);
+                scroll_defaults.multiplier
+            },
+            alternate_scroll: scroll_table
+                .get::<Option<bool>>("alternate_scroll")?
+                .unwrap_or(scroll_defaults.alternate_scroll),
+        }
+    } else {
+        scroll_defaults
+    };
+
+    // confirm_close accepts a policy name, or true/false for "always"/"never"
+    let confirm_close = match config_table.get::<mlua::Value>("confirm_close")? {
+        mlua::Value::Boolean(true) => CloseConfirmation::Always,
+        mlua::Value::Boolean(false) => CloseConfirmation::Never,
+        mlua::Value::String(name) => {
+            let name = name.to_str()?.to_string();
+            CloseConfirmation::parse(&name).unwrap_or_else(|| {
+                log::warn!("Unknown confirm_close '{}', using default", name);
+                config_defaults.confirm_close
+            })
+        }
+        _ => config_defaults.confirm_close,
+    };
+
+    let keystroke_overlay = match config_table.get::<Option<String>>("keystroke_overlay")? {
+        Some(mode) => KeystrokeMode::parse(&mode).unwrap_or_else(|| {
+            log::warn!("Unknown keystroke_overlay '{}', using default", mode);
+            config_defaults.keystroke_overlay
+        }),
+        None => config_defaults.keystroke_overlay,
+    };
 
     // font_family is nil (None) by default, string if set
     let font_family: Option<String> = config_table.get("font_family").ok();
 
-    let config = Config {
+    let lua_hooks = scripting::collect(lua.clone())?;
+
+    let mut config = Config {
         sidebar: SidebarConfig {
             width: config_table.get("sidebar_width")?,
             workspace_font_size: config_table.get("workspace_font_size")?,
@@ -480,14 +1422,122 @@ fn load_config_from_file(path: &PathBuf) -> LuaResult<Config> {
             description_font_size: config_table.get("status_bar_description_font_size")?,
         },
         font_family,
+        shell: config_table
+            .get::<Option<String>>("shell")?
+            .filter(|shell| !shell.trim().is_empty()),
+        startup_command: config_table
+            .get::<Option<String>>("startup_command")?
+            .filter(|command| !command.trim().is_empty()),
         terminal_font_size: config_table.get("terminal_font_size")?,
+        font_size_by_width,
+        ui_scale: config_table.get("ui_scale")?,
+        scrollback_lines: config_table.get("scrollback_lines")?,
         terminal_padding_x: config_table.get("terminal_padding_x")?,
         terminal_padding_y: config_table.get("terminal_padding_y")?,
         perf_log_interval: config_table.get("perf_log_interval")?,
+        perf_log_file: config_table
+            .get::<Option<String>>("perf_log_file")?
+            .map(|path| crate::project::expand_home(&path)),
+        perf_log_max_kb: config_table.get("perf_log_max_kb")?,
+        output_repaint_ms: config_table.get("output_repaint_ms")?,
+        focused_output_repaint_ms: config_table.get("focused_output_repaint_ms")?,
+        strip_overscan: config_table.get("strip_overscan")?,
+        min_columns: config_table.get("min_columns")?,
+        inherit: InheritConfig {
+            icon: config_table.get("inherit_icon")?,
+            description: config_table.get("inherit_description")?,
+            env: config_table.get("inherit_env")?,
+            ssh: config_table.get("inherit_ssh")?,
+        },
+        new_terminal_position,
+        max_terminals_per_workspace: config_table.get("max_terminals_per_workspace")?,
+        width_ratios,
+        title_disambiguation,
+        on_last_terminal,
+        font_zoom,
+        terminal_engine,
+        animations,
+        keystroke_overlay,
+        confirm_close,
+        project_discovery: config_table.get("project_discovery")?,
+        workspace_name_from_git: config_table.get("workspace_name_from_git")?,
+        watch_config: config_table.get("watch_config")?,
+        restore_on_start: config_table.get("restore_on_start")?,
+        lazy_spawn: config_table.get("lazy_spawn")?,
+        copy_on_select: config_table.get("copy_on_select")?,
+        auto_passthrough: config_table.get("auto_passthrough")?,
+        title_bars: config_table.get("title_bars")?,
+        window_title: config_table
+            .get::<Option<String>>("window_title")?
+            .unwrap_or_else(|| config_defaults.window_title.clone()),
+        notifications,
+        scroll,
+        ssh_timeout_secs: config_table.get("ssh_timeout_secs")?,
+        watchdog_secs: config_table.get("watchdog_secs")?,
+        empty_workspace_linger_secs: config_table.get("empty_workspace_linger_secs")?,
+        ipc_non_interactive: config_table.get("ipc_non_interactive")?,
         icons,
         colors,
+        schemes,
+        templates,
+        commands,
+        status_segments,
+        lua_hooks,
+        link_exclusions,
+        configured_ui_colors: ui_colors.clone(),
         ui_colors,
+        appearance,
     };
+    if config_table.get::<bool>("high_contrast")? {
+        config.set_high_contrast(true);
+    }
 
     Ok(config)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::contrast::{self, contrast_ratio};
+
+    fn rgb(color: Color32) -> [u8; 3] {
+        [color.r(), color.g(), color.b()]
+    }
+
+    #[test]
+    fn high_contrast_palette_meets_wcag_aaa() {
+        let ui = UiConfig::high_contrast(false);
+        let text_pairs = [
+            (ui.sidebar_text, ui.sidebar_background),
+            (ui.sidebar_text_dim, ui.sidebar_background),
+            (ui.focused_border, ui.sidebar_background),
+            (ui.sidebar_text, ui.notified_background),
+            (ui.status_bar_text, ui.status_bar_background),
+        ];
+        for (text, background) in text_pairs {
+            assert!(contrast_ratio(rgb(text), rgb(background)) >= contrast::AAA_TEXT);
+        }
+        for dot in [ui.activity_dot, ui.bell_dot] {
+            assert!(contrast_ratio(rgb(dot), rgb(ui.status_bar_background)) >= contrast::NON_TEXT);
+        }
+    }
+
+    #[test]
+    fn high_contrast_toggles_back_to_configured_colors() {
+        let mut config = Config::default();
+        config.configured_ui_colors.focused_border = Color32::RED;
+        config.set_high_contrast(true);
+        assert!(config.ui_colors.high_contrast);
+        config.set_high_contrast(false);
+        assert_eq!(config.ui_colors.focused_border, Color32::RED);
+        assert!(!config.ui_colors.high_contrast);
+    }
+
+    #[test]
+    fn spawn_placement_defaults_to_new_terminal_position() {
+        let mut config = Config::default();
+        config.new_terminal_position = TerminalPlacement::Start;
+        assert_eq!(config.placement(None), TerminalPlacement::Start);
+        assert_eq!(config.placement(Some(TerminalPlacement::Index(2))), TerminalPlacement::Index(2));
+    }
+}
@@ -0,0 +1,141 @@
+//! Bug report assembled by a running instance for `manse debug-report`: recent log
+//! records, the scrubbed `init.lua`, perf counters, instance details and layout counts,
+//! as one JSON object.
+
+use serde_json::json;
+use std::path::Path;
+
+/// Words marking a config line whose quoted values are redacted
+const SECRET_WORDS: [&str; 7] =
+    ["token", "secret", "password", "passwd", "api_key", "apikey", "credential"];
+
+/// Placeholder for redacted values
+const REDACTED: &str = "<redacted>";
+
+/// What the report is made of, gathered by the app
+pub struct ReportSources<'a> {
+    pub socket: Option<&'a Path>,
+    /// Seconds since the window opened
+    pub uptime_secs: f64,
+    /// Current perf window (`PerfWindow::to_json`)
+    pub perf: serde_json::Value,
+    /// Terminal count of each workspace, in sidebar order
+    pub terminals_per_workspace: Vec<usize>,
+    pub config_path: Option<&'a Path>,
+    /// Recent log records, oldest first
+    pub logs: Vec<String>,
+}
+
+/// The report. Workspace names, titles and the config's secrets are left out, and the
+/// home directory is shortened to `~` in the config and logs.
+pub fn assemble(sources: ReportSources) -> serde_json::Value {
+    let home = std::env::var("HOME").ok().filter(|home| !home.is_empty());
+    let home = home.as_deref();
+    let config = sources.config_path.map(|path| {
+        let text = std::fs::read_to_string(path).unwrap_or_default();
+        json!({
+            "path": shorten_home(&path.display().to_string(), home),
+            "init_lua": shorten_home(&scrub_config(&text), home),
+        })
+    });
+    let terminals: usize = sources.terminals_per_workspace.iter().sum();
+
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "generated_at": std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        "instance": {
+            "pid": std::process::id(),
+            "socket": sources.socket.map(|socket| socket.display().to_string()),
+            "os": std::env::consts::OS,
+            "arch": std::env::consts::ARCH,
+            "uptime_secs": sources.uptime_secs,
+        },
+        "counts": {
+            "workspaces": sources.terminals_per_workspace.len(),
+            "terminals": terminals,
+            "terminals_per_workspace": sources.terminals_per_workspace,
+        },
+        "perf": sources.perf,
+        "config": config,
+        "logs": sources
+            .logs
+            .iter()
+            .map(|line| shorten_home(line, home))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// `text` with the quoted values on lines mentioning a token, secret or password
+/// replaced by a placeholder.
+pub fn scrub_config(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let lower = line.to_lowercase();
+            if SECRET_WORDS.iter().any(|word| lower.contains(word)) {
+                redact_quoted(line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `line` with the contents of every '…' or "…" string replaced by the placeholder.
+fn redact_quoted(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut open_quote = None;
+    for c in line.chars() {
+        match open_quote {
+            Some(quote) if c == quote => {
+                out.push_str(REDACTED);
+                out.push(c);
+                open_quote = None;
+            }
+            Some(_) => {}
+            None => {
+                out.push(c);
+                if c == '"' || c == '\'' {
+                    open_quote = Some(c);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// `text` with the home directory written as `~`.
+fn shorten_home(text: &str, home: Option<&str>) -> String {
+    match home {
+        Some(home) => text.replace(home, "~"),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrub_redacts_secret_lines_only() {
+        let config = "config.shell = \"/bin/zsh\"\nconfig.env = { GITHUB_TOKEN = \"ghp_abc\", NAME = 'x' }";
+        assert_eq!(
+            scrub_config(config),
+            "config.shell = \"/bin/zsh\"\nconfig.env = { GITHUB_TOKEN = \"<redacted>\", NAME = '<redacted>' }"
+        );
+    }
+
+    #[test]
+    fn scrub_is_case_insensitive() {
+        assert_eq!(scrub_config("local Password = 'hunter2'"), "local Password = '<redacted>'");
+    }
+
+    #[test]
+    fn shorten_home_replaces_prefix() {
+        assert_eq!(shorten_home("/home/me/src/init.lua", Some("/home/me")), "~/src/init.lua");
+        assert_eq!(shorten_home("/etc/x", None), "/etc/x");
+    }
+}
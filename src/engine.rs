@@ -0,0 +1,89 @@
+//! Terminal engines: the `egui_term::TerminalBackendTrait` implementations terminals can
+//! run on. `TerminalPanel` and the UI only see the trait, so adding an engine means a
+//! variant here and a backend in egui_term.
+
+use egui_term::{BackendSettings, PtyEvent, StubBackend, TerminalBackend, TerminalBackendTrait};
+use eframe::egui;
+use std::io;
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+
+/// Set to run new terminals on the stub engine whatever `terminal_engine` says, for UI and
+/// IPC tests. The value names a file whose contents are played as each terminal's output;
+/// any other value (e.g. `1`) shows just a prompt.
+pub const STUB_BACKEND_ENV: &str = "MANSE_STUB_BACKEND";
+
+/// Engine new terminals run on (`terminal_engine` in the config)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum TerminalEngine {
+    /// A shell in a PTY, emulated by alacritty_terminal
+    #[default]
+    Pty,
+    /// No process: plays `script` (or a bare prompt) and echoes what is typed
+    Stub { script: Option<PathBuf> },
+}
+
+impl TerminalEngine {
+    /// Parse a config value ("pty", "stub").
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pty" => Some(Self::Pty),
+            "stub" => Some(Self::Stub { script: None }),
+            _ => None,
+        }
+    }
+
+    /// `self`, unless `MANSE_STUB_BACKEND` is set.
+    pub fn with_env_override(&self) -> Self {
+        match std::env::var_os(STUB_BACKEND_ENV) {
+            Some(script) => Self::Stub { script: Some(PathBuf::from(script)) },
+            None => self.clone(),
+        }
+    }
+
+    /// Start a backend for terminal `id`. `settings` (shell, working directory,
+    /// environment) only apply to engines that run a process. Every engine reports
+    /// title changes, bells, wakeups and exit as `(id, PtyEvent)` on `event_tx`.
+    pub fn spawn(
+        &self,
+        id: u64,
+        ctx: &egui::Context,
+        event_tx: Sender<(u64, PtyEvent)>,
+        settings: BackendSettings,
+    ) -> io::Result<Box<dyn TerminalBackendTrait>> {
+        Ok(match self {
+            Self::Pty => Box::new(TerminalBackend::new(id, ctx.clone(), event_tx, settings)?),
+            Self::Stub { script } => {
+                let script = script
+                    .as_ref()
+                    .and_then(|path| std::fs::read(path).ok())
+                    .unwrap_or_else(|| b"$ ".to_vec());
+                Box::new(StubBackend::new(id, ctx.clone(), event_tx, &script))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn parse_names() {
+        assert_eq!(TerminalEngine::parse("pty"), Some(TerminalEngine::Pty));
+        assert_eq!(TerminalEngine::parse("stub"), Some(TerminalEngine::Stub { script: None }));
+        assert_eq!(TerminalEngine::parse("wezterm"), None);
+    }
+
+    #[test]
+    fn stub_without_script_shows_prompt() {
+        let (tx, _rx) = mpsc::channel();
+        let engine = TerminalEngine::Stub { script: Some(PathBuf::from("/nonexistent/script")) };
+        let backend = engine
+            .spawn(3, &egui::Context::default(), tx, BackendSettings::default())
+            .unwrap();
+        assert_eq!(backend.id(), 3);
+        assert!(backend.capture_text(egui_term::CaptureRegion::Screen, false).starts_with("$"));
+    }
+}
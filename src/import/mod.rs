@@ -0,0 +1,4 @@
+//! Importers for other tools' configuration files (`manse import-*`).
+
+pub mod theme;
+pub mod tmuxinator;
@@ -0,0 +1,321 @@
+//! Terminal color schemes from other emulators: kitty `.conf`, WezTerm `.toml` and iTerm2
+//! `.itermcolors` files, converted to a `ColorsConfig` (`manse import-theme` and the
+//! `import_theme()` Lua helper).
+
+use crate::config::ColorsConfig;
+use std::path::Path;
+
+/// `ColorsConfig` names of the 16 ANSI colors, by index
+pub const ANSI_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright_black",
+    "bright_red",
+    "bright_green",
+    "bright_yellow",
+    "bright_blue",
+    "bright_magenta",
+    "bright_cyan",
+    "bright_white",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThemeFormat {
+    Kitty,
+    Wezterm,
+    Iterm,
+}
+
+impl ThemeFormat {
+    /// Format of the file at `path`, from its extension or else its contents.
+    pub fn detect(path: &Path, text: &str) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::Wezterm,
+            Some("itermcolors") => Self::Iterm,
+            Some("conf") => Self::Kitty,
+            _ if text.contains("<plist") => Self::Iterm,
+            _ if text.contains("[colors]") => Self::Wezterm,
+            _ => Self::Kitty,
+        }
+    }
+}
+
+/// Read and convert the scheme file at `path`.
+pub fn load(path: &Path) -> Result<ColorsConfig, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse(&text, ThemeFormat::detect(path, &text))
+}
+
+pub fn parse(text: &str, format: ThemeFormat) -> Result<ColorsConfig, String> {
+    let mut colors = ColorsConfig::default();
+    match format {
+        ThemeFormat::Kitty => parse_kitty(text, &mut colors),
+        ThemeFormat::Wezterm => parse_wezterm(text, &mut colors)?,
+        ThemeFormat::Iterm => parse_iterm(text, &mut colors),
+    }
+    if entries(&colors).is_empty() {
+        return Err("No colors found".to_string());
+    }
+    Ok(colors)
+}
+
+/// The colors an import can set, by `ColorsConfig` name (unset ones are left out).
+pub fn entries(colors: &ColorsConfig) -> Vec<(&'static str, String)> {
+    let ansi = [
+        &colors.black,
+        &colors.red,
+        &colors.green,
+        &colors.yellow,
+        &colors.blue,
+        &colors.magenta,
+        &colors.cyan,
+        &colors.white,
+        &colors.bright_black,
+        &colors.bright_red,
+        &colors.bright_green,
+        &colors.bright_yellow,
+        &colors.bright_blue,
+        &colors.bright_magenta,
+        &colors.bright_cyan,
+        &colors.bright_white,
+    ];
+    [("foreground", &colors.foreground), ("background", &colors.background)]
+        .into_iter()
+        .chain(ANSI_NAMES.into_iter().zip(ansi))
+        .filter_map(|(name, value)| Some((name, value.clone()?)))
+        .collect()
+}
+
+/// `colors` as an init.lua assignment: to `config.schemes[scheme]`, or `config.colors`.
+pub fn to_lua(colors: &ColorsConfig, scheme: Option<&str>) -> String {
+    let target = match scheme {
+        Some(name) => format!("config.schemes[{:?}]", name),
+        None => "config.colors".to_string(),
+    };
+    let mut lua = format!("{} = {{\n", target);
+    for (name, value) in entries(colors) {
+        lua.push_str(&format!("  {} = \"{}\",\n", name, value));
+    }
+    lua.push_str("}\n");
+    lua
+}
+
+fn set(colors: &mut ColorsConfig, name: &str, value: String) {
+    let slot = match name {
+        "foreground" => &mut colors.foreground,
+        "background" => &mut colors.background,
+        "black" => &mut colors.black,
+        "red" => &mut colors.red,
+        "green" => &mut colors.green,
+        "yellow" => &mut colors.yellow,
+        "blue" => &mut colors.blue,
+        "magenta" => &mut colors.magenta,
+        "cyan" => &mut colors.cyan,
+        "white" => &mut colors.white,
+        "bright_black" => &mut colors.bright_black,
+        "bright_red" => &mut colors.bright_red,
+        "bright_green" => &mut colors.bright_green,
+        "bright_yellow" => &mut colors.bright_yellow,
+        "bright_blue" => &mut colors.bright_blue,
+        "bright_magenta" => &mut colors.bright_magenta,
+        "bright_cyan" => &mut colors.bright_cyan,
+        "bright_white" => &mut colors.bright_white,
+        _ => return,
+    };
+    *slot = Some(value);
+}
+
+/// `#rrggbb` (lowercase) from `#rgb` / `#rrggbb`, or `None` for other color syntaxes.
+fn hex_color(value: &str) -> Option<String> {
+    let digits = value.trim().strip_prefix('#')?;
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digits = match digits.len() {
+        6 => digits.to_string(),
+        3 => digits.chars().flat_map(|c| [c, c]).collect(),
+        _ => return None,
+    };
+    Some(format!("#{}", digits.to_ascii_lowercase()))
+}
+
+/// kitty: `foreground #c0c0c0`, `color0 #000000` … `color15`.
+fn parse_kitty(text: &str, colors: &mut ColorsConfig) {
+    for line in text.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(key), Some(value)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        let Some(value) = hex_color(value) else {
+            continue;
+        };
+        let name = match key.strip_prefix("color").and_then(|n| n.parse::<usize>().ok()) {
+            Some(index) if index < 16 => ANSI_NAMES[index],
+            _ => key,
+        };
+        set(colors, name, value);
+    }
+}
+
+/// WezTerm: `[colors]` with `foreground`, `background`, `ansi = [8]` and `brights = [8]`.
+fn parse_wezterm(text: &str, colors: &mut ColorsConfig) -> Result<(), String> {
+    let doc: toml::Table = text.parse().map_err(|e| format!("Invalid TOML: {}", e))?;
+    let table = doc
+        .get("colors")
+        .and_then(toml::Value::as_table)
+        .ok_or("No [colors] table")?;
+
+    for key in ["foreground", "background"] {
+        if let Some(value) = table.get(key).and_then(toml::Value::as_str).and_then(hex_color) {
+            set(colors, key, value);
+        }
+    }
+    for (key, offset) in [("ansi", 0), ("brights", 8)] {
+        let Some(list) = table.get(key).and_then(toml::Value::as_array) else {
+            continue;
+        };
+        for (index, value) in list.iter().take(8).enumerate() {
+            if let Some(value) = value.as_str().and_then(hex_color) {
+                set(colors, ANSI_NAMES[offset + index], value);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// iTerm2 plist: `<key>Ansi 0 Color</key><dict>` with `Red/Green/Blue Component` reals.
+fn parse_iterm(text: &str, colors: &mut ColorsConfig) {
+    let mut rest = text;
+    while let Some(start) = rest.find("<key>") {
+        rest = &rest[start + "<key>".len()..];
+        let Some(end) = rest.find("</key>") else {
+            break;
+        };
+        let key = rest[..end].trim();
+        let name = match key {
+            "Foreground Color" => "foreground",
+            "Background Color" => "background",
+            _ => match key
+                .strip_prefix("Ansi ")
+                .and_then(|k| k.strip_suffix(" Color"))
+                .and_then(|n| n.parse::<usize>().ok())
+            {
+                Some(index) if index < 16 => ANSI_NAMES[index],
+                _ => continue,
+            },
+        };
+        let dict = rest.find("</dict>").map_or(rest, |close| &rest[..close]);
+        let component = |channel: &str| -> Option<u8> {
+            let after = &dict[dict.find(&format!("<key>{} Component</key>", channel))?..];
+            let value = &after[after.find("<real>")? + "<real>".len()..];
+            let value: f64 = value[..value.find("</real>")?].trim().parse().ok()?;
+            Some((value.clamp(0.0, 1.0) * 255.0).round() as u8)
+        };
+        if let (Some(r), Some(g), Some(b)) = (component("Red"), component("Green"), component("Blue")) {
+            set(colors, name, format!("#{:02x}{:02x}{:02x}", r, g, b));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kitty_conf() {
+        let colors = parse(
+            "# Theme\nforeground   #C5C8C6\nbackground #1d1f21\ncursor #fff\ncolor0 #000\ncolor9 #cc6666\ncolor16 #123456\nselection_foreground none\n",
+            ThemeFormat::Kitty,
+        )
+        .unwrap();
+        assert_eq!(colors.foreground.as_deref(), Some("#c5c8c6"));
+        assert_eq!(colors.black.as_deref(), Some("#000000"));
+        assert_eq!(colors.bright_red.as_deref(), Some("#cc6666"));
+        assert_eq!(entries(&colors).len(), 4);
+    }
+
+    #[test]
+    fn wezterm_toml() {
+        let colors = parse(
+            r##"
+[colors]
+foreground = "#dcd7ba"
+background = "#1f1f28"
+ansi = ["#090618", "#c34043", "#76946a", "#c0a36e", "#7e9cd8", "#957fb8", "#6a9589", "#c8c093"]
+brights = ["#727169", "#e82424", "#98bb6c", "#e6c384", "#7fb4ca", "#938aa9", "#7aa89f", "#dcd7ba"]
+
+[metadata]
+name = "Kanagawa"
+"##,
+            ThemeFormat::Wezterm,
+        )
+        .unwrap();
+        assert_eq!(colors.background.as_deref(), Some("#1f1f28"));
+        assert_eq!(colors.red.as_deref(), Some("#c34043"));
+        assert_eq!(colors.bright_white.as_deref(), Some("#dcd7ba"));
+        assert_eq!(entries(&colors).len(), 18);
+    }
+
+    #[test]
+    fn iterm_plist() {
+        let colors = parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+	<key>Ansi 1 Color</key>
+	<dict>
+		<key>Alpha Component</key>
+		<real>1</real>
+		<key>Blue Component</key>
+		<real>0.0</real>
+		<key>Color Space</key>
+		<string>sRGB</string>
+		<key>Green Component</key>
+		<real>0.5</real>
+		<key>Red Component</key>
+		<real>1</real>
+	</dict>
+	<key>Cursor Color</key>
+	<dict>
+		<key>Blue Component</key>
+		<real>1</real>
+		<key>Green Component</key>
+		<real>1</real>
+		<key>Red Component</key>
+		<real>1</real>
+	</dict>
+</dict>
+</plist>"#,
+            ThemeFormat::Iterm,
+        )
+        .unwrap();
+        assert_eq!(colors.red.as_deref(), Some("#ff8000"));
+        assert_eq!(entries(&colors).len(), 1);
+    }
+
+    #[test]
+    fn detects_format() {
+        assert_eq!(ThemeFormat::detect(Path::new("a.toml"), ""), ThemeFormat::Wezterm);
+        assert_eq!(ThemeFormat::detect(Path::new("a"), "<plist>"), ThemeFormat::Iterm);
+        assert_eq!(ThemeFormat::detect(Path::new("a"), "color0 #000"), ThemeFormat::Kitty);
+    }
+
+    #[test]
+    fn lua_snippet() {
+        let colors = ColorsConfig {
+            foreground: Some("#ffffff".to_string()),
+            red: Some("#ff0000".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(to_lua(&colors, None), "config.colors = {\n  foreground = \"#ffffff\",\n  red = \"#ff0000\",\n}\n");
+        assert!(to_lua(&colors, Some("night")).starts_with("config.schemes[\"night\"] = {"));
+        assert!(parse("nothing here", ThemeFormat::Kitty).is_err());
+    }
+}
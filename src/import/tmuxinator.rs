@@ -0,0 +1,271 @@
+//! tmuxinator / teamocil project files: each tmux window becomes a workspace and each
+//! pane a terminal (`manse import-tmuxinator`).
+
+use crate::project::expand_home;
+use serde_yaml::{Mapping, Value};
+use std::path::{Path, PathBuf};
+
+/// A window of the project
+#[derive(Debug, PartialEq)]
+pub struct ImportedWorkspace {
+    /// `<project>:<window>`, or just the window name for unnamed projects
+    pub name: String,
+    pub terminals: Vec<ImportedTerminal>,
+}
+
+/// A pane of a window
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportedTerminal {
+    pub cwd: Option<PathBuf>,
+    /// Lines typed into the new shell (`pre_window` first)
+    pub command: Option<String>,
+    /// Pane name, for tmuxinator's named panes
+    pub title: Option<String>,
+}
+
+/// Convert a tmuxinator or teamocil project file.
+pub fn parse(yaml: &str) -> Result<Vec<ImportedWorkspace>, String> {
+    let doc: Value = serde_yaml::from_str(yaml).map_err(|e| format!("Invalid YAML: {}", e))?;
+    // teamocil 0.x nests everything under `session`
+    let doc = doc.get("session").unwrap_or(&doc);
+    if !doc.is_mapping() {
+        return Err("Expected a mapping at the top level".to_string());
+    }
+
+    let project = string(doc.get("name").or_else(|| doc.get("project_name")));
+    let root = string(doc.get("root").or_else(|| doc.get("project_root"))).map(|r| expand_home(&r));
+    let pre_window = commands(doc.get("pre_window").or_else(|| doc.get("pre_tab")));
+    let windows = doc
+        .get("windows")
+        .or_else(|| doc.get("tabs"))
+        .and_then(Value::as_sequence)
+        .ok_or("No windows in project file")?;
+
+    windows
+        .iter()
+        .enumerate()
+        .map(|(index, window)| {
+            let window = Window::parse(window, index)?;
+            let cwd = match (&root, window.root) {
+                (Some(root), Some(dir)) => Some(root.join(dir)),
+                (root, dir) => dir.or_else(|| root.clone()),
+            };
+            let mut panes = window.panes;
+            if panes.is_empty() {
+                panes.push(Pane::default());
+            }
+            let terminals = panes
+                .into_iter()
+                .map(|pane| {
+                    let lines: Vec<String> =
+                        pre_window.iter().chain(&window.pre).chain(&pane.commands).cloned().collect();
+                    ImportedTerminal {
+                        cwd: cwd.clone(),
+                        command: (!lines.is_empty()).then(|| lines.join("\n")),
+                        title: pane.title,
+                    }
+                })
+                .collect();
+            let name = match &project {
+                Some(project) => format!("{}:{}", project, window.name),
+                None => window.name,
+            };
+            Ok(ImportedWorkspace { name, terminals })
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct Window {
+    name: String,
+    /// Relative to the project root, if any
+    root: Option<PathBuf>,
+    pre: Vec<String>,
+    panes: Vec<Pane>,
+}
+
+impl Window {
+    fn parse(value: &Value, index: usize) -> Result<Self, String> {
+        let map = value
+            .as_mapping()
+            .ok_or_else(|| format!("Window {} is not a mapping", index + 1))?;
+
+        // teamocil: `- name: editor` with `root` and `panes` alongside
+        if map.contains_key("name") {
+            return Ok(Self::from_options(string(map.get("name")).unwrap_or_default(), map));
+        }
+
+        // tmuxinator: `- editor: <command(s) | options>`
+        let (name, body) = map
+            .iter()
+            .next()
+            .ok_or_else(|| format!("Window {} is empty", index + 1))?;
+        let name = string(Some(name)).unwrap_or_else(|| (index + 1).to_string());
+        Ok(match body {
+            Value::Mapping(options) => Self::from_options(name, options),
+            commands_value => Self {
+                name,
+                panes: vec![Pane {
+                    commands: commands(Some(commands_value)),
+                    title: None,
+                }],
+                ..Default::default()
+            },
+        })
+    }
+
+    fn from_options(name: String, options: &Mapping) -> Self {
+        let panes = options
+            .get("panes")
+            .or_else(|| options.get("splits"))
+            .and_then(Value::as_sequence)
+            .map(|panes| panes.iter().map(Pane::parse).collect())
+            .unwrap_or_default();
+        Self {
+            name,
+            root: string(options.get("root")).map(|r| expand_home(&r)),
+            pre: commands(options.get("pre")),
+            panes,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Pane {
+    commands: Vec<String>,
+    title: Option<String>,
+}
+
+impl Pane {
+    fn parse(value: &Value) -> Self {
+        let Value::Mapping(map) = value else {
+            return Self {
+                commands: commands(Some(value)),
+                title: None,
+            };
+        };
+        // teamocil: `commands: [...]` (or `cmd` in 0.x) next to `focus`
+        if let Some(list) = map.get("commands").or_else(|| map.get("cmd")) {
+            return Self {
+                commands: commands(Some(list)),
+                title: None,
+            };
+        }
+        // tmuxinator named pane: `- logs: tail -f log/dev.log`
+        match map.iter().next() {
+            Some((name, list)) => Self {
+                commands: commands(Some(list)),
+                title: string(Some(name)),
+            },
+            None => Self::default(),
+        }
+    }
+}
+
+/// A scalar as text (window names may be numbers).
+fn string(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// A command or list of commands; blank entries are dropped.
+fn commands(value: Option<&Value>) -> Vec<String> {
+    let commands: Vec<String> = match value {
+        Some(Value::Sequence(items)) => items.iter().filter_map(|item| string(Some(item))).collect(),
+        value => string(value).into_iter().collect(),
+    };
+    commands.into_iter().filter(|command| !command.trim().is_empty()).collect()
+}
+
+/// Read and convert the project file at `path`.
+pub fn load(path: &Path) -> Result<Vec<ImportedWorkspace>, String> {
+    let yaml = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse(&yaml)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tmuxinator_project() {
+        let workspaces = parse(
+            r#"
+name: blog
+root: /srv/blog
+pre_window: nvm use
+windows:
+  - editor:
+      layout: main-vertical
+      panes:
+        - vim
+        -
+        - logs:
+            - cd log
+            - tail -f development.log
+  - server: bundle exec rails s
+  - 2:
+  - assets:
+      root: app/assets
+"#,
+        )
+        .unwrap();
+
+        let names: Vec<_> = workspaces.iter().map(|ws| ws.name.as_str()).collect();
+        assert_eq!(names, ["blog:editor", "blog:server", "blog:2", "blog:assets"]);
+
+        let editor = &workspaces[0].terminals;
+        assert_eq!(editor.len(), 3);
+        assert_eq!(editor[0].command.as_deref(), Some("nvm use\nvim"));
+        assert_eq!(editor[1].command.as_deref(), Some("nvm use"));
+        assert_eq!(editor[2].title.as_deref(), Some("logs"));
+        assert_eq!(editor[2].command.as_deref(), Some("nvm use\ncd log\ntail -f development.log"));
+        assert_eq!(editor[0].cwd, Some(PathBuf::from("/srv/blog")));
+
+        assert_eq!(workspaces[1].terminals[0].command.as_deref(), Some("nvm use\nbundle exec rails s"));
+        assert_eq!(workspaces[2].terminals.len(), 1);
+        assert_eq!(workspaces[3].terminals[0].cwd, Some(PathBuf::from("/srv/blog/app/assets")));
+    }
+
+    #[test]
+    fn teamocil_project() {
+        let workspaces = parse(
+            r#"
+windows:
+  - name: api
+    root: /srv/api
+    panes:
+      - git status
+      - commands: [make build, make run]
+        focus: true
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(workspaces.len(), 1);
+        assert_eq!(workspaces[0].name, "api");
+        let panes = &workspaces[0].terminals;
+        assert_eq!(panes[0].command.as_deref(), Some("git status"));
+        assert_eq!(panes[1].command.as_deref(), Some("make build\nmake run"));
+        assert_eq!(panes[1].cwd, Some(PathBuf::from("/srv/api")));
+    }
+
+    #[test]
+    fn teamocil_session_wrapper() {
+        let workspaces = parse("session:\n  name: s\n  windows:\n    - name: w\n").unwrap();
+        assert_eq!(workspaces[0].name, "s:w");
+        assert_eq!(workspaces[0].terminals, vec![ImportedTerminal::default()]);
+    }
+
+    #[test]
+    fn rejects_files_without_windows() {
+        assert!(parse("name: x\n").is_err());
+        assert!(parse("- a\n- b\n").is_err());
+        assert!(parse("windows: [42]\n").is_err());
+    }
+}
@@ -0,0 +1,71 @@
+//! Logger that also keeps the most recent records in memory, so `manse debug-report`
+//! can include them even when RUST_LOG wasn't set.
+
+use log::{Level, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Records kept for debug reports
+const CAPACITY: usize = 500;
+
+/// Records at this level and above are kept whatever RUST_LOG says
+const BUFFER_LEVEL: Level = Level::Info;
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+struct BufferedLogger {
+    /// Output to stderr, filtered by RUST_LOG
+    inner: env_logger::Logger,
+    start: Instant,
+}
+
+impl Log for BufferedLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= BUFFER_LEVEL || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.inner.matches(record) {
+            self.inner.log(record);
+        }
+        if record.level() > BUFFER_LEVEL {
+            return;
+        }
+        let line = format!(
+            "{:>9.3}s {:<5} {}: {}",
+            self.start.elapsed().as_secs_f64(),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        let mut recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+        if recent.len() == CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(line);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Install the logger: env_logger output as configured by RUST_LOG, plus the buffer.
+pub fn init() {
+    let inner = env_logger::Builder::from_default_env().build();
+    let max_level = inner.filter().max(BUFFER_LEVEL.to_level_filter());
+    let logger = BufferedLogger {
+        inner,
+        start: Instant::now(),
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// The most recent log records (info and above), oldest first.
+pub fn recent() -> Vec<String> {
+    let recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+    recent.iter().cloned().collect()
+}
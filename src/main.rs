@@ -1,20 +1,39 @@
 mod app;
 mod config;
+mod debug_report;
+mod engine;
 mod fonts;
-mod ipc_protocol;
+mod import;
+mod log_buffer;
+mod notifications;
+mod palette_usage;
 mod persist;
+mod project;
+#[cfg(unix)]
+mod rescue;
+mod scripting;
+mod session;
+mod status_segments;
 mod terminal;
 mod ui;
 mod util;
+mod watchdog;
 mod workspace;
 
 use clap::{Parser, Subcommand};
+use manse_ctl::instances;
 use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "manse")]
 #[command(about = "A scrolling window manager for terminals")]
 struct Cli {
+    /// Name of the instance: `run` registers itself under it (with a socket of its own
+    /// unless --socket is given), other commands are sent to it instead of --socket
+    /// (term-to-instance moves the terminal to it). Unnamed instances are registered
+    /// under their process ID
+    #[arg(long, global = true)]
+    instance: Option<String>,
     #[command(subcommand)]
     command: Commands,
 }
@@ -23,9 +42,11 @@ struct Cli {
 enum Commands {
     /// Run the terminal window manager
     Run {
-        /// Path to IPC socket
-        #[arg(short, long, default_value = "/tmp/manse.sock")]
-        socket: PathBuf,
+        /// Path to IPC socket (defaults to /tmp/manse.sock, or one named after --instance
+        /// in $XDG_RUNTIME_DIR/manse; manse-<pid>.sock there when another instance has
+        /// /tmp/manse.sock)
+        #[arg(short, long)]
+        socket: Option<PathBuf>,
     },
     /// Resume from persisted state (internal, called after exec)
     Resume {
@@ -35,80 +56,62 @@ enum Commands {
         /// Path to IPC socket
         #[arg(short, long, default_value = "/tmp/manse.sock")]
         socket: PathBuf,
+        /// When a crash rescue started this process (Unix seconds)
+        #[arg(long, hide = true)]
+        rescued_at: Option<u64>,
     },
-    /// Trigger restart of running instance
-    Restart {
-        /// Path to IPC socket
-        #[arg(short, long, default_value = "/tmp/manse.sock")]
-        socket: PathBuf,
-    },
-    /// Ping a running instance
-    Ping {
-        /// Path to IPC socket
-        #[arg(short, long, default_value = "/tmp/manse.sock")]
-        socket: PathBuf,
-    },
-    /// Rename a terminal
-    TermRename {
-        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
-        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
-        socket: PathBuf,
-        /// Terminal ID (defaults to $MANSE_TERMINAL)
-        #[arg(short, long, env = "MANSE_TERMINAL")]
-        terminal: String,
-        /// New title for the terminal
-        title: String,
-    },
-    /// Set terminal description
-    TermDesc {
-        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
-        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
-        socket: PathBuf,
-        /// Terminal ID (defaults to $MANSE_TERMINAL)
-        #[arg(short, long, env = "MANSE_TERMINAL")]
-        terminal: String,
-        /// Description for the terminal
-        description: String,
+    #[command(flatten)]
+    Client(manse_ctl::Command),
+    /// Initialize a .manse.json project file in the current directory
+    Init {
+        /// Project name (defaults to current directory name)
+        name: Option<String>,
     },
-    /// Set terminal icon (Nerd Font codepoint)
-    TermIcon {
+    /// Create workspaces and terminals from a tmuxinator or teamocil project file
+    /// (one workspace per window, one terminal per pane)
+    ImportTmuxinator {
         /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
         #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
         socket: PathBuf,
-        /// Terminal ID (defaults to $MANSE_TERMINAL)
-        #[arg(short, long, env = "MANSE_TERMINAL")]
-        terminal: String,
-        /// Icon for the terminal (Nerd Font codepoint, empty string to clear)
-        icon: String,
+        /// Project YAML file
+        file: PathBuf,
     },
-    /// Move a terminal to a workspace (creates workspace if needed)
-    TermToWorkspace {
+    /// Convert a kitty (.conf), WezTerm (.toml) or iTerm2 (.itermcolors) color scheme into
+    /// init.lua colors, printed as a Lua snippet
+    ImportTheme {
         /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
         #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
         socket: PathBuf,
-        /// Terminal ID (defaults to $MANSE_TERMINAL)
-        #[arg(short, long, env = "MANSE_TERMINAL")]
-        terminal: String,
-        /// Name of the workspace to move to
-        #[arg(short, long)]
-        workspace_name: String,
+        /// Color scheme file
+        file: PathBuf,
+        /// Add it to config.schemes under this name instead of setting config.colors
+        #[arg(long)]
+        scheme: Option<String>,
+        /// Append the snippet to init.lua and reload the config of the running instance
+        #[arg(long)]
+        apply: bool,
     },
-    /// Notify a terminal (shows indicator until focused)
-    TermNotify {
-        /// Path to IPC socket (defaults to $MANSE_SOCKET or /tmp/manse.sock)
-        #[arg(short, long, env = "MANSE_SOCKET", default_value = "/tmp/manse.sock")]
-        socket: PathBuf,
-        /// Terminal ID (defaults to $MANSE_TERMINAL)
-        #[arg(short, long, env = "MANSE_TERMINAL")]
-        terminal: String,
+    /// Save or load named layouts (stored in ~/.config/manse/sessions/)
+    Session {
+        #[command(subcommand)]
+        command: SessionCommand,
     },
-    /// Initialize a .manse.json project file in the current directory
-    Init {
-        /// Project name (defaults to current directory name)
-        name: Option<String>,
+    /// List running instances with their sockets and workspaces
+    Instances {
+        /// Print the raw JSON list
+        #[arg(long)]
+        json: bool,
     },
 }
 
+#[derive(Subcommand)]
+enum SessionCommand {
+    #[command(flatten)]
+    Client(manse_ctl::SessionCommand),
+    /// List saved profiles
+    List,
+}
+
 /// Run a fresh instance (no restore).
 fn run_fresh(socket: PathBuf, config: config::Config) -> eframe::Result<()> {
     let options = eframe::NativeOptions {
@@ -127,13 +130,42 @@ fn run_fresh(socket: PathBuf, config: config::Config) -> eframe::Result<()> {
 }
 
 fn main() -> eframe::Result<()> {
-    env_logger::init();
+    log_buffer::init();
 
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+
+    // Client commands go to the named instance's socket
+    if let Some(name) = &cli.instance {
+        match &mut cli.command {
+            Commands::Client(command) => command.set_instance(instances::resolve_or_exit(name)),
+            Commands::Session { command: SessionCommand::Client(command) } => {
+                *command.socket_mut() = instances::resolve_or_exit(name);
+            }
+            Commands::ImportTmuxinator { socket, .. } | Commands::ImportTheme { socket, .. } => {
+                *socket = instances::resolve_or_exit(name);
+            }
+            _ => {}
+        }
+    }
 
     match cli.command {
         Commands::Run { socket } => {
+            #[cfg(unix)]
+            rescue::install_panic_hook(None);
             let config = config::load_config();
+            let socket = match (socket, &cli.instance) {
+                (Some(socket), _) => socket,
+                (None, Some(name)) => instances::default_socket(name),
+                // Another unnamed instance is running: take a socket of our own
+                (None, None) if instances::is_running(instances::DEFAULT_SOCKET.as_ref()) => {
+                    instances::auto_socket()
+                }
+                (None, None) => PathBuf::from(instances::DEFAULT_SOCKET),
+            };
+            let name = cli.instance.clone().unwrap_or_else(|| std::process::id().to_string());
+            if let Err(e) = instances::register(&name, &socket) {
+                log::warn!("Failed to register instance {}: {}", name, e);
+            }
 
             let options = eframe::NativeOptions {
                 viewport: egui::ViewportBuilder::default()
@@ -143,13 +175,22 @@ fn main() -> eframe::Result<()> {
                 ..Default::default()
             };
 
-            eframe::run_native(
+            let app_socket = socket.clone();
+            let result = eframe::run_native(
                 "manse",
                 options,
-                Box::new(move |cc| Ok(Box::new(app::App::new(cc, Some(socket), config)))),
-            )
+                Box::new(move |cc| Ok(Box::new(app::App::new(cc, Some(app_socket), config)))),
+            );
+            instances::unregister(&socket);
+            result
         }
-        Commands::Resume { state_file, socket } => {
+        Commands::Resume {
+            state_file,
+            socket,
+            rescued_at,
+        } => {
+            #[cfg(unix)]
+            rescue::install_panic_hook(rescued_at);
             let config = config::load_config();
 
             // Load persisted state
@@ -186,197 +227,164 @@ fn main() -> eframe::Result<()> {
             // Clean up state file after loading
             let _ = std::fs::remove_file(&state_file);
 
-            eframe::run_native(
+            // A named instance keeps its registry entry across the restart
+            let app_socket = socket.clone();
+            let result = eframe::run_native(
                 "manse",
                 options,
                 Box::new(move |cc| {
-                    match app::App::from_persisted(cc, state, socket.clone(), config.clone()) {
+                    match app::App::from_persisted(cc, state, app_socket.clone(), config.clone()) {
                         Ok(app) => Ok(Box::new(app)),
                         Err(e) => {
                             log::warn!("Failed to restore from persisted state: {}. Starting fresh.", e);
-                            Ok(Box::new(app::App::new(cc, Some(socket), config)))
+                            Ok(Box::new(app::App::new(cc, Some(app_socket), config)))
                         }
                     }
                 }),
-            )
+            );
+            instances::unregister(&socket);
+            result
         }
-        Commands::Restart { socket } => {
-            let mut client = ipc_protocol::IpcClient::connect(&socket)
-                .map_err(|e| eprintln!("Failed to connect: {}", e))
-                .unwrap();
-
-            let response = client
-                .request(&ipc_protocol::Request::Restart)
-                .map_err(|e| eprintln!("Request failed: {}", e))
-                .unwrap();
-
-            if response.ok {
-                println!("Restart initiated");
-            } else {
-                eprintln!(
-                    "Failed to restart: {}",
-                    response.error.unwrap_or_else(|| "Unknown error".into())
-                );
-            }
+        Commands::Client(command) => {
+            manse_ctl::run(command);
             Ok(())
         }
-        Commands::Ping { socket } => {
-            let mut client = ipc_protocol::IpcClient::connect(&socket)
-                .map_err(|e| eprintln!("Failed to connect: {}", e))
-                .unwrap();
-
-            match client.ping() {
-                Ok(()) => {
-                    println!("Pong!");
-                    Ok(())
-                }
-                Err(e) => {
-                    eprintln!("Ping failed: {}", e);
-                    Ok(())
-                }
-            }
+        Commands::Instances { json } => {
+            manse_ctl::run_instances(json);
+            Ok(())
         }
-        Commands::TermRename {
-            socket,
-            terminal,
-            title,
-        } => {
-            let mut client = ipc_protocol::IpcClient::connect(&socket)
-                .map_err(|e| eprintln!("Failed to connect: {}", e))
-                .unwrap();
+        Commands::Init { name } => {
+            let project_name = name.unwrap_or_else(|| {
+                std::env::current_dir()
+                    .ok()
+                    .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+                    .unwrap_or_else(|| "project".to_string())
+            });
 
-            let response = client
-                .request(&ipc_protocol::Request::TermRename { terminal, title })
-                .map_err(|e| eprintln!("Request failed: {}", e))
-                .unwrap();
+            let config = serde_json::json!({
+                "workspaceName": project_name
+            });
 
-            if response.ok {
-                println!("Terminal renamed");
-            } else {
-                eprintln!(
-                    "Failed to rename: {}",
-                    response.error.unwrap_or_else(|| "Unknown error".into())
-                );
+            let path = PathBuf::from(".manse.json");
+            if path.exists() {
+                eprintln!(".manse.json already exists");
+                return Ok(());
             }
-            Ok(())
-        }
-        Commands::TermDesc {
-            socket,
-            terminal,
-            description,
-        } => {
-            let mut client = ipc_protocol::IpcClient::connect(&socket)
-                .map_err(|e| eprintln!("Failed to connect: {}", e))
-                .unwrap();
-
-            let response = client
-                .request(&ipc_protocol::Request::TermDesc { terminal, description })
-                .map_err(|e| eprintln!("Request failed: {}", e))
-                .unwrap();
 
-            if response.ok {
-                println!("Terminal description set");
-            } else {
-                eprintln!(
-                    "Failed to set description: {}",
-                    response.error.unwrap_or_else(|| "Unknown error".into())
-                );
+            match std::fs::write(&path, serde_json::to_string_pretty(&config).unwrap() + "\n") {
+                Ok(()) => println!("Created .manse.json with name: {}", project_name),
+                Err(e) => eprintln!("Failed to create .manse.json: {}", e),
             }
             Ok(())
         }
-        Commands::TermIcon {
-            socket,
-            terminal,
-            icon,
-        } => {
+        Commands::ImportTmuxinator { socket, file } => {
+            let workspaces = match import::tmuxinator::load(&file) {
+                Ok(workspaces) => workspaces,
+                Err(e) => {
+                    eprintln!("Failed to import: {}", e);
+                    return Ok(());
+                }
+            };
+
             let mut client = ipc_protocol::IpcClient::connect(&socket)
                 .map_err(|e| eprintln!("Failed to connect: {}", e))
                 .unwrap();
 
-            let response = client
-                .request(&ipc_protocol::Request::TermIcon { terminal, icon })
-                .map_err(|e| eprintln!("Request failed: {}", e))
-                .unwrap();
-
-            if response.ok {
-                println!("Terminal icon set");
-            } else {
-                eprintln!(
-                    "Failed to set icon: {}",
-                    response.error.unwrap_or_else(|| "Unknown error".into())
-                );
+            let mut spawned = 0;
+            for (ws_index, workspace) in workspaces.iter().enumerate() {
+                for (index, terminal) in workspace.terminals.iter().enumerate() {
+                    let cwd = terminal.cwd.clone().filter(|dir| {
+                        let exists = dir.is_dir();
+                        if !exists {
+                            eprintln!("Not a directory, using the default: {}", dir.display());
+                        }
+                        exists
+                    });
+                    let request = ipc_protocol::Request::TermSpawn {
+                        command: terminal.command.clone(),
+                        cwd,
+                        workspace: Some(workspace.name.clone()),
+                        title: terminal.title.clone(),
+                        // Land on the first window
+                        focus: ws_index == 0 && index == 0,
+                        placement: Some(ipc_protocol::TerminalPlacement::End),
+                    };
+                    let response = client
+                        .request(&request)
+                        .map_err(|e| eprintln!("Request failed: {}", e))
+                        .unwrap();
+                    if response.ok {
+                        spawned += 1;
+                    } else {
+                        eprintln!(
+                            "Failed to spawn a terminal in '{}': {}",
+                            workspace.name,
+                            response.error.unwrap_or_else(|| "Unknown error".into())
+                        );
+                    }
+                }
             }
+            println!("Imported {} terminals into {} workspaces", spawned, workspaces.len());
             Ok(())
         }
-        Commands::TermToWorkspace {
+        Commands::ImportTheme {
             socket,
-            terminal,
-            workspace_name,
+            file,
+            scheme,
+            apply,
         } => {
-            let mut client = ipc_protocol::IpcClient::connect(&socket)
-                .map_err(|e| eprintln!("Failed to connect: {}", e))
-                .unwrap();
+            let colors = match import::theme::load(&file) {
+                Ok(colors) => colors,
+                Err(e) => {
+                    eprintln!("Failed to import {}: {}", file.display(), e);
+                    return Ok(());
+                }
+            };
+            let snippet = import::theme::to_lua(&colors, scheme.as_deref());
+            if !apply {
+                print!("{}", snippet);
+                return Ok(());
+            }
 
-            let response = client
-                .request(&ipc_protocol::Request::TermToWorkspace {
-                    terminal,
-                    workspace_name: workspace_name.clone(),
-                })
-                .map_err(|e| eprintln!("Request failed: {}", e))
-                .unwrap();
+            let Some(config_path) = config::config_path() else {
+                eprintln!("Could not find the directory of init.lua");
+                return Ok(());
+            };
+            let appended = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&config_path)
+                .and_then(|mut file| {
+                    use std::io::Write;
+                    write!(file, "\n-- Imported by `manse import-theme`\n{}", snippet)
+                });
+            if let Err(e) = appended {
+                eprintln!("Failed to write {}: {}", config_path.display(), e);
+                return Ok(());
+            }
+            println!("Added to {}", config_path.display());
 
-            if response.ok {
-                println!("Terminal moved to workspace '{}'", workspace_name);
-            } else {
-                eprintln!(
-                    "Failed to move terminal: {}",
-                    response.error.unwrap_or_else(|| "Unknown error".into())
-                );
+            // Without a running instance the colors apply on the next start
+            if let Ok(mut client) = ipc_protocol::IpcClient::connect(&socket) {
+                match client.request(&ipc_protocol::Request::ConfigReload) {
+                    Ok(response) if response.ok => println!("Config reloaded"),
+                    Ok(response) => eprintln!(
+                        "Failed to reload config: {}",
+                        response.error.unwrap_or_else(|| "Unknown error".into())
+                    ),
+                    Err(e) => eprintln!("Request failed: {}", e),
+                }
             }
             Ok(())
         }
-        Commands::TermNotify { socket, terminal } => {
-            let mut client = ipc_protocol::IpcClient::connect(&socket)
-                .map_err(|e| eprintln!("Failed to connect: {}", e))
-                .unwrap();
-
-            let response = client
-                .request(&ipc_protocol::Request::TermNotify { terminal })
-                .map_err(|e| eprintln!("Request failed: {}", e))
-                .unwrap();
-
-            if response.ok {
-                println!("Terminal notified");
-            } else {
-                eprintln!(
-                    "Failed to notify: {}",
-                    response.error.unwrap_or_else(|| "Unknown error".into())
-                );
+        Commands::Session { command: SessionCommand::List } => {
+            for name in session::list() {
+                println!("{}", name);
             }
             Ok(())
         }
-        Commands::Init { name } => {
-            let project_name = name.unwrap_or_else(|| {
-                std::env::current_dir()
-                    .ok()
-                    .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
-                    .unwrap_or_else(|| "project".to_string())
-            });
-
-            let config = serde_json::json!({
-                "workspaceName": project_name
-            });
-
-            let path = PathBuf::from(".manse.json");
-            if path.exists() {
-                eprintln!(".manse.json already exists");
-                return Ok(());
-            }
-
-            match std::fs::write(&path, serde_json::to_string_pretty(&config).unwrap() + "\n") {
-                Ok(()) => println!("Created .manse.json with name: {}", project_name),
-                Err(e) => eprintln!("Failed to create .manse.json: {}", e),
-            }
+        Commands::Session { command: SessionCommand::Client(command) } => {
+            manse_ctl::run_session(command);
             Ok(())
         }
     }
@@ -0,0 +1,94 @@
+//! Notifications from terminals (`term-notify`, OSC 9 / 777, the bell): the history
+//! kept for the notification history panel, and native desktop notifications.
+
+use notify_rust::Notification;
+use std::collections::VecDeque;
+use std::time::SystemTime;
+
+/// Maximum number of notification events kept in the history
+pub const MAX_NOTIFICATIONS: usize = 200;
+
+/// What produced a notification event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotificationKind {
+    /// `term-notify` / `workspace_notify_all` over IPC (e.g. a command-completion hook),
+    /// or an OSC 9 / OSC 777 sequence printed by a program
+    Notify,
+    /// Terminal bell (BEL)
+    Bell,
+}
+
+impl NotificationKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            NotificationKind::Notify => "notify",
+            NotificationKind::Bell => "bell",
+        }
+    }
+}
+
+/// A single entry in the notification history
+#[derive(Debug, Clone)]
+pub struct NotificationEvent {
+    /// Internal ID of the terminal that produced the event
+    pub panel_id: u64,
+    pub kind: NotificationKind,
+    /// Optional text supplied by the sender
+    pub message: Option<String>,
+    pub timestamp: SystemTime,
+}
+
+/// Bounded log of notification events, kept after the sidebar badge clears
+#[derive(Default)]
+pub struct NotificationLog {
+    events: VecDeque<NotificationEvent>,
+    /// Events ever pushed, including dropped and cleared ones
+    pushed: u64,
+}
+
+impl NotificationLog {
+    /// Record an event, dropping the oldest once the log is full.
+    pub fn push(&mut self, panel_id: u64, kind: NotificationKind, message: Option<String>) {
+        if self.events.len() >= MAX_NOTIFICATIONS {
+            self.events.pop_front();
+        }
+        self.events.push_back(NotificationEvent {
+            panel_id,
+            kind,
+            message,
+            timestamp: SystemTime::now(),
+        });
+        self.pushed += 1;
+    }
+
+    /// Number of events ever pushed (not reduced by dropping or clearing), to tell
+    /// which events are new since an earlier count.
+    pub fn pushed(&self) -> u64 {
+        self.pushed
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Events from newest to oldest.
+    pub fn newest_first(&self) -> impl Iterator<Item = &NotificationEvent> {
+        self.events.iter().rev()
+    }
+}
+
+/// Show a native desktop notification. Runs in the background, since delivery can
+/// block on the notification daemon; failures are only logged.
+pub fn show_desktop(title: &str, body: &str) {
+    let mut notification = Notification::new();
+    notification.appname("manse").summary(title).body(body);
+    std::thread::spawn(move || {
+        if let Err(e) = notification.show() {
+            log::warn!("Failed to show desktop notification: {}", e);
+        }
+    });
+}
@@ -0,0 +1,145 @@
+//! How often and how recently each command palette entry was run, so the palette can
+//! list the commands a user reaches for first. Kept across restarts in a small JSON file.
+
+use crate::project::expand_home;
+use crate::util::fuzzy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where the usage counts are stored
+pub const USAGE_FILE: &str = "~/.local/state/manse/palette.json";
+
+/// Days for the weight of a use to halve
+const HALF_LIFE_DAYS: f64 = 7.0;
+/// How much usage counts against fuzzy match quality when searching
+const FRECENCY_WEIGHT: f64 = 6.0;
+
+/// Uses of one command
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    pub count: u32,
+    /// Unix time of the last use (seconds)
+    pub last_used: u64,
+}
+
+/// Uses per command, by command name
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PaletteUsage {
+    commands: HashMap<String, Usage>,
+}
+
+impl PaletteUsage {
+    /// Read the usage file. A missing or unreadable file starts empty.
+    pub fn load() -> Self {
+        Self::load_from(&expand_home(USAGE_FILE))
+    }
+
+    fn load_from(path: &Path) -> Self {
+        let Ok(data) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&data).unwrap_or_else(|e| {
+            log::warn!("Ignoring invalid {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    /// Write the usage file, creating its directory if needed.
+    pub fn save(&self) -> Result<(), String> {
+        let path = expand_home(USAGE_FILE);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Count a use of the command `name` at `now` (Unix seconds).
+    pub fn record(&mut self, name: &str, now: u64) {
+        let usage = self.commands.entry(name.to_string()).or_default();
+        usage.count += 1;
+        usage.last_used = now;
+    }
+
+    /// Uses of `name`, each weighing less the longer ago the last one was.
+    fn frecency(&self, name: &str, now: u64) -> f64 {
+        let Some(usage) = self.commands.get(name) else {
+            return 0.0;
+        };
+        let age_days = now.saturating_sub(usage.last_used) as f64 / 86_400.0;
+        usage.count as f64 * 0.5f64.powf(age_days / HALF_LIFE_DAYS)
+    }
+
+    /// How well the command `name` fits `query`: its usage alone for an empty query,
+    /// otherwise fuzzy match quality plus usage. `None` if it doesn't match.
+    pub fn score(&self, name: &str, query: &str, now: u64) -> Option<f64> {
+        let matched = fuzzy::score(query, name)? as f64;
+        let used = self.frecency(name, now);
+        Some(if query.trim().is_empty() {
+            used
+        } else {
+            matched + FRECENCY_WEIGHT * used.ln_1p()
+        })
+    }
+}
+
+/// Current Unix time in seconds
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 4] = ["New Terminal", "Close Terminal", "Zoom In", "Zoom Out"];
+    const DAY: u64 = 86_400;
+
+    impl PaletteUsage {
+        fn rank(&self, names: &[&str], query: &str, now: u64) -> Vec<usize> {
+            fuzzy::rank(names.iter().map(|name| self.score(name, query, now)))
+        }
+    }
+
+    #[test]
+    fn unused_commands_keep_their_order() {
+        let usage = PaletteUsage::default();
+        assert_eq!(usage.rank(&NAMES, "", 0), vec![0, 1, 2, 3]);
+        assert_eq!(usage.rank(&NAMES, "zoom", 0), vec![2, 3]);
+    }
+
+    #[test]
+    fn frequent_and_recent_commands_come_first() {
+        let mut usage = PaletteUsage::default();
+        let now = 100 * DAY;
+        for _ in 0..5 {
+            usage.record("Close Terminal", now - 60 * DAY);
+        }
+        usage.record("Zoom Out", now - DAY);
+        usage.record("Zoom Out", now);
+
+        assert_eq!(usage.rank(&NAMES, "", now), vec![3, 1, 0, 2]);
+        assert_eq!(usage.rank(&NAMES, "zoom", now), vec![3, 2]);
+    }
+
+    #[test]
+    fn match_quality_outweighs_a_single_use() {
+        let mut usage = PaletteUsage::default();
+        usage.record("Close Terminal", 0);
+        assert_eq!(usage.rank(&NAMES, "in", 0), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let mut usage = PaletteUsage::default();
+        usage.record("Zoom In", 42);
+        let json = serde_json::to_string(&usage).unwrap();
+        assert_eq!(serde_json::from_str::<PaletteUsage>(&json).unwrap(), usage);
+    }
+}
@@ -4,6 +4,7 @@
 //! while preserving terminal sessions. PTY file descriptors survive across
 //! exec() when CLOEXEC is cleared.
 
+use crate::workspace::ProjectLink;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::{self, Read, Write};
@@ -62,6 +63,9 @@ pub struct PersistedState {
     pub active_workspace: usize,
     /// Next internal panel ID to use.
     pub next_id: u64,
+    /// Terminal font scale of every workspace (global zoom).
+    #[serde(default = "default_font_scale")]
+    pub font_scale: f32,
 }
 
 impl PersistedState {
@@ -98,21 +102,8 @@ impl PersistedState {
 
         for (ws_idx, ws) in self.workspaces.iter().enumerate() {
             for (term_idx, term) in ws.terminals.iter().enumerate() {
-                // Check if fd is valid
-                let fd_valid = unsafe {
-                    libc::fcntl(term.pty_fd, libc::F_GETFD) != -1
-                };
-                if !fd_valid {
-                    errors.push((ws_idx, term_idx, PersistError::InvalidFd(term.pty_fd)));
-                    continue;
-                }
-
-                // Check if process is still running
-                let proc_running = unsafe {
-                    libc::kill(term.pty_pid as i32, 0) == 0
-                };
-                if !proc_running {
-                    errors.push((ws_idx, term_idx, PersistError::ProcessNotRunning(term.pty_pid)));
+                if let Err(e) = term.validate() {
+                    errors.push((ws_idx, term_idx, e));
                 }
             }
         }
@@ -130,10 +121,23 @@ pub struct PersistedWorkspace {
     pub panel_order: Vec<u64>,
     /// Index of the focused panel within this workspace.
     pub focused_index: usize,
+    /// Terminal font scale (workspace zoom).
+    #[serde(default = "default_font_scale")]
+    pub font_scale: f32,
+    /// Project file the workspace was named from.
+    #[serde(default)]
+    pub project: Option<ProjectLink>,
+    /// Sidebar group the workspace is listed under.
+    #[serde(default)]
+    pub group: Option<String>,
     /// Terminals in this workspace.
     pub terminals: Vec<PersistedTerminal>,
 }
 
+fn default_font_scale() -> f32 {
+    1.0
+}
+
 /// Persisted terminal state.
 #[derive(Serialize, Deserialize)]
 pub struct PersistedTerminal {
@@ -147,6 +151,9 @@ pub struct PersistedTerminal {
     pub pty_pid: u32,
     /// Width ratio (fraction of viewport).
     pub width_ratio: f32,
+    /// Width ratio to restore when unzoomed (set while zoomed).
+    #[serde(default)]
+    pub zoomed_from: Option<f32>,
     /// Terminal title (from shell escape sequences).
     #[serde(default)]
     pub title: String,
@@ -158,10 +165,52 @@ pub struct PersistedTerminal {
     pub cli_description: Option<String>,
     /// Icon (Nerd Font codepoint).
     pub icon: Option<String>,
+    /// Color scheme override name.
+    #[serde(default)]
+    pub color_scheme: Option<String>,
+    /// Accent color ("#rrggbb").
+    #[serde(default)]
+    pub accent: Option<String>,
+    /// Docked at the left edge of the strip.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Font scale of this terminal alone (terminal zoom).
+    #[serde(default = "default_font_scale")]
+    pub font_scale: f32,
     /// Current working directory (from OSC 7).
     pub cwd: Option<std::path::PathBuf>,
 }
 
+impl PersistedTerminal {
+    /// Check that the PTY fd survived the exec and the child is still running.
+    #[cfg(unix)]
+    pub fn validate(&self) -> Result<(), PersistError> {
+        // Check if fd is valid
+        let fd_valid = unsafe { libc::fcntl(self.pty_fd, libc::F_GETFD) != -1 };
+        if !fd_valid {
+            return Err(PersistError::InvalidFd(self.pty_fd));
+        }
+
+        // Check if process is still running
+        let proc_running = unsafe { libc::kill(self.pty_pid as i32, 0) == 0 };
+        if !proc_running {
+            return Err(PersistError::ProcessNotRunning(self.pty_pid));
+        }
+
+        Ok(())
+    }
+}
+
+/// Write `contents` to a new file at `path` that only the user can read. Fails if
+/// `path` already exists, so a file planted in a shared temp directory is never used.
+pub fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(contents)
+}
+
 /// Clear the CLOEXEC flag on a file descriptor so it survives exec().
 #[cfg(unix)]
 pub fn clear_cloexec(fd: i32) -> io::Result<()> {
@@ -0,0 +1,193 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Project file name written by `manse init`
+pub const PROJECT_FILE: &str = ".manse.json";
+
+/// Contents of a `.manse.json` project file
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectFile {
+    /// Workspace the project's terminals are grouped into
+    pub workspace_name: String,
+    /// Icon (Nerd Font codepoint) given to terminals entering the project
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Named color scheme from `config.schemes` applied to terminals entering the project
+    #[serde(default)]
+    pub color_scheme: Option<String>,
+    /// Accent color ("#rrggbb") given to terminals entering the project
+    #[serde(default)]
+    pub accent: Option<String>,
+    /// Wheel scroll multiplier of terminals in the project (e.g. slower for a
+    /// project with long logs); `scroll.multiplier` from init.lua otherwise
+    #[serde(default)]
+    pub scroll_multiplier: Option<f32>,
+    /// One terminal per command when the project is opened from the start screen
+    /// ("" = plain shell). Never run on a plain `cd` into the project.
+    #[serde(default)]
+    pub startup_commands: Vec<String>,
+    /// Terminals opened with the project from the start screen, in place of
+    /// `startupCommands` when given
+    #[serde(default)]
+    pub terminals: Vec<ProjectTerminal>,
+}
+
+/// A terminal of a project's layout in `.manse.json`
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTerminal {
+    /// Custom title of the terminal
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Working directory, relative to the project directory
+    #[serde(default)]
+    pub dir: Option<PathBuf>,
+    /// Command typed into the shell once its prompt is up (e.g. `npm run dev`)
+    #[serde(default)]
+    pub run_on_open: Option<String>,
+}
+
+impl ProjectFile {
+    /// Read `<dir>/.manse.json`, if present and valid.
+    pub fn load(dir: &Path) -> Option<Self> {
+        let data = std::fs::read_to_string(dir.join(PROJECT_FILE)).ok()?;
+        match serde_json::from_str(&data) {
+            Ok(project) => Some(project),
+            Err(e) => {
+                log::warn!("Invalid {} in {}: {}", PROJECT_FILE, dir.display(), e);
+                None
+            }
+        }
+    }
+
+    /// The project `dir` belongs to: the nearest `.manse.json` in `dir` or one of its
+    /// parents, with the directory holding it.
+    pub fn find(dir: &Path) -> Option<(PathBuf, Self)> {
+        dir.ancestors()
+            .filter(|ancestor| ancestor.join(PROJECT_FILE).is_file())
+            .find_map(|root| Some((root.to_path_buf(), Self::load(root)?)))
+    }
+}
+
+/// Root of the git repository `dir` is in: the nearest directory holding a `.git`
+/// (a directory, or a file in worktrees and submodules).
+pub fn git_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|ancestor| ancestor.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Set the `workspaceName` of `<dir>/.manse.json`, keeping its other keys. The new
+/// contents are written next to it and renamed over the file, so readers never see a
+/// half-written project.
+pub fn set_workspace_name(dir: &Path, name: &str) -> Result<(), String> {
+    let path = dir.join(PROJECT_FILE);
+    let data = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut value: serde_json::Value = serde_json::from_str(&data)
+        .map_err(|e| format!("Invalid {} in {}: {}", PROJECT_FILE, dir.display(), e))?;
+    let Some(object) = value.as_object_mut() else {
+        return Err(format!("Invalid {} in {}: not an object", PROJECT_FILE, dir.display()));
+    };
+    object.insert("workspaceName".to_string(), name.into());
+
+    let json = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())? + "\n";
+    let temp = dir.join(format!("{}.tmp", PROJECT_FILE));
+    std::fs::write(&temp, json)
+        .and_then(|()| std::fs::rename(&temp, &path))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Expand a leading `~` to the home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest.trim_start_matches('/')),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_nearest_project_file() {
+        let root = std::env::temp_dir().join(format!("manse-project-{}", std::process::id()));
+        let nested = root.join("app/src");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(
+            root.join(PROJECT_FILE),
+            r##"{"workspaceName": "outer", "icon": "", "accent": "#e06c75", "startupCommands": ["cargo watch", ""]}"##,
+        )
+        .unwrap();
+
+        let (found, project) = ProjectFile::find(&nested).unwrap();
+        assert_eq!(found, root);
+        assert_eq!(project.workspace_name, "outer");
+        assert_eq!(project.startup_commands, vec!["cargo watch", ""]);
+        assert_eq!(project.color_scheme, None);
+        assert_eq!(project.accent.as_deref(), Some("#e06c75"));
+        assert!(project.terminals.is_empty());
+
+        std::fs::write(
+            root.join("app").join(PROJECT_FILE),
+            r#"{"workspaceName": "inner", "terminals": [{"title": "web", "dir": "src", "runOnOpen": "npm run dev"}, {}]}"#,
+        )
+        .unwrap();
+        let (found, project) = ProjectFile::find(&nested).unwrap();
+        assert_eq!(found, root.join("app"));
+        assert_eq!(project.workspace_name, "inner");
+        assert_eq!(
+            project.terminals,
+            vec![
+                ProjectTerminal {
+                    title: Some("web".into()),
+                    dir: Some("src".into()),
+                    run_on_open: Some("npm run dev".into()),
+                },
+                ProjectTerminal::default(),
+            ]
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn finds_nearest_git_root() {
+        let root = std::env::temp_dir().join(format!("manse-git-{}", std::process::id()));
+        let submodule = root.join("vendor/lib");
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(submodule.join("src")).unwrap();
+        std::fs::write(submodule.join(".git"), "gitdir: ../../.git/modules/lib").unwrap();
+
+        assert_eq!(git_root(&root.join("vendor")), Some(root.clone()));
+        assert_eq!(git_root(&submodule.join("src")), Some(submodule.clone()));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn renames_workspace_keeping_other_keys() {
+        let root = std::env::temp_dir().join(format!("manse-project-rename-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join(PROJECT_FILE),
+            r#"{"workspaceName": "api", "icon": "", "custom": [1, 2]}"#,
+        )
+        .unwrap();
+
+        set_workspace_name(&root, "backend").unwrap();
+        let project = ProjectFile::load(&root).unwrap();
+        assert_eq!(project.workspace_name, "backend");
+        assert_eq!(project.icon.as_deref(), Some(""));
+        let data = std::fs::read_to_string(root.join(PROJECT_FILE)).unwrap();
+        assert!(data.find("workspaceName") < data.find("custom"));
+        assert!(!root.join(format!("{}.tmp", PROJECT_FILE)).exists());
+
+        std::fs::write(root.join(PROJECT_FILE), "[]").unwrap();
+        assert!(set_workspace_name(&root, "x").is_err());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,204 @@
+//! Keeping the shells alive when the UI panics.
+//!
+//! The app keeps a recent snapshot of its state here (`App::update_rescue_snapshot`),
+//! since the panic hook cannot reach the app itself. When the main thread panics, the
+//! hook writes the snapshot to a state file, clears CLOEXEC on the PTYs and execs
+//! `manse resume` like a restart does, so the terminals come back with their shells.
+//! The watchdog (see `watchdog`) resumes the same way when the UI thread hangs. A
+//! rescued instance that fails again soon after is left to exit (with its shells)
+//! rather than restarted in a loop.
+
+use crate::persist::{self, PersistedState};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds after a rescue in which another panic is not rescued (a crash loop)
+const RESCUE_LOOP_SECS: u64 = 30;
+
+/// State as of the last snapshot
+struct Snapshot {
+    /// `PersistedState` as JSON, serialized ahead of time
+    json: String,
+    /// PTY master of every terminal in it
+    fds: Vec<i32>,
+    socket: PathBuf,
+}
+
+static SNAPSHOT: Mutex<Option<Snapshot>> = Mutex::new(None);
+
+/// When this process was started by a rescue (Unix seconds, 0 if it was not)
+static RESCUED_AT: AtomicU64 = AtomicU64::new(0);
+
+/// Keep `state` for the panic hook.
+pub fn set_snapshot(state: &PersistedState, socket: &Path) {
+    let json = match serde_json::to_string(state) {
+        Ok(json) => json,
+        Err(e) => {
+            log::warn!("Failed to snapshot state for crash rescue: {}", e);
+            return;
+        }
+    };
+    let fds = state
+        .workspaces
+        .iter()
+        .flat_map(|ws| &ws.terminals)
+        .map(|term| term.pty_fd)
+        .collect();
+    let snapshot = Snapshot {
+        json,
+        fds,
+        socket: socket.to_path_buf(),
+    };
+    if let Ok(mut slot) = SNAPSHOT.lock() {
+        *slot = Some(snapshot);
+    }
+}
+
+/// Rescue the terminals when the calling (main) thread panics. `rescued_at` is when
+/// this process was started by a rescue (Unix seconds), if it was.
+pub fn install_panic_hook(rescued_at: Option<u64>) {
+    RESCUED_AT.store(rescued_at.unwrap_or_default(), Ordering::Relaxed);
+    let main_thread = thread::current().id();
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        rescue(main_thread);
+    }));
+}
+
+/// Whether a process rescued at `rescued_at` (Unix seconds, 0 if it was not) failing
+/// at `now` is in a crash loop.
+fn crash_loop(rescued_at: u64, now: u64) -> bool {
+    rescued_at > 0 && now.saturating_sub(rescued_at) < RESCUE_LOOP_SECS
+}
+
+fn rescue_state_path() -> PathBuf {
+    std::env::temp_dir().join(format!("manse-rescue-{}.json", std::process::id()))
+}
+
+fn rescue(main_thread: ThreadId) {
+    // Background threads panicking leave the UI running
+    if thread::current().id() != main_thread {
+        return;
+    }
+    resume("crashed");
+}
+
+/// Exec `manse resume` from the last snapshot, `what` happened saying why. Returns only
+/// when that is not possible, or when this process was itself rescued moments ago.
+pub fn resume(what: &str) {
+    // A panic may have struck while the snapshot was being replaced
+    let Ok(slot) = SNAPSHOT.try_lock() else {
+        return;
+    };
+    let Some(snapshot) = slot.as_ref() else {
+        return;
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    if crash_loop(RESCUED_AT.load(Ordering::Relaxed), now) {
+        eprintln!("manse: {} again within {}s of a rescue; not resuming", what, RESCUE_LOOP_SECS);
+        return;
+    }
+
+    let state_path = rescue_state_path();
+    // Left by an earlier rescue of this process (exec keeps the pid) if its resume died
+    let _ = std::fs::remove_file(&state_path);
+    if let Err(e) = persist::write_private(&state_path, snapshot.json.as_bytes()) {
+        eprintln!("manse: failed to save the layout for a rescue: {}", e);
+        return;
+    }
+    let command = format!(
+        "manse resume --state-file {} -s {}",
+        state_path.display(),
+        snapshot.socket.display()
+    );
+
+    for &fd in &snapshot.fds {
+        let _ = persist::clear_cloexec(fd);
+    }
+    eprintln!("manse: {}; resuming with the running shells:\n  {}", what, command);
+    let Ok(exe) = std::env::current_exe() else {
+        eprintln!("manse: cannot find the manse executable to resume with");
+        return;
+    };
+    let err = std::process::Command::new(exe)
+        .arg("resume")
+        .arg("--state-file")
+        .arg(&state_path)
+        .arg("-s")
+        .arg(&snapshot.socket)
+        .arg("--rescued-at")
+        .arg(now.to_string())
+        .exec();
+    eprintln!("manse: failed to resume: {}", err);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(fds: &[i32]) -> PersistedState {
+        let terminals: Vec<_> = fds
+            .iter()
+            .enumerate()
+            .map(|(i, fd)| {
+                serde_json::json!({
+                    "internal_id": i,
+                    "external_id": format!("term-{}", i),
+                    "pty_fd": fd,
+                    "pty_pid": 1,
+                    "width_ratio": 0.5,
+                    "description": "",
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "version": persist::STATE_VERSION,
+            "workspaces": [{
+                "name": "default",
+                "panel_order": (0..fds.len()).collect::<Vec<_>>(),
+                "focused_index": 0,
+                "terminals": terminals,
+            }],
+            "active_workspace": 0,
+            "next_id": fds.len(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn crash_loop_only_soon_after_a_rescue() {
+        assert!(!crash_loop(0, 1_000));
+        assert!(crash_loop(1_000, 1_000));
+        assert!(crash_loop(1_000, 1_000 + RESCUE_LOOP_SECS - 1));
+        assert!(!crash_loop(1_000, 1_000 + RESCUE_LOOP_SECS));
+        // A clock that went backwards counts as soon
+        assert!(crash_loop(1_000, 900));
+    }
+
+    #[test]
+    fn snapshot_then_resume_in_a_crash_loop() {
+        set_snapshot(&state(&[7, 9]), Path::new("/tmp/manse-test.sock"));
+        {
+            let slot = SNAPSHOT.lock().unwrap();
+            let snapshot = slot.as_ref().unwrap();
+            assert_eq!(snapshot.fds, vec![7, 9]);
+            assert_eq!(snapshot.socket, Path::new("/tmp/manse-test.sock"));
+            let restored: PersistedState = serde_json::from_str(&snapshot.json).unwrap();
+            assert_eq!(restored.workspaces[0].terminals.len(), 2);
+        }
+
+        // Rescued just now: resume gives up before writing the state or exec'ing
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        RESCUED_AT.store(now, Ordering::Relaxed);
+        resume("crashed");
+        assert!(!rescue_state_path().exists());
+    }
+}
@@ -0,0 +1,281 @@
+//! Functions `init.lua` registers to compute text shown by the UI:
+//!
+//! ```lua
+//! manse.on_status(function(ctx) return ctx.workspace .. " · " .. ctx.terminals end)
+//! manse.on_sidebar(function(term) return term.cwd end)
+//! ```
+//!
+//! The Lua state is kept alive with the config. The app calls these functions at most
+//! every `HOOK_INTERVAL_SECS` and caches what they return. All the calls of one refresh
+//! (`begin_pass` to `end_pass`) share `PASS_BUDGET`: a Lua loop running past it is
+//! aborted, and once it is spent (a slow C function cannot be interrupted) the remaining
+//! calls are skipped until the next refresh.
+
+use mlua::{Function, HookTriggers, Lua, Result as LuaResult, Table, VmState};
+use std::cell::Cell;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Seconds between calls of the registered functions
+pub const HOOK_INTERVAL_SECS: f64 = 1.0;
+
+/// Longest the calls of one refresh may run in total
+const PASS_BUDGET: Duration = Duration::from_millis(20);
+
+/// Lua instructions between checks of the budget
+const BUDGET_CHECK_INSTRUCTIONS: u32 = 1000;
+
+/// Registry key of the `manse.on_status` function
+const STATUS_KEY: &str = "manse_on_status";
+
+/// Registry key of the `manse.on_sidebar` function
+const SIDEBAR_KEY: &str = "manse_on_sidebar";
+
+/// A terminal as passed to the Lua functions
+pub struct TerminalInfo<'a> {
+    /// External ID ("term-…")
+    pub id: &'a str,
+    pub title: &'a str,
+    pub description: &'a str,
+    pub cwd: Option<&'a Path>,
+    pub workspace: &'a str,
+    pub focused: bool,
+    pub notified: bool,
+}
+
+/// What `manse.on_status` is called with
+pub struct StatusInfo<'a> {
+    /// Name of the active workspace
+    pub workspace: &'a str,
+    /// Number of workspaces
+    pub workspaces: usize,
+    /// Terminals in the active workspace
+    pub terminals: usize,
+    pub focused: Option<TerminalInfo<'a>>,
+}
+
+/// The functions `init.lua` registered, with the Lua state they live in
+#[derive(Clone, Debug)]
+pub struct LuaHooks {
+    lua: Lua,
+    /// When the running pass must stop, checked by the instruction hook
+    deadline: Rc<Cell<Option<Instant>>>,
+    /// Whether the running pass has run out of budget (warned about once)
+    exhausted: Rc<Cell<bool>>,
+}
+
+/// Add the `manse` table with `on_status` and `on_sidebar` to `lua`'s globals.
+pub fn install(lua: &Lua) -> LuaResult<()> {
+    let manse = lua.create_table()?;
+    for (name, key) in [("on_status", STATUS_KEY), ("on_sidebar", SIDEBAR_KEY)] {
+        let register = lua.create_function(move |lua, function: Function| lua.set_named_registry_value(key, function))?;
+        manse.set(name, register)?;
+    }
+    lua.globals().set("manse", manse)
+}
+
+/// The hooks registered while `init.lua` ran, if it registered any.
+pub fn collect(lua: Lua) -> LuaResult<Option<LuaHooks>> {
+    let registered = |key| lua.named_registry_value::<Option<Function>>(key).map(|f| f.is_some());
+    if !registered(STATUS_KEY)? && !registered(SIDEBAR_KEY)? {
+        return Ok(None);
+    }
+
+    let deadline: Rc<Cell<Option<Instant>>> = Rc::new(Cell::new(None));
+    let budget = deadline.clone();
+    lua.set_hook(
+        HookTriggers::new().every_nth_instruction(BUDGET_CHECK_INSTRUCTIONS),
+        move |_, _| match budget.get() {
+            Some(deadline) if Instant::now() > deadline => Err(mlua::Error::runtime(format!(
+                "took longer than {}ms",
+                PASS_BUDGET.as_millis()
+            ))),
+            _ => Ok(VmState::Continue),
+        },
+    );
+    Ok(Some(LuaHooks {
+        lua,
+        deadline,
+        exhausted: Rc::new(Cell::new(false)),
+    }))
+}
+
+impl LuaHooks {
+    /// Start a refresh: the calls until `end_pass` share `PASS_BUDGET`.
+    pub fn begin_pass(&self) {
+        self.deadline.set(Some(Instant::now() + PASS_BUDGET));
+        self.exhausted.set(false);
+    }
+
+    /// End the refresh started by `begin_pass`.
+    pub fn end_pass(&self) {
+        self.deadline.set(None);
+    }
+
+    /// Text for the status bar from `manse.on_status`.
+    pub fn status(&self, info: &StatusInfo) -> Option<String> {
+        let table = self.status_table(info).ok()?;
+        self.call(STATUS_KEY, table)
+    }
+
+    /// Whether `manse.on_sidebar` was registered
+    pub fn has_sidebar(&self) -> bool {
+        self.lua
+            .named_registry_value::<Option<Function>>(SIDEBAR_KEY)
+            .is_ok_and(|f| f.is_some())
+    }
+
+    /// Extra line under a terminal's sidebar entry from `manse.on_sidebar`.
+    pub fn sidebar(&self, terminal: &TerminalInfo) -> Option<String> {
+        let table = self.terminal_table(terminal).ok()?;
+        self.call(SIDEBAR_KEY, table)
+    }
+
+    /// Call the function registered under `key` with `arg`, within the budget of the
+    /// running pass (a pass of its own outside one). Errors are logged; `nil`, an empty
+    /// string or a spent budget gives `None`.
+    fn call(&self, key: &str, arg: Table) -> Option<String> {
+        let function = self.lua.named_registry_value::<Option<Function>>(key).ok()??;
+        let own_pass = self.deadline.get().is_none();
+        if own_pass {
+            self.begin_pass();
+        }
+        let spent = self.deadline.get().is_some_and(|deadline| Instant::now() > deadline);
+        let result = if spent { None } else { Some(function.call::<Option<String>>(arg)) };
+        if own_pass {
+            self.end_pass();
+        }
+        let Some(result) = result else {
+            if !self.exhausted.replace(true) {
+                log::warn!(
+                    "Lua hooks took longer than {}ms; skipping the rest until the next refresh",
+                    PASS_BUDGET.as_millis()
+                );
+            }
+            return None;
+        };
+        match result {
+            Ok(text) => text.filter(|text| !text.is_empty()),
+            Err(e) => {
+                log::warn!("{} failed: {}", key.replacen('_', ".", 1), e);
+                None
+            }
+        }
+    }
+
+    fn terminal_table(&self, terminal: &TerminalInfo) -> LuaResult<Table> {
+        let table = self.lua.create_table()?;
+        table.set("id", terminal.id)?;
+        table.set("title", terminal.title)?;
+        table.set("description", terminal.description)?;
+        table.set("cwd", terminal.cwd.map(|cwd| cwd.to_string_lossy().into_owned()))?;
+        table.set("workspace", terminal.workspace)?;
+        table.set("focused", terminal.focused)?;
+        table.set("notified", terminal.notified)?;
+        Ok(table)
+    }
+
+    fn status_table(&self, info: &StatusInfo) -> LuaResult<Table> {
+        let table = self.lua.create_table()?;
+        table.set("workspace", info.workspace)?;
+        table.set("workspaces", info.workspaces)?;
+        table.set("terminals", info.terminals)?;
+        if let Some(focused) = &info.focused {
+            table.set("focused", self.terminal_table(focused)?)?;
+        }
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hooks(script: &str) -> Option<LuaHooks> {
+        let lua = Lua::new();
+        install(&lua).unwrap();
+        lua.load(script).exec().unwrap();
+        collect(lua).unwrap()
+    }
+
+    fn status_info() -> StatusInfo<'static> {
+        StatusInfo {
+            workspace: "code",
+            workspaces: 2,
+            terminals: 3,
+            focused: None,
+        }
+    }
+
+    fn terminal() -> TerminalInfo<'static> {
+        TerminalInfo {
+            id: "term-1",
+            title: "vim",
+            description: "",
+            cwd: Some(Path::new("/src/manse")),
+            workspace: "code",
+            focused: true,
+            notified: false,
+        }
+    }
+
+    #[test]
+    fn nothing_registered() {
+        assert!(hooks("local x = 1").is_none());
+    }
+
+    #[test]
+    fn status_from_registered_function() {
+        let hooks = hooks(r#"manse.on_status(function(ctx) return ctx.workspace .. " · " .. ctx.terminals end)"#).unwrap();
+        assert_eq!(hooks.status(&status_info()).as_deref(), Some("code · 3"));
+        assert!(!hooks.has_sidebar());
+        assert_eq!(hooks.sidebar(&terminal()), None);
+    }
+
+    #[test]
+    fn sidebar_from_registered_function() {
+        let hooks = hooks("manse.on_sidebar(function(term) return term.cwd end)").unwrap();
+        assert!(hooks.has_sidebar());
+        assert_eq!(hooks.sidebar(&terminal()).as_deref(), Some("/src/manse"));
+        assert_eq!(hooks.status(&status_info()), None);
+    }
+
+    #[test]
+    fn nil_and_empty_give_none() {
+        let hooks = hooks(
+            r#"
+            manse.on_status(function() return nil end)
+            manse.on_sidebar(function() return "" end)
+            "#,
+        )
+        .unwrap();
+        assert_eq!(hooks.status(&status_info()), None);
+        assert_eq!(hooks.sidebar(&terminal()), None);
+    }
+
+    #[test]
+    fn infinite_loop_is_aborted() {
+        let hooks = hooks(
+            r#"
+            manse.on_status(function() while true do end end)
+            manse.on_sidebar(function(term) return term.title end)
+            "#,
+        )
+        .unwrap();
+        let started = Instant::now();
+        assert_eq!(hooks.status(&status_info()), None);
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        // Within one pass, the budget the loop spent is gone for the other calls
+        hooks.begin_pass();
+        assert_eq!(hooks.status(&status_info()), None);
+        assert_eq!(hooks.sidebar(&terminal()), None);
+        hooks.end_pass();
+
+        // The next pass starts afresh
+        hooks.begin_pass();
+        assert_eq!(hooks.sidebar(&terminal()).as_deref(), Some("vim"));
+        hooks.end_pass();
+    }
+}
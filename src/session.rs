@@ -0,0 +1,183 @@
+//! Named session profiles (`manse session save/load`).
+//!
+//! A profile records the layout: workspaces, and for each terminal its working directory,
+//! title, width and the command it was running. Unlike `persist`, no process survives:
+//! loading a profile starts new shells in the saved directories. With `restore_on_start`
+//! the layout is also saved as a profile on quit and loaded on the next start.
+
+use crate::project::expand_home;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where profiles are stored, one `<name>.json` each
+pub const SESSIONS_DIR: &str = "~/.config/manse/sessions";
+
+/// Layout saved on quit and recreated on the next start (`restore_on_start`)
+pub const LAST_SESSION_FILE: &str = "~/.local/state/manse/last_session.json";
+
+/// A saved layout
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionProfile {
+    pub workspaces: Vec<SessionWorkspace>,
+    /// Index of the active workspace
+    #[serde(default)]
+    pub active_workspace: usize,
+    /// Terminal font scale of every workspace (global zoom)
+    #[serde(default = "default_font_scale")]
+    pub font_scale: f32,
+}
+
+fn default_font_scale() -> f32 {
+    1.0
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct SessionWorkspace {
+    pub name: String,
+    #[serde(default)]
+    pub focused_index: usize,
+    /// Sidebar group (`ws-group`)
+    #[serde(default)]
+    pub group: Option<String>,
+    pub terminals: Vec<SessionTerminal>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionTerminal {
+    pub cwd: Option<PathBuf>,
+    /// Custom title (titles set by the shell come back on their own)
+    pub title: Option<String>,
+    pub description: String,
+    pub icon: Option<String>,
+    pub color_scheme: Option<String>,
+    /// Accent color ("#rrggbb")
+    pub accent: Option<String>,
+    /// Docked at the left edge of the strip
+    pub pinned: bool,
+    pub width_ratio: f32,
+    /// Font scale of the terminal alone (terminal zoom)
+    pub font_scale: f32,
+    /// Foreground command when saved, typed into the new shell (best effort)
+    pub command: Option<String>,
+}
+
+impl Default for SessionTerminal {
+    fn default() -> Self {
+        Self {
+            cwd: None,
+            title: None,
+            description: String::new(),
+            icon: None,
+            color_scheme: None,
+            accent: None,
+            pinned: false,
+            width_ratio: 1.0,
+            font_scale: 1.0,
+            command: None,
+        }
+    }
+}
+
+impl SessionProfile {
+    /// Write the profile to `path`, creating the sessions directory if needed.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&data).map_err(|e| format!("Invalid profile {}: {}", path.display(), e))
+    }
+}
+
+/// File of the profile called `name`. Names are plain file names.
+pub fn profile_path(name: &str) -> Result<PathBuf, String> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(format!("Invalid session name: '{}'", name));
+    }
+    Ok(expand_home(SESSIONS_DIR).join(format!("{}.json", name)))
+}
+
+/// Names of the saved profiles, sorted.
+pub fn list() -> Vec<String> {
+    list_in(&expand_home(SESSIONS_DIR))
+}
+
+fn list_in(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "json" {
+                return None;
+            }
+            Some(path.file_stem()?.to_string_lossy().into_owned())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_path_like_names() {
+        assert!(profile_path("work").unwrap().ends_with("manse/sessions/work.json"));
+        for name in ["", "../x", "a/b", ".hidden"] {
+            assert!(profile_path(name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let profile: SessionProfile = serde_json::from_str(
+            r#"{"workspaces": [{"name": "dev", "terminals": [{"cwd": "/tmp"}]}]}"#,
+        )
+        .unwrap();
+        let terminal = &profile.workspaces[0].terminals[0];
+        assert_eq!(terminal.cwd, Some(PathBuf::from("/tmp")));
+        assert_eq!(terminal.width_ratio, 1.0);
+        assert_eq!(terminal.font_scale, 1.0);
+        assert_eq!(profile.active_workspace, 0);
+        assert_eq!(profile.font_scale, 1.0);
+    }
+
+    #[test]
+    fn save_load_and_list() {
+        let dir = std::env::temp_dir().join(format!("manse-sessions-{}", std::process::id()));
+        let profile = SessionProfile {
+            workspaces: vec![SessionWorkspace {
+                name: "dev".to_string(),
+                focused_index: 1,
+                group: Some("client".to_string()),
+                terminals: vec![
+                    SessionTerminal::default(),
+                    SessionTerminal {
+                        command: Some("cargo watch".to_string()),
+                        width_ratio: 0.5,
+                        ..Default::default()
+                    },
+                ],
+            }],
+            active_workspace: 0,
+            font_scale: 1.1,
+        };
+        profile.save(&dir.join("b.json")).unwrap();
+        profile.save(&dir.join("a.json")).unwrap();
+
+        assert_eq!(SessionProfile::load(&dir.join("b.json")).unwrap(), profile);
+        assert_eq!(list_in(&dir), vec!["a", "b"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,238 @@
+//! Text of the status bar segments declared in `config.status_segments`: clock, host
+//! name, battery charge and shell commands. Each segment's text is cached and refreshed
+//! on its own interval; battery and command segments are read on background threads,
+//! so a slow command never holds up a frame.
+
+use crate::config::StatusSegment;
+use eframe::egui;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+/// Seconds between battery readings
+const BATTERY_REFRESH_SECS: f64 = 60.0;
+
+/// A background refresh's result: generation, segment index and text
+type SegmentResult = (u64, usize, String);
+
+/// Cached segment text, refreshed as segments come due
+pub struct StatusSegments {
+    /// Segments the texts belong to; a different config starts over
+    config: Vec<StatusSegment>,
+    texts: Vec<String>,
+    /// Time (egui seconds) each segment is next refreshed
+    due: Vec<f64>,
+    /// Segments waiting on a background refresh
+    running: Vec<bool>,
+    /// Bumped when the config changes, so results for older segments are dropped
+    generation: u64,
+    results_tx: Sender<SegmentResult>,
+    results: Receiver<SegmentResult>,
+}
+
+impl Default for StatusSegments {
+    fn default() -> Self {
+        let (results_tx, results) = mpsc::channel();
+        Self {
+            config: Vec::new(),
+            texts: Vec::new(),
+            due: Vec::new(),
+            running: Vec::new(),
+            generation: 0,
+            results_tx,
+            results,
+        }
+    }
+}
+
+impl StatusSegments {
+    /// Refresh the segments of `config` that are due, and collect finished background
+    /// refreshes. Schedules a repaint for the next one due.
+    pub fn update(&mut self, ctx: &egui::Context, config: &[StatusSegment]) {
+        if self.config != config {
+            self.config = config.to_vec();
+            self.texts = vec![String::new(); config.len()];
+            self.due = vec![0.0; config.len()];
+            self.running = vec![false; config.len()];
+            self.generation += 1;
+        }
+        for (generation, index, text) in self.results.try_iter() {
+            if generation == self.generation {
+                self.texts[index] = text;
+                self.running[index] = false;
+            }
+        }
+
+        let now = ctx.input(|i| i.time);
+        for index in 0..self.config.len() {
+            if self.running[index] || now < self.due[index] {
+                continue;
+            }
+            match &self.config[index] {
+                StatusSegment::Clock { format } => {
+                    let (text, secs_to_next) = local_time(format);
+                    self.texts[index] = text;
+                    self.due[index] = now + secs_to_next;
+                }
+                StatusSegment::Hostname => {
+                    self.texts[index] = hostname();
+                    self.due[index] = f64::INFINITY;
+                }
+                StatusSegment::Battery => {
+                    self.refresh_in_background(ctx, index, battery);
+                    self.due[index] = now + BATTERY_REFRESH_SECS;
+                }
+                StatusSegment::Command { exec, interval_secs } => {
+                    let (exec, interval_secs) = (exec.clone(), *interval_secs);
+                    self.refresh_in_background(ctx, index, move || run_command(&exec));
+                    self.due[index] = now + interval_secs;
+                }
+            }
+        }
+
+        if let Some(next) = self.due.iter().copied().reduce(f64::min).filter(|next| next.is_finite()) {
+            ctx.request_repaint_after(Duration::from_secs_f64((next - now).max(0.0)));
+        }
+    }
+
+    /// Text of the segments that have any, left to right
+    pub fn texts(&self) -> Vec<&str> {
+        self.texts.iter().map(String::as_str).filter(|text| !text.is_empty()).collect()
+    }
+
+    /// Compute segment `index` with `read` on a thread of its own. Its text stays as it
+    /// is until `read` returns.
+    fn refresh_in_background(
+        &mut self,
+        ctx: &egui::Context,
+        index: usize,
+        read: impl FnOnce() -> String + Send + 'static,
+    ) {
+        self.running[index] = true;
+        let (tx, generation, ctx) = (self.results_tx.clone(), self.generation, ctx.clone());
+        std::thread::spawn(move || {
+            if tx.send((generation, index, read())).is_ok() {
+                ctx.request_repaint();
+            }
+        });
+    }
+}
+
+/// The current local time in strftime `format`, and the seconds until it next changes
+/// (the next second when the format shows seconds, otherwise the next minute).
+#[cfg(unix)]
+fn local_time(format: &str) -> (String, f64) {
+    let Ok(c_format) = std::ffi::CString::new(format) else {
+        return (String::new(), f64::INFINITY);
+    };
+    let Some(tm) = crate::util::time::local_tm() else {
+        return (String::new(), 60.0);
+    };
+    let mut buf = [0u8; 256];
+    let len = unsafe { libc::strftime(buf.as_mut_ptr() as *mut libc::c_char, buf.len(), c_format.as_ptr(), &tm) };
+    let text = String::from_utf8_lossy(&buf[..len]).into_owned();
+    let shows_seconds = ["%S", "%T", "%r", "%X", "%c", "%s"].iter().any(|spec| format.contains(spec));
+    let secs_to_next = if shows_seconds { 1.0 } else { (60 - tm.tm_sec.clamp(0, 59)) as f64 };
+    (text, secs_to_next)
+}
+
+#[cfg(not(unix))]
+fn local_time(_format: &str) -> (String, f64) {
+    (String::new(), f64::INFINITY)
+}
+
+/// Host name without its domain
+#[cfg(unix)]
+fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    if unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) } != 0 {
+        return String::new();
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    let name = String::from_utf8_lossy(&buf[..len]);
+    name.split('.').next().unwrap_or_default().to_string()
+}
+
+#[cfg(not(unix))]
+fn hostname() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_default()
+}
+
+/// Battery charge ("⚡ 83%" while charging), empty without a battery.
+fn battery() -> String {
+    match read_battery() {
+        Some((percent, true)) => format!("⚡ {}%", percent),
+        Some((percent, false)) => format!("🔋 {}%", percent),
+        None => String::new(),
+    }
+}
+
+/// Charge percentage and whether it is charging, from `pmset`.
+#[cfg(target_os = "macos")]
+fn read_battery() -> Option<(u8, bool)> {
+    let output = std::process::Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    parse_pmset(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Charge percentage and whether it is charging, from the first battery in sysfs.
+#[cfg(not(target_os = "macos"))]
+fn read_battery() -> Option<(u8, bool)> {
+    let supplies = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    supplies.flatten().find_map(|supply| {
+        let path = supply.path();
+        let kind = std::fs::read_to_string(path.join("type")).ok()?;
+        if kind.trim() != "Battery" {
+            return None;
+        }
+        let percent = std::fs::read_to_string(path.join("capacity")).ok()?.trim().parse().ok()?;
+        let status = std::fs::read_to_string(path.join("status")).unwrap_or_default();
+        Some((percent, status.trim() == "Charging"))
+    })
+}
+
+/// Charge and charging state from `pmset -g batt` output, e.g.
+/// ` -InternalBattery-0 (id=1234)<TAB>83%; charging; 1:02 remaining present: true`.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_pmset(output: &str) -> Option<(u8, bool)> {
+    output.lines().find_map(|line| {
+        let (before, after) = line.split_once('%')?;
+        let digits = before.len() - before.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+        let percent = before[before.len() - digits..].parse().ok()?;
+        let state = after.trim_start_matches(';').trim_start();
+        Some((percent, state.starts_with("charging") || state.starts_with("charged")))
+    })
+}
+
+/// First line `exec` prints on stdout, run with `sh -c`.
+fn run_command(exec: &str) -> String {
+    let output = std::process::Command::new("sh")
+        .args(["-c", exec])
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output();
+    match output {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+        Err(e) => {
+            log::warn!("Status segment command '{}' failed: {}", exec, e);
+            String::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_pmset_output() {
+        let charging = "Now drawing from 'AC Power'\n -InternalBattery-0 (id=4653155)\t83%; charging; 1:02 remaining present: true\n";
+        assert_eq!(parse_pmset(charging), Some((83, true)));
+        let draining = "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=4653155)\t7%; discharging; 0:20 remaining present: true\n";
+        assert_eq!(parse_pmset(draining), Some((7, false)));
+        assert_eq!(parse_pmset("Now drawing from 'AC Power'\n"), None);
+    }
+}
@@ -1,6 +1,15 @@
+use crate::config::{hex_to_color32, InheritConfig};
+use crate::engine::TerminalEngine;
 use crate::persist::PersistedTerminal;
+use crate::util::activity::ActivityMarks;
+use crate::util::bytes::ActivityHeat;
+use crate::util::layout;
 use eframe::egui;
-use egui_term::{BackendSettings, PtyEvent, TerminalBackend};
+use egui_term::{
+    BackendCommand, BackendSettings, PtyEvent, StubBackend, TerminalBackend, TerminalBackendTrait,
+    TerminalMode,
+};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::io;
 use std::path::PathBuf;
@@ -8,12 +17,77 @@ use std::sync::mpsc::Sender;
 
 // howdypal!
 
+/// Options for spawning a new terminal process
+#[derive(Debug, Clone, Default)]
+pub struct SpawnOptions {
+    /// Working directory (falls back to the current directory)
+    pub working_directory: Option<PathBuf>,
+    /// Extra environment variables set in the shell
+    pub env: HashMap<String, String>,
+    /// Command typed into the shell once it has started
+    pub startup_command: Option<String>,
+    /// External ID to keep (a reconnected terminal stays addressable by its old ID)
+    pub terminal_id: Option<String>,
+    /// Custom title given to the terminal
+    pub title: Option<String>,
+    /// Command pasted into the shell once its prompt is up (a project's `runOnOpen`)
+    pub run_on_open: Option<String>,
+    /// Shell to run instead of `$SHELL`, optionally followed by arguments
+    pub shell: Option<String>,
+    /// Lines of scrollback kept (None = the engine's default)
+    pub scrollback_lines: Option<usize>,
+    /// Hold off starting the shell until the terminal is first shown (`lazy_spawn`)
+    pub deferred: bool,
+}
+
+/// Seconds of quiet after the shell's first output before its prompt counts as drawn
+const PROMPT_QUIET_SECS: f64 = 0.3;
+/// Seconds after which pending input is sent even if no prompt was recognized
+const PROMPT_TIMEOUT_SECS: f64 = 5.0;
+
+/// A command waiting to be typed into a new shell until its prompt is up. Unlike a
+/// startup command it isn't written straight away: rc files that read stdin or clear
+/// the screen would swallow or garble it.
+#[derive(Debug)]
+pub struct PendingInput {
+    pub command: String,
+    /// Time (egui seconds) of the first check
+    since: Option<f64>,
+    /// Output bytes at the last check, and when they last changed
+    output: (u64, f64),
+}
+
+impl PendingInput {
+    pub fn new(command: String) -> Self {
+        Self {
+            command,
+            since: None,
+            output: (0, 0.0),
+        }
+    }
+
+    /// Whether the prompt looks ready at `now`, given the total output so far and
+    /// whether the line editor turned on bracketed paste (zsh, bash 5.1+ and fish do
+    /// while reading a command). Otherwise the shell must have printed something and
+    /// gone quiet, or the wait timed out.
+    pub fn is_ready(&mut self, now: f64, bytes_read: u64, line_editor: bool) -> bool {
+        let since = *self.since.get_or_insert(now);
+        if self.output.0 != bytes_read {
+            self.output = (bytes_read, now);
+        }
+        let quiet = bytes_read > 0 && now - self.output.1 >= PROMPT_QUIET_SECS;
+        (bytes_read > 0 && line_editor) || quiet || now - since >= PROMPT_TIMEOUT_SECS
+    }
+}
+
 /// A terminal panel in the window manager
 pub struct TerminalPanel {
     /// Unique identifier for external reference (nanoid with "term-" prefix)
     pub id: String,
-    pub backend: TerminalBackend,
+    pub backend: Box<dyn TerminalBackendTrait>,
     pub width_ratio: f32,
+    /// Width ratio to return to while zoomed to the full viewport width
+    pub zoomed_from: Option<f32>,
     /// Terminal title (from shell escape sequences)
     pub title: String,
     /// Custom title set via IPC (overrides natural title when Some)
@@ -24,10 +98,38 @@ pub struct TerminalPanel {
     pub cli_description: Option<String>,
     /// Optional icon (Nerd Font codepoint) set via IPC
     pub icon: Option<String>,
+    /// Named color scheme overriding the base theme (from `config.schemes`)
+    pub color_scheme: Option<String>,
+    /// Accent color ("#rrggbb") tinting the focus border, sidebar entry and minimap rectangle
+    pub accent: Option<String>,
+    /// Docked at the left edge of the strip, where it stays while the others scroll
+    pub pinned: bool,
+    /// Font scale of this terminal alone, on top of the workspace and global zoom
+    pub font_scale: f32,
     /// Current working directory (from OSC 7 escape sequences)
     pub current_working_directory: Option<PathBuf>,
+    /// Directory of the `.manse.json` project the working directory is in
+    pub project_root: Option<PathBuf>,
+    /// Wheel scroll multiplier of the project, overriding `scroll.multiplier`
+    pub scroll_multiplier: Option<f32>,
     /// Whether this terminal has a pending notification
     pub notified: bool,
+    /// Whether output reading is paused (output waits in the kernel PTY buffer)
+    pub paused: bool,
+    /// Whether input typed into the focused terminal is mirrored here in broadcast mode
+    pub broadcast: bool,
+    /// Raw key passthrough toggled with ⌘⇧\, overriding detection (`auto_passthrough`)
+    pub passthrough: Option<bool>,
+    /// SSH session seen in this terminal by the health check, and whether it still answers
+    pub ssh: Option<SshLink>,
+    /// Recent output rate, shown as the minimap heatmap
+    pub activity: ActivityHeat,
+    /// Last output and bell, shown as a fading dot in the sidebar and minimap
+    pub marks: ActivityMarks,
+    /// Command to type once the shell's prompt is up
+    pub pending_input: Option<PendingInput>,
+    /// Options to start the shell with once the terminal is shown (a placeholder until then)
+    pub deferred: Option<SpawnOptions>,
 }
 
 impl TerminalPanel {
@@ -35,21 +137,18 @@ impl TerminalPanel {
         id: u64,
         ctx: &egui::Context,
         event_tx: Sender<(u64, PtyEvent)>,
+        engine: &TerminalEngine,
         socket_path: Option<&PathBuf>,
-        working_directory: Option<PathBuf>,
-    ) -> Self {
-        let term_id = crate::util::ids::new_terminal_id();
+        options: SpawnOptions,
+    ) -> io::Result<Self> {
+        let term_id = options
+            .terminal_id
+            .unwrap_or_else(crate::util::ids::new_terminal_id);
 
-        let shell = std::env::var("SHELL").unwrap_or_else(|_| {
-            if cfg!(windows) {
-                "cmd.exe".to_string()
-            } else {
-                "/bin/bash".to_string()
-            }
-        });
+        let (shell, args) = shell_command(options.shell.as_deref());
 
         // Set environment variables for the terminal
-        let mut env = HashMap::new();
+        let mut env = options.env;
         env.insert("TERM".to_string(), "xterm-256color".to_string());
         env.insert("MANSE_TERMINAL".to_string(), term_id.clone());
         if let Some(path) = socket_path {
@@ -57,30 +156,117 @@ impl TerminalPanel {
         }
 
         // Use provided working directory or fall back to current dir
-        let working_directory = working_directory.or_else(|| std::env::current_dir().ok());
+        let working_directory = options
+            .working_directory
+            .or_else(|| std::env::current_dir().ok());
 
+        let defaults = BackendSettings::default();
         let settings = BackendSettings {
             shell,
+            args,
             working_directory: working_directory.clone(),
             env,
-            ..Default::default()
+            scrollback_lines: options.scrollback_lines.unwrap_or(defaults.scrollback_lines),
         };
 
-        let backend = TerminalBackend::new(id, ctx.clone(), event_tx, settings)
-            .expect("Failed to create terminal backend");
+        let mut backend = engine.spawn(id, ctx, event_tx, settings)?;
 
-        Self {
+        // The PTY buffers input until the shell reads it, so this runs once the prompt is up
+        if let Some(command) = options.startup_command {
+            backend.process_command(BackendCommand::Write(format!("{}\n", command).into_bytes()));
+        }
+
+        Ok(Self {
             id: term_id,
             backend,
             width_ratio: 1.0,
+            zoomed_from: None,
             title: String::from("Terminal"),
-            custom_title: None,
+            custom_title: options.title,
             description: String::new(),
             cli_description: None,
             icon: None,
+            color_scheme: None,
+            accent: None,
+            pinned: false,
+            font_scale: 1.0,
             current_working_directory: working_directory,
+            project_root: None,
+            scroll_multiplier: None,
+            notified: false,
+            paused: false,
+            broadcast: false,
+            passthrough: None,
+            ssh: None,
+            activity: ActivityHeat::default(),
+            marks: ActivityMarks::default(),
+            pending_input: options.run_on_open.map(PendingInput::new),
+            deferred: None,
+        })
+    }
+
+    /// A placeholder for a terminal whose shell starts later (see `start`). It has a
+    /// stub backend showing nothing, and already the ID and title it will have.
+    pub fn deferred(
+        id: u64,
+        ctx: &egui::Context,
+        event_tx: Sender<(u64, PtyEvent)>,
+        mut options: SpawnOptions,
+    ) -> Self {
+        let term_id = options
+            .terminal_id
+            .get_or_insert_with(crate::util::ids::new_terminal_id)
+            .clone();
+        Self {
+            id: term_id,
+            backend: Box::new(StubBackend::new(id, ctx.clone(), event_tx, b"")),
+            width_ratio: 1.0,
+            zoomed_from: None,
+            title: String::from("Terminal"),
+            custom_title: options.title.clone(),
+            description: String::new(),
+            cli_description: None,
+            icon: None,
+            color_scheme: None,
+            accent: None,
+            pinned: false,
+            font_scale: 1.0,
+            current_working_directory: options.working_directory.clone(),
+            project_root: None,
+            scroll_multiplier: None,
             notified: false,
+            paused: false,
+            broadcast: false,
+            passthrough: None,
+            ssh: None,
+            activity: ActivityHeat::default(),
+            marks: ActivityMarks::default(),
+            pending_input: None,
+            deferred: Some(options),
+        }
+    }
+
+    /// Start the shell of a placeholder made by `deferred`. Does nothing for a terminal
+    /// that has one; on error the terminal stays a placeholder that can be started again.
+    pub fn start(
+        &mut self,
+        id: u64,
+        ctx: &egui::Context,
+        event_tx: Sender<(u64, PtyEvent)>,
+        engine: &TerminalEngine,
+        socket_path: Option<&PathBuf>,
+    ) -> io::Result<()> {
+        let Some(options) = self.deferred.clone() else {
+            return Ok(());
+        };
+        let started = Self::new(id, ctx, event_tx, engine, socket_path, options)?;
+        self.deferred = None;
+        self.backend = started.backend;
+        self.pending_input = started.pending_input;
+        if self.current_working_directory.is_none() {
+            self.current_working_directory = started.current_working_directory;
         }
+        Ok(())
     }
 
     /// Returns the display title (custom title if set, otherwise natural title)
@@ -88,11 +274,47 @@ impl TerminalPanel {
         self.custom_title.as_deref().unwrap_or(&self.title)
     }
 
-    pub fn pixel_width(&self, viewport_width: f32) -> f32 {
-        viewport_width * self.width_ratio
+    /// Display title with the workspace's disambiguation suffix, if any
+    pub fn disambiguated_title(&self, suffix: Option<&String>) -> Cow<'_, str> {
+        match suffix {
+            Some(suffix) => Cow::Owned(format!("{} · {}", self.display_title(), suffix)),
+            None => Cow::Borrowed(self.display_title()),
+        }
+    }
+
+    /// The accent color, if one is set
+    pub fn accent_color(&self) -> Option<egui::Color32> {
+        self.accent.as_deref().and_then(hex_to_color32)
+    }
+
+    /// Expand to the full viewport width, or return to the width it had before.
+    pub fn toggle_zoom(&mut self) {
+        match self.zoomed_from.take() {
+            Some(ratio) => self.width_ratio = ratio,
+            None => self.zoomed_from = Some(std::mem::replace(&mut self.width_ratio, 1.0)),
+        }
+    }
+
+    /// Step to the next wider (`grow`) or narrower width in `ratios`, ending a zoom.
+    pub fn step_width(&mut self, ratios: &[f32], grow: bool) {
+        self.zoomed_from = None;
+        let step = if grow {
+            layout::next_ratio(ratios, self.width_ratio, 0.01)
+        } else {
+            layout::prev_ratio(ratios, self.width_ratio, 0.01)
+        };
+        if let Some(ratio) = step {
+            self.width_ratio = ratio;
+        }
+    }
+
+    /// Width on screen: its share of the viewport, but no less than `min_width`.
+    pub fn pixel_width(&self, viewport_width: f32, min_width: f32) -> f32 {
+        (viewport_width * self.width_ratio).max(min_width)
     }
 
-    /// Restore a terminal panel from persisted state.
+    /// Restore a terminal panel from persisted state. Always a PTY backend: only
+    /// PTYs survive a restart.
     ///
     /// # Safety
     /// The PTY fd must be valid and the process must be running.
@@ -102,6 +324,7 @@ impl TerminalPanel {
         persisted: &PersistedTerminal,
         ctx: &egui::Context,
         event_tx: Sender<(u64, PtyEvent)>,
+        scrollback_lines: usize,
     ) -> io::Result<Self> {
         let backend = unsafe {
             TerminalBackend::from_raw_fd(
@@ -110,13 +333,15 @@ impl TerminalPanel {
                 persisted.pty_pid,
                 ctx.clone(),
                 event_tx,
+                scrollback_lines,
             )?
         };
 
         Ok(Self {
             id: persisted.external_id.clone(),
-            backend,
+            backend: Box::new(backend),
             width_ratio: persisted.width_ratio,
+            zoomed_from: persisted.zoomed_from,
             title: if persisted.title.is_empty() {
                 String::from("Terminal")
             } else {
@@ -126,45 +351,279 @@ impl TerminalPanel {
             description: persisted.description.clone(),
             cli_description: persisted.cli_description.clone(),
             icon: persisted.icon.clone(),
+            color_scheme: persisted.color_scheme.clone(),
+            accent: persisted.accent.clone(),
+            pinned: persisted.pinned,
+            font_scale: persisted.font_scale,
             current_working_directory: persisted.cwd.clone(),
+            project_root: None,
+            scroll_multiplier: None,
             notified: false,
+            paused: false,
+            broadcast: false,
+            passthrough: None,
+            ssh: None,
+            activity: ActivityHeat::default(),
+            marks: ActivityMarks::default(),
+            pending_input: None,
+            deferred: None,
         })
     }
 
-    /// Convert to persisted form for serialization.
+    /// Convert to persisted form for serialization (`None` without a PTY to hand over).
     #[cfg(unix)]
-    pub fn to_persisted(&self, internal_id: u64) -> PersistedTerminal {
-        PersistedTerminal {
+    pub fn to_persisted(&self, internal_id: u64) -> Option<PersistedTerminal> {
+        Some(PersistedTerminal {
             internal_id,
             external_id: self.id.clone(),
-            pty_fd: self.backend.pty_fd(),
-            pty_pid: self.backend.pty_id(),
+            pty_fd: self.backend.pty_fd()?,
+            pty_pid: self.backend.pty_id()?,
             width_ratio: self.width_ratio,
+            zoomed_from: self.zoomed_from,
             title: self.title.clone(),
             custom_title: self.custom_title.clone(),
             description: self.description.clone(),
             cli_description: self.cli_description.clone(),
             icon: self.icon.clone(),
+            color_scheme: self.color_scheme.clone(),
+            accent: self.accent.clone(),
+            pinned: self.pinned,
+            font_scale: self.font_scale,
             cwd: self.current_working_directory.clone(),
-        }
+        })
     }
 
     /// Get the PTY file descriptor.
     #[cfg(unix)]
-    pub fn pty_fd(&self) -> i32 {
+    pub fn pty_fd(&self) -> Option<i32> {
         self.backend.pty_fd()
     }
 
+    /// Paste the pending command followed by Enter once the prompt is up (see
+    /// [`PendingInput::is_ready`]). Returns whether a command is still waiting.
+    pub fn send_pending_input(&mut self, now: f64) -> bool {
+        let Some(pending) = &mut self.pending_input else {
+            return false;
+        };
+        let bracketed = self
+            .backend
+            .sync()
+            .terminal_mode
+            .contains(TerminalMode::BRACKETED_PASTE);
+        if !pending.is_ready(now, self.backend.io_stats().bytes_read, bracketed) {
+            return true;
+        }
+        let mut bytes = egui_term::paste_bytes(&pending.command, bracketed);
+        bytes.push(b'\r');
+        self.backend.process_command(BackendCommand::Write(bytes));
+        self.pending_input = None;
+        false
+    }
+
+    /// Whether ⌘ shortcuts go to the program instead of manse: as toggled with ⌘⇧\, else
+    /// (with `auto`) while the program asks for the kitty keyboard protocol.
+    pub fn passthrough_active(&self, auto: bool) -> bool {
+        self.passthrough.unwrap_or_else(|| {
+            auto && self
+                .backend
+                .last_content()
+                .terminal_mode
+                .intersects(TerminalMode::KITTY_KEYBOARD_PROTOCOL)
+        })
+    }
+
+    /// Stop or resume reading output from the PTY.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.backend.set_paused(paused);
+    }
+
+    /// Bytes of output waiting unread in the kernel PTY buffer.
+    #[cfg(unix)]
+    pub fn pending_output_bytes(&self) -> Option<usize> {
+        let mut pending: libc::c_int = 0;
+        let result = unsafe { libc::ioctl(self.pty_fd()?, libc::FIONREAD, &mut pending) };
+        (result != -1).then_some(pending as usize)
+    }
+
+    /// Name of the process group leader in the terminal's foreground job (e.g. "nvim").
+    #[cfg(unix)]
+    pub fn foreground_process_name(&self) -> Option<String> {
+        let pgid = unsafe { libc::tcgetpgrp(self.pty_fd()?) };
+        if pgid <= 0 {
+            return None;
+        }
+        process_name(pgid as u32)
+    }
+
+    /// Command line of the terminal's foreground job (e.g. "cargo watch -x test"), or
+    /// `None` while the shell itself is in the foreground. Quoting is not preserved.
+    #[cfg(unix)]
+    pub fn foreground_command(&self) -> Option<String> {
+        let pgid = unsafe { libc::tcgetpgrp(self.pty_fd()?) };
+        if pgid <= 0 || Some(pgid as u32) == self.pty_pid() {
+            return None;
+        }
+        let output = std::process::Command::new("ps")
+            .args(["-o", "args=", "-p", &pgid.to_string()])
+            .output()
+            .ok()?;
+        let args = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (!args.is_empty()).then_some(args)
+    }
+
+    /// Name of the job running in the foreground instead of the shell (e.g. "cargo"),
+    /// `None` while the shell is at its prompt.
+    #[cfg(unix)]
+    pub fn running_process(&self) -> Option<String> {
+        let pgid = unsafe { libc::tcgetpgrp(self.pty_fd()?) };
+        if pgid <= 0 || Some(pgid as u32) == self.pty_pid() {
+            return None;
+        }
+        process_name(pgid as u32)
+    }
+
+    /// Whether anything besides the shell runs in the terminal: a foreground job, or
+    /// background jobs and other processes below the shell in `processes`.
+    #[cfg(unix)]
+    pub fn is_busy(&self, processes: &ProcessTable) -> bool {
+        let Some(shell_pid) = self.pty_pid() else {
+            return false;
+        };
+        let foreground = self.pty_fd().map(|fd| unsafe { libc::tcgetpgrp(fd) });
+        foreground.is_some_and(|pgid| pgid > 0 && pgid as u32 != shell_pid)
+            || !processes.descendants(shell_pid).is_empty()
+    }
+
+    /// Signal the shell and its jobs to exit before the terminal is dropped, killing
+    /// them if they don't (see [`terminate_process_tree`]).
+    #[cfg(unix)]
+    pub fn terminate(&self) {
+        let Some(shell_pid) = self.pty_pid() else {
+            return;
+        };
+        let foreground = self
+            .pty_fd()
+            .map(|fd| unsafe { libc::tcgetpgrp(fd) })
+            .filter(|&pgid| pgid > 0)
+            .map(|pgid| pgid as u32);
+        terminate_process_tree(shell_pid, foreground);
+    }
+
     /// Get the PTY child process ID.
-    pub fn pty_pid(&self) -> u32 {
+    pub fn pty_pid(&self) -> Option<u32> {
         self.backend.pty_id()
     }
 
-    /// Check if this terminal is running an SSH session by inspecting the process tree.
-    /// Returns the parsed SSH info if found.
-    pub fn detect_ssh(&self) -> Option<SshSession> {
-        detect_ssh_in_process_tree(self.pty_pid())
+    /// Options for a terminal spawned from this one: its working directory, plus the
+    /// environment its shell was started with and its ssh command as `inherit` asks.
+    pub fn inherited_options(&self, inherit: &InheritConfig) -> SpawnOptions {
+        let mut options = SpawnOptions {
+            working_directory: self.current_working_directory.clone(),
+            ..Default::default()
+        };
+        if inherit.env {
+            options.env = self.pty_pid().and_then(process_environment).unwrap_or_default();
+        }
+        if inherit.ssh {
+            options.startup_command = self.ssh.as_ref().map(|link| link.session.full_command.clone());
+        }
+        options
+    }
+}
+
+/// Description of a terminal spawned from one described as `description`: the text
+/// before its first `:` (all of it without one), as a prefix ("api: migrations" gives
+/// "api:").
+pub fn description_prefix(description: &str) -> String {
+    match description.split(':').next().map(str::trim) {
+        Some(prefix) if !prefix.is_empty() => format!("{}:", prefix),
+        _ => String::new(),
+    }
+}
+
+/// Open file descriptors of this process and its soft limit on them.
+#[cfg(unix)]
+pub fn fd_usage() -> Option<(u64, u64)> {
+    let dir = if cfg!(target_os = "linux") { "/proc/self/fd" } else { "/dev/fd" };
+    // The directory handle used for listing is counted too
+    let open = std::fs::read_dir(dir).ok()?.count().saturating_sub(1) as u64;
+    let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        return None;
     }
+    Some((open, limit.rlim_cur))
+}
+
+/// Short name of process `pid`.
+#[cfg(target_os = "linux")]
+fn process_name(pid: u32) -> Option<String> {
+    let comm = std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+    Some(comm.trim_end().to_string())
+}
+
+/// Short name of process `pid`.
+#[cfg(target_os = "macos")]
+fn process_name(pid: u32) -> Option<String> {
+    let mut buf = [0u8; 256];
+    let len = unsafe { libc::proc_name(pid as i32, buf.as_mut_ptr().cast(), buf.len() as u32) };
+    (len > 0).then(|| String::from_utf8_lossy(&buf[..len as usize]).into_owned())
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+fn process_name(_pid: u32) -> Option<String> {
+    None
+}
+
+/// Variables of a `NAME=value` list, as found in a process's environment block.
+fn parse_environment<'a>(entries: impl Iterator<Item = &'a [u8]>) -> HashMap<String, String> {
+    entries
+        .filter_map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            let (name, value) = entry.split_once('=')?;
+            (!name.is_empty()).then(|| (name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Environment process `pid` was started with (later `export`s in a shell are not seen).
+#[cfg(target_os = "linux")]
+fn process_environment(pid: u32) -> Option<HashMap<String, String>> {
+    let environ = std::fs::read(format!("/proc/{}/environ", pid)).ok()?;
+    Some(parse_environment(environ.split(|&b| b == 0)))
+}
+
+/// Environment process `pid` was started with (later `export`s in a shell are not seen).
+#[cfg(target_os = "macos")]
+fn process_environment(pid: u32) -> Option<HashMap<String, String>> {
+    let mut mib = [libc::CTL_KERN, libc::KERN_PROCARGS2, pid as libc::c_int];
+    let mut size: libc::size_t = 0;
+    let null = std::ptr::null_mut();
+    if unsafe { libc::sysctl(mib.as_mut_ptr(), 3, null, &mut size, null, 0) } != 0 {
+        return None;
+    }
+    let mut buf = vec![0u8; size];
+    if unsafe { libc::sysctl(mib.as_mut_ptr(), 3, buf.as_mut_ptr().cast(), &mut size, null, 0) } != 0 {
+        return None;
+    }
+    buf.truncate(size);
+
+    // argc, the executable path padded with NULs, the arguments, then the environment
+    // up to an empty string
+    let argc = i32::from_ne_bytes(buf.get(..4)?.try_into().ok()?) as usize;
+    let rest = &buf[4..];
+    let path_end = rest.iter().position(|&b| b == 0)?;
+    let args_start = path_end + rest[path_end..].iter().position(|&b| b != 0)?;
+    let mut strings = rest[args_start..].split(|&b| b == 0);
+    for _ in 0..argc {
+        strings.next()?;
+    }
+    Some(parse_environment(strings.take_while(|entry| !entry.is_empty())))
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+fn process_environment(_pid: u32) -> Option<HashMap<String, String>> {
+    None
 }
 
 /// Information about a detected SSH session.
@@ -190,57 +649,171 @@ impl std::fmt::Display for SshSession {
     }
 }
 
-/// Walk the process tree rooted at `pid` looking for an ssh process.
-/// Uses `ps` to find descendant processes.
-fn detect_ssh_in_process_tree(pid: u32) -> Option<SshSession> {
-    // Get all processes with their parent pid and command
-    let output = std::process::Command::new("ps")
-        .args(["-eo", "pid,ppid,comm,args"])
-        .output()
-        .ok()?;
-
-    let text = String::from_utf8_lossy(&output.stdout);
-
-    // Build a map of pid -> (ppid, command, full_args)
-    let mut children: std::collections::HashMap<u32, Vec<u32>> = std::collections::HashMap::new();
-    let mut commands: std::collections::HashMap<u32, (String, String)> =
-        std::collections::HashMap::new();
-
-    for line in text.lines().skip(1) {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 4 {
-            continue;
+/// Whether an SSH session seen by the health check still answers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SshHealth {
+    Connected,
+    /// The connection looks dead, with the reason shown on the panel
+    Disconnected(String),
+}
+
+/// An SSH session tracked by the health check.
+#[derive(Debug, Clone)]
+pub struct SshLink {
+    pub session: SshSession,
+    pub health: SshHealth,
+}
+
+/// Lines at the bottom of the screen searched for ssh's disconnect messages
+const DISCONNECT_TAIL_LINES: usize = 3;
+
+/// Reason a finished ssh client lost its connection, read from the last lines of the
+/// screen. `None` when it ended normally (logout, `exit`).
+pub fn disconnect_reason(screen: &str) -> Option<&'static str> {
+    const MESSAGES: [(&str, &str); 6] = [
+        ("broken pipe", "Broken pipe"),
+        ("closed by remote host", "Connection closed by remote host"),
+        ("connection reset", "Connection reset"),
+        ("not responding", "Server not responding"),
+        ("timed out", "Connection timed out"),
+        ("network is unreachable", "Network is unreachable"),
+    ];
+    let tail = screen
+        .lines()
+        .rev()
+        .filter(|line| !line.trim().is_empty())
+        .take(DISCONNECT_TAIL_LINES)
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("\n");
+    MESSAGES
+        .iter()
+        .find(|(needle, _)| tail.contains(needle))
+        .map(|&(_, reason)| reason)
+}
+
+/// The system's processes as a tree, read with a single `ps` call.
+#[derive(Debug, Default)]
+pub struct ProcessTable {
+    children: HashMap<u32, Vec<u32>>,
+    /// pid -> (command name, full command line)
+    commands: HashMap<u32, (String, String)>,
+}
+
+impl ProcessTable {
+    /// The current process table. Slow enough (tens of ms) to avoid calling every frame.
+    pub fn snapshot() -> Option<Self> {
+        let output = std::process::Command::new("ps")
+            .args(["-eo", "pid,ppid,comm,args"])
+            .output()
+            .ok()?;
+        Some(Self::parse(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Parse the table listed by `ps -eo pid,ppid,comm,args`.
+    fn parse(table: &str) -> Self {
+        let mut processes = Self::default();
+        for line in table.lines().skip(1) {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 4 {
+                continue;
+            }
+            let Some(p) = parts[0].parse::<u32>().ok() else {
+                continue;
+            };
+            let Some(ppid) = parts[1].parse::<u32>().ok() else {
+                continue;
+            };
+            let comm = parts[2].to_string();
+            let args = parts[3..].join(" ");
+            processes.children.entry(ppid).or_default().push(p);
+            processes.commands.insert(p, (comm, args));
         }
-        let Some(p) = parts[0].parse::<u32>().ok() else {
-            continue;
-        };
-        let Some(ppid) = parts[1].parse::<u32>().ok() else {
-            continue;
-        };
-        let comm = parts[2].to_string();
-        let args = parts[3..].join(" ");
-        children.entry(ppid).or_default().push(p);
-        commands.insert(p, (comm, args));
-    }
-
-    // BFS from pid to find any descendant running ssh
-    let mut queue = std::collections::VecDeque::new();
-    queue.push_back(pid);
-
-    while let Some(current) = queue.pop_front() {
-        if let Some(kids) = children.get(&current) {
-            for &kid in kids {
-                if let Some((comm, args)) = commands.get(&kid) {
-                    if comm == "ssh" || comm.ends_with("/ssh") {
-                        return parse_ssh_args(args);
-                    }
-                }
+        processes
+    }
+
+    /// Every process below `pid`, breadth first.
+    pub fn descendants(&self, pid: u32) -> Vec<u32> {
+        let mut found = Vec::new();
+        let mut queue = std::collections::VecDeque::from([pid]);
+        while let Some(current) = queue.pop_front() {
+            for &kid in self.children.get(&current).into_iter().flatten() {
+                found.push(kid);
                 queue.push_back(kid);
             }
         }
+        found
     }
 
-    None
+    /// Command name of `pid` (e.g. "ssh" or "/usr/bin/ssh").
+    pub fn command_name(&self, pid: u32) -> Option<&str> {
+        self.commands.get(&pid).map(|(comm, _)| comm.as_str())
+    }
+
+    /// The first ssh process below `pid`.
+    fn ssh_session_under(&self, pid: u32) -> Option<SshSession> {
+        self.descendants(pid).into_iter().find_map(|kid| {
+            let (comm, args) = self.commands.get(&kid)?;
+            (comm == "ssh" || comm.ends_with("/ssh"))
+                .then(|| parse_ssh_args(args))
+                .flatten()
+        })
+    }
+}
+
+/// SSH sessions running under each of `pids` (terminal shells): the first ssh process
+/// in each one's process tree, from a single `ps` call.
+/// Slow enough that the UI calls it from a background thread.
+pub fn detect_ssh_sessions(pids: &[u32]) -> HashMap<u32, SshSession> {
+    let Some(processes) = ProcessTable::snapshot() else {
+        return HashMap::new();
+    };
+    ssh_sessions_in(&processes, pids)
+}
+
+/// SSH sessions under each of `pids` in `processes`
+fn ssh_sessions_in(processes: &ProcessTable, pids: &[u32]) -> HashMap<u32, SshSession> {
+    pids.iter()
+        .filter_map(|&pid| Some((pid, processes.ssh_session_under(pid)?)))
+        .collect()
+}
+
+/// How long a closed terminal's processes get to exit after SIGHUP/SIGTERM before
+/// the ones still running are sent SIGKILL
+pub const CLOSE_KILL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Hang up the process tree of a closed terminal's shell `shell_pid`: SIGHUP and SIGTERM
+/// to the shell's and the foreground job's process groups right away, then SIGKILL to
+/// whatever is still running after [`CLOSE_KILL_TIMEOUT`] (from a background thread).
+#[cfg(unix)]
+pub fn terminate_process_tree(shell_pid: u32, foreground_pgid: Option<u32>) {
+    let processes = ProcessTable::snapshot().unwrap_or_default();
+    // Remember names so a pid reused in the meantime isn't killed
+    let tree: Vec<(u32, Option<String>)> = std::iter::once(shell_pid)
+        .chain(processes.descendants(shell_pid))
+        .map(|pid| (pid, processes.command_name(pid).map(str::to_string)))
+        .collect();
+
+    let groups = std::iter::once(shell_pid).chain(foreground_pgid.filter(|&pgid| pgid != shell_pid));
+    for group in groups {
+        unsafe {
+            libc::kill(-(group as i32), libc::SIGHUP);
+            libc::kill(-(group as i32), libc::SIGTERM);
+        }
+    }
+
+    std::thread::spawn(move || {
+        std::thread::sleep(CLOSE_KILL_TIMEOUT);
+        let Some(processes) = ProcessTable::snapshot() else {
+            return;
+        };
+        for (pid, name) in tree {
+            if name.is_some() && processes.command_name(pid) == name.as_deref() {
+                log::info!("Killing pid {} ({}) left by a closed terminal", pid, name.unwrap_or_default());
+                unsafe { libc::kill(pid as i32, libc::SIGKILL) };
+            }
+        }
+    });
 }
 
 /// Parse an ssh command line to extract user, host, and port.
@@ -315,10 +888,107 @@ fn parse_ssh_args(args: &str) -> Option<SshSession> {
     })
 }
 
+/// Program and arguments of the shell to start: `configured` ("/bin/fish -l") split on
+/// whitespace, or else `$SHELL` with a platform fallback.
+fn shell_command(configured: Option<&str>) -> (String, Vec<String>) {
+    let mut words = configured.unwrap_or_default().split_whitespace().map(String::from);
+    if let Some(program) = words.next() {
+        return (program, words.collect());
+    }
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| {
+        if cfg!(windows) {
+            "cmd.exe".to_string()
+        } else {
+            "/bin/bash".to_string()
+        }
+    });
+    (shell, Vec::new())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn configured_shell_with_arguments() {
+        assert_eq!(shell_command(Some("/bin/fish")), ("/bin/fish".to_string(), vec![]));
+        assert_eq!(
+            shell_command(Some(" /bin/zsh  -l ")),
+            ("/bin/zsh".to_string(), vec!["-l".to_string()])
+        );
+        assert_eq!(shell_command(Some("  ")), shell_command(None));
+    }
+
+    #[test]
+    fn disconnect_reason_from_ssh_messages() {
+        let screen = "me@box:~$ ls\nclient_loop: send disconnect: Broken pipe\n$ \n\n";
+        assert_eq!(disconnect_reason(screen), Some("Broken pipe"));
+        let screen = "Connection to prod closed by remote host.\nConnection to prod closed.\n$ ";
+        assert_eq!(disconnect_reason(screen), Some("Connection closed by remote host"));
+        assert_eq!(disconnect_reason("Timeout, server box not responding.\n$ "), Some("Server not responding"));
+    }
+
+    #[test]
+    fn disconnect_reason_ignores_normal_logout() {
+        assert_eq!(disconnect_reason("logout\nConnection to box closed.\n$ "), None);
+        // Old messages scrolled above the last lines don't count
+        let screen = "Broken pipe\n$ ssh box\nwelcome\nlogout\nConnection to box closed.\n$ ";
+        assert_eq!(disconnect_reason(screen), None);
+    }
+
+    #[test]
+    fn pending_input_waits_for_a_quiet_prompt() {
+        let mut pending = PendingInput::new("npm run dev".into());
+        assert!(!pending.is_ready(1.0, 0, false));
+        assert!(!pending.is_ready(1.5, 120, false));
+        assert!(!pending.is_ready(1.7, 300, false));
+        assert!(pending.is_ready(1.7 + PROMPT_QUIET_SECS, 300, false));
+    }
+
+    #[test]
+    fn pending_input_sent_to_a_line_editor_or_after_timeout() {
+        let mut pending = PendingInput::new("ls".into());
+        assert!(!pending.is_ready(0.0, 0, true));
+        assert!(pending.is_ready(0.1, 40, true));
+
+        let mut silent = PendingInput::new("ls".into());
+        assert!(!silent.is_ready(2.0, 0, false));
+        assert!(silent.is_ready(2.0 + PROMPT_TIMEOUT_SECS, 0, false));
+    }
+
+    #[test]
+    fn finds_ssh_sessions_under_each_shell() {
+        let table = "  PID  PPID COMM ARGS
+  100     1 zsh  -zsh
+  101   100 tmux tmux attach
+  102   101 ssh  ssh -p 2222 alice@prod
+  200     1 bash -bash
+  201   200 vim  vim notes.txt
+  300     1 zsh  -zsh
+  301   300 ssh  /usr/bin/ssh box
+";
+        let sessions = ssh_sessions_in(&ProcessTable::parse(table), &[100, 200, 300, 400]);
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[&100].to_string(), "alice@prod:2222");
+        assert_eq!(sessions[&300].to_string(), "box");
+    }
+
+    #[test]
+    fn process_tree_descendants() {
+        let table = "  PID  PPID COMM ARGS
+  100     1 zsh   -zsh
+  101   100 cargo cargo watch
+  102   101 rustc rustc --edition 2021
+  103   100 sleep sleep 60
+  200     1 bash  -bash
+";
+        let processes = ProcessTable::parse(table);
+        assert_eq!(processes.descendants(100), vec![101, 103, 102]);
+        assert!(processes.descendants(200).is_empty());
+        assert_eq!(processes.command_name(102), Some("rustc"));
+        assert_eq!(processes.command_name(999), None);
+    }
+
     #[test]
     fn parse_simple_host() {
         let s = parse_ssh_args("ssh mybox").unwrap();
@@ -386,4 +1056,144 @@ mod tests {
         };
         assert_eq!(s.to_string(), "mybox");
     }
+
+    #[test]
+    fn deferred_terminal_starts_once_and_stays_a_placeholder_on_failure() {
+        let ctx = egui::Context::default();
+        let (event_tx, _events) = std::sync::mpsc::channel();
+        let options = SpawnOptions {
+            shell: Some("/nonexistent/shell".into()),
+            title: Some("later".into()),
+            ..Default::default()
+        };
+        let mut panel = TerminalPanel::deferred(1, &ctx, event_tx.clone(), options);
+        let id = panel.id.clone();
+
+        assert!(panel.start(1, &ctx, event_tx.clone(), &TerminalEngine::Pty, None).is_err());
+        assert!(panel.deferred.is_some());
+
+        panel.start(1, &ctx, event_tx.clone(), &TerminalEngine::Stub { script: None }, None).unwrap();
+        assert!(panel.deferred.is_none());
+        assert_eq!(panel.id, id);
+        assert_eq!(panel.display_title(), "later");
+        // Already started: nothing to do
+        panel.start(1, &ctx, event_tx, &TerminalEngine::Pty, None).unwrap();
+    }
+
+    fn placeholder(width_ratio: f32) -> TerminalPanel {
+        let (event_tx, _events) = std::sync::mpsc::channel();
+        let mut panel = TerminalPanel::deferred(1, &egui::Context::default(), event_tx, SpawnOptions::default());
+        panel.width_ratio = width_ratio;
+        panel
+    }
+
+    #[test]
+    fn zoom_round_trip() {
+        let mut panel = placeholder(0.5);
+        panel.toggle_zoom();
+        assert_eq!((panel.width_ratio, panel.zoomed_from), (1.0, Some(0.5)));
+        panel.toggle_zoom();
+        assert_eq!((panel.width_ratio, panel.zoomed_from), (0.5, None));
+    }
+
+    #[test]
+    fn stepping_the_width_ends_a_zoom() {
+        let ratios = [0.33, 0.5, 0.66, 1.0];
+        let mut panel = placeholder(0.5);
+        panel.toggle_zoom();
+        panel.step_width(&ratios, false);
+        assert_eq!((panel.width_ratio, panel.zoomed_from), (0.66, None));
+
+        let mut panel = placeholder(0.5);
+        panel.toggle_zoom();
+        // Already as wide as it gets: the zoom still ends, at full width
+        panel.step_width(&ratios, true);
+        assert_eq!((panel.width_ratio, panel.zoomed_from), (1.0, None));
+        panel.toggle_zoom();
+        assert_eq!(panel.zoomed_from, Some(1.0));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn zoom_survives_persistence() {
+        let ctx = egui::Context::default();
+        let (event_tx, _events) = std::sync::mpsc::channel();
+        let options = SpawnOptions {
+            shell: Some("/bin/sh".into()),
+            ..Default::default()
+        };
+        let mut panel = TerminalPanel::new(1, &ctx, event_tx.clone(), &TerminalEngine::Pty, None, options).unwrap();
+        panel.width_ratio = 0.5;
+        panel.toggle_zoom();
+
+        let persisted = panel.to_persisted(1).unwrap();
+        let json = serde_json::to_string(&persisted).unwrap();
+        let mut persisted: PersistedTerminal = serde_json::from_str(&json).unwrap();
+        assert_eq!((persisted.width_ratio, persisted.zoomed_from), (1.0, Some(0.5)));
+
+        // The restored terminal gets a descriptor of its own
+        persisted.pty_fd = unsafe { libc::dup(persisted.pty_fd) };
+        let mut restored = unsafe { TerminalPanel::from_persisted(2, &persisted, &ctx, event_tx, 100) }.unwrap();
+        assert_eq!((restored.width_ratio, restored.zoomed_from), (1.0, Some(0.5)));
+        restored.toggle_zoom();
+        assert_eq!((restored.width_ratio, restored.zoomed_from), (0.5, None));
+    }
+
+    #[test]
+    fn description_prefix_before_the_first_colon() {
+        assert_eq!(description_prefix("api: migrations: step 2"), "api:");
+        assert_eq!(description_prefix("web server"), "web server:");
+        assert_eq!(description_prefix(""), "");
+        assert_eq!(description_prefix(": untitled"), "");
+    }
+
+    #[test]
+    fn environment_entries() {
+        let environ = b"HOME=/home/me\0EMPTY=\0EQ=a=b\0junk\0=x\0";
+        let env = parse_environment(environ.split(|&b| b == 0));
+        assert_eq!(env.len(), 3);
+        assert_eq!(env["HOME"], "/home/me");
+        assert_eq!(env["EMPTY"], "");
+        assert_eq!(env["EQ"], "a=b");
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn inherited_spawn_options() {
+        let ctx = egui::Context::default();
+        let (event_tx, _events) = std::sync::mpsc::channel();
+        let options = SpawnOptions {
+            shell: Some("/bin/sh".into()),
+            working_directory: Some(std::env::temp_dir()),
+            env: HashMap::from([("MANSE_TEST_INHERITED".to_string(), "yes".to_string())]),
+            ..Default::default()
+        };
+        let mut panel = TerminalPanel::new(1, &ctx, event_tx, &TerminalEngine::Pty, None, options).unwrap();
+        panel.ssh = Some(SshLink {
+            session: SshSession {
+                user: None,
+                host: "build".into(),
+                port: None,
+                full_command: "ssh build".into(),
+            },
+            health: SshHealth::Connected,
+        });
+
+        let nothing = panel.inherited_options(&InheritConfig::default());
+        assert_eq!(nothing.working_directory, Some(std::env::temp_dir()));
+        assert!(nothing.env.is_empty());
+        assert_eq!(nothing.startup_command, None);
+
+        let all = InheritConfig {
+            icon: true,
+            description: true,
+            env: true,
+            ssh: true,
+        };
+        let inherited = panel.inherited_options(&all);
+        assert_eq!(inherited.working_directory, Some(std::env::temp_dir()));
+        assert_eq!(inherited.env.get("MANSE_TEST_INHERITED").map(String::as_str), Some("yes"));
+        assert_eq!(inherited.env.get("MANSE_TERMINAL"), Some(&panel.id));
+        assert_eq!(inherited.startup_command.as_deref(), Some("ssh build"));
+    }
 }
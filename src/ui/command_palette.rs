@@ -1,11 +1,16 @@
 /// Command palette UI and command definitions.
 
+use crate::config::{CustomCommand, FontZoom};
+use crate::palette_usage::{self, PaletteUsage};
+use crate::util::fuzzy;
 use eframe::egui;
 
 /// A command available in the command palette.
 #[derive(Clone, Copy, PartialEq)]
 pub enum Command {
     NewTerminal,
+    RunInNewTerminal,
+    NewScratchTerminal,
     CloseTerminal,
     FocusPrevious,
     FocusNext,
@@ -14,16 +19,60 @@ pub enum Command {
     MoveToSpot,
     ShrinkTerminal,
     GrowTerminal,
+    EqualizeWidths,
     FollowMode,
     SetDescription,
+    RenameTerminal,
+    CycleColorScheme,
     ToggleSidebar,
+    ToggleOverlayTerminal,
+    NotificationHistory,
+    TogglePause,
+    TogglePassthrough,
+    FindInScrollback,
+    PipeSelection,
+    SaveScreen,
+    SaveScrollback,
+    /// Zoom the terminal font of the focused terminal, the workspace or every terminal
+    ZoomIn(FontZoom),
+    ZoomOut(FontZoom),
+    ZoomReset(FontZoom),
+    UiScaleUp,
+    UiScaleDown,
+    UiScaleReset,
+    CycleKeystrokeOverlay,
+    ToggleHighContrast,
+    RecordMacro,
+    PlayMacro,
+    ToggleDebugHud,
+    ReloadConfig,
+    RenameWorkspace,
+    ToggleBroadcast,
+    ToggleZoom,
+    TogglePin,
+    ReconnectSsh,
+    UndoWorkspaceRemoval,
+    NextWorkspace,
+    PreviousWorkspace,
+    /// Entry of `config.commands`, by index
+    Custom(usize),
 }
 
 impl Command {
-    /// Returns all commands that should be shown in the command palette.
+    /// Name shown in the palette, `custom` being `config.commands`
+    pub fn label<'a>(&self, custom: &'a [CustomCommand]) -> &'a str {
+        match self {
+            Command::Custom(index) => custom.get(*index).map_or("", |command| command.name.as_str()),
+            _ => self.name(),
+        }
+    }
+
+    /// Returns all built-in commands that should be shown in the command palette.
     pub fn all() -> &'static [Command] {
         &[
             Command::NewTerminal,
+            Command::RunInNewTerminal,
+            Command::NewScratchTerminal,
             Command::CloseTerminal,
             Command::FocusPrevious,
             Command::FocusNext,
@@ -32,15 +81,54 @@ impl Command {
             Command::MoveToSpot,
             Command::ShrinkTerminal,
             Command::GrowTerminal,
+            Command::ToggleZoom,
+            Command::TogglePin,
+            Command::EqualizeWidths,
             Command::FollowMode,
             Command::SetDescription,
+            Command::RenameTerminal,
+            Command::RenameWorkspace,
+            Command::NextWorkspace,
+            Command::PreviousWorkspace,
+            Command::CycleColorScheme,
             Command::ToggleSidebar,
+            Command::ToggleOverlayTerminal,
+            Command::NotificationHistory,
+            Command::TogglePause,
+            Command::TogglePassthrough,
+            Command::ToggleBroadcast,
+            Command::ReconnectSsh,
+            Command::UndoWorkspaceRemoval,
+            Command::FindInScrollback,
+            Command::PipeSelection,
+            Command::SaveScreen,
+            Command::SaveScrollback,
+            Command::ZoomIn(FontZoom::Terminal),
+            Command::ZoomOut(FontZoom::Terminal),
+            Command::ZoomReset(FontZoom::Terminal),
+            Command::ZoomIn(FontZoom::Workspace),
+            Command::ZoomOut(FontZoom::Workspace),
+            Command::ZoomReset(FontZoom::Workspace),
+            Command::ZoomIn(FontZoom::All),
+            Command::ZoomOut(FontZoom::All),
+            Command::ZoomReset(FontZoom::All),
+            Command::UiScaleUp,
+            Command::UiScaleDown,
+            Command::UiScaleReset,
+            Command::CycleKeystrokeOverlay,
+            Command::ToggleHighContrast,
+            Command::RecordMacro,
+            Command::PlayMacro,
+            Command::ToggleDebugHud,
+            Command::ReloadConfig,
         ]
     }
 
     pub fn name(&self) -> &'static str {
         match self {
             Command::NewTerminal => "New Terminal",
+            Command::RunInNewTerminal => "New Terminal With Command…",
+            Command::NewScratchTerminal => "New Scratch Terminal",
             Command::CloseTerminal => "Close Terminal",
             Command::FocusPrevious => "Focus Previous Terminal",
             Command::FocusNext => "Focus Next Terminal",
@@ -49,15 +137,56 @@ impl Command {
             Command::MoveToSpot => "Move to Spot",
             Command::ShrinkTerminal => "Shrink Terminal",
             Command::GrowTerminal => "Grow Terminal",
+            Command::ToggleZoom => "Zoom/Unzoom Terminal (Full Width)",
+            Command::TogglePin => "Pin/Unpin Terminal (Dock at Left Edge)",
+            Command::EqualizeWidths => "Equalize Terminal Widths",
             Command::FollowMode => "Follow Mode",
             Command::SetDescription => "Set Terminal Description",
+            Command::RenameTerminal => "Rename Terminal",
+            Command::RenameWorkspace => "Rename Workspace…",
+            Command::CycleColorScheme => "Cycle Terminal Color Scheme",
             Command::ToggleSidebar => "Toggle Sidebar",
+            Command::ToggleOverlayTerminal => "Toggle Drop-down Terminal",
+            Command::NotificationHistory => "Notification History",
+            Command::TogglePause => "Pause/Resume Terminal Output",
+            Command::TogglePassthrough => "Toggle Raw Key Passthrough (Shortcuts to Program)",
+            Command::ToggleBroadcast => "Toggle Broadcast Input (Synchronized Typing)",
+            Command::ReconnectSsh => "Reconnect SSH Session",
+            Command::UndoWorkspaceRemoval => "Undo Workspace Removal",
+            Command::NextWorkspace => "Next Workspace",
+            Command::PreviousWorkspace => "Previous Workspace",
+            Command::FindInScrollback => "Find in Scrollback",
+            Command::PipeSelection => "Pipe to Command…",
+            Command::SaveScreen => "Save Screen to File…",
+            Command::SaveScrollback => "Save Scrollback to File…",
+            Command::ZoomIn(FontZoom::Terminal) => "Zoom In (Terminal)",
+            Command::ZoomOut(FontZoom::Terminal) => "Zoom Out (Terminal)",
+            Command::ZoomReset(FontZoom::Terminal) => "Reset Zoom (Terminal)",
+            Command::ZoomIn(FontZoom::Workspace) => "Zoom In (Workspace)",
+            Command::ZoomOut(FontZoom::Workspace) => "Zoom Out (Workspace)",
+            Command::ZoomReset(FontZoom::Workspace) => "Reset Zoom (Workspace)",
+            Command::ZoomIn(FontZoom::All) => "Zoom In (All Terminals)",
+            Command::ZoomOut(FontZoom::All) => "Zoom Out (All Terminals)",
+            Command::ZoomReset(FontZoom::All) => "Reset Zoom (All Terminals)",
+            Command::UiScaleUp => "Increase UI Size",
+            Command::UiScaleDown => "Decrease UI Size",
+            Command::UiScaleReset => "Reset UI Size",
+            Command::CycleKeystrokeOverlay => "Cycle Keystroke Overlay (Off/Shortcuts/All)",
+            Command::ToggleHighContrast => "Toggle High Contrast",
+            Command::RecordMacro => "Start/Stop Recording Macro",
+            Command::PlayMacro => "Play Macro",
+            Command::ToggleDebugHud => "Toggle Debug HUD (Latency/Throughput)",
+            Command::ReloadConfig => "Reload Config (init.lua)",
+            Command::Custom(_) => "Custom Command",
         }
     }
 
-    pub fn keybinding(&self) -> &'static str {
+    /// Shortcut of the command, the font zoom keys zooming `font_zoom` (`config.font_zoom`)
+    pub fn keybinding(&self, font_zoom: FontZoom) -> &'static str {
         match self {
             Command::NewTerminal => "⌘T",
+            Command::RunInNewTerminal => "",
+            Command::NewScratchTerminal => "⌘⇧T",
             Command::CloseTerminal => "⌘W",
             Command::FocusPrevious => "⌘[",
             Command::FocusNext => "⌘]",
@@ -66,29 +195,177 @@ impl Command {
             Command::MoveToSpot => "⌘⇧J",
             Command::ShrinkTerminal => "⌘-",
             Command::GrowTerminal => "⌘=",
+            Command::ToggleZoom => "⌘⇧↵",
+            Command::TogglePin => "",
+            Command::EqualizeWidths => "",
             Command::FollowMode => "⌘J",
             Command::SetDescription => "⌘D",
+            Command::RenameTerminal => "F2",
+            Command::RenameWorkspace => "",
+            Command::CycleColorScheme => "",
             Command::ToggleSidebar => "⌘B",
+            Command::ToggleOverlayTerminal => "⌘`",
+            Command::NotificationHistory => "⌘I",
+            Command::TogglePause => "⌘⇧F",
+            Command::TogglePassthrough => "⌘⇧\\",
+            Command::ToggleBroadcast => "",
+            Command::ReconnectSsh => "",
+            Command::UndoWorkspaceRemoval => "⌘Z",
+            Command::NextWorkspace => "⌘⇧↓",
+            Command::PreviousWorkspace => "⌘⇧↑",
+            Command::FindInScrollback => "⌘F",
+            Command::PipeSelection => "",
+            Command::SaveScreen => "",
+            Command::SaveScrollback => "",
+            Command::ZoomIn(scope) if *scope == font_zoom => "⌘⇧=",
+            Command::ZoomOut(scope) if *scope == font_zoom => "⌘⇧-",
+            Command::ZoomReset(scope) if *scope == font_zoom => "⌘⇧0",
+            Command::ZoomIn(_) | Command::ZoomOut(_) | Command::ZoomReset(_) => "",
+            Command::UiScaleUp => "⌘⌥=",
+            Command::UiScaleDown => "⌘⌥-",
+            Command::UiScaleReset => "⌘⌥0",
+            Command::CycleKeystrokeOverlay => "",
+            Command::ToggleHighContrast => "",
+            Command::RecordMacro => "⌘⇧R",
+            Command::PlayMacro => "⌘⇧M",
+            Command::ToggleDebugHud => "",
+            Command::ReloadConfig => "⌘⇧L",
+            Command::Custom(_) => "",
+        }
+    }
+}
+
+/// Open command palette: the search query and the highlighted entry.
+pub struct CommandPalette {
+    pub query: String,
+    /// Highlighted entry, by position in the ranked list
+    pub selected: usize,
+    /// Whether the search field still needs to grab keyboard focus
+    request_focus: bool,
+}
+
+impl CommandPalette {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            selected: 0,
+            request_focus: true,
         }
     }
 }
 
+/// A terminal the palette can switch to
+pub struct PaletteTerminal {
+    pub panel_id: u64,
+    pub title: String,
+    pub description: String,
+    pub workspace: String,
+}
+
+/// Entry picked in the palette
+#[derive(Clone, Copy)]
+pub enum PaletteSelection {
+    /// Run a command
+    Command(Command),
+    /// Focus a terminal, by panel ID
+    Terminal(u64),
+}
+
 /// Result of rendering the command palette.
 pub struct CommandPaletteResult {
     /// Whether the background was clicked (should close palette)
     pub background_clicked: bool,
-    /// Command that was selected (if any)
-    pub selected_command: Option<Command>,
+    /// Entry that was selected (if any)
+    pub selected: Option<PaletteSelection>,
 }
 
-/// Renders the command palette overlay.
-/// Returns the result indicating if background was clicked or a command was selected.
-pub fn render(ctx: &egui::Context) -> CommandPaletteResult {
+/// A ranked palette row
+#[derive(Clone, Copy)]
+enum Entry<'a> {
+    Command(Command),
+    Terminal(&'a PaletteTerminal),
+}
+
+impl Entry<'_> {
+    fn selection(self) -> PaletteSelection {
+        match self {
+            Entry::Command(cmd) => PaletteSelection::Command(cmd),
+            Entry::Terminal(terminal) => PaletteSelection::Terminal(terminal.panel_id),
+        }
+    }
+}
+
+/// Rank the commands (built-in, then `custom` ones) and `terminals` together for
+/// `query`. Commands score by match and `usage`, terminals by their best matching
+/// title, description or workspace name; terminals only show up once something is typed.
+fn rank_entries<'a>(
+    query: &str,
+    usage: &PaletteUsage,
+    custom: &[CustomCommand],
+    terminals: &'a [PaletteTerminal],
+) -> Vec<Entry<'a>> {
+    let now = palette_usage::now_secs();
+    let searching = !query.trim().is_empty();
+    let commands: Vec<Command> = Command::all()
+        .iter()
+        .copied()
+        .chain((0..custom.len()).map(Command::Custom))
+        .collect();
+    let command_scores = commands
+        .iter()
+        .map(|cmd| usage.score(cmd.label(custom), query, now));
+    let terminal_scores = terminals.iter().map(|terminal| {
+        let fields = [
+            terminal.title.as_str(),
+            terminal.description.as_str(),
+            terminal.workspace.as_str(),
+        ];
+        fuzzy::best_score(query, fields)
+            .filter(|_| searching)
+            .map(f64::from)
+    });
+
+    fuzzy::rank(command_scores.chain(terminal_scores))
+        .into_iter()
+        .map(|index| match commands.get(index) {
+            Some(&cmd) => Entry::Command(cmd),
+            None => Entry::Terminal(&terminals[index - commands.len()]),
+        })
+        .collect()
+}
+
+/// Renders the command palette overlay: a search field over the commands (with the
+/// `custom` ones from `config.commands`) ranked by `usage` and the query, and once something is typed the matching `terminals` of
+/// every workspace. Enter runs (or focuses) the highlighted entry (the top one unless
+/// moved with the arrow keys).
+/// Returns the result indicating if background was clicked or an entry was selected.
+pub fn render(
+    ctx: &egui::Context,
+    palette: &mut CommandPalette,
+    usage: &PaletteUsage,
+    custom: &[CustomCommand],
+    font_zoom: FontZoom,
+    terminals: &[PaletteTerminal],
+    scale: f32,
+) -> CommandPaletteResult {
     let mut result = CommandPaletteResult {
         background_clicked: false,
-        selected_command: None,
+        selected: None,
     };
 
+    let ranked = rank_entries(&palette.query, usage, custom, terminals);
+
+    // Arrow keys move the highlight instead of the text cursor
+    ctx.input_mut(|i| {
+        if i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown) {
+            palette.selected += 1;
+        }
+        if i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp) {
+            palette.selected = palette.selected.saturating_sub(1);
+        }
+    });
+    palette.selected = palette.selected.min(ranked.len().saturating_sub(1));
+
     // Semi-transparent background
     #[allow(deprecated)]
     let screen_rect = ctx.screen_rect();
@@ -108,7 +385,7 @@ pub fn render(ctx: &egui::Context) -> CommandPaletteResult {
         });
 
     // Command palette window
-    let palette_width = 400.0;
+    let palette_width = 400.0 * scale;
     let palette_x = (screen_rect.width() - palette_width) / 2.0;
     let palette_y = screen_rect.height() * 0.2;
 
@@ -121,30 +398,47 @@ pub fn render(ctx: &egui::Context) -> CommandPaletteResult {
                 .corner_radius(8.0)
                 .show(ui, |ui| {
                     ui.set_width(palette_width);
-                    ui.add_space(8.0);
+                    ui.add_space(8.0 * scale);
 
-                    // Title
-                    ui.vertical_centered(|ui| {
-                        ui.label(
-                            egui::RichText::new("Command Palette")
-                                .size(14.0)
-                                .color(egui::Color32::from_rgb(180, 180, 180)),
-                        );
-                    });
+                    // Search field
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut palette.query)
+                            .hint_text("Type a command or terminal")
+                            .desired_width(palette_width - 16.0 * scale),
+                    );
+                    if palette.request_focus {
+                        response.request_focus();
+                        palette.request_focus = false;
+                    } else if response.changed() {
+                        palette.selected = 0;
+                    } else if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        result.selected = ranked.get(palette.selected).map(|entry| entry.selection());
+                    } else if !response.has_focus() {
+                        // Keep typing in the field after clicking around the palette
+                        response.request_focus();
+                    }
 
-                    ui.add_space(8.0);
+                    ui.add_space(4.0 * scale);
                     ui.separator();
-                    ui.add_space(4.0);
+                    ui.add_space(4.0 * scale);
 
-                    // Command list
-                    for cmd in Command::all() {
+                    if ranked.is_empty() {
+                        ui.label(
+                            egui::RichText::new("No matching commands or terminals")
+                                .size(13.0 * scale)
+                                .color(egui::Color32::from_rgb(140, 140, 140)),
+                        );
+                    }
+
+                    // Command and terminal list
+                    for (index, entry) in ranked.iter().enumerate() {
                         let (rect, response) = ui.allocate_exact_size(
-                            egui::vec2(palette_width - 16.0, 28.0),
+                            egui::vec2(palette_width - 16.0 * scale, 28.0 * scale),
                             egui::Sense::click(),
                         );
 
-                        // Paint hover background first (before text)
-                        if response.hovered() {
+                        // Paint hover/highlight background first (before text)
+                        if index == palette.selected || response.hovered() {
                             ui.painter().rect_filled(
                                 rect,
                                 4.0,
@@ -153,20 +447,42 @@ pub fn render(ctx: &egui::Context) -> CommandPaletteResult {
                         }
 
                         // Then paint the text on top
+                        let (text, detail) = match entry {
+                            Entry::Command(cmd) => {
+                                (format!("{}  {}", cmd.label(custom), cmd.keybinding(font_zoom)), "")
+                            }
+                            Entry::Terminal(terminal) => {
+                                let text = if terminal.description.is_empty() {
+                                    format!("› {}", terminal.title)
+                                } else {
+                                    format!("› {} — {}", terminal.title, terminal.description)
+                                };
+                                (text, terminal.workspace.as_str())
+                            }
+                        };
                         ui.painter().text(
-                            rect.left_center() + egui::vec2(8.0, 0.0),
+                            rect.left_center() + egui::vec2(8.0 * scale, 0.0),
                             egui::Align2::LEFT_CENTER,
-                            format!("{}  {}", cmd.name(), cmd.keybinding()),
-                            egui::FontId::proportional(13.0),
+                            text,
+                            egui::FontId::proportional(13.0 * scale),
                             egui::Color32::from_rgb(220, 220, 220),
                         );
+                        if !detail.is_empty() {
+                            ui.painter().text(
+                                rect.right_center() - egui::vec2(8.0 * scale, 0.0),
+                                egui::Align2::RIGHT_CENTER,
+                                detail,
+                                egui::FontId::proportional(11.0 * scale),
+                                egui::Color32::from_rgb(140, 140, 140),
+                            );
+                        }
 
                         if response.clicked() {
-                            result.selected_command = Some(*cmd);
+                            result.selected = Some(entry.selection());
                         }
                     }
 
-                    ui.add_space(8.0);
+                    ui.add_space(8.0 * scale);
                 });
         });
 
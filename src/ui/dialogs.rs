@@ -1,25 +1,27 @@
 /// Modal dialog rendering.
 
+use crate::project::PROJECT_FILE;
 use eframe::egui;
+use std::path::Path;
 
-/// Result from rendering the confirm close dialog.
-pub enum ConfirmCloseResult {
+/// Result from rendering a confirmation dialog.
+pub enum ConfirmResult {
     /// Dialog still open, no action
     None,
     /// User cancelled (escape, background click, or cancel button)
     Cancelled,
-    /// User confirmed close
+    /// User confirmed the action
     Confirmed,
 }
 
-/// Result from rendering the set description dialog.
-pub enum SetDescriptionResult {
+/// Result from rendering a single-line text input dialog.
+pub enum TextInputResult {
     /// Dialog still open with current input
     Open { input: String },
     /// User cancelled
     Cancelled,
-    /// User saved with this description
-    Saved { description: String },
+    /// User confirmed with this input
+    Submitted { input: String },
 }
 
 /// Render a semi-transparent background overlay.
@@ -46,19 +48,87 @@ fn render_background(ctx: &egui::Context, id: &str) -> bool {
 }
 
 /// Render the confirm close terminal dialog.
-pub fn render_confirm_close(ctx: &egui::Context) -> ConfirmCloseResult {
+pub fn render_confirm_close(ctx: &egui::Context, scale: f32, running: Option<&str>) -> ConfirmResult {
+    let message = match running {
+        Some(name) => format!("\"{}\" is still running and will be terminated.", name),
+        None => "This will terminate the running process.".to_string(),
+    };
+    render_confirm(
+        ctx,
+        scale,
+        "confirm_close_dialog",
+        "Close Terminal?",
+        &message,
+        "Close",
+    )
+}
+
+/// Render the confirm quit dialog.
+pub fn render_confirm_quit(ctx: &egui::Context, scale: f32) -> ConfirmResult {
+    render_confirm(
+        ctx,
+        scale,
+        "confirm_quit_dialog",
+        "Quit Manse?",
+        "This will terminate all running terminals.",
+        "Quit",
+    )
+}
+
+/// Render the dialog offering to rename a workspace in its `.manse.json` too.
+pub fn render_confirm_project_rename(
+    ctx: &egui::Context,
+    scale: f32,
+    root: &Path,
+    name: &str,
+) -> ConfirmResult {
+    render_confirm(
+        ctx,
+        scale,
+        "confirm_project_rename_dialog",
+        "Update Project File?",
+        &format!(
+            "Rename the workspace to \"{}\" in {} too?",
+            name,
+            root.join(PROJECT_FILE).display()
+        ),
+        "Update",
+    )
+}
+
+/// Render the dialog confirming a sidebar group's close-all.
+pub fn render_confirm_close_group(ctx: &egui::Context, scale: f32, group: &str) -> ConfirmResult {
+    render_confirm(
+        ctx,
+        scale,
+        "confirm_close_group_dialog",
+        "Close Group?",
+        &format!("Close every terminal in \"{}\" and remove its workspaces?", group),
+        "Close All",
+    )
+}
+
+/// Render a confirm/cancel dialog for a destructive action.
+fn render_confirm(
+    ctx: &egui::Context,
+    scale: f32,
+    id: &str,
+    title: &str,
+    message: &str,
+    confirm_label: &str,
+) -> ConfirmResult {
     let bg_clicked = render_background(ctx, "dialog_bg");
 
     #[allow(deprecated)]
     let screen_rect = ctx.screen_rect();
-    let dialog_width = 300.0;
+    let dialog_width = 300.0 * scale;
     let dialog_x = (screen_rect.width() - dialog_width) / 2.0;
     let dialog_y = screen_rect.height() * 0.3;
 
     let mut should_close = bg_clicked;
     let mut should_confirm = false;
 
-    egui::Area::new(egui::Id::new("confirm_close_dialog"))
+    egui::Area::new(egui::Id::new(id))
         .fixed_pos(egui::pos2(dialog_x, dialog_y))
         .show(ctx, |ui| {
             egui::Frame::popup(ui.style())
@@ -67,39 +137,39 @@ pub fn render_confirm_close(ctx: &egui::Context) -> ConfirmCloseResult {
                 .corner_radius(8.0)
                 .show(ui, |ui| {
                     ui.set_width(dialog_width);
-                    ui.add_space(16.0);
+                    ui.add_space(16.0 * scale);
 
                     ui.vertical_centered(|ui| {
                         ui.label(
-                            egui::RichText::new("Close Terminal?")
-                                .size(16.0)
+                            egui::RichText::new(title)
+                                .size(16.0 * scale)
                                 .color(egui::Color32::WHITE),
                         );
                     });
 
-                    ui.add_space(8.0);
+                    ui.add_space(8.0 * scale);
 
                     ui.vertical_centered(|ui| {
                         ui.label(
-                            egui::RichText::new("This will terminate the running process.")
-                                .size(12.0)
+                            egui::RichText::new(message)
+                                .size(12.0 * scale)
                                 .color(egui::Color32::from_rgb(160, 160, 160)),
                         );
                     });
 
-                    ui.add_space(16.0);
+                    ui.add_space(16.0 * scale);
 
                     ui.horizontal(|ui| {
-                        ui.add_space((dialog_width - 160.0) / 2.0);
+                        ui.add_space((dialog_width - 160.0 * scale) / 2.0);
 
                         if ui.button("Cancel").clicked() {
                             should_close = true;
                         }
 
-                        ui.add_space(8.0);
+                        ui.add_space(8.0 * scale);
 
                         let close_btn = egui::Button::new(
-                            egui::RichText::new("Close").color(egui::Color32::WHITE),
+                            egui::RichText::new(confirm_label).color(egui::Color32::WHITE),
                         )
                         .fill(egui::Color32::from_rgb(180, 60, 60));
 
@@ -108,7 +178,7 @@ pub fn render_confirm_close(ctx: &egui::Context) -> ConfirmCloseResult {
                         }
                     });
 
-                    ui.add_space(16.0);
+                    ui.add_space(16.0 * scale);
                 });
         });
 
@@ -121,21 +191,81 @@ pub fn render_confirm_close(ctx: &egui::Context) -> ConfirmCloseResult {
     }
 
     if should_confirm {
-        ConfirmCloseResult::Confirmed
+        ConfirmResult::Confirmed
     } else if should_close {
-        ConfirmCloseResult::Cancelled
+        ConfirmResult::Cancelled
     } else {
-        ConfirmCloseResult::None
+        ConfirmResult::None
     }
 }
 
 /// Render the set description dialog.
-pub fn render_set_description(ctx: &egui::Context, current_input: &str) -> SetDescriptionResult {
+pub fn render_set_description(ctx: &egui::Context, scale: f32, current_input: &str) -> TextInputResult {
+    render_text_input(
+        ctx,
+        scale,
+        "set_description_dialog",
+        "Set Terminal Description",
+        "Enter description...",
+        "Save",
+        current_input,
+    )
+}
+
+/// Render the "Pipe to command" dialog.
+pub fn render_pipe_command(ctx: &egui::Context, scale: f32, current_input: &str) -> TextInputResult {
+    render_text_input(
+        ctx,
+        scale,
+        "pipe_command_dialog",
+        "Pipe Selection to Command",
+        "e.g. grep error, jq ., pbcopy",
+        "Run",
+        current_input,
+    )
+}
+
+/// Render the "New terminal with command" dialog.
+pub fn render_terminal_command(ctx: &egui::Context, scale: f32, current_input: &str) -> TextInputResult {
+    render_text_input(
+        ctx,
+        scale,
+        "terminal_command_dialog",
+        "New Terminal With Command",
+        "e.g. htop, npm run dev, ssh prod",
+        "Run",
+        current_input,
+    )
+}
+
+/// Render the "Rename workspace" dialog.
+pub fn render_rename_workspace(ctx: &egui::Context, scale: f32, current_input: &str) -> TextInputResult {
+    render_text_input(
+        ctx,
+        scale,
+        "rename_workspace_dialog",
+        "Rename Workspace",
+        "Workspace name",
+        "Rename",
+        current_input,
+    )
+}
+
+/// Render a dialog with a single-line text input.
+fn render_text_input(
+    ctx: &egui::Context,
+    scale: f32,
+    id: &str,
+    title: &str,
+    hint: &str,
+    confirm_label: &str,
+    current_input: &str,
+) -> TextInputResult {
     let bg_clicked = render_background(ctx, "dialog_bg_desc");
 
     #[allow(deprecated)]
     let screen_rect = ctx.screen_rect();
-    let dialog_width = 400.0;
+    let dialog_width = 400.0 * scale;
     let dialog_x = (screen_rect.width() - dialog_width) / 2.0;
     let dialog_y = screen_rect.height() * 0.3;
 
@@ -143,7 +273,7 @@ pub fn render_set_description(ctx: &egui::Context, current_input: &str) -> SetDe
     let mut should_confirm = false;
     let mut input = current_input.to_string();
 
-    egui::Area::new(egui::Id::new("set_description_dialog"))
+    egui::Area::new(egui::Id::new(id))
         .fixed_pos(egui::pos2(dialog_x, dialog_y))
         .show(ctx, |ui| {
             egui::Frame::popup(ui.style())
@@ -152,23 +282,23 @@ pub fn render_set_description(ctx: &egui::Context, current_input: &str) -> SetDe
                 .corner_radius(8.0)
                 .show(ui, |ui| {
                     ui.set_width(dialog_width);
-                    ui.add_space(16.0);
+                    ui.add_space(16.0 * scale);
 
                     ui.vertical_centered(|ui| {
                         ui.label(
-                            egui::RichText::new("Set Terminal Description")
-                                .size(16.0)
+                            egui::RichText::new(title)
+                                .size(16.0 * scale)
                                 .color(egui::Color32::WHITE),
                         );
                     });
 
-                    ui.add_space(12.0);
+                    ui.add_space(12.0 * scale);
 
                     ui.horizontal(|ui| {
-                        ui.add_space(16.0);
+                        ui.add_space(16.0 * scale);
                         let text_edit = egui::TextEdit::singleline(&mut input)
-                            .desired_width(dialog_width - 40.0)
-                            .hint_text("Enter description...");
+                            .desired_width(dialog_width - 40.0 * scale)
+                            .hint_text(hint);
                         let response = ui.add(text_edit);
 
                         // Always request focus for the text input
@@ -178,22 +308,22 @@ pub fn render_set_description(ctx: &egui::Context, current_input: &str) -> SetDe
                         if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
                             should_confirm = true;
                         }
-                        ui.add_space(16.0);
+                        ui.add_space(16.0 * scale);
                     });
 
-                    ui.add_space(16.0);
+                    ui.add_space(16.0 * scale);
 
                     ui.horizontal(|ui| {
-                        ui.add_space((dialog_width - 160.0) / 2.0);
+                        ui.add_space((dialog_width - 160.0 * scale) / 2.0);
 
                         if ui.button("Cancel").clicked() {
                             should_close = true;
                         }
 
-                        ui.add_space(8.0);
+                        ui.add_space(8.0 * scale);
 
                         let save_btn = egui::Button::new(
-                            egui::RichText::new("Save").color(egui::Color32::WHITE),
+                            egui::RichText::new(confirm_label).color(egui::Color32::WHITE),
                         )
                         .fill(egui::Color32::from_rgb(60, 120, 180));
 
@@ -202,7 +332,7 @@ pub fn render_set_description(ctx: &egui::Context, current_input: &str) -> SetDe
                         }
                     });
 
-                    ui.add_space(16.0);
+                    ui.add_space(16.0 * scale);
                 });
         });
 
@@ -212,10 +342,10 @@ pub fn render_set_description(ctx: &egui::Context, current_input: &str) -> SetDe
     }
 
     if should_confirm {
-        SetDescriptionResult::Saved { description: input }
+        TextInputResult::Submitted { input }
     } else if should_close {
-        SetDescriptionResult::Cancelled
+        TextInputResult::Cancelled
     } else {
-        SetDescriptionResult::Open { input }
+        TextInputResult::Open { input }
     }
 }
@@ -1,54 +1,165 @@
 use crate::ui::dialogs;
 use eframe::egui;
+use std::path::PathBuf;
 
 /// Active dialog type
 #[derive(Default)]
 pub enum ActiveDialog {
     #[default]
     None,
-    /// Confirm close terminal dialog
-    ConfirmClose,
+    /// Confirm close terminal dialog, naming the job still `running` in it
+    ConfirmClose { panel_id: u64, running: Option<String> },
+    /// Confirm quit application dialog
+    ConfirmQuit,
     /// Set description input dialog
     SetDescription {
         input: String,
     },
+    /// Command to run with `text` (captured when the dialog opened) on stdin
+    PipeToCommand { input: String, text: String },
+    /// New name for the active workspace
+    RenameWorkspace { input: String },
+    /// Command to run in a new terminal
+    NewTerminalWithCommand { input: String },
+    /// Also rename the workspace in the project file in `root`
+    ConfirmProjectRename { root: PathBuf, name: String },
+    /// Confirm closing every terminal in a sidebar group
+    ConfirmCloseGroup { group: String },
 }
 
 pub enum DialogAction {
     None,
-    ConfirmClose,
+    ConfirmClose(u64),
+    ConfirmQuit,
     SaveDescription(String),
+    PipeToCommand { command: String, text: String },
+    RenameWorkspace(String),
+    NewTerminalWithCommand(String),
+    RenameProject { root: PathBuf, name: String },
+    CloseGroup(String),
 }
 
 pub fn render_dialogs(
     ctx: &egui::Context,
     active: &mut ActiveDialog,
+    scale: f32,
     ) -> DialogAction {
     match active {
         ActiveDialog::None => DialogAction::None,
-        ActiveDialog::ConfirmClose => match dialogs::render_confirm_close(ctx) {
-            dialogs::ConfirmCloseResult::None => DialogAction::None,
-            dialogs::ConfirmCloseResult::Cancelled => {
+        ActiveDialog::ConfirmClose { panel_id, running } => {
+            let panel_id = *panel_id;
+            match dialogs::render_confirm_close(ctx, scale, running.as_deref()) {
+                dialogs::ConfirmResult::None => DialogAction::None,
+                dialogs::ConfirmResult::Cancelled => {
+                    *active = ActiveDialog::None;
+                    DialogAction::None
+                }
+                dialogs::ConfirmResult::Confirmed => {
+                    *active = ActiveDialog::None;
+                    DialogAction::ConfirmClose(panel_id)
+                }
+            }
+        }
+        ActiveDialog::ConfirmQuit => match dialogs::render_confirm_quit(ctx, scale) {
+            dialogs::ConfirmResult::None => DialogAction::None,
+            dialogs::ConfirmResult::Cancelled => {
                 *active = ActiveDialog::None;
                 DialogAction::None
             }
-            dialogs::ConfirmCloseResult::Confirmed => {
+            dialogs::ConfirmResult::Confirmed => {
                 *active = ActiveDialog::None;
-                DialogAction::ConfirmClose
+                DialogAction::ConfirmQuit
             }
         },
-        ActiveDialog::SetDescription { input } => match dialogs::render_set_description(ctx, input) {
-            dialogs::SetDescriptionResult::Open { input } => {
+        ActiveDialog::SetDescription { input } => match dialogs::render_set_description(ctx, scale, input) {
+            dialogs::TextInputResult::Open { input } => {
                 *active = ActiveDialog::SetDescription { input };
                 DialogAction::None
             }
-            dialogs::SetDescriptionResult::Cancelled => {
+            dialogs::TextInputResult::Cancelled => {
+                *active = ActiveDialog::None;
+                DialogAction::None
+            }
+            dialogs::TextInputResult::Submitted { input } => {
+                *active = ActiveDialog::None;
+                DialogAction::SaveDescription(input)
+            }
+        },
+        ActiveDialog::PipeToCommand { input, text } => match dialogs::render_pipe_command(ctx, scale, input) {
+            dialogs::TextInputResult::Open { input: new_input } => {
+                *input = new_input;
+                DialogAction::None
+            }
+            dialogs::TextInputResult::Cancelled => {
+                *active = ActiveDialog::None;
+                DialogAction::None
+            }
+            dialogs::TextInputResult::Submitted { input: command } => {
+                let text = std::mem::take(text);
+                *active = ActiveDialog::None;
+                if command.trim().is_empty() {
+                    DialogAction::None
+                } else {
+                    DialogAction::PipeToCommand { command, text }
+                }
+            }
+        },
+        ActiveDialog::RenameWorkspace { input } => match dialogs::render_rename_workspace(ctx, scale, input) {
+            dialogs::TextInputResult::Open { input: new_input } => {
+                *input = new_input;
+                DialogAction::None
+            }
+            dialogs::TextInputResult::Cancelled => {
+                *active = ActiveDialog::None;
+                DialogAction::None
+            }
+            dialogs::TextInputResult::Submitted { input: name } => {
+                *active = ActiveDialog::None;
+                DialogAction::RenameWorkspace(name.trim().to_string())
+            }
+        },
+        ActiveDialog::NewTerminalWithCommand { input } => match dialogs::render_terminal_command(ctx, scale, input) {
+            dialogs::TextInputResult::Open { input: new_input } => {
+                *input = new_input;
+                DialogAction::None
+            }
+            dialogs::TextInputResult::Cancelled => {
+                *active = ActiveDialog::None;
+                DialogAction::None
+            }
+            dialogs::TextInputResult::Submitted { input: command } => {
+                *active = ActiveDialog::None;
+                if command.trim().is_empty() {
+                    DialogAction::None
+                } else {
+                    DialogAction::NewTerminalWithCommand(command.trim().to_string())
+                }
+            }
+        },
+        ActiveDialog::ConfirmProjectRename { root, name } => {
+            match dialogs::render_confirm_project_rename(ctx, scale, root, name) {
+                dialogs::ConfirmResult::None => DialogAction::None,
+                dialogs::ConfirmResult::Cancelled => {
+                    *active = ActiveDialog::None;
+                    DialogAction::None
+                }
+                dialogs::ConfirmResult::Confirmed => {
+                    let (root, name) = (std::mem::take(root), std::mem::take(name));
+                    *active = ActiveDialog::None;
+                    DialogAction::RenameProject { root, name }
+                }
+            }
+        }
+        ActiveDialog::ConfirmCloseGroup { group } => match dialogs::render_confirm_close_group(ctx, scale, group) {
+            dialogs::ConfirmResult::None => DialogAction::None,
+            dialogs::ConfirmResult::Cancelled => {
                 *active = ActiveDialog::None;
                 DialogAction::None
             }
-            dialogs::SetDescriptionResult::Saved { description } => {
+            dialogs::ConfirmResult::Confirmed => {
+                let group = std::mem::take(group);
                 *active = ActiveDialog::None;
-                DialogAction::SaveDescription(description)
+                DialogAction::CloseGroup(group)
             }
         },
     }
@@ -0,0 +1,116 @@
+//! Start screen shown in an empty workspace (or once every terminal has exited).
+
+use crate::config::UiConfig;
+use eframe::egui;
+use std::path::PathBuf;
+
+/// Action chosen on the start screen
+pub enum EmptyStateAction {
+    /// Open a terminal in the active workspace
+    NewTerminal,
+    /// Recreate a recently closed workspace with a fresh terminal
+    OpenWorkspace(String),
+    /// Open the project in this directory (workspace named by its `.manse.json`)
+    OpenProject(PathBuf),
+    /// Spawn the terminals of a named template from `config.templates`
+    ApplyTemplate(String),
+    /// Open a terminal in the active workspace starting in this directory
+    OpenDirectory(PathBuf),
+}
+
+/// Data shown on the start screen
+pub struct StartScreen<'a> {
+    /// Name of the empty workspace
+    pub workspace: &'a str,
+    pub recent_workspaces: &'a [String],
+    pub recent_directories: &'a [PathBuf],
+    /// Template names, sorted
+    pub templates: &'a [String],
+    /// Text of the "Open project" path field (kept across frames)
+    pub project_path: &'a mut String,
+}
+
+/// Renders the start screen centered in the main area.
+pub fn render(
+    ui: &mut egui::Ui,
+    screen: &mut StartScreen,
+    ui_colors: &UiConfig,
+    scale: f32,
+) -> Option<EmptyStateAction> {
+    let mut action = None;
+
+    let section = |ui: &mut egui::Ui, title: &str| {
+        ui.add_space(20.0 * scale);
+        ui.label(
+            egui::RichText::new(title)
+                .size(12.0 * scale)
+                .color(ui_colors.sidebar_text_dim),
+        );
+        ui.add_space(6.0 * scale);
+    };
+
+    ui.vertical_centered(|ui| {
+        ui.add_space(ui.available_height() * 0.2);
+
+        ui.label(
+            egui::RichText::new(format!("Workspace \"{}\" is empty", screen.workspace))
+                .size(18.0 * scale)
+                .color(ui_colors.sidebar_text),
+        );
+        ui.add_space(16.0 * scale);
+
+        if ui
+            .add(egui::Button::new(egui::RichText::new("New terminal  ⌘T").size(14.0 * scale)))
+            .clicked()
+        {
+            action = Some(EmptyStateAction::NewTerminal);
+        }
+
+        section(ui, "Open project (directory with a .manse.json)");
+        ui.horizontal(|ui| {
+            let field_width = 320.0 * scale;
+            ui.add_space((ui.available_width() - field_width - 60.0).max(0.0) / 2.0);
+            let response = ui.add(
+                egui::TextEdit::singleline(screen.project_path)
+                    .hint_text("~/src/project")
+                    .desired_width(field_width),
+            );
+            let submitted =
+                response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if (ui.button("Open").clicked() || submitted) && !screen.project_path.is_empty() {
+                action = Some(EmptyStateAction::OpenProject(crate::project::expand_home(
+                    screen.project_path.trim(),
+                )));
+            }
+        });
+
+        if !screen.templates.is_empty() {
+            section(ui, "Apply template");
+            for name in screen.templates {
+                if ui.button(name).clicked() {
+                    action = Some(EmptyStateAction::ApplyTemplate(name.clone()));
+                }
+            }
+        }
+
+        if !screen.recent_directories.is_empty() {
+            section(ui, "Recent directories");
+            for dir in screen.recent_directories {
+                if ui.button(dir.display().to_string()).clicked() {
+                    action = Some(EmptyStateAction::OpenDirectory(dir.clone()));
+                }
+            }
+        }
+
+        if !screen.recent_workspaces.is_empty() {
+            section(ui, "Recent workspaces");
+            for name in screen.recent_workspaces {
+                if ui.button(name).clicked() {
+                    action = Some(EmptyStateAction::OpenWorkspace(name.clone()));
+                }
+            }
+        }
+    });
+
+    action
+}
@@ -0,0 +1,58 @@
+//! Shape cues that supplement color-only state indicators (`accessible_indicators`),
+//! and the high-contrast focus ring (`high_contrast`).
+
+use eframe::egui;
+
+/// Dash and gap length of the striped focus border
+const DASH_LENGTH: f32 = 10.0;
+const GAP_LENGTH: f32 = 5.0;
+
+/// Width of the bar marking the focused terminal in the sidebar
+const FOCUS_BAR_WIDTH: f32 = 3.0;
+
+/// Radius of the dot marking a notified terminal in the sidebar
+const NOTIFIED_DOT_RADIUS: f32 = 3.5;
+
+/// Striped border just inside `rect`, used instead of a solid focus border.
+pub fn striped_border(painter: &egui::Painter, rect: egui::Rect, stroke: egui::Stroke) {
+    let r = rect.shrink(stroke.width / 2.0);
+    let corners = [r.left_top(), r.right_top(), r.right_bottom(), r.left_bottom(), r.left_top()];
+    painter.extend(egui::Shape::dashed_line(&corners, stroke, DASH_LENGTH, GAP_LENGTH));
+}
+
+/// Width of each line of the high-contrast focus ring and of the dark gap between them
+const DOUBLE_BORDER_LINE: f32 = 3.0;
+const DOUBLE_BORDER_GAP: f32 = 2.0;
+
+/// Total width of the high-contrast focus ring
+pub const DOUBLE_BORDER_WIDTH: f32 = DOUBLE_BORDER_LINE * 2.0 + DOUBLE_BORDER_GAP;
+
+/// Two solid outlines just inside `rect` with a black gap between them, visible on
+/// any terminal background.
+pub fn double_border(painter: &egui::Painter, rect: egui::Rect, color: egui::Color32) {
+    let ring = egui::Stroke::new(DOUBLE_BORDER_LINE, color);
+    let gap = egui::Stroke::new(DOUBLE_BORDER_GAP, egui::Color32::BLACK);
+    painter.rect_stroke(rect, 0.0, ring, egui::StrokeKind::Inside);
+    painter.rect_stroke(rect.shrink(DOUBLE_BORDER_LINE), 0.0, gap, egui::StrokeKind::Inside);
+    painter.rect_stroke(
+        rect.shrink(DOUBLE_BORDER_LINE + DOUBLE_BORDER_GAP),
+        0.0,
+        ring,
+        egui::StrokeKind::Inside,
+    );
+}
+
+/// Solid bar along the left edge of a sidebar entry.
+pub fn focus_bar(painter: &egui::Painter, rect: egui::Rect, color: egui::Color32) {
+    let bar = egui::Rect::from_min_size(
+        rect.left_top() - egui::vec2(FOCUS_BAR_WIDTH + 2.0, 0.0),
+        egui::vec2(FOCUS_BAR_WIDTH, rect.height()),
+    );
+    painter.rect_filled(bar, 1.0, color);
+}
+
+/// Dot at the right edge of a sidebar entry.
+pub fn notified_dot(painter: &egui::Painter, rect: egui::Rect, color: egui::Color32) {
+    let center = egui::pos2(rect.right() - NOTIFIED_DOT_RADIUS - 4.0, rect.center().y);
+    painter.circle_filled(center, NOTIFIED_DOT_RADIUS, color);
+}
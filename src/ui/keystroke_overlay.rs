@@ -0,0 +1,40 @@
+//! Recently pressed key chords, shown in the bottom-right corner for screencasts.
+
+use crate::util::keystrokes::{self, KeystrokeLog};
+use eframe::egui;
+
+/// Renders the chords in `log`, newest at the bottom, each fading out as it ages.
+pub fn render(ctx: &egui::Context, log: &KeystrokeLog, now: f64) {
+    egui::Area::new(egui::Id::new("keystroke_overlay"))
+        .order(egui::Order::Foreground)
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-16.0, -16.0))
+        .interactable(false)
+        .show(ctx, |ui| {
+            ui.with_layout(egui::Layout::top_down(egui::Align::Max), |ui| {
+                ui.spacing_mut().item_spacing.y = 6.0;
+                for keystroke in log.iter() {
+                    let opacity = keystrokes::opacity(now - keystroke.time);
+                    let text = if keystroke.count > 1 {
+                        format!("{} ×{}", keystroke.chord, keystroke.count)
+                    } else {
+                        keystroke.chord.clone()
+                    };
+                    egui::Frame::NONE
+                        .fill(egui::Color32::from_black_alpha(200).gamma_multiply(opacity))
+                        .stroke(egui::Stroke::new(
+                            1.0,
+                            egui::Color32::from_gray(90).gamma_multiply(opacity),
+                        ))
+                        .corner_radius(6.0)
+                        .inner_margin(egui::Margin::symmetric(12, 6))
+                        .show(ui, |ui| {
+                            ui.label(
+                                egui::RichText::new(text)
+                                    .size(22.0)
+                                    .color(egui::Color32::WHITE.gamma_multiply(opacity)),
+                            );
+                        });
+                }
+            });
+        });
+}
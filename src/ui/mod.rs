@@ -1,11 +1,42 @@
 pub mod command_palette;
 pub mod dialogs;
 pub mod dialogs_state;
+pub mod empty_state;
+pub mod indicators;
+pub mod keystroke_overlay;
+pub mod mru_switcher;
+pub mod notification_history;
+pub mod overlay_terminal;
+pub mod restore_progress;
+pub mod search;
 pub mod sidebar;
 pub mod status_bar;
 pub mod terminal_strip;
+pub mod toasts;
+
+use eframe::egui;
 
 // Re-export Command for convenience
 pub use command_palette::Command;
 pub use dialogs_state::ActiveDialog;
 pub use dialogs_state::DialogAction;
+
+/// Scale egui's text styles and spacing (dialogs, command palette, toasts) by `scale`
+/// (`ui_scale`). Terminals set their font size themselves and keep it.
+pub fn apply_ui_scale(ctx: &egui::Context, scale: f32) {
+    let base = egui::Style::default();
+    ctx.all_styles_mut(|style| {
+        for (text_style, font) in style.text_styles.iter_mut() {
+            if let Some(base_font) = base.text_styles.get(text_style) {
+                font.size = base_font.size * scale;
+            }
+        }
+        let spacing = &mut style.spacing;
+        spacing.item_spacing = base.spacing.item_spacing * scale;
+        spacing.button_padding = base.spacing.button_padding * scale;
+        spacing.interact_size = base.spacing.interact_size * scale;
+        spacing.icon_width = base.spacing.icon_width * scale;
+        spacing.icon_width_inner = base.spacing.icon_width_inner * scale;
+        spacing.icon_spacing = base.spacing.icon_spacing * scale;
+    });
+}
@@ -0,0 +1,43 @@
+//! ⌃Tab switcher: the workspace's terminals by recent focus, shown while ⌃ is held.
+
+use crate::config::UiConfig;
+use eframe::egui;
+
+/// Renders `titles` (most recently focused first) in the middle of the window, with
+/// the entry at `selected` highlighted.
+pub fn render(ctx: &egui::Context, titles: &[String], selected: usize, ui_colors: &UiConfig, scale: f32) {
+    egui::Area::new(egui::Id::new("mru_switcher"))
+        .order(egui::Order::Foreground)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style())
+                .fill(egui::Color32::from_rgb(40, 40, 40))
+                .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(80, 80, 80)))
+                .corner_radius(8.0)
+                .inner_margin(egui::Margin::same((8.0 * scale) as i8))
+                .show(ui, |ui| {
+                    ui.set_min_width(320.0 * scale);
+                    for (index, title) in titles.iter().enumerate() {
+                        let is_selected = index == selected;
+                        egui::Frame::NONE
+                            .fill(if is_selected {
+                                ui_colors.focused_border.gamma_multiply(0.35)
+                            } else {
+                                egui::Color32::TRANSPARENT
+                            })
+                            .corner_radius(4.0)
+                            .inner_margin(egui::Margin::symmetric((10.0 * scale) as i8, (5.0 * scale) as i8))
+                            .show(ui, |ui| {
+                                ui.set_width(ui.available_width());
+                                let color = if is_selected {
+                                    egui::Color32::WHITE
+                                } else {
+                                    egui::Color32::from_gray(170)
+                                };
+                                ui.label(egui::RichText::new(title).size(15.0 * scale).color(color));
+                            });
+                    }
+                });
+        });
+}
@@ -0,0 +1,158 @@
+//! Notification history overlay.
+
+use crate::notifications::NotificationLog;
+use crate::terminal::TerminalPanel;
+use crate::util::time;
+use eframe::egui;
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+/// Result of rendering the notification history.
+#[derive(Default)]
+pub struct NotificationHistoryResult {
+    /// Whether the background was clicked (should close the overlay)
+    pub background_clicked: bool,
+    /// Terminal to focus (an entry was clicked)
+    pub focus_panel: Option<u64>,
+    /// Whether "Clear all" was clicked
+    pub clear_all: bool,
+}
+
+/// Renders the notification history overlay, newest events first.
+pub fn render(
+    ctx: &egui::Context,
+    log: &NotificationLog,
+    panels: &HashMap<u64, TerminalPanel>,
+    scale: f32,
+) -> NotificationHistoryResult {
+    let mut result = NotificationHistoryResult::default();
+
+    #[allow(deprecated)]
+    let screen_rect = ctx.screen_rect();
+
+    egui::Area::new(egui::Id::new("notification_history_bg"))
+        .fixed_pos(screen_rect.min)
+        .show(ctx, |ui| {
+            let response = ui.allocate_response(screen_rect.size(), egui::Sense::click());
+            ui.painter()
+                .rect_filled(screen_rect, 0.0, egui::Color32::from_black_alpha(128));
+            if response.clicked() {
+                result.background_clicked = true;
+            }
+        });
+
+    let width = 460.0 * scale;
+    let x = (screen_rect.width() - width) / 2.0;
+    let y = screen_rect.height() * 0.15;
+    let now = SystemTime::now();
+
+    egui::Area::new(egui::Id::new("notification_history"))
+        .fixed_pos(egui::pos2(x, y))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style())
+                .fill(egui::Color32::from_rgb(40, 40, 40))
+                .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(80, 80, 80)))
+                .corner_radius(8.0)
+                .show(ui, |ui| {
+                    ui.set_width(width);
+                    ui.add_space(8.0 * scale);
+
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0 * scale);
+                        ui.label(
+                            egui::RichText::new("Notifications")
+                                .size(14.0 * scale)
+                                .color(egui::Color32::from_rgb(180, 180, 180)),
+                        );
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.add_space(8.0 * scale);
+                            if ui
+                                .add_enabled(!log.is_empty(), egui::Button::new("Clear all"))
+                                .clicked()
+                            {
+                                result.clear_all = true;
+                            }
+                        });
+                    });
+
+                    ui.add_space(4.0 * scale);
+                    ui.separator();
+                    ui.add_space(4.0 * scale);
+
+                    if log.is_empty() {
+                        ui.vertical_centered(|ui| {
+                            ui.label(
+                                egui::RichText::new("No notifications")
+                                    .size(13.0 * scale)
+                                    .color(egui::Color32::from_rgb(120, 120, 120)),
+                            );
+                        });
+                        ui.add_space(8.0 * scale);
+                        return;
+                    }
+
+                    egui::ScrollArea::vertical().max_height(screen_rect.height() * 0.6).show(
+                        ui,
+                        |ui| {
+                            for event in log.newest_first() {
+                                let panel = panels.get(&event.panel_id);
+                                let title = panel.map_or("(closed)", |p| p.display_title());
+                                let age = now
+                                    .duration_since(event.timestamp)
+                                    .map(|d| time::format_age(d.as_secs()))
+                                    .unwrap_or_default();
+
+                                let (rect, response) = ui.allocate_exact_size(
+                                    egui::vec2(width - 16.0 * scale, 36.0 * scale),
+                                    if panel.is_some() {
+                                        egui::Sense::click()
+                                    } else {
+                                        egui::Sense::hover()
+                                    },
+                                );
+
+                                if response.hovered() && panel.is_some() {
+                                    ui.painter().rect_filled(
+                                        rect,
+                                        4.0,
+                                        egui::Color32::from_rgb(60, 60, 60),
+                                    );
+                                }
+
+                                ui.painter().text(
+                                    rect.left_top() + egui::vec2(8.0, 4.0) * scale,
+                                    egui::Align2::LEFT_TOP,
+                                    format!("[{}] {}", event.kind.label(), title),
+                                    egui::FontId::proportional(13.0 * scale),
+                                    egui::Color32::from_rgb(220, 220, 220),
+                                );
+                                ui.painter().text(
+                                    rect.right_top() + egui::vec2(-8.0, 4.0) * scale,
+                                    egui::Align2::RIGHT_TOP,
+                                    age,
+                                    egui::FontId::proportional(11.0 * scale),
+                                    egui::Color32::from_rgb(140, 140, 140),
+                                );
+                                if let Some(message) = &event.message {
+                                    ui.painter().text(
+                                        rect.left_bottom() + egui::vec2(8.0, -4.0) * scale,
+                                        egui::Align2::LEFT_BOTTOM,
+                                        message,
+                                        egui::FontId::proportional(11.0 * scale),
+                                        egui::Color32::from_rgb(160, 160, 160),
+                                    );
+                                }
+
+                                if response.clicked() {
+                                    result.focus_panel = Some(event.panel_id);
+                                }
+                            }
+                        },
+                    );
+
+                    ui.add_space(8.0 * scale);
+                });
+        });
+
+    result
+}
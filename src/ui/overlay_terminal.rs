@@ -0,0 +1,64 @@
+//! Quake-style drop-down terminal rendered over the strip.
+
+use crate::config::{Config, SchemeTheme};
+use crate::terminal::TerminalPanel;
+use crate::util::layout;
+use eframe::egui;
+use egui_term::{FontSettings, TerminalFont, TerminalView};
+
+/// Render the drop-down terminal sliding in from the top of the window.
+///
+/// `progress` runs from 0.0 (fully hidden) to 1.0 (fully shown).
+pub fn render(
+    ctx: &egui::Context,
+    config: &Config,
+    theme: &SchemeTheme,
+    panel: &mut TerminalPanel,
+    progress: f32,
+    has_focus: bool,
+) {
+    let screen = ctx.content_rect();
+    let height = (screen.height() * layout::OVERLAY_HEIGHT_RATIO).round();
+    let top = screen.top() - height * (1.0 - layout::ease_out_cubic(progress));
+    let rect = egui::Rect::from_min_size(
+        egui::pos2(screen.left(), top),
+        egui::vec2(screen.width(), height),
+    );
+
+    egui::Area::new(egui::Id::new("overlay_terminal"))
+        .order(egui::Order::Foreground)
+        .fixed_pos(rect.min)
+        .show(ctx, |ui| {
+            let border_width = 2.0;
+            let pad = egui::Margin::symmetric(
+                config.terminal_padding_x as i8,
+                config.terminal_padding_y as i8,
+            );
+            let inner_width = rect.width() - border_width * 2.0 - config.terminal_padding_x * 2.0;
+            let inner_height =
+                rect.height() - border_width * 2.0 - config.terminal_padding_y * 2.0;
+
+            egui::Frame::NONE
+                .inner_margin(pad)
+                .fill(theme.background)
+                .stroke(egui::Stroke::new(border_width, config.ui_colors.focused_border))
+                .show(ui, |ui| {
+                    let font = TerminalFont::new(FontSettings {
+                        font_type: egui::FontId::monospace(config.terminal_font_size),
+                    });
+                    let term_view = TerminalView::new(ui, panel.backend.as_mut())
+                        .set_focus(has_focus)
+                        .set_font(font)
+                        .set_theme(theme.theme.clone())
+                        .set_size(egui::vec2(inner_width, inner_height))
+                        .copy_on_select(config.copy_on_select)
+                        .scroll_multiplier(config.scroll.multiplier)
+                        .alternate_scroll(config.scroll.alternate_scroll);
+                    let response = ui.add(term_view);
+
+                    if has_focus {
+                        response.request_focus();
+                    }
+                });
+        });
+}
@@ -0,0 +1,94 @@
+//! Progress screen shown while terminals are re-attached after a restart.
+
+use crate::config::UiConfig;
+use eframe::egui;
+
+/// Outcome of re-attaching one terminal
+pub enum RestoreStatus {
+    Pending,
+    Restored,
+    /// Validation or re-attaching failed, with the reason
+    Failed(String),
+}
+
+/// One persisted terminal in the restore list
+pub struct RestoreEntry {
+    pub workspace: String,
+    pub title: String,
+    pub status: RestoreStatus,
+}
+
+const LIST_WIDTH: f32 = 480.0;
+
+/// Renders the restore list centered in the main area. `done` once every terminal
+/// was tried; returns whether the user chose to continue with what was restored.
+pub fn render(
+    ui: &mut egui::Ui,
+    entries: &[RestoreEntry],
+    done: bool,
+    ui_colors: &UiConfig,
+    scale: f32,
+) -> bool {
+    let total = entries.len();
+    let tried = entries
+        .iter()
+        .filter(|entry| !matches!(entry.status, RestoreStatus::Pending))
+        .count();
+    let restored = entries
+        .iter()
+        .filter(|entry| matches!(entry.status, RestoreStatus::Restored))
+        .count();
+    let failed_color = egui::Color32::from_rgb(230, 110, 100);
+    let mut continue_clicked = false;
+
+    ui.vertical_centered(|ui| {
+        ui.add_space(ui.available_height() * 0.15);
+
+        let heading = if done { "Some terminals could not be restored" } else { "Restoring session…" };
+        ui.label(egui::RichText::new(heading).size(18.0 * scale).color(ui_colors.sidebar_text));
+        ui.add_space(12.0 * scale);
+
+        ui.add(
+            egui::ProgressBar::new(tried as f32 / total.max(1) as f32)
+                .desired_width(LIST_WIDTH * scale)
+                .text(format!("{} of {} terminals", tried, total)),
+        );
+
+        if done {
+            ui.add_space(12.0 * scale);
+            let label = if restored > 0 {
+                format!("Continue with partial restore ({} of {})", restored, total)
+            } else {
+                "Start fresh".to_string()
+            };
+            if ui.add(egui::Button::new(egui::RichText::new(label).size(14.0 * scale))).clicked() {
+                continue_clicked = true;
+            }
+        }
+
+        ui.add_space(16.0 * scale);
+        ui.allocate_ui(egui::vec2(LIST_WIDTH * scale, ui.available_height()), |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for entry in entries {
+                    ui.horizontal(|ui| {
+                        let (mark, color) = match entry.status {
+                            RestoreStatus::Pending => ("·", ui_colors.sidebar_text_dim),
+                            RestoreStatus::Restored => ("✓", egui::Color32::from_rgb(120, 200, 120)),
+                            RestoreStatus::Failed(_) => ("✗", failed_color),
+                        };
+                        ui.label(egui::RichText::new(mark).size(14.0 * scale).color(color));
+                        ui.label(
+                            egui::RichText::new(format!("{}  {}", entry.workspace, entry.title))
+                                .color(ui_colors.sidebar_text),
+                        );
+                        if let RestoreStatus::Failed(reason) = &entry.status {
+                            ui.label(egui::RichText::new(reason).size(11.0 * scale).color(failed_color));
+                        }
+                    });
+                }
+            });
+        });
+    });
+
+    continue_clicked
+}
@@ -0,0 +1,96 @@
+//! Scrollback search bar, shown over the top-right corner of the focused terminal.
+
+use eframe::egui;
+
+/// Open search over a terminal's scrollback
+pub struct SearchBar {
+    pub panel_id: u64,
+    pub query: String,
+    /// Whether the last search found anything (`None` while the query is empty)
+    pub found: Option<bool>,
+    /// Whether the text field still needs to grab keyboard focus
+    pub request_focus: bool,
+}
+
+impl SearchBar {
+    pub fn new(panel_id: u64) -> Self {
+        Self {
+            panel_id,
+            query: String::new(),
+            found: None,
+            request_focus: true,
+        }
+    }
+}
+
+pub enum SearchAction {
+    /// The query was edited
+    Search(String),
+    /// Jump to the next match towards older (Enter) or newer (Shift+Enter) output
+    Next { older: bool },
+    /// Escape or focus moved elsewhere
+    Close,
+}
+
+const BAR_WIDTH: f32 = 280.0;
+
+/// Renders the search bar in the top-right corner of `terminal_rect`.
+pub fn render(
+    ctx: &egui::Context,
+    bar: &mut SearchBar,
+    terminal_rect: egui::Rect,
+    scale: f32,
+) -> Option<SearchAction> {
+    let mut action = None;
+    let bar_width = BAR_WIDTH * scale;
+    let pos = egui::pos2(terminal_rect.right() - bar_width - 12.0, terminal_rect.top() + 8.0);
+
+    egui::Area::new(egui::Id::new("scrollback_search"))
+        .order(egui::Order::Foreground)
+        .fixed_pos(pos)
+        .show(ctx, |ui| {
+            egui::Frame::NONE
+                .fill(egui::Color32::from_gray(35))
+                .stroke(egui::Stroke::new(1.0, egui::Color32::from_gray(70)))
+                .corner_radius(6.0)
+                .inner_margin(egui::Margin::symmetric(8, 6))
+                .show(ui, |ui| {
+                    ui.set_width(bar_width);
+                    ui.horizontal(|ui| {
+                        let status = match bar.found {
+                            Some(false) => "No matches",
+                            _ => "↵ older  ⇧↵ newer",
+                        };
+                        let status_color = match bar.found {
+                            Some(false) => egui::Color32::from_rgb(230, 110, 100),
+                            _ => egui::Color32::from_gray(140),
+                        };
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut bar.query)
+                                .hint_text("Find in scrollback")
+                                .desired_width(bar_width - 120.0 * scale),
+                        );
+                        ui.label(egui::RichText::new(status).size(11.0 * scale).color(status_color));
+
+                        if bar.request_focus {
+                            response.request_focus();
+                            bar.request_focus = false;
+                        } else if response.changed() {
+                            action = Some(SearchAction::Search(bar.query.clone()));
+                        } else if response.lost_focus() {
+                            let (enter, shift) =
+                                ui.input(|i| (i.key_pressed(egui::Key::Enter), i.modifiers.shift));
+                            action = Some(if enter {
+                                // Enter drops focus from a single-line field; keep typing in the bar
+                                bar.request_focus = true;
+                                SearchAction::Next { older: !shift }
+                            } else {
+                                SearchAction::Close
+                            });
+                        }
+                    });
+                });
+        });
+
+    action
+}
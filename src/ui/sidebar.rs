@@ -1,11 +1,13 @@
-use crate::config::{IconConfig, SidebarConfig, UiConfig};
-use crate::terminal::TerminalPanel;
+use crate::config::{hex_to_color32, IconConfig, SidebarConfig, UiConfig};
+use crate::terminal::{SshHealth, TerminalPanel};
+use crate::util::activity::Indicator;
+use crate::ui::indicators;
 use crate::util::icons;
-use crate::util::layout;
+use crate::util::labels::{LabelCache, LabelSource};
+use crate::util::layout::{self, RowHeight};
 use crate::workspace::Workspace;
 use eframe::egui;
-use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Result of sidebar interaction
 pub enum SidebarAction {
@@ -13,8 +15,77 @@ pub enum SidebarAction {
     SwitchWorkspace(usize),
     /// A terminal was clicked (switch workspace and focus terminal)
     FocusTerminal { workspace: usize, terminal: usize },
+    /// An inline title edit was committed (empty title clears the custom title)
+    RenameTerminal { panel_id: u64, title: String },
+    /// A terminal's broadcast marker was clicked (add it to or remove it from the set)
+    ToggleBroadcast(u64),
+    /// An accent color was picked in a terminal's context menu (`None` clears it)
+    SetAccent { panel_id: u64, accent: Option<String> },
+    /// A group header was clicked (fold or unfold the group)
+    ToggleGroup(String),
+    /// "Close All Terminals" was picked in a group header's context menu
+    CloseGroup(String),
 }
 
+/// In-progress inline edit of a terminal's title (kept across frames)
+pub struct TitleEdit {
+    pub panel_id: u64,
+    pub input: String,
+    /// Whether the text field still needs to grab keyboard focus
+    pub request_focus: bool,
+}
+
+impl TitleEdit {
+    pub fn new(panel_id: u64, title: &str) -> Self {
+        Self {
+            panel_id,
+            input: title.to_string(),
+            request_focus: true,
+        }
+    }
+}
+
+/// Per-frame sidebar state owned by the app
+pub struct SidebarState<'a> {
+    /// Whether follow/move-to-spot letters are shown before entries
+    pub show_jump_letters: bool,
+    /// Broadcast mode: terminals of the active workspace show a marker to toggle
+    pub broadcast: bool,
+    pub title_edit: &'a mut Option<TitleEdit>,
+    /// Formatted entry text, reused across frames
+    pub labels: &'a mut LabelCache,
+    /// Entry heights measured when last drawn, used as placeholders for entries out of view
+    pub row_heights: &'a mut HashMap<u64, RowHeight>,
+    /// Groups folded to their header
+    pub collapsed_groups: &'a HashSet<String>,
+    /// Extra line under each terminal from `init.lua`'s `manse.on_sidebar`
+    pub script_text: &'a HashMap<u64, String>,
+    /// Current time (egui seconds), for fading activity dots
+    pub now: f64,
+}
+
+/// Terminal count above which entries scrolled out of view are replaced by empty space
+/// of their last height instead of being laid out every frame
+const VIRTUALIZE_MIN_TERMINALS: usize = 50;
+
+/// Extra indentation of the workspaces in a group
+const GROUP_INDENT: f32 = 10.0;
+
+/// Radius of the activity dot before a terminal entry
+const ACTIVITY_DOT_RADIUS: f32 = 2.5;
+
+/// Accent colors offered in a terminal's context menu
+const ACCENTS: [(&str, &str); 8] = [
+    ("Red", "#e06c75"),
+    ("Orange", "#d19a66"),
+    ("Yellow", "#e5c07b"),
+    ("Green", "#98c379"),
+    ("Teal", "#56b6c2"),
+    ("Blue", "#61afef"),
+    ("Purple", "#c678dd"),
+    ("Gray", "#8b929e"),
+];
+
 /// Build info captured at compile time
 pub const BUILD_GIT_HASH: &str = env!("BUILD_GIT_HASH");
 pub const BUILD_TIME: &str = env!("BUILD_TIME");
@@ -26,13 +97,15 @@ pub fn render(
     workspaces: &[Workspace],
     active_workspace: usize,
     panels: &HashMap<u64, TerminalPanel>,
-    show_jump_letters: bool,
+    state: &mut SidebarState,
     config: &SidebarConfig,
     icons: &IconConfig,
     ui_colors: &UiConfig,
 ) -> Option<SidebarAction> {
     let mut action: Option<SidebarAction> = None;
     let mut global_term_idx: usize = 0;
+    let show_jump_letters = state.show_jump_letters;
+    let virtualize = panels.len() > VIRTUALIZE_MIN_TERMINALS;
 
     // Reserve space for footer at bottom
     let footer_height = 24.0;
@@ -45,9 +118,38 @@ pub fn render(
         .show(ui, |ui| {
             ui.add_space(10.0);
 
+            let mut shown_group: Option<&str> = None;
             for (ws_idx, ws) in workspaces.iter().enumerate() {
+                // Emptied workspaces are listed in their own section below
+                if ws.is_lingering() {
+                    continue;
+                }
                 let is_active_workspace = ws_idx == active_workspace;
 
+                // Members of a group follow each other; the first one brings the header
+                let group = ws.group.as_deref();
+                if let Some(name) = group.filter(|_| group != shown_group) {
+                    if let Some(group_action) = group_header(
+                        ui,
+                        name,
+                        workspaces,
+                        active_workspace,
+                        panels,
+                        state.collapsed_groups.contains(name),
+                        config,
+                        ui_colors,
+                    ) {
+                        action = Some(group_action);
+                    }
+                }
+                shown_group = group;
+                if group.is_some_and(|group| state.collapsed_groups.contains(group)) {
+                    // Hidden entries keep their jump letters
+                    global_term_idx += ws.panel_order.len();
+                    continue;
+                }
+                let indent = if group.is_some() { GROUP_INDENT } else { 0.0 };
+
                 // Workspace name (clickable)
                 let ws_color = if is_active_workspace {
                     ui_colors.sidebar_text
@@ -56,16 +158,17 @@ pub fn render(
                 };
 
                 ui.horizontal(|ui| {
-                    ui.add_space(12.0);
-                    let response = ui.add(
-                        egui::Label::new(
-                            egui::RichText::new(&ws.name)
-                                .size(config.workspace_font_size + 2.0)
-                                .strong()
-                                .color(ws_color),
-                        )
-                        .sense(egui::Sense::click()),
-                    );
+                    ui.add_space(12.0 + indent);
+                    let mut name = egui::RichText::new(&ws.name)
+                        .size(config.workspace_font_size + 2.0)
+                        .strong()
+                        .color(ws_color);
+                    // Scratch terminals are throwaway; set the workspace apart
+                    if ws.is_scratch() {
+                        name = name.italics();
+                    }
+                    let response =
+                        ui.add(egui::Label::new(name).sense(egui::Sense::click()));
                     if response.clicked() {
                         action = Some(SidebarAction::SwitchWorkspace(ws_idx));
                     }
@@ -75,14 +178,51 @@ pub fn render(
 
                 // Terminals in this workspace (indented under workspace header)
                 ui.horizontal(|ui| {
-                    ui.add_space(16.0);
+                    ui.add_space(16.0 + indent);
                     ui.vertical(|ui| {
                         for (term_idx, &id) in ws.panel_order.iter().enumerate() {
                             if let Some(panel) = panels.get(&id) {
+                                let editing = state
+                                    .title_edit
+                                    .as_ref()
+                                    .is_some_and(|edit| edit.panel_id == id);
+
+                                // Text is formatted once and reused until the terminal's inputs change
+                                let label = state.labels.get(
+                                    id,
+                                    &LabelSource {
+                                        title: panel.display_title(),
+                                        suffix: ws.title_suffixes.get(&id).map(String::as_str),
+                                        description: &panel.description,
+                                        cli_description: panel.cli_description.as_deref(),
+                                        jump_letter: show_jump_letters
+                                            .then(|| layout::index_to_letter(global_term_idx))
+                                            .flatten(),
+                                    },
+                                );
+
+                                let lines = 1
+                                    + usize::from(label.secondary.is_some())
+                                    + usize::from(label.tertiary.is_some())
+                                    + usize::from(state.script_text.contains_key(&id));
+
+                                // Out of view: reserve the entry's last height and skip it
+                                let last = state.row_heights.get(&id).copied();
+                                let width = ui.available_width();
+                                let cursor = ui.cursor().min;
+                                if let Some(height) = layout::row_placeholder(last, lines, virtualize, editing, |height| {
+                                    ui.is_rect_visible(egui::Rect::from_min_size(cursor, egui::vec2(width, height)))
+                                }) {
+                                    ui.allocate_space(egui::vec2(width, height));
+                                    global_term_idx += 1;
+                                    continue;
+                                }
+
                                 let is_focused =
                                     is_active_workspace && term_idx == ws.focused_index;
+                                let accent = panel.accent_color();
                                 let text_color = if is_focused {
-                                    ui_colors.focused_border
+                                    accent.unwrap_or(ui_colors.focused_border)
                                 } else {
                                     ui_colors.sidebar_text
                                 };
@@ -92,23 +232,11 @@ pub fn render(
                                     icons::detect_icon(panel.display_title(), icons)
                                 });
 
-                                // Title (with optional follow mode letter prefix)
-                                // Use Cow to avoid allocation when not in follow mode
-                                let title_text: Cow<str> = if show_jump_letters {
-                                    if let Some(letter) = layout::index_to_letter(global_term_idx) {
-                                        Cow::Owned(format!("{} {}", letter, panel.display_title()))
-                                    } else {
-                                        Cow::Borrowed(panel.display_title())
-                                    }
-                                } else {
-                                    Cow::Borrowed(panel.display_title())
-                                };
-
-                                // Background color for notified terminals (dark reddish)
+                                // Background color for notified terminals, else a tint of the accent
                                 let bg_color = if panel.notified {
-                                    Some(egui::Color32::from_rgb(60, 25, 25))
+                                    Some(ui_colors.notified_background)
                                 } else {
-                                    None
+                                    accent.map(|accent| accent.gamma_multiply(0.2))
                                 };
 
                                 // Wrap terminal entry in a frame if notified
@@ -118,51 +246,32 @@ pub fn render(
                                     .corner_radius(4.0);
 
                                 let frame_response = frame.show(ui, |ui| {
-                                    // Determine if we have a user-set description
-                                    let has_description = !panel.description.is_empty();
-                                    let has_cli_description = panel.cli_description.is_some();
-                                    let has_any_description =
-                                        has_description || has_cli_description;
-
-                                    // Primary text: description if set, otherwise title
-                                    let primary_text: Cow<str> = if has_description {
-                                        // Use in-app description as primary
-                                        if show_jump_letters {
-                                            if let Some(letter) =
-                                                layout::index_to_letter(global_term_idx)
-                                            {
-                                                Cow::Owned(format!(
-                                                    "{} {}",
-                                                    letter, &panel.description
-                                                ))
-                                            } else {
-                                                Cow::Borrowed(&panel.description)
-                                            }
-                                        } else {
-                                            Cow::Borrowed(&panel.description)
-                                        }
-                                    } else if has_cli_description {
-                                        // Use CLI description as primary
-                                        let cli_desc = panel.cli_description.as_ref().unwrap();
-                                        if show_jump_letters {
-                                            if let Some(letter) =
-                                                layout::index_to_letter(global_term_idx)
-                                            {
-                                                Cow::Owned(format!("{} {}", letter, cli_desc))
-                                            } else {
-                                                Cow::Borrowed(cli_desc.as_str())
-                                            }
-                                        } else {
-                                            Cow::Borrowed(cli_desc.as_str())
-                                        }
-                                    } else {
-                                        // No description, use title as primary
-                                        title_text.clone()
-                                    };
+                                    let has_any_description = label.secondary.is_some();
 
                                     // Render icon and primary text horizontally
                                     let response = ui
                                         .horizontal(|ui| {
+                                            if state.broadcast && is_active_workspace {
+                                                let (marker, color) = if panel.broadcast {
+                                                    ("◉", egui::Color32::from_rgb(200, 140, 30))
+                                                } else {
+                                                    ("○", ui_colors.sidebar_text_dim)
+                                                };
+                                                let marker = ui
+                                                    .add(
+                                                        egui::Label::new(
+                                                            egui::RichText::new(marker)
+                                                                .size(config.terminal_title_font_size)
+                                                                .color(color),
+                                                        )
+                                                        .sense(egui::Sense::click()),
+                                                    )
+                                                    .on_hover_text("Mirror typed input to this terminal");
+                                                if marker.clicked() {
+                                                    action = Some(SidebarAction::ToggleBroadcast(id));
+                                                }
+                                            }
+
                                             // Show icon in fixed-width container for uniform alignment
                                             let icon_text = icon;
                                             let icon_width = config.terminal_title_font_size * 1.5;
@@ -182,27 +291,75 @@ pub fn render(
                                                 },
                                             );
 
-                                            ui.add(
+                                            match panel.ssh.as_ref().map(|link| (link, &link.health)) {
+                                                Some((link, SshHealth::Connected)) => {
+                                                    ui.label(
+                                                        egui::RichText::new(link.session.to_string())
+                                                            .size(config.description_font_size)
+                                                            .color(ui_colors.sidebar_text_dim),
+                                                    )
+                                                    .on_hover_text(&link.session.full_command);
+                                                }
+                                                Some((_, SshHealth::Disconnected(reason))) => {
+                                                    ui.label(
+                                                        egui::RichText::new("⚠")
+                                                            .size(config.terminal_title_font_size)
+                                                            .color(egui::Color32::from_rgb(230, 110, 100)),
+                                                    )
+                                                    .on_hover_text(format!("SSH disconnected: {}", reason));
+                                                }
+                                                None => {}
+                                            }
+
+                                            if editing && !has_any_description {
+                                                if let Some((panel_id, title)) = title_edit_field(
+                                                    ui,
+                                                    state.title_edit,
+                                                    config.terminal_title_font_size,
+                                                ) {
+                                                    action = Some(SidebarAction::RenameTerminal {
+                                                        panel_id,
+                                                        title,
+                                                    });
+                                                }
+                                                return None;
+                                            }
+                                            Some(ui.add(
                                                 egui::Label::new(
-                                                    egui::RichText::new(&*primary_text)
+                                                    egui::RichText::new(&label.primary)
                                                         .size(config.terminal_title_font_size)
                                                         .color(text_color),
                                                 )
                                                 .truncate()
                                                 .sense(egui::Sense::click()),
-                                            )
+                                            ))
                                         })
                                         .inner;
 
-                                    if response.clicked() {
-                                        action = Some(SidebarAction::FocusTerminal {
-                                            workspace: ws_idx,
-                                            terminal: term_idx,
+                                    if let Some(response) = response {
+                                        if response.clicked() {
+                                            action = Some(SidebarAction::FocusTerminal {
+                                                workspace: ws_idx,
+                                                terminal: term_idx,
+                                            });
+                                        }
+                                        response.context_menu(|ui| {
+                                            if let Some(accent) = accent_menu(ui, panel) {
+                                                action = Some(SidebarAction::SetAccent {
+                                                    panel_id: id,
+                                                    accent,
+                                                });
+                                            }
                                         });
+                                        // The primary row shows the title only when there is no description
+                                        if response.double_clicked() && !has_any_description {
+                                            *state.title_edit =
+                                                Some(TitleEdit::new(id, panel.display_title()));
+                                        }
                                     }
 
                                     // If we have a description, show title as secondary (subdued)
-                                    if has_any_description {
+                                    if let Some(title) = &label.secondary {
                                         let secondary_color = if is_focused {
                                             ui_colors.focused_border
                                         } else {
@@ -214,28 +371,51 @@ pub fn render(
                                                 ui.add_space(
                                                     config.terminal_title_font_size * 1.5 + 4.0,
                                                 );
-                                                ui.add(
+                                                if editing {
+                                                    if let Some((panel_id, title)) =
+                                                        title_edit_field(
+                                                            ui,
+                                                            state.title_edit,
+                                                            config.description_font_size,
+                                                        )
+                                                    {
+                                                        action =
+                                                            Some(SidebarAction::RenameTerminal {
+                                                                panel_id,
+                                                                title,
+                                                            });
+                                                    }
+                                                    return None;
+                                                }
+                                                Some(ui.add(
                                                     egui::Label::new(
-                                                        egui::RichText::new(panel.display_title())
+                                                        egui::RichText::new(title)
                                                             .size(config.description_font_size)
                                                             .color(secondary_color),
                                                     )
                                                     .truncate()
                                                     .sense(egui::Sense::click()),
-                                                )
+                                                ))
                                             })
                                             .inner;
-                                        if title_response.clicked() {
-                                            action = Some(SidebarAction::FocusTerminal {
-                                                workspace: ws_idx,
-                                                terminal: term_idx,
-                                            });
+                                        if let Some(title_response) = title_response {
+                                            if title_response.clicked() {
+                                                action = Some(SidebarAction::FocusTerminal {
+                                                    workspace: ws_idx,
+                                                    terminal: term_idx,
+                                                });
+                                            }
+                                            if title_response.double_clicked() {
+                                                *state.title_edit = Some(TitleEdit::new(
+                                                    id,
+                                                    panel.display_title(),
+                                                ));
+                                            }
                                         }
                                     }
 
                                     // Show CLI description as tertiary if in-app description is also set
-                                    if has_description && has_cli_description {
-                                        let cli_desc = panel.cli_description.as_ref().unwrap();
+                                    if let Some(cli_desc) = &label.tertiary {
                                         let desc_color = if is_focused {
                                             ui_colors.focused_border
                                         } else {
@@ -265,8 +445,75 @@ pub fn render(
                                             });
                                         }
                                     }
+
+                                    // Line from init.lua's manse.on_sidebar
+                                    if let Some(text) = state.script_text.get(&id) {
+                                        ui.horizontal(|ui| {
+                                            ui.add_space(config.terminal_title_font_size * 1.5 + 4.0);
+                                            ui.add(
+                                                egui::Label::new(
+                                                    egui::RichText::new(text)
+                                                        .size(config.description_font_size)
+                                                        .color(ui_colors.sidebar_text_dim),
+                                                )
+                                                .truncate(),
+                                            );
+                                        });
+                                    }
                                 });
 
+                                // Fading dot for recent output or a bell, left of the entry
+                                if let Some((indicator, alpha)) = panel.marks.indicator(state.now) {
+                                    let color = match indicator {
+                                        Indicator::Output => ui_colors.activity_dot,
+                                        Indicator::Bell => ui_colors.bell_dot,
+                                    };
+                                    let entry_rect = frame_response.response.rect;
+                                    let center = egui::pos2(
+                                        entry_rect.left() - ACTIVITY_DOT_RADIUS - 8.0,
+                                        entry_rect.top() + entry_rect.height().min(20.0) / 2.0,
+                                    );
+                                    ui.painter().circle_filled(
+                                        center,
+                                        ACTIVITY_DOT_RADIUS,
+                                        color.gamma_multiply(alpha),
+                                    );
+                                }
+
+                                // Shape cues so focus and notifications don't rely on color alone
+                                if ui_colors.shape_cues {
+                                    let entry_rect = frame_response.response.rect;
+                                    if is_focused {
+                                        indicators::focus_bar(
+                                            ui.painter(),
+                                            entry_rect,
+                                            ui_colors.focused_border,
+                                        );
+                                    }
+                                    if panel.notified {
+                                        indicators::notified_dot(
+                                            ui.painter(),
+                                            entry_rect,
+                                            ui_colors.sidebar_text,
+                                        );
+                                    }
+                                }
+
+                                // High contrast outlines the focused entry
+                                if is_focused && ui_colors.high_contrast {
+                                    ui.painter().rect_stroke(
+                                        frame_response.response.rect,
+                                        4.0,
+                                        egui::Stroke::new(1.5, ui_colors.focused_border),
+                                        egui::StrokeKind::Inside,
+                                    );
+                                }
+
+                                if virtualize {
+                                    let height = frame_response.response.rect.height();
+                                    state.row_heights.insert(id, RowHeight { height, lines });
+                                }
+
                                 // Also make the frame background clickable
                                 if frame_response.response.clicked() {
                                     action = Some(SidebarAction::FocusTerminal {
@@ -283,8 +530,20 @@ pub fn render(
 
                 ui.add_space(4.0);
             }
+
+            if let Some(idx) = empty_workspaces(ui, workspaces, active_workspace, config, ui_colors) {
+                action = Some(SidebarAction::SwitchWorkspace(idx));
+            }
         });
 
+    // Forget labels of closed terminals
+    if state.labels.len() > panels.len() {
+        state.labels.retain(|id| panels.contains_key(&id));
+    }
+    if state.row_heights.len() > panels.len() {
+        state.row_heights.retain(|id, _| panels.contains_key(id));
+    }
+
     // Footer with build info
     ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
         ui.add_space(6.0);
@@ -300,3 +559,253 @@ pub fn render(
 
     action
 }
+
+/// Header of a sidebar group: a fold marker, the name and how many of its terminals
+/// are notified. Clicking folds or unfolds the group; its context menu closes it.
+#[allow(clippy::too_many_arguments)]
+fn group_header(
+    ui: &mut egui::Ui,
+    group: &str,
+    workspaces: &[Workspace],
+    active_workspace: usize,
+    panels: &HashMap<u64, TerminalPanel>,
+    collapsed: bool,
+    config: &SidebarConfig,
+    ui_colors: &UiConfig,
+) -> Option<SidebarAction> {
+    let notified = workspaces
+        .iter()
+        .filter(|ws| ws.group.as_deref() == Some(group))
+        .flat_map(|ws| &ws.panel_order)
+        .filter(|id| panels.get(id).is_some_and(|panel| panel.notified))
+        .count();
+    let has_active = workspaces.get(active_workspace).is_some_and(|ws| ws.group.as_deref() == Some(group));
+    let color = if has_active {
+        ui_colors.sidebar_text
+    } else {
+        ui_colors.sidebar_text_dim
+    };
+
+    let mut action = None;
+    ui.horizontal(|ui| {
+        ui.add_space(8.0);
+        let marker = if collapsed { "▸" } else { "▾" };
+        let response = ui.add(
+            egui::Label::new(
+                egui::RichText::new(format!("{} {}", marker, group.to_uppercase()))
+                    .size(config.workspace_font_size)
+                    .strong()
+                    .color(color),
+            )
+            .sense(egui::Sense::click()),
+        );
+        // Rollup of the notifications inside, visible while folded
+        if notified > 0 {
+            egui::Frame::new()
+                .fill(ui_colors.notified_background)
+                .inner_margin(egui::Margin::symmetric(5, 0))
+                .corner_radius(6.0)
+                .show(ui, |ui| {
+                    ui.label(
+                        egui::RichText::new(notified.to_string())
+                            .size(config.description_font_size)
+                            .color(ui_colors.sidebar_text),
+                    )
+                })
+                .inner
+                .on_hover_text(format!("{} notified terminal(s) in {}", notified, group));
+        }
+        if response.clicked() {
+            action = Some(SidebarAction::ToggleGroup(group.to_string()));
+        }
+        response.context_menu(|ui| {
+            if ui.button("Close All Terminals…").clicked() {
+                action = Some(SidebarAction::CloseGroup(group.to_string()));
+                ui.close();
+            }
+        });
+    });
+    ui.add_space(4.0);
+    action
+}
+
+/// Section listing emptied workspaces that linger before being removed, so one emptied
+/// by accident can be reopened. Returns the index of a clicked workspace.
+fn empty_workspaces(
+    ui: &mut egui::Ui,
+    workspaces: &[Workspace],
+    active_workspace: usize,
+    config: &SidebarConfig,
+    ui_colors: &UiConfig,
+) -> Option<usize> {
+    if !workspaces.iter().any(Workspace::is_lingering) {
+        return None;
+    }
+    let mut clicked = None;
+    ui.add_space(8.0);
+    ui.horizontal(|ui| {
+        ui.add_space(12.0);
+        ui.label(
+            egui::RichText::new("Empty workspaces")
+                .size(config.description_font_size)
+                .color(ui_colors.sidebar_text_dim),
+        );
+    });
+    for (ws_idx, ws) in workspaces.iter().enumerate() {
+        if !ws.is_lingering() {
+            continue;
+        }
+        let color = if ws_idx == active_workspace {
+            ui_colors.sidebar_text
+        } else {
+            ui_colors.sidebar_text_dim
+        };
+        ui.horizontal(|ui| {
+            ui.add_space(16.0);
+            let response = ui
+                .add(
+                    egui::Label::new(
+                        egui::RichText::new(&ws.name)
+                            .size(config.workspace_font_size)
+                            .italics()
+                            .color(color),
+                    )
+                    .sense(egui::Sense::click()),
+                )
+                .on_hover_text("No terminals: removed soon unless one is opened here");
+            if response.clicked() {
+                clicked = Some(ws_idx);
+            }
+        });
+    }
+    clicked
+}
+
+/// Context menu of a terminal entry: accent color swatches, and a reset once one is
+/// set. Returns the pick (`Some(None)` clears the accent).
+fn accent_menu(ui: &mut egui::Ui, panel: &TerminalPanel) -> Option<Option<String>> {
+    let mut picked = None;
+    ui.label("Accent color");
+    ui.horizontal(|ui| {
+        for (name, hex) in ACCENTS {
+            let Some(color) = hex_to_color32(hex) else {
+                continue;
+            };
+            let swatch = egui::Button::new("")
+                .fill(color)
+                .min_size(egui::vec2(18.0, 18.0))
+                .selected(panel.accent.as_deref() == Some(hex));
+            if ui.add(swatch).on_hover_text(name).clicked() {
+                picked = Some(Some(hex.to_string()));
+            }
+        }
+    });
+    if panel.accent.is_some() && ui.button("Clear accent").clicked() {
+        picked = Some(None);
+    }
+    if picked.is_some() {
+        ui.close();
+    }
+    picked
+}
+
+/// Inline text field for an in-progress title edit.
+/// Returns `(panel_id, title)` when committed with Enter; any other loss of focus cancels.
+fn title_edit_field(
+    ui: &mut egui::Ui,
+    title_edit: &mut Option<TitleEdit>,
+    font_size: f32,
+) -> Option<(u64, String)> {
+    let edit = title_edit.as_mut()?;
+    let response = ui.add(
+        egui::TextEdit::singleline(&mut edit.input)
+            .font(egui::FontId::proportional(font_size))
+            .desired_width(f32::INFINITY),
+    );
+
+    if edit.request_focus {
+        response.request_focus();
+        edit.request_focus = false;
+        return None;
+    }
+
+    if response.lost_focus() {
+        let committed = ui.input(|i| i.key_pressed(egui::Key::Enter));
+        let edit = title_edit.take()?;
+        if committed {
+            return Some((edit.panel_id, edit.input.trim().to_string()));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::SpawnOptions;
+
+    /// 200 terminals in a window showing about 15: once their heights are known, only
+    /// the entries in view are laid out.
+    #[test]
+    fn virtualized_sidebar_lays_out_only_visible_rows() {
+        const TERMINALS: u64 = 200;
+        let ctx = egui::Context::default();
+        let (event_tx, _events) = std::sync::mpsc::channel();
+        let mut workspace = Workspace::new("virtualized");
+        let mut panels = HashMap::new();
+        for id in 0..TERMINALS {
+            let mut panel = TerminalPanel::deferred(id, &ctx, event_tx.clone(), SpawnOptions::default());
+            panel.title = format!("nvim src/module_{}.rs", id);
+            if id % 2 == 0 {
+                panel.description = format!("task {}", id);
+            }
+            panels.insert(id, panel);
+            workspace.panel_order.push(id);
+        }
+        let workspaces = [workspace];
+        let (config, icons, ui_colors) = (SidebarConfig::default(), IconConfig::default(), UiConfig::default());
+        let (mut title_edit, mut labels) = (None, LabelCache::default());
+        let (collapsed_groups, script_text) = (HashSet::new(), HashMap::new());
+        let input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(250.0, 600.0))),
+            ..Default::default()
+        };
+
+        let mut frame = |row_heights: &mut HashMap<u64, RowHeight>| {
+            let _ = ctx.run(input.clone(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let mut state = SidebarState {
+                        show_jump_letters: false,
+                        broadcast: false,
+                        title_edit: &mut title_edit,
+                        labels: &mut labels,
+                        row_heights,
+                        collapsed_groups: &collapsed_groups,
+                        script_text: &script_text,
+                        now: 0.0,
+                    };
+                    render(ui, &workspaces, 0, &panels, &mut state, &config, &icons, &ui_colors);
+                });
+            });
+        };
+        // No heights yet: every entry is laid out
+        let mut row_heights = HashMap::new();
+        frame(&mut row_heights);
+        assert_eq!(row_heights.len(), TERMINALS as usize);
+
+        // Mark the heights; an entry laid out again records its real height over the mark
+        let marked: HashMap<u64, RowHeight> = row_heights
+            .iter()
+            .map(|(&id, &row)| (id, RowHeight { height: row.height + 0.25, ..row }))
+            .collect();
+        let mut row_heights = marked.clone();
+        frame(&mut row_heights);
+        let laid_out = marked.iter().filter(|(id, row)| row_heights[id] != **row).count();
+        assert!(
+            laid_out > 0 && laid_out < TERMINALS as usize / 4,
+            "{} of {} entries laid out",
+            laid_out,
+            TERMINALS
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::config::{StatusBarConfig, UiConfig};
-use crate::terminal::TerminalPanel;
-use crate::util::layout::compute_minimap_viewport;
+use crate::terminal::{SshHealth, TerminalPanel};
+use crate::util::activity::Indicator;
+use crate::util::layout::{compute_minimap_viewport, minimap_fixed_rects, minimap_to_strip_x};
 use crate::workspace::Workspace;
 use eframe::egui;
 
@@ -12,18 +13,63 @@ pub struct MinimapState {
     pub scroll_offset: f32,
     /// Width of the visible viewport
     pub viewport_width: f32,
+    /// Recent output activity per terminal, 0.0 (idle) to 1.0, in `positions` order
+    pub heat: Vec<f32>,
+    /// Activity dot per terminal (kind and opacity), in `positions` order
+    pub indicators: Vec<Option<(Indicator, f32)>>,
+    /// Accent color per terminal, in `positions` order
+    pub accents: Vec<Option<egui::Color32>>,
+}
+
+/// How much larger the focused minimap marker is with `high_contrast` (pixels)
+const HIGH_CONTRAST_MARKER_GROWTH: f32 = 3.0;
+
+/// Minimap color of the busiest terminals
+const HOT_COLOR: egui::Color32 = egui::Color32::from_rgb(240, 120, 50);
+
+/// Minimap color of an unfocused terminal: its idle color warming towards
+/// `HOT_COLOR` with output activity.
+fn heat_color(idle: egui::Color32, heat: f32) -> egui::Color32 {
+    idle.lerp_to_gamma(HOT_COLOR, heat)
+}
+
+/// Result of status bar interaction
+pub enum StatusBarAction {
+    /// Pan the strip by this many pixels (scroll over the minimap)
+    PanBy(f32),
+    /// Center the strip on this x position (click on the minimap)
+    CenterOn(f32),
+}
+
+/// Modes flagged next to the terminal position, and the text segments on the right
+#[derive(Clone, Copy)]
+pub struct StatusModes<'a> {
+    /// A macro is being recorded
+    pub recording_macro: bool,
+    /// Typed input is broadcast to marked terminals
+    pub broadcasting: bool,
+    /// The focused terminal gets ⌘ shortcuts (raw key passthrough)
+    pub passthrough: bool,
+    /// Font zoom of the focused terminal: global, workspace and terminal zoom together
+    pub font_scale: f32,
+    /// Text from `init.lua`'s `manse.on_status`, then the `status_segments`
+    pub segments: &'a [&'a str],
 }
 
 /// Renders the status bar with terminal indicators and focused terminal info.
+/// Returns an action if the minimap was scrolled or clicked.
 pub fn render(
     ui: &mut egui::Ui,
     workspace: &Workspace,
     focused_panel: Option<&TerminalPanel>,
     minimap_state: Option<&MinimapState>,
+    modes: StatusModes,
     config: &StatusBarConfig,
     ui_colors: &UiConfig,
-) {
+) -> Option<StatusBarAction> {
     let num_panels = workspace.panel_order.len();
+    let segments = modes.segments;
+    let mut action = None;
 
     ui.horizontal(|ui| {
         ui.add_space(8.0);
@@ -35,15 +81,78 @@ pub fn render(
                 .color(ui_colors.status_bar_text),
         );
 
+        // Font zoom, when not at the configured size
+        if (modes.font_scale - 1.0).abs() > 0.001 {
+            ui.add_space(8.0);
+            ui.label(
+                egui::RichText::new(format!("{:.0}%", modes.font_scale * 100.0))
+                    .size(config.title_font_size)
+                    .color(ui_colors.status_bar_text),
+            );
+        }
+
+        if modes.recording_macro {
+            ui.add_space(8.0);
+            ui.label(
+                egui::RichText::new("● REC")
+                    .size(config.title_font_size)
+                    .color(egui::Color32::from_rgb(220, 60, 60)),
+            )
+            .on_hover_text("Recording macro (⌘⇧R to stop)");
+        }
+
+        if modes.broadcasting {
+            ui.add_space(8.0);
+            ui.label(
+                egui::RichText::new("⇶ BROADCAST")
+                    .size(config.title_font_size)
+                    .color(egui::Color32::from_rgb(200, 140, 30)),
+            )
+            .on_hover_text("Typing is mirrored to the terminals marked in the sidebar");
+        }
+
+        if modes.passthrough {
+            ui.add_space(8.0);
+            ui.label(
+                egui::RichText::new("⌨ RAW")
+                    .size(config.title_font_size)
+                    .color(egui::Color32::from_rgb(120, 170, 230)),
+            )
+            .on_hover_text("Shortcuts go to the program in this terminal (⌘⇧\\ to turn off)");
+        }
+
         // Focused terminal title and description
         if let Some(panel) = focused_panel {
             ui.add_space(8.0);
             ui.separator();
             ui.add_space(8.0);
 
+            // SSH session, checked in the background
+            if let Some(link) = &panel.ssh {
+                let (color, hover) = match &link.health {
+                    SshHealth::Connected => (ui_colors.focused_border, link.session.full_command.clone()),
+                    SshHealth::Disconnected(reason) => (
+                        egui::Color32::from_rgb(230, 110, 100),
+                        format!("SSH disconnected: {}", reason),
+                    ),
+                };
+                ui.label(
+                    egui::RichText::new(format!("⇄ {}", link.session))
+                        .size(config.title_font_size)
+                        .color(color),
+                )
+                .on_hover_text(hover);
+                ui.add_space(4.0);
+            }
+
             ui.add(
                 egui::Label::new(
-                    egui::RichText::new(panel.display_title())
+                    egui::RichText::new(panel.disambiguated_title(
+                        workspace
+                            .panel_order
+                            .get(workspace.focused_index)
+                            .and_then(|id| workspace.title_suffixes.get(id)),
+                    ))
                         .size(config.title_font_size)
                         .color(ui_colors.sidebar_text),
                 )
@@ -77,10 +186,14 @@ pub fn render(
             }
         }
 
-        // Right side: Minimap (use remaining space to push to right)
-        if config.show_minimap {
+        // Right side: Minimap, then the configured segments before it
+        if config.show_minimap || !segments.is_empty() {
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 ui.add_space(8.0);
+                if !config.show_minimap {
+                    segment_labels(ui, segments, config, ui_colors);
+                    return;
+                }
 
                 // Terminal minimap with fixed-size rectangles
                 let minimap_container_width = 160.0;
@@ -93,7 +206,7 @@ pub fn render(
 
                 let (response, mut painter) = ui.allocate_painter(
                     egui::vec2(minimap_container_width, 20.0),
-                    egui::Sense::hover(),
+                    egui::Sense::click(),
                 );
 
                 let container_rect = response.rect;
@@ -104,24 +217,32 @@ pub fn render(
 
                 if let Some(state) = minimap_state {
                     // Calculate fixed widths for each terminal based on ratio
-                    let mut term_rects: Vec<(f32, f32, bool)> = Vec::new(); // (x, width, is_focused)
-                    let mut x = 0.0;
-                    for (i, &(_, term_width)) in state.positions.iter().enumerate() {
-                        // Compute width ratio from pixel width
-                        let ratio = term_width / state.viewport_width;
-                        let rect_width = (ratio / min_ratio) * base_rect_width;
-                        term_rects.push((x, rect_width, i == workspace.focused_index));
-                        x += rect_width + rect_gap;
-                    }
-                    let total_content_width = if x > rect_gap { x - rect_gap } else { 0.0 };
+                    let fixed_rects = minimap_fixed_rects(
+                        &state.positions,
+                        state.viewport_width,
+                        base_rect_width,
+                        min_ratio,
+                        rect_gap,
+                    );
+                    let term_rects: Vec<(f32, f32, bool, f32)> = fixed_rects
+                        .iter()
+                        .enumerate()
+                        .map(|(i, &(x, w))| {
+                            let heat = state.heat.get(i).copied().unwrap_or(0.0);
+                            (x, w, i == workspace.focused_index, heat)
+                        })
+                        .collect();
+                    let accent = |i: usize| state.accents.get(i).copied().flatten();
+                    let total_content_width =
+                        fixed_rects.last().map(|(x, w)| x + w).unwrap_or(0.0);
 
                     // Calculate scroll offset to keep focused terminal visible
                     let minimap_scroll = if total_content_width > minimap_container_width {
                         // Find focused terminal position
                         let focused_x = term_rects
                             .iter()
-                            .find(|(_, _, focused)| *focused)
-                            .map(|(x, w, _)| x + w / 2.0)
+                            .find(|(_, _, focused, _)| *focused)
+                            .map(|(x, w, _, _)| x + w / 2.0)
                             .unwrap_or(0.0);
 
                         // Center focused terminal in view, clamped to valid range
@@ -131,22 +252,89 @@ pub fn render(
                         0.0
                     };
 
+                    // Scroll over the minimap pans the strip (Shift+wheel counts as horizontal)
+                    if response.hovered() {
+                        let (delta, shift) = ui.input(|i| (i.raw_scroll_delta, i.modifiers.shift));
+                        let pan = if delta.x != 0.0 {
+                            delta.x
+                        } else if shift {
+                            delta.y
+                        } else {
+                            0.0
+                        };
+                        if pan != 0.0 {
+                            action = Some(StatusBarAction::PanBy(-pan));
+                        }
+                        // Keep the wheel from also scrolling the focused terminal's scrollback
+                        ui.input_mut(|i| {
+                            i.events.retain(|e| !matches!(e, egui::Event::MouseWheel { .. }));
+                        });
+                    }
+
+                    // Clicking the minimap centers the strip on that point without changing focus
+                    if response.clicked() {
+                        if let Some(pos) = response.interact_pointer_pos() {
+                            let minimap_x = pos.x - container_rect.left() + minimap_scroll;
+                            let strip_x =
+                                minimap_to_strip_x(minimap_x, &fixed_rects, &state.positions);
+                            action = Some(StatusBarAction::CenterOn(strip_x));
+                        }
+                    }
+
                     // Draw terminal rectangles
-                    for (term_x, rect_width, is_focused) in &term_rects {
+                    for (i, (term_x, rect_width, is_focused, heat)) in term_rects.iter().enumerate() {
                         let screen_x = container_rect.left() + term_x - minimap_scroll;
 
-                        let term_rect = egui::Rect::from_min_size(
+                        let mut term_rect = egui::Rect::from_min_size(
                             egui::pos2(screen_x, minimap_y),
                             egui::vec2(*rect_width, minimap_height),
                         );
+                        // High contrast: the focused rectangle sticks out above and below
+                        if *is_focused && ui_colors.high_contrast {
+                            term_rect = term_rect.expand2(egui::vec2(1.0, HIGH_CONTRAST_MARKER_GROWTH));
+                        }
 
-                        let color = if *is_focused {
-                            ui_colors.focused_border
-                        } else {
-                            ui_colors.sidebar_text_dim
+                        let color = match accent(i) {
+                            Some(accent) if *is_focused => accent,
+                            Some(accent) => heat_color(accent.gamma_multiply(0.6), *heat),
+                            None if *is_focused => ui_colors.focused_border,
+                            None => heat_color(ui_colors.sidebar_text_dim, *heat),
                         };
 
                         painter.rect_filled(term_rect, corner_radius, color);
+                        if *is_focused && ui_colors.high_contrast {
+                            painter.rect_stroke(
+                                term_rect,
+                                corner_radius,
+                                egui::Stroke::new(1.5, ui_colors.status_bar_text),
+                                egui::StrokeKind::Outside,
+                            );
+                        }
+
+                        // Shape cue: punch a dot into the focused rectangle
+                        if *is_focused && ui_colors.shape_cues {
+                            painter.circle_filled(
+                                term_rect.center(),
+                                (minimap_height / 4.0).min(*rect_width / 4.0),
+                                ui_colors.status_bar_background,
+                            );
+                        }
+                    }
+
+                    // Activity dots above the rectangles of busy or ringing terminals
+                    for (i, (term_x, rect_width, _, _)) in term_rects.iter().enumerate() {
+                        let Some(Some((indicator, alpha))) = state.indicators.get(i) else {
+                            continue;
+                        };
+                        let color = match indicator {
+                            Indicator::Output => ui_colors.activity_dot,
+                            Indicator::Bell => ui_colors.bell_dot,
+                        };
+                        let center = egui::pos2(
+                            container_rect.left() + term_x + rect_width / 2.0 - minimap_scroll,
+                            minimap_y - 2.5,
+                        );
+                        painter.circle_filled(center, 1.5, color.gamma_multiply(*alpha));
                     }
 
                     // Draw viewport indicator
@@ -185,6 +373,11 @@ pub fn render(
                     for i in 0..num_panels {
                         let x = container_rect.left() + dot_radius + (i as f32 * dot_spacing);
                         let is_active = i == workspace.focused_index;
+                        let radius = if is_active && ui_colors.high_contrast {
+                            dot_radius + HIGH_CONTRAST_MARKER_GROWTH
+                        } else {
+                            dot_radius
+                        };
 
                         let color = if is_active {
                             ui_colors.focused_border
@@ -192,10 +385,40 @@ pub fn render(
                             ui_colors.sidebar_text_dim
                         };
 
-                        painter.circle_filled(egui::pos2(x, y), dot_radius, color);
+                        // Shape cue: the focused dot is drawn as a ring
+                        if is_active && ui_colors.shape_cues {
+                            painter.circle_stroke(
+                                egui::pos2(x, y),
+                                radius,
+                                egui::Stroke::new(1.5, color),
+                            );
+                        } else {
+                            painter.circle_filled(egui::pos2(x, y), radius, color);
+                        }
                     }
                 }
+
+                ui.add_space(8.0);
+                segment_labels(ui, segments, config, ui_colors);
             });
         }
     });
+
+    action
+}
+
+/// Status segments laid out right to left, so the first one ends up leftmost.
+fn segment_labels(ui: &mut egui::Ui, segments: &[&str], config: &StatusBarConfig, ui_colors: &UiConfig) {
+    for (i, text) in segments.iter().rev().enumerate() {
+        if i > 0 {
+            ui.add_space(4.0);
+            ui.separator();
+            ui.add_space(4.0);
+        }
+        ui.label(
+            egui::RichText::new(*text)
+                .size(config.title_font_size)
+                .color(ui_colors.status_bar_text),
+        );
+    }
 }
@@ -1,35 +1,90 @@
-use crate::config::Config;
-use crate::terminal::TerminalPanel;
+use crate::config::{Config, ThemeCache};
+use crate::terminal::{SshHealth, TerminalPanel};
+use crate::ui::indicators;
+use crate::util::{bytes, icons, layout};
 use eframe::egui;
-use egui_term::{FontSettings, TerminalFont, TerminalTheme, TerminalView};
+use egui_term::{FontSettings, TerminalFont, TerminalView};
 use std::collections::HashMap;
 
-pub struct TerminalStripState {
+/// Border of terminals receiving broadcast input
+const BROADCAST_COLOR: egui::Color32 = egui::Color32::from_rgb(200, 140, 30);
+
+/// Width of the grab area on a terminal's right border for resizing it (pixels)
+const RESIZE_HANDLE_WIDTH: f32 = 6.0;
+
+/// Height of the title bar above each terminal (`title_bars`)
+const TITLE_BAR_HEIGHT: f32 = 22.0;
+
+/// Button clicked in a terminal's title bar
+pub enum TitleBarAction {
+    Close(u64),
+    /// Zoom the terminal to full width, or back
+    ToggleZoom(u64),
+}
+
+pub struct TerminalStripState<'a> {
     pub scroll_offset: f32,
     pub focused_index: usize,
-    pub positions: Vec<(u64, f32, f32)>,
+    /// Global and workspace zoom applied to the configured terminal font size (each
+    /// terminal's own zoom comes on top)
+    pub font_scale: f32,
+    /// The workspace's cached positions: (panel_id, x_start, width)
+    pub positions: &'a [(u64, f32, f32)],
+    /// Number of pinned terminals at the front of `positions`, docked at the left edge
+    pub pinned: usize,
+    /// Debug HUD text drawn over the focused terminal
+    pub hud: Option<&'a str>,
+    /// Broadcast mode: capture what is typed into the focused terminal and mark the
+    /// terminals it is mirrored to
+    pub broadcast: bool,
+    /// Letter index of the workspace's first terminal while follow or move-to-spot mode
+    /// shows jump letters over the terminals
+    pub jump_letters: Option<usize>,
+}
+
+pub struct StripResponse {
+    /// Index of the terminal that was clicked, if any
+    pub clicked_index: Option<usize>,
+    /// Screen rect of the focused terminal, if it is on screen
+    pub focused_rect: Option<egui::Rect>,
+    /// Bytes typed or pasted into the focused terminal this frame (broadcast mode only)
+    pub typed: Vec<u8>,
+    /// A terminal border being dragged this frame
+    pub resize: Option<StripResize>,
+    /// Terminal whose "disconnected" banner was clicked to reconnect it
+    pub reconnect: Option<u64>,
+    /// Close or zoom button clicked in a title bar
+    pub title_bar: Option<TitleBarAction>,
+}
+
+/// Drag of a terminal's right border
+pub struct StripResize {
+    pub panel_id: u64,
+    /// Width under the pointer as a fraction of the viewport
+    pub ratio: f32,
+    /// The border was let go this frame
+    pub released: bool,
 }
 
-/// Returns the index of the terminal that was clicked, if any
 pub fn render(
     ui: &mut egui::Ui,
     config: &Config,
-    theme: &TerminalTheme,
+    themes: &ThemeCache,
     state: &TerminalStripState,
     panels: &mut HashMap<u64, TerminalPanel>,
     dialog_open: bool,
     viewport_width: f32,
     padded_height: f32,
     padding: f32,
-) -> Option<usize> {
+) -> StripResponse {
     let scroll_offset = state.scroll_offset;
     let focused_index = state.focused_index;
-    let terminal_positions = &state.positions;
+    let terminal_positions = state.positions;
 
     ui.add_space(padding);
 
-    let border_width = 2.0;
-    let terminal_font_size = config.terminal_font_size;
+    let high_contrast = config.ui_colors.high_contrast;
+    let border_width = border_width(config);
 
     let view_left = scroll_offset;
     let view_right = scroll_offset + viewport_width;
@@ -39,41 +94,76 @@ pub fn render(
     let base_y = terminal_area.top();
 
     let mut clicked_index = None;
+    let mut focused_rect = None;
+    let mut typed = Vec::new();
+    let mut resize = None;
+    let mut reconnect = None;
+    let mut title_bar = None;
+    let title_bar_height = if config.title_bars { TITLE_BAR_HEIGHT } else { 0.0 };
 
-    for (idx, &(id, term_x, term_width)) in terminal_positions.iter().enumerate() {
-        let term_right = term_x + term_width;
+    // Pinned terminals stay put at the left edge; the others scroll in the space beside
+    // them, clipped so they slide under the docked ones
+    let spans: Vec<(f32, f32)> = terminal_positions.iter().map(|&(_, x, width)| (x, width)).collect();
+    let pinned = state.pinned.min(spans.len());
+    let (docked_width, scrolling) = layout::split_docked(&spans, pinned);
+    let mut scrolling_ui = ui.new_child(egui::UiBuilder::new().max_rect(terminal_area));
+    scrolling_ui.set_clip_rect(ui.clip_rect().intersect(terminal_area.with_min_x(base_x + docked_width)));
 
-        if term_right < view_left || term_x > view_right {
-            continue;
-        }
+    let scrolling_range =
+        layout::visible_range(&scrolling, scroll_offset, viewport_width - docked_width, config.strip_overscan);
+    for idx in scrolling_range.map(|idx| idx + pinned).chain(0..pinned) {
+        let (id, term_x, term_width) = terminal_positions[idx];
+        let docked = idx < pinned;
+        // Overscan terminals are rendered off-screen (clipped) so they are ready to scroll in
+        let on_screen = docked || (term_x + term_width >= view_left + docked_width && term_x <= view_right);
+        let ui: &mut egui::Ui = if docked { &mut *ui } else { &mut scrolling_ui };
 
         if let Some(panel) = panels.get_mut(&id) {
             let is_focused = idx == focused_index;
+            // Sized per width (`font_size_by_width`); the view resizes the PTY grid to match
+            let terminal_font_size = config.font_size_for(panel.width_ratio) * state.font_scale * panel.font_scale;
+            let scheme = themes.get(panel.color_scheme.as_deref());
 
-            let screen_x = base_x + term_x - scroll_offset;
+            let screen_x = base_x + term_x - if docked { 0.0 } else { scroll_offset };
             let rect = egui::Rect::from_min_size(
                 egui::pos2(screen_x, base_y),
                 egui::vec2(term_width, padded_height),
             );
+            // The terminal itself, below the title bar if there is one
+            let content_rect = egui::Rect::from_min_max(
+                egui::pos2(rect.left(), rect.top() + title_bar_height),
+                rect.max,
+            );
 
-            let mut child_ui = ui.new_child(egui::UiBuilder::new().max_rect(rect));
+            let mut child_ui = ui.new_child(egui::UiBuilder::new().max_rect(content_rect));
+            let visible_rect = rect.intersect(ui.clip_rect());
 
-            let pad = egui::Margin::symmetric(config.terminal_padding_x as i8, config.terminal_padding_y as i8);
+            // The high-contrast ring is painted over the panel instead of being a frame stroke
+            let ring = if high_contrast { border_width } else { 0.0 };
+            let pad = egui::Margin::symmetric(
+                (config.terminal_padding_x + ring) as i8,
+                (config.terminal_padding_y + ring) as i8,
+            );
             let base_frame = egui::Frame::NONE
                 .inner_margin(pad)
-                .fill(config.terminal_background());
-            let frame = if is_focused {
-                base_frame.stroke(egui::Stroke::new(border_width, config.ui_colors.focused_border))
+                .fill(scheme.background);
+            let focus_color = panel.accent_color().unwrap_or(config.ui_colors.focused_border);
+            let focus_stroke = egui::Stroke::new(border_width, focus_color);
+            let frame = if is_focused && !config.ui_colors.shape_cues && !high_contrast {
+                base_frame.stroke(focus_stroke)
             } else {
                 base_frame
             };
 
-            let inner_width = term_width - border_width * 2.0 - config.terminal_padding_x * 2.0;
-            let inner_height = padded_height - border_width * 2.0 - config.terminal_padding_y * 2.0;
+            let inner_width = term_width - horizontal_chrome(config);
+            let inner_height = padded_height
+                - title_bar_height
+                - border_width * 2.0
+                - config.terminal_padding_y * 2.0;
 
             // Check if a primary click happened in this terminal's rect
-            let was_clicked = child_ui.input(|i| {
-                i.pointer.primary_clicked() && rect.contains(i.pointer.interact_pos().unwrap_or_default())
+            let was_clicked = on_screen && child_ui.input(|i| {
+                i.pointer.primary_clicked() && visible_rect.contains(i.pointer.interact_pos().unwrap_or_default())
             });
 
             if was_clicked {
@@ -81,24 +171,321 @@ pub fn render(
             }
 
             frame.show(&mut child_ui, |ui| {
+                if panel.deferred.is_some() {
+                    let size = egui::vec2(inner_width, inner_height);
+                    deferred_placeholder(ui, size, panel, config.ui_colors.sidebar_text_dim);
+                    return;
+                }
                 let font = TerminalFont::new(FontSettings {
                     font_type: egui::FontId::monospace(terminal_font_size),
                 });
-                let term_view = TerminalView::new(ui, &mut panel.backend)
+                let mut term_view = TerminalView::new(ui, panel.backend.as_mut())
                     .set_focus(is_focused && !dialog_open)
                     .set_font(font)
-                    .set_theme(theme.clone())
-                    .set_size(egui::vec2(inner_width, inner_height));
+                    .set_theme(scheme.theme.clone())
+                    .set_size(egui::vec2(inner_width, inner_height))
+                    .copy_on_select(config.copy_on_select)
+                    .scroll_multiplier(panel.scroll_multiplier.unwrap_or(config.scroll.multiplier))
+                    .alternate_scroll(config.scroll.alternate_scroll);
+                if is_focused && state.broadcast {
+                    term_view = term_view.mirror_input(&mut typed);
+                }
                 let response = ui.add(term_view);
 
                 if is_focused && !dialog_open {
                     response.request_focus();
                 }
             });
+
+            if config.title_bars {
+                let bar_rect = egui::Rect::from_min_size(rect.min, egui::vec2(term_width, TITLE_BAR_HEIGHT));
+                if let Some(action) = title_bar_ui(ui, bar_rect, id, panel, is_focused, config) {
+                    if !dialog_open {
+                        title_bar = Some(action);
+                    }
+                }
+            }
+
+            if is_focused {
+                focused_rect = Some(content_rect);
+            }
+
+            if is_focused && high_contrast {
+                indicators::double_border(ui.painter(), rect, focus_color);
+            } else if is_focused && config.ui_colors.shape_cues {
+                indicators::striped_border(ui.painter(), rect, focus_stroke);
+            }
+
+            if state.broadcast && panel.broadcast && !is_focused {
+                let stroke = egui::Stroke::new(border_width, BROADCAST_COLOR);
+                ui.painter().rect_stroke(rect, 0.0, stroke, egui::StrokeKind::Inside);
+            }
+
+            if panel.paused {
+                paused_banner(ui, content_rect, panel);
+            }
+
+            scrollback_indicator(ui, content_rect, panel, config.ui_colors.focused_border);
+
+            if let Some(SshHealth::Disconnected(reason)) = panel.ssh.as_ref().map(|link| &link.health) {
+                let host = panel.ssh.as_ref().map(|link| link.session.to_string()).unwrap_or_default();
+                if disconnected_banner(ui, content_rect, id, &host, reason) && !dialog_open {
+                    reconnect = Some(id);
+                }
+            }
+
+            if !dialog_open {
+                let handle_rect = egui::Rect::from_min_max(
+                    egui::pos2(rect.right() - RESIZE_HANDLE_WIDTH / 2.0, rect.top()),
+                    egui::pos2(rect.right() + RESIZE_HANDLE_WIDTH / 2.0, rect.bottom()),
+                );
+                let handle = ui
+                    .interact(handle_rect, ui.id().with(("resize_handle", id)), egui::Sense::drag())
+                    .on_hover_cursor(egui::CursorIcon::ResizeHorizontal);
+                if handle.dragged() || handle.drag_stopped() {
+                    let pointer_x = ui.ctx().pointer_latest_pos().map_or(rect.right(), |pos| pos.x);
+                    resize = Some(StripResize {
+                        panel_id: id,
+                        ratio: (pointer_x - screen_x) / viewport_width,
+                        released: handle.drag_stopped(),
+                    });
+                }
+            }
+
+            if let Some(text) = state.hud.filter(|_| is_focused) {
+                debug_hud(ui, content_rect, text);
+            }
+
+            if let Some(letter) = state.jump_letters.and_then(|first| layout::index_to_letter(first + idx)) {
+                jump_hint(ui, content_rect, letter, config.ui_colors.focused_border);
+            }
         }
     }
 
+    // Edge of the docked terminals while others are scrolled under them
+    if docked_width > 0.0 && scroll_offset > 0.5 {
+        let x = base_x + docked_width;
+        ui.painter().vline(
+            x,
+            base_y..=base_y + padded_height,
+            egui::Stroke::new(1.0, config.ui_colors.sidebar_text_dim),
+        );
+    }
+
     ui.allocate_space(egui::vec2(viewport_width + padding * 2.0, padded_height));
 
-    clicked_index
+    StripResponse {
+        clicked_index,
+        focused_rect,
+        typed,
+        resize,
+        reconnect,
+        title_bar,
+    }
+}
+
+/// Width of the focus border around each terminal
+fn border_width(config: &Config) -> f32 {
+    if config.ui_colors.high_contrast { indicators::DOUBLE_BORDER_WIDTH } else { 2.0 }
+}
+
+/// Width a terminal's border and padding take from its content, left and right together
+pub fn horizontal_chrome(config: &Config) -> f32 {
+    border_width(config) * 2.0 + config.terminal_padding_x * 2.0
+}
+
+/// Draws a terminal's title bar: icon and title, with zoom and close buttons on the
+/// right. Returns the button clicked, if any.
+fn title_bar_ui(
+    ui: &mut egui::Ui,
+    bar: egui::Rect,
+    id: u64,
+    panel: &TerminalPanel,
+    is_focused: bool,
+    config: &Config,
+) -> Option<TitleBarAction> {
+    let colors = &config.ui_colors;
+    let painter = ui.painter_at(bar);
+    painter.rect_filled(bar, 0.0, colors.sidebar_background);
+
+    let button_size = egui::vec2(TITLE_BAR_HEIGHT, TITLE_BAR_HEIGHT);
+    let close_rect = egui::Rect::from_min_size(bar.right_top() - egui::vec2(button_size.x, 0.0), button_size);
+    let zoom_rect = close_rect.translate(egui::vec2(-button_size.x, 0.0));
+    let zoom_symbol = if panel.zoomed_from.is_some() { "⤡" } else { "⤢" };
+
+    let mut action = None;
+    for (rect, symbol, salt) in [(zoom_rect, zoom_symbol, "title_zoom"), (close_rect, "×", "title_close")] {
+        let response = ui
+            .interact(rect, ui.id().with((salt, id)), egui::Sense::click())
+            .on_hover_cursor(egui::CursorIcon::PointingHand);
+        let color = if response.hovered() { colors.sidebar_text } else { colors.sidebar_text_dim };
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            symbol,
+            egui::FontId::proportional(14.0),
+            color,
+        );
+        if response.clicked() {
+            action = Some(match salt {
+                "title_zoom" => TitleBarAction::ToggleZoom(id),
+                _ => TitleBarAction::Close(id),
+            });
+        }
+    }
+
+    let icon = panel
+        .icon
+        .as_deref()
+        .unwrap_or_else(|| icons::detect_icon(panel.display_title(), &config.icons));
+    let text_color = if is_focused { colors.focused_border } else { colors.sidebar_text };
+    let title_painter = ui.painter_at(bar.with_max_x(zoom_rect.left() - 4.0));
+    title_painter.text(
+        bar.left_center() + egui::vec2(8.0, 0.0),
+        egui::Align2::LEFT_CENTER,
+        format!("{} {}", icon, panel.display_title()),
+        egui::FontId::proportional(12.0),
+        text_color,
+    );
+    action
+}
+
+/// Child ui covering `rect` shifted by `offset_x` and faded to `opacity`, clipped to `rect`,
+/// for drawing a strip during a workspace switch animation.
+pub fn transition_ui(
+    ui: &mut egui::Ui,
+    rect: egui::Rect,
+    id_salt: &str,
+    offset_x: f32,
+    opacity: f32,
+) -> egui::Ui {
+    let mut child = ui.new_child(
+        egui::UiBuilder::new()
+            .id_salt(id_salt)
+            .max_rect(rect.translate(egui::vec2(offset_x, 0.0))),
+    );
+    child.set_clip_rect(rect.intersect(ui.clip_rect()));
+    child.set_opacity(opacity);
+    child
+}
+
+/// Draws the name of the workspace being peeked at across the top of the strip.
+pub fn peek_banner(ui: &egui::Ui, rect: egui::Rect, workspace: &str, ui_scale: f32) {
+    let painter = ui.painter_at(rect);
+    let galley = painter.layout_no_wrap(
+        format!("Peeking at {} — release to return", workspace),
+        egui::FontId::proportional(13.0 * ui_scale),
+        egui::Color32::WHITE,
+    );
+    let margin = egui::vec2(10.0, 4.0) * ui_scale;
+    let size = galley.size() + margin * 2.0;
+    let min = egui::pos2(rect.center().x - size.x / 2.0, rect.top() + 8.0 * ui_scale);
+    painter.rect_filled(egui::Rect::from_min_size(min, size), 4.0 * ui_scale, egui::Color32::from_black_alpha(200));
+    painter.galley(min + margin, galley, egui::Color32::WHITE);
+}
+
+/// Draws the debug HUD in the top-right corner of a terminal.
+fn debug_hud(ui: &egui::Ui, rect: egui::Rect, text: &str) {
+    let painter = ui.painter_at(rect);
+    let galley = painter.layout_no_wrap(
+        text.to_string(),
+        egui::FontId::monospace(11.0),
+        egui::Color32::from_rgb(140, 230, 140),
+    );
+    let margin = egui::vec2(6.0, 4.0);
+    let min = egui::pos2(
+        rect.right() - galley.size().x - margin.x * 2.0 - 8.0,
+        rect.top() + 8.0,
+    );
+    let background = egui::Rect::from_min_size(min, galley.size() + margin * 2.0);
+    painter.rect_filled(background, 4.0, egui::Color32::from_black_alpha(200));
+    painter.galley(min + margin, galley, egui::Color32::WHITE);
+}
+
+/// Draws a terminal's follow / move-to-spot letter large in its center.
+fn jump_hint(ui: &egui::Ui, rect: egui::Rect, letter: char, color: egui::Color32) {
+    let painter = ui.painter_at(rect);
+    let galley = painter.layout_no_wrap(
+        letter.to_string(),
+        egui::FontId::proportional(56.0),
+        egui::Color32::from_rgb(20, 20, 20),
+    );
+    let side = galley.size().x.max(galley.size().y) + 24.0;
+    let background = egui::Rect::from_center_size(rect.center(), egui::vec2(side, side));
+    painter.rect_filled(background, 10.0, color);
+    painter.galley(background.center() - galley.size() / 2.0, galley, egui::Color32::WHITE);
+}
+
+/// Draws the scrollback position along the right edge of a terminal scrolled up into
+/// its history.
+fn scrollback_indicator(ui: &egui::Ui, rect: egui::Rect, panel: &TerminalPanel, color: egui::Color32) {
+    let position = panel.backend.scrollback_position();
+    let Some((top, height)) =
+        layout::scrollback_thumb(position.offset, position.history, position.screen_lines)
+    else {
+        return;
+    };
+    let height = (rect.height() * height).max(12.0);
+    let y = (rect.top() + rect.height() * top).min(rect.bottom() - height);
+    let thumb = egui::Rect::from_min_size(egui::pos2(rect.right() - 6.0, y), egui::vec2(4.0, height));
+    ui.painter_at(rect).rect_filled(thumb, 2.0, color.gamma_multiply(0.8));
+}
+
+/// Draws the "disconnected" banner across the top of a terminal. Returns whether it was
+/// clicked to reconnect.
+fn disconnected_banner(ui: &mut egui::Ui, rect: egui::Rect, id: u64, host: &str, reason: &str) -> bool {
+    let banner = egui::Rect::from_min_size(rect.min, egui::vec2(rect.width(), 24.0));
+    let response = ui
+        .interact(banner, ui.id().with(("reconnect", id)), egui::Sense::click())
+        .on_hover_cursor(egui::CursorIcon::PointingHand);
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(banner, 0.0, egui::Color32::from_rgb(190, 70, 60));
+    painter.text(
+        banner.center(),
+        egui::Align2::CENTER_CENTER,
+        format!("DISCONNECTED from {} ({}) — click to reconnect", host, reason),
+        egui::FontId::proportional(13.0),
+        egui::Color32::WHITE,
+    );
+    response.clicked()
+}
+
+/// Draws the "output paused" banner across the top of a terminal.
+/// Stands in for a terminal whose shell has not started yet (`lazy_spawn`): where it
+/// will start, in dim text.
+fn deferred_placeholder(ui: &mut egui::Ui, size: egui::Vec2, panel: &TerminalPanel, color: egui::Color32) {
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let dir = panel
+        .current_working_directory
+        .as_ref()
+        .map(|dir| format!("\n{}", dir.display()))
+        .unwrap_or_default();
+    ui.painter_at(rect).text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        format!("Starts when shown{}", dir),
+        egui::FontId::proportional(13.0),
+        color,
+    );
+}
+
+fn paused_banner(ui: &egui::Ui, rect: egui::Rect, panel: &TerminalPanel) {
+    #[cfg(unix)]
+    let buffered = panel
+        .pending_output_bytes()
+        .map(|n| format!(" — {} buffered", bytes::format_bytes(n)))
+        .unwrap_or_default();
+    #[cfg(not(unix))]
+    let buffered = String::new();
+
+    let banner = egui::Rect::from_min_size(rect.min, egui::vec2(rect.width(), 24.0));
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(banner, 0.0, egui::Color32::from_rgb(200, 140, 30));
+    painter.text(
+        banner.center(),
+        egui::Align2::CENTER_CENTER,
+        format!("OUTPUT PAUSED{} (⌘⇧F to resume)", buffered),
+        egui::FontId::proportional(13.0),
+        egui::Color32::BLACK,
+    );
 }
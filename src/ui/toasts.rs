@@ -0,0 +1,35 @@
+//! Info, warning and error toasts, stacked at the top center of the window.
+
+use crate::util::toasts::{self, ToastLevel, Toasts};
+use eframe::egui;
+
+/// Renders the toasts in `queue`, oldest on top, each fading out as it expires.
+pub fn render(ctx: &egui::Context, queue: &Toasts, now: f64) {
+    egui::Area::new(egui::Id::new("toasts"))
+        .order(egui::Order::Foreground)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 16.0))
+        .interactable(false)
+        .show(ctx, |ui| {
+            ui.spacing_mut().item_spacing.y = 6.0;
+            for toast in queue.iter() {
+                let opacity = toasts::opacity(now - toast.time);
+                let accent = match toast.level {
+                    ToastLevel::Info => egui::Color32::from_rgb(100, 150, 255),
+                    ToastLevel::Warning => egui::Color32::from_rgb(230, 180, 60),
+                    ToastLevel::Error => egui::Color32::from_rgb(230, 90, 80),
+                };
+                egui::Frame::NONE
+                    .fill(egui::Color32::from_gray(30).gamma_multiply(opacity))
+                    .stroke(egui::Stroke::new(1.0, accent.gamma_multiply(opacity)))
+                    .corner_radius(6.0)
+                    .inner_margin(egui::Margin::symmetric(12, 8))
+                    .show(ui, |ui| {
+                        ui.set_max_width(520.0);
+                        ui.label(
+                            egui::RichText::new(&toast.text)
+                                .color(egui::Color32::from_gray(230).gamma_multiply(opacity)),
+                        );
+                    });
+            }
+        });
+}
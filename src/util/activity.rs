@@ -0,0 +1,91 @@
+//! Recent output and bells of a terminal, shown as a fading dot in the sidebar and
+//! on its minimap rectangle.
+
+/// Seconds an output dot takes to fade after the last output
+const OUTPUT_FADE_SECS: f64 = 3.0;
+/// Seconds a bell stays marked, fading over the last third
+const BELL_FADE_SECS: f64 = 10.0;
+
+/// What the activity dot of a terminal shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Indicator {
+    /// Produced output recently
+    Output,
+    /// Rang the bell recently (wins over output)
+    Bell,
+}
+
+/// Times (egui seconds) of a terminal's last output and last bell
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ActivityMarks {
+    last_output: Option<f64>,
+    last_bell: Option<f64>,
+}
+
+impl ActivityMarks {
+    /// Note output at `now` (seconds).
+    pub fn output(&mut self, now: f64) {
+        self.last_output = Some(now);
+    }
+
+    /// Note a bell at `now` (seconds).
+    pub fn bell(&mut self, now: f64) {
+        self.last_bell = Some(now);
+    }
+
+    /// Forget both marks (e.g. once the terminal is focused).
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// The dot to draw at `now` with its opacity (0.0..=1.0), or `None` once faded.
+    pub fn indicator(&self, now: f64) -> Option<(Indicator, f32)> {
+        let fade = |since: Option<f64>, secs: f64, solid: f64| {
+            let age = now - since?;
+            let alpha = 1.0 - ((age - solid) / (secs - solid)).clamp(0.0, 1.0);
+            (alpha > 0.0).then_some(alpha as f32)
+        };
+        fade(self.last_bell, BELL_FADE_SECS, BELL_FADE_SECS * 2.0 / 3.0)
+            .map(|alpha| (Indicator::Bell, alpha))
+            .or_else(|| {
+                fade(self.last_output, OUTPUT_FADE_SECS, 0.0).map(|alpha| (Indicator::Output, alpha))
+            })
+    }
+
+    /// Whether a dot is still fading (the UI keeps repainting until it is gone)
+    pub fn is_fading(&self, now: f64) -> bool {
+        self.indicator(now).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_dot_fades_out() {
+        let mut marks = ActivityMarks::default();
+        assert_eq!(marks.indicator(0.0), None);
+
+        marks.output(10.0);
+        assert_eq!(marks.indicator(10.0), Some((Indicator::Output, 1.0)));
+        let (_, alpha) = marks.indicator(11.5).unwrap();
+        assert!((alpha - 0.5).abs() < 1e-6);
+        assert_eq!(marks.indicator(10.0 + OUTPUT_FADE_SECS), None);
+    }
+
+    #[test]
+    fn bell_wins_over_output_until_it_fades() {
+        let mut marks = ActivityMarks::default();
+        marks.bell(0.0);
+        marks.output(5.0);
+        assert_eq!(marks.indicator(5.0), Some((Indicator::Bell, 1.0)));
+        assert!(matches!(marks.indicator(8.0), Some((Indicator::Bell, alpha)) if alpha < 1.0));
+
+        marks.output(BELL_FADE_SECS);
+        assert_eq!(marks.indicator(BELL_FADE_SECS), Some((Indicator::Output, 1.0)));
+
+        marks.clear();
+        assert!(!marks.is_fading(BELL_FADE_SECS));
+    }
+}
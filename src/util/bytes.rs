@@ -0,0 +1,169 @@
+//! Byte count formatting, rates and output activity.
+
+/// Format a byte count with a binary unit ("512 B", "4.0 KiB", "1.5 MiB").
+pub fn format_bytes(bytes: usize) -> String {
+    const KIB: usize = 1024;
+    const MIB: usize = 1024 * 1024;
+    match bytes {
+        0..KIB => format!("{} B", bytes),
+        KIB..MIB => format!("{:.1} KiB", bytes as f64 / KIB as f64),
+        _ => format!("{:.1} MiB", bytes as f64 / MIB as f64),
+    }
+}
+
+/// Rate of a growing byte counter, averaged over windows of at least `window` seconds.
+pub struct ByteRate {
+    window: f64,
+    /// Time and counter value at the start of the current window
+    start: Option<(f64, u64)>,
+    /// Bytes per second over the last complete window
+    rate: Option<f64>,
+}
+
+impl ByteRate {
+    pub fn new(window: f64) -> Self {
+        Self {
+            window,
+            start: None,
+            rate: None,
+        }
+    }
+
+    /// Feed the counter's `total` at time `now` (seconds); returns the latest rate.
+    pub fn update(&mut self, now: f64, total: u64) -> Option<f64> {
+        match self.start {
+            Some((_, start_total)) if total < start_total => {
+                // Counter replaced (different terminal): start over
+                self.start = Some((now, total));
+                self.rate = None;
+            }
+            Some((start, start_total)) if now - start >= self.window => {
+                self.rate = Some((total - start_total) as f64 / (now - start));
+                self.start = Some((now, total));
+            }
+            Some(_) => {}
+            None => self.start = Some((now, total)),
+        }
+        self.rate
+    }
+
+    /// Forget all samples (e.g. when measuring a different counter).
+    pub fn reset(&mut self) {
+        self.start = None;
+        self.rate = None;
+    }
+}
+
+/// Output rate drawn at full heat (bytes per second)
+const HOT_BYTES_PER_SEC: f64 = 256.0 * 1024.0;
+/// Seconds for a terminal at full heat to cool down once output stops
+const COOL_DOWN_SECS: f64 = 5.0;
+
+/// Recent output activity of a terminal as a heat from 0.0 (idle) to 1.0, for the
+/// minimap heatmap: it follows bursts right away and cools down over a few seconds.
+#[derive(Debug, Clone, Default)]
+pub struct ActivityHeat {
+    /// Time and counter value of the previous sample
+    last: Option<(f64, u64)>,
+    heat: f64,
+}
+
+impl ActivityHeat {
+    /// Feed the byte counter's `total` at time `now` (seconds).
+    pub fn update(&mut self, now: f64, total: u64) {
+        if let Some((last, last_total)) = self.last {
+            let elapsed = now - last;
+            if elapsed <= 0.0 {
+                return;
+            }
+            // A smaller total is a replaced counter: nothing new was read
+            let rate = total.saturating_sub(last_total) as f64 / elapsed;
+            let cooled = self.heat - elapsed / COOL_DOWN_SECS;
+            self.heat = rate_heat(rate).max(cooled).max(0.0);
+        }
+        self.last = Some((now, total));
+    }
+
+    pub fn heat(&self) -> f32 {
+        self.heat as f32
+    }
+}
+
+/// Heat of an output rate, on a log scale so a prompt redraw already shows while a
+/// build log is clearly hotter.
+fn rate_heat(bytes_per_sec: f64) -> f64 {
+    (bytes_per_sec.ln_1p() / HOT_BYTES_PER_SEC.ln_1p()).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_bytes_plain() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+    }
+
+    #[test]
+    fn format_bytes_kib() {
+        assert_eq!(format_bytes(1024), "1.0 KiB");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+    }
+
+    #[test]
+    fn format_bytes_mib() {
+        assert_eq!(format_bytes(1024 * 1024), "1.0 MiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 / 2), "1.5 MiB");
+    }
+
+    #[test]
+    fn byte_rate_over_window() {
+        let mut rate = ByteRate::new(1.0);
+        assert_eq!(rate.update(0.0, 100), None);
+        assert_eq!(rate.update(0.5, 600), None);
+        assert_eq!(rate.update(2.0, 2100), Some(1000.0));
+        // Kept until the next window completes
+        assert_eq!(rate.update(2.5, 2200), Some(1000.0));
+    }
+
+    #[test]
+    fn byte_rate_restarts_on_counter_drop() {
+        let mut rate = ByteRate::new(1.0);
+        rate.update(0.0, 5000);
+        rate.update(1.0, 6000);
+        assert_eq!(rate.update(1.5, 10), None);
+        assert_eq!(rate.update(2.5, 1010), Some(1000.0));
+        rate.reset();
+        assert_eq!(rate.update(3.0, 2000), None);
+    }
+
+    #[test]
+    fn heat_rises_with_output_and_cools_down() {
+        let mut activity = ActivityHeat::default();
+        activity.update(0.0, 0);
+        assert_eq!(activity.heat(), 0.0);
+
+        activity.update(0.5, 100);
+        let trickle = activity.heat();
+        assert!(trickle > 0.0);
+
+        activity.update(1.0, 100 + 4 * 1024 * 1024);
+        let burst = activity.heat();
+        assert!(burst > trickle);
+        assert!(burst <= 1.0);
+
+        activity.update(3.0, 100 + 4 * 1024 * 1024);
+        assert!(activity.heat() < burst);
+        activity.update(10.0, 100 + 4 * 1024 * 1024);
+        assert_eq!(activity.heat(), 0.0);
+    }
+
+    #[test]
+    fn heat_ignores_counter_drop() {
+        let mut activity = ActivityHeat::default();
+        activity.update(0.0, 5000);
+        activity.update(1.0, 10);
+        assert_eq!(activity.heat(), 0.0);
+    }
+}
@@ -0,0 +1,53 @@
+//! WCAG 2 contrast math for UI colors. Only tests use it, to check that the
+//! `high_contrast` palette meets its ratios.
+
+/// Minimum contrast of normal text against its background at WCAG level AAA
+pub const AAA_TEXT: f32 = 7.0;
+
+/// Minimum contrast of focus indicators and other non-text UI parts (WCAG 1.4.11)
+pub const NON_TEXT: f32 = 3.0;
+
+/// Relative luminance of an sRGB color, from 0 (black) to 1 (white).
+pub fn relative_luminance([r, g, b]: [u8; 3]) -> f32 {
+    let linear = |channel: u8| {
+        let c = channel as f32 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// Contrast ratio between two colors, from 1 (the same) to 21 (black and white).
+pub fn contrast_ratio(a: [u8; 3], b: [u8; 3]) -> f32 {
+    let (la, lb) = (relative_luminance(a), relative_luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn black_and_white_have_maximum_contrast() {
+        let ratio = contrast_ratio([0, 0, 0], [255, 255, 255]);
+        assert!((ratio - 21.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn contrast_is_symmetric_and_one_for_equal_colors() {
+        let (a, b) = ([30, 30, 30], [200, 200, 200]);
+        assert_eq!(contrast_ratio(a, b), contrast_ratio(b, a));
+        assert_eq!(contrast_ratio(a, a), 1.0);
+    }
+
+    #[test]
+    fn mid_grey_on_white_is_below_aaa() {
+        // #777777 on white is the classic 4.48:1 example
+        let ratio = contrast_ratio([0x77, 0x77, 0x77], [255, 255, 255]);
+        assert!((ratio - 4.48).abs() < 0.01);
+        assert!(ratio < AAA_TEXT);
+    }
+}
@@ -0,0 +1,97 @@
+//! Fuzzy matching for the command palette.
+
+/// Points for each matched character
+const MATCH: u32 = 1;
+/// Extra points when a match directly follows the previous one
+const CONSECUTIVE_BONUS: u32 = 5;
+/// Extra points when a match starts a word ("rt" → "Rename Terminal")
+const WORD_START_BONUS: u32 = 8;
+
+/// Score `text` against `query` when every query character appears in it in order,
+/// ignoring case and spaces in the query. Higher is better: consecutive characters and
+/// word starts count extra. `None` if it doesn't match; an empty query scores 0.
+pub fn score(query: &str, text: &str) -> Option<u32> {
+    let mut query = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .peekable();
+    let mut total = 0;
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+
+    for c in text.chars() {
+        let Some(&wanted) = query.peek() else {
+            break;
+        };
+        let matched = c.to_lowercase().eq(std::iter::once(wanted));
+        if matched {
+            query.next();
+            total += MATCH;
+            if previous_matched {
+                total += CONSECUTIVE_BONUS;
+            }
+            if previous.is_none_or(|p| !p.is_alphanumeric()) {
+                total += WORD_START_BONUS;
+            }
+        }
+        previous_matched = matched;
+        previous = Some(c);
+    }
+
+    query.peek().is_none().then_some(total)
+}
+
+/// Best `score` of `query` over several fields of one entry (e.g. a terminal's title
+/// and description), `None` if no field matches.
+pub fn best_score<'a>(query: &str, fields: impl IntoIterator<Item = &'a str>) -> Option<u32> {
+    fields.into_iter().filter_map(|field| score(query, field)).max()
+}
+
+/// Indices of the entries with a score, highest first. Ties keep their order.
+pub fn rank(scores: impl IntoIterator<Item = Option<f64>>) -> Vec<usize> {
+    let mut scored: Vec<(usize, f64)> = scores
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, score)| Some((index, score?)))
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored.into_iter().map(|(index, _)| index).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_subsequence_ignoring_case() {
+        assert!(score("newterm", "New Terminal").is_some());
+        assert!(score("NT", "new terminal").is_some());
+        assert!(score("new term", "New Terminal").is_some());
+        assert_eq!(score("tw", "New Terminal"), None);
+        assert_eq!(score("x", "New Terminal"), None);
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(score("", "Close Terminal"), Some(0));
+    }
+
+    #[test]
+    fn prefers_word_starts_and_runs() {
+        assert!(score("rt", "Rename Terminal").unwrap() > score("rt", "Start").unwrap());
+        assert!(score("term", "Terminal").unwrap() > score("term", "The Error Room").unwrap());
+    }
+
+    #[test]
+    fn best_score_takes_the_best_field() {
+        assert_eq!(best_score("api", ["zsh", "api server"]), score("api", "api server"));
+        assert_eq!(best_score("api", ["zsh", ""]), None);
+        assert_eq!(best_score("api", []), None);
+    }
+
+    #[test]
+    fn rank_sorts_by_score_and_keeps_ties_in_order() {
+        assert_eq!(rank([Some(1.0), None, Some(3.0), Some(1.0)]), vec![2, 0, 3]);
+    }
+}
@@ -0,0 +1,218 @@
+//! Recently pressed key chords for the keystroke overlay.
+
+use std::collections::VecDeque;
+
+/// Seconds a chord stays fully visible
+pub const HOLD_SECS: f64 = 1.0;
+/// Seconds a chord takes to fade out after the hold
+pub const FADE_SECS: f64 = 0.5;
+/// Most chords shown at once (older ones are dropped)
+pub const MAX_CHORDS: usize = 6;
+
+/// Which key presses the keystroke overlay shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeystrokeMode {
+    /// No overlay
+    Off,
+    /// Only chords with the command modifier (the window manager shortcuts) and function keys
+    Shortcuts,
+    /// Every key press
+    All,
+}
+
+impl KeystrokeMode {
+    /// Parse a config value ("off", "shortcuts", "all").
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "off" => Some(Self::Off),
+            "shortcuts" => Some(Self::Shortcuts),
+            "all" => Some(Self::All),
+            _ => None,
+        }
+    }
+
+    /// Next mode when cycling from the command palette.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Shortcuts,
+            Self::Shortcuts => Self::All,
+            Self::All => Self::Off,
+        }
+    }
+}
+
+/// Modifiers held during a key press
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChordModifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    /// The macOS Command key
+    pub command: bool,
+}
+
+/// Chord text in the style of the keybinding hints ("⌘⇧T", "⌃C", "Enter").
+pub fn format_chord(modifiers: ChordModifiers, key: &str) -> String {
+    let mut chord = String::new();
+    for (held, symbol) in [
+        (modifiers.ctrl, '⌃'),
+        (modifiers.alt, '⌥'),
+        (modifiers.command, '⌘'),
+        (modifiers.shift, '⇧'),
+    ] {
+        if held {
+            chord.push(symbol);
+        }
+    }
+    chord.push_str(key);
+    chord
+}
+
+/// Whether a press is shown in `mode` (`is_shortcut`: the platform command modifier was
+/// held or the key is a function key).
+pub fn is_shown(mode: KeystrokeMode, is_shortcut: bool) -> bool {
+    match mode {
+        KeystrokeMode::Off => false,
+        KeystrokeMode::Shortcuts => is_shortcut,
+        KeystrokeMode::All => true,
+    }
+}
+
+/// Whether `key_name` names a function key ("F1" through "F35").
+pub fn is_function_key(key_name: &str) -> bool {
+    key_name
+        .strip_prefix('F')
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Opacity of a chord pressed `age` seconds ago (1 during the hold, then fading to 0).
+pub fn opacity(age: f64) -> f32 {
+    if age <= HOLD_SECS {
+        1.0
+    } else {
+        (1.0 - (age - HOLD_SECS) / FADE_SECS).clamp(0.0, 1.0) as f32
+    }
+}
+
+/// A chord in the overlay, with repeats folded into a count
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keystroke {
+    pub chord: String,
+    /// Consecutive presses of the same chord
+    pub count: u32,
+    /// Time of the latest press, in seconds
+    pub time: f64,
+}
+
+/// Chords pressed recently, oldest first
+#[derive(Default)]
+pub struct KeystrokeLog {
+    entries: VecDeque<Keystroke>,
+}
+
+impl KeystrokeLog {
+    /// Record a press at `now`, folding it into the newest entry when it repeats a visible chord.
+    pub fn record(&mut self, chord: String, now: f64) {
+        if let Some(last) = self.entries.back_mut() {
+            if last.chord == chord && now - last.time < HOLD_SECS + FADE_SECS {
+                last.count += 1;
+                last.time = now;
+                return;
+            }
+        }
+        self.entries.push_back(Keystroke { chord, count: 1, time: now });
+        while self.entries.len() > MAX_CHORDS {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Drop chords that have fully faded by `now`.
+    pub fn prune(&mut self, now: f64) {
+        self.entries.retain(|k| now - k.time < HOLD_SECS + FADE_SECS);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Keystroke> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cmd_shift() -> ChordModifiers {
+        ChordModifiers { command: true, shift: true, ..Default::default() }
+    }
+
+    #[test]
+    fn parse_and_cycle_modes() {
+        assert_eq!(KeystrokeMode::parse("shortcuts"), Some(KeystrokeMode::Shortcuts));
+        assert_eq!(KeystrokeMode::parse("ALL"), None);
+        assert_eq!(KeystrokeMode::All.next(), KeystrokeMode::Off);
+    }
+
+    #[test]
+    fn chord_text() {
+        assert_eq!(format_chord(cmd_shift(), "T"), "⌘⇧T");
+        let ctrl_alt = ChordModifiers { ctrl: true, alt: true, ..Default::default() };
+        assert_eq!(format_chord(ctrl_alt, "C"), "⌃⌥C");
+        assert_eq!(format_chord(ChordModifiers::default(), "Enter"), "Enter");
+    }
+
+    #[test]
+    fn shortcuts_mode_filters_plain_keys() {
+        assert!(!is_shown(KeystrokeMode::Shortcuts, false));
+        assert!(is_shown(KeystrokeMode::Shortcuts, true));
+        assert!(is_shown(KeystrokeMode::All, false));
+        assert!(!is_shown(KeystrokeMode::Off, true));
+    }
+
+    #[test]
+    fn function_keys() {
+        assert!(is_function_key("F2"));
+        assert!(is_function_key("F12"));
+        assert!(!is_function_key("F"));
+        assert!(!is_function_key("Fn"));
+        assert!(!is_function_key("Enter"));
+    }
+
+    #[test]
+    fn opacity_holds_then_fades() {
+        assert_eq!(opacity(0.5), 1.0);
+        assert!((opacity(HOLD_SECS + FADE_SECS / 2.0) - 0.5).abs() < 1e-6);
+        assert_eq!(opacity(HOLD_SECS + FADE_SECS + 1.0), 0.0);
+    }
+
+    #[test]
+    fn log_folds_repeats_and_prunes() {
+        let mut log = KeystrokeLog::default();
+        log.record("⌘]".into(), 0.0);
+        log.record("⌘]".into(), 0.2);
+        log.record("⌘T".into(), 0.4);
+        let entries: Vec<_> = log.iter().map(|k| (k.chord.as_str(), k.count)).collect();
+        assert_eq!(entries, vec![("⌘]", 2), ("⌘T", 1)]);
+
+        log.prune(0.2 + HOLD_SECS + FADE_SECS);
+        assert_eq!(log.iter().count(), 1);
+        log.prune(10.0);
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn log_keeps_newest_chords() {
+        let mut log = KeystrokeLog::default();
+        for i in 0..MAX_CHORDS + 2 {
+            log.record(i.to_string(), 0.0);
+        }
+        assert_eq!(log.iter().count(), MAX_CHORDS);
+        assert_eq!(log.iter().next().unwrap().chord, "2");
+    }
+}
@@ -0,0 +1,233 @@
+//! Cached sidebar label text.
+
+use std::collections::HashMap;
+
+/// Inputs that determine a terminal's sidebar text
+pub struct LabelSource<'a> {
+    pub title: &'a str,
+    /// Disambiguation suffix appended to the title
+    pub suffix: Option<&'a str>,
+    /// In-app description (empty when unset)
+    pub description: &'a str,
+    pub cli_description: Option<&'a str>,
+    /// Follow/move-to-spot letter shown before the primary text
+    pub jump_letter: Option<char>,
+}
+
+/// Owned copy of the inputs a cached label was built from
+struct CachedSource {
+    title: String,
+    suffix: Option<String>,
+    description: String,
+    cli_description: Option<String>,
+    jump_letter: Option<char>,
+}
+
+impl CachedSource {
+    fn new(source: &LabelSource) -> Self {
+        Self {
+            title: source.title.to_string(),
+            suffix: source.suffix.map(str::to_string),
+            description: source.description.to_string(),
+            cli_description: source.cli_description.map(str::to_string),
+            jump_letter: source.jump_letter,
+        }
+    }
+
+    /// Compares field by field, which costs no allocation (unlike rebuilding the label).
+    fn matches(&self, source: &LabelSource) -> bool {
+        self.jump_letter == source.jump_letter
+            && self.title == source.title
+            && self.suffix.as_deref() == source.suffix
+            && self.description == source.description
+            && self.cli_description.as_deref() == source.cli_description
+    }
+}
+
+/// Formatted sidebar text for one terminal
+#[derive(Debug, Clone, PartialEq)]
+pub struct SidebarLabel {
+    /// Description if set, otherwise the title (prefixed with the jump letter)
+    pub primary: String,
+    /// Title, shown under the description when there is one
+    pub secondary: Option<String>,
+    /// CLI description, shown when an in-app description is also set
+    pub tertiary: Option<String>,
+}
+
+impl SidebarLabel {
+    pub fn build(source: &LabelSource) -> Self {
+        let title = match source.suffix {
+            Some(suffix) => format!("{} · {}", source.title, suffix),
+            None => source.title.to_string(),
+        };
+        let has_description = !source.description.is_empty();
+
+        let primary_text = if has_description {
+            source.description
+        } else {
+            source.cli_description.unwrap_or(&title)
+        };
+        let primary = match source.jump_letter {
+            Some(letter) => format!("{} {}", letter, primary_text),
+            None => primary_text.to_string(),
+        };
+
+        let has_any_description = has_description || source.cli_description.is_some();
+        Self {
+            primary,
+            secondary: has_any_description.then_some(title),
+            tertiary: source.cli_description.filter(|_| has_description).map(str::to_string),
+        }
+    }
+}
+
+/// Labels per terminal id, rebuilt only when a terminal's inputs change
+#[derive(Default)]
+pub struct LabelCache {
+    entries: HashMap<u64, (CachedSource, SidebarLabel)>,
+}
+
+impl LabelCache {
+    /// Label for terminal `id`, rebuilt if `source` differs from the cached inputs.
+    pub fn get(&mut self, id: u64, source: &LabelSource) -> &SidebarLabel {
+        self.get_with(id, source, SidebarLabel::build)
+    }
+
+    /// Like `get`, making missing or stale labels with `build`.
+    fn get_with(
+        &mut self,
+        id: u64,
+        source: &LabelSource,
+        mut build: impl FnMut(&LabelSource) -> SidebarLabel,
+    ) -> &SidebarLabel {
+        let entry = self
+            .entries
+            .entry(id)
+            .or_insert_with(|| (CachedSource::new(source), build(source)));
+        if !entry.0.matches(source) {
+            *entry = (CachedSource::new(source), build(source));
+        }
+        &entry.1
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Drop labels of terminals for which `keep` returns false.
+    pub fn retain(&mut self, mut keep: impl FnMut(u64) -> bool) {
+        self.entries.retain(|&id, _| keep(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source<'a>(title: &'a str, description: &'a str, cli: Option<&'a str>) -> LabelSource<'a> {
+        LabelSource {
+            title,
+            suffix: None,
+            description,
+            cli_description: cli,
+            jump_letter: None,
+        }
+    }
+
+    #[test]
+    fn title_only() {
+        let label = SidebarLabel::build(&source("zsh", "", None));
+        assert_eq!(label.primary, "zsh");
+        assert_eq!(label.secondary, None);
+        assert_eq!(label.tertiary, None);
+    }
+
+    #[test]
+    fn description_moves_title_to_secondary() {
+        let mut src = source("zsh", "api server", Some("from cli"));
+        src.suffix = Some("web");
+        src.jump_letter = Some('c');
+        let label = SidebarLabel::build(&src);
+        assert_eq!(label.primary, "c api server");
+        assert_eq!(label.secondary.as_deref(), Some("zsh · web"));
+        assert_eq!(label.tertiary.as_deref(), Some("from cli"));
+    }
+
+    #[test]
+    fn cli_description_is_primary_without_description() {
+        let label = SidebarLabel::build(&source("zsh", "", Some("build")));
+        assert_eq!(label.primary, "build");
+        assert_eq!(label.secondary.as_deref(), Some("zsh"));
+        assert_eq!(label.tertiary, None);
+    }
+
+    #[test]
+    fn cache_rebuilds_on_change() {
+        let mut cache = LabelCache::default();
+        assert_eq!(cache.get(1, &source("zsh", "", None)).primary, "zsh");
+        assert_eq!(cache.get(1, &source("vim", "", None)).primary, "vim");
+        let mut lettered = source("vim", "", None);
+        lettered.jump_letter = Some('a');
+        assert_eq!(cache.get(1, &lettered).primary, "a vim");
+    }
+
+    #[test]
+    fn cache_hit_reuses_label() {
+        let mut cache = LabelCache::default();
+        let first = cache.get(1, &source("zsh", "desc", None)).primary.as_ptr();
+        let second = cache.get(1, &source("zsh", "desc", None)).primary.as_ptr();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn cache_retain_drops_closed() {
+        let mut cache = LabelCache::default();
+        cache.get(1, &source("a", "", None));
+        cache.get(2, &source("b", "", None));
+        cache.retain(|id| id == 2);
+        assert_eq!(cache.len(), 1);
+    }
+
+    /// Labels built (each allocating its strings) for 60 terminals over 100 frames:
+    /// every frame without the cache, only on the first with it.
+    #[test]
+    fn bench_label_cache() {
+        const TERMINALS: u64 = 60;
+        const FRAMES: usize = 100;
+        let titles: Vec<String> = (0..TERMINALS).map(|i| format!("nvim src/module_{}.rs", i)).collect();
+        let descriptions: Vec<String> = (0..TERMINALS).map(|i| format!("task {}", i)).collect();
+        let sources = || {
+            (0..TERMINALS as usize).map(|i| LabelSource {
+                title: &titles[i],
+                suffix: (i % 3 == 0).then_some("api"),
+                description: if i % 2 == 0 { &descriptions[i] } else { "" },
+                cli_description: (i % 5 == 0).then_some("from cli"),
+                jump_letter: None,
+            })
+        };
+
+        let mut built = 0;
+        let mut cache = LabelCache::default();
+        for _ in 0..FRAMES {
+            for (id, src) in sources().enumerate() {
+                cache.get_with(id as u64, &src, |src| {
+                    built += 1;
+                    SidebarLabel::build(src)
+                });
+            }
+        }
+        assert_eq!(built, TERMINALS as usize);
+
+        // A changed title rebuilds that terminal's label only
+        let mut renamed: Vec<LabelSource> = sources().collect();
+        renamed[7].title = "htop";
+        for (id, src) in renamed.iter().enumerate() {
+            cache.get_with(id as u64, src, |src| {
+                built += 1;
+                SidebarLabel::build(src)
+            });
+        }
+        assert_eq!(built, TERMINALS as usize + 1);
+    }
+}
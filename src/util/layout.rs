@@ -5,6 +5,28 @@
 /// Scroll animation easing factor
 pub const SCROLL_EASING: f32 = 0.15;
 
+/// Maximum distance (pixels) the strip stretches past its edges
+pub const OVERSCROLL_MAX: f32 = 48.0;
+
+/// Overscroll applied when trying to move focus past the first/last terminal
+pub const OVERSCROLL_BUMP: f32 = 40.0;
+
+/// Easing factor for overscroll snapping back to rest
+pub const OVERSCROLL_EASING: f32 = 0.2;
+
+/// Fraction of the window height covered by the drop-down terminal
+pub const OVERLAY_HEIGHT_RATIO: f32 = 1.0 / 3.0;
+
+/// Duration (seconds) of the drop-down terminal slide animation
+pub const OVERLAY_SLIDE_SECS: f32 = 0.15;
+
+/// Workspace font scale change per zoom step
+pub const FONT_SCALE_STEP: f32 = 0.1;
+
+/// Smallest and largest workspace font scale
+pub const FONT_SCALE_MIN: f32 = 0.5;
+pub const FONT_SCALE_MAX: f32 = 3.0;
+
 /// Compute (x_position, width) for each panel given their widths.
 pub fn compute_positions(panel_widths: impl Iterator<Item = f32>) -> Vec<(f32, f32)> {
     let mut positions = Vec::new();
@@ -21,6 +43,15 @@ pub fn total_width(positions: &[(f32, f32)]) -> f32 {
     positions.last().map(|(x, w)| x + w).unwrap_or(0.0)
 }
 
+/// Width of a terminal showing `columns` columns of `cell_width`, with `chrome` for its
+/// border and padding. 0 columns gives 0 (no minimum).
+pub fn columns_width(columns: u32, cell_width: f32, chrome: f32) -> f32 {
+    if columns == 0 {
+        return 0.0;
+    }
+    columns as f32 * cell_width + chrome
+}
+
 /// Calculate scroll target to keep a terminal visible in viewport.
 ///
 /// Returns a new target scroll offset that ensures the terminal at `index`
@@ -74,24 +105,48 @@ pub fn is_animating(current: f32, target: f32) -> bool {
 
 /// Determine which panels are visible in the current viewport.
 ///
-/// Returns indices of panels that are at least partially visible.
+/// Returns indices of panels that are at least partially visible, plus up to
+/// `overscan` panels on either side. Panels are laid out left to right, so the
+/// visible ones are contiguous.
 pub fn visible_range(
     positions: &[(f32, f32)],
     scroll_offset: f32,
     viewport_width: f32,
-) -> impl Iterator<Item = usize> + '_ {
+    overscan: usize,
+) -> std::ops::Range<usize> {
     let view_left = scroll_offset;
     let view_right = scroll_offset + viewport_width;
+    // Panel is visible if it overlaps with viewport
+    let is_visible = |&(x, w): &(f32, f32)| x + w > view_left && x < view_right;
 
-    positions
-        .iter()
-        .enumerate()
-        .filter(move |(_, (x, w))| {
-            let right = x + w;
-            // Panel is visible if it overlaps with viewport
-            right > view_left && *x < view_right
-        })
-        .map(|(i, _)| i)
+    let Some(first) = positions.iter().position(is_visible) else {
+        return 0..0;
+    };
+    let last = positions.iter().rposition(is_visible).unwrap_or(first);
+    first.saturating_sub(overscan)..(last + 1 + overscan).min(positions.len())
+}
+
+/// Last measured height of a sidebar entry, which stands in for it while out of view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RowHeight {
+    pub height: f32,
+    /// Text lines the entry had (title, descriptions, script line)
+    pub lines: usize,
+}
+
+/// Height of empty space to put in place of a sidebar entry of `lines` text lines
+/// instead of laying it out, or `None` to lay it out. Only when virtualizing, never for
+/// the entry being edited, and only with a last height measured at as many lines (any
+/// other is stale) when `visible` says a rect that high at the cursor is out of view.
+pub fn row_placeholder(
+    last: Option<RowHeight>,
+    lines: usize,
+    virtualize: bool,
+    editing: bool,
+    visible: impl FnOnce(f32) -> bool,
+) -> Option<f32> {
+    let last = last.filter(|last| virtualize && !editing && last.lines == lines)?;
+    (!visible(last.height)).then_some(last.height)
 }
 
 /// Maximum number of follow mode targets (a-z)
@@ -99,11 +154,12 @@ pub const MAX_FOLLOW_TARGETS: usize = 26;
 
 /// Build a flat mapping of index (0-25) to (workspace_idx, terminal_idx).
 ///
-/// Given a list of terminal counts per workspace, returns coordinates for
-/// follow mode navigation. Limited to 26 entries (a-z).
-pub fn build_follow_targets(workspace_terminal_counts: &[usize]) -> Vec<(usize, usize)> {
+/// Given the terminal counts per workspace, returns coordinates for follow mode
+/// navigation. Limited to 26 entries (a-z): counts past those are not read, so the
+/// work does not grow with the number of terminals.
+pub fn build_follow_targets(workspace_terminal_counts: impl IntoIterator<Item = usize>) -> Vec<(usize, usize)> {
     let mut targets = Vec::new();
-    for (ws_idx, &count) in workspace_terminal_counts.iter().enumerate() {
+    for (ws_idx, count) in workspace_terminal_counts.into_iter().enumerate() {
         for term_idx in 0..count {
             if targets.len() >= MAX_FOLLOW_TARGETS {
                 return targets;
@@ -149,6 +205,209 @@ pub fn prev_ratio(ratios: &[f32], current: f32, epsilon: f32) -> Option<f32> {
     ratios.iter().rev().find(|&&r| r < current - epsilon).copied()
 }
 
+/// Find the ratio in a non-empty list closest to `current`, for snapping after a drag.
+pub fn nearest_ratio(ratios: &[f32], current: f32) -> f32 {
+    ratios
+        .iter()
+        .copied()
+        .min_by(|a, b| (a - current).abs().total_cmp(&(b - current).abs()))
+        .unwrap_or(current)
+}
+
+/// Width ratio giving `count` terminals equal shares of the viewport, no narrower than
+/// `min` (then they don't all fit at once).
+pub fn equal_ratio(count: usize, min: f32) -> f32 {
+    (1.0 / count.max(1) as f32).clamp(min.min(1.0), 1.0)
+}
+
+/// Font size configured as (ratio, size) for terminals `ratio` wide: the entry whose
+/// ratio is within `epsilon` of it, if any.
+pub fn font_size_for_ratio(sizes: &[(f32, f32)], ratio: f32, epsilon: f32) -> Option<f32> {
+    sizes
+        .iter()
+        .find(|&&(r, _)| (r - ratio).abs() <= epsilon)
+        .map(|&(_, size)| size)
+}
+
+/// Scrollback position indicator for a terminal scrolled `offset` lines up into `history`
+/// lines of scrollback, as (top, height) fractions of the terminal's height. `None` at the
+/// bottom, where no indicator is shown.
+pub fn scrollback_thumb(offset: usize, history: usize, screen_lines: usize) -> Option<(f32, f32)> {
+    let total = history + screen_lines;
+    if offset == 0 || total == 0 {
+        return None;
+    }
+    let top = history.saturating_sub(offset) as f32 / total as f32;
+    Some((top, screen_lines as f32 / total as f32))
+}
+
+/// Move `current` toward `target` by at most `step`, without overshooting.
+pub fn step_toward(current: f32, target: f32, step: f32) -> f32 {
+    if current < target {
+        (current + step).min(target)
+    } else {
+        (current - step).max(target)
+    }
+}
+
+/// Cubic ease-out curve for `t` in 0..=1 (fast start, gentle stop).
+pub fn ease_out_cubic(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    1.0 - (1.0 - t).powi(3)
+}
+
+/// Rubber-band resistance for scrolling past an edge.
+///
+/// Maps an unbounded excess distance to a displacement that approaches
+/// `max` asymptotically, preserving sign.
+pub fn rubber_band(excess: f32, max: f32) -> f32 {
+    if max <= 0.0 {
+        return 0.0;
+    }
+    let stretched = max * (1.0 - 1.0 / (excess.abs() / max + 1.0));
+    stretched.copysign(excess)
+}
+
+/// Font scale after `steps` zoom steps (negative zooms out), snapped to whole steps
+/// and clamped to `FONT_SCALE_MIN..=FONT_SCALE_MAX`.
+pub fn step_font_scale(scale: f32, steps: i32) -> f32 {
+    let snapped = (scale / FONT_SCALE_STEP).round() as i32 + steps;
+    (snapped as f32 * FONT_SCALE_STEP).clamp(FONT_SCALE_MIN, FONT_SCALE_MAX)
+}
+
+/// Clamp a scroll offset to the valid range for the given content.
+pub fn clamp_scroll(offset: f32, positions: &[(f32, f32)], viewport_width: f32) -> f32 {
+    let max_scroll = (total_width(positions) - viewport_width).max(0.0);
+    offset.clamp(0.0, max_scroll)
+}
+
+/// Split a strip whose first `pinned` terminals are docked at its left edge.
+///
+/// Returns the docked width and the positions of the remaining terminals relative to
+/// it. The scroll functions take those with `viewport_width` less the docked width; the
+/// scroll offsets they give apply to the strip unchanged.
+pub fn split_docked(positions: &[(f32, f32)], pinned: usize) -> (f32, Vec<(f32, f32)>) {
+    let pinned = pinned.min(positions.len());
+    let docked_width = total_width(&positions[..pinned]);
+    let scrolling = positions[pinned..].iter().map(|&(x, w)| (x - docked_width, w)).collect();
+    (docked_width, scrolling)
+}
+
+/// Lay out fixed-size minimap rectangles as (x, width) pairs.
+///
+/// Each rectangle's width is proportional to its terminal's width ratio, with the
+/// narrowest standard ratio (`min_ratio`) mapping to `base_width`.
+pub fn minimap_fixed_rects(
+    positions: &[(f32, f32)],
+    viewport_width: f32,
+    base_width: f32,
+    min_ratio: f32,
+    gap: f32,
+) -> Vec<(f32, f32)> {
+    let mut rects = Vec::with_capacity(positions.len());
+    let mut x = 0.0;
+    for &(_, term_width) in positions {
+        let ratio = term_width / viewport_width;
+        let width = (ratio / min_ratio) * base_width;
+        rects.push((x, width));
+        x += width + gap;
+    }
+    rects
+}
+
+/// Map an x coordinate in minimap space to the corresponding x in strip space.
+///
+/// Points inside a rectangle map proportionally into that terminal; points in a
+/// gap map to the boundary between terminals; points past either end clamp.
+pub fn minimap_to_strip_x(
+    minimap_x: f32,
+    rects: &[(f32, f32)],
+    positions: &[(f32, f32)],
+) -> f32 {
+    for (&(rx, rw), &(px, pw)) in rects.iter().zip(positions) {
+        if minimap_x < rx {
+            return px;
+        }
+        if minimap_x <= rx + rw {
+            let t = if rw > 0.0 { (minimap_x - rx) / rw } else { 0.0 };
+            return px + t * pw;
+        }
+    }
+    total_width(positions)
+}
+
+// Defined in ipc_protocol so `term_spawn` requests can carry it
+pub use ipc_protocol::TerminalPlacement;
+
+/// Animation played when the active workspace changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WorkspaceSwitchAnimation {
+    /// Switch instantly
+    Off,
+    /// Fade the incoming workspace in over the outgoing one
+    Crossfade,
+    /// Slide the strips horizontally (toward the later workspace in sidebar order)
+    Slide,
+}
+
+impl WorkspaceSwitchAnimation {
+    /// Parse an animation name ("off", "crossfade", "slide").
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "crossfade" => Some(Self::Crossfade),
+            "slide" => Some(Self::Slide),
+            _ => None,
+        }
+    }
+}
+
+/// Horizontal offsets and opacities of both workspaces during a switch animation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransitionFrame {
+    pub outgoing_x: f32,
+    pub outgoing_opacity: f32,
+    pub incoming_x: f32,
+    pub incoming_opacity: f32,
+}
+
+/// Frame of a workspace switch at `progress` (0..=1, eased here).
+///
+/// `forward` is true when the incoming workspace comes after the outgoing one,
+/// in which case slides move left; `width` is the distance a strip slides.
+pub fn transition_frame(
+    animation: WorkspaceSwitchAnimation,
+    progress: f32,
+    forward: bool,
+    width: f32,
+) -> TransitionFrame {
+    let t = ease_out_cubic(progress);
+    match animation {
+        WorkspaceSwitchAnimation::Off => TransitionFrame {
+            outgoing_x: 0.0,
+            outgoing_opacity: 0.0,
+            incoming_x: 0.0,
+            incoming_opacity: 1.0,
+        },
+        // The outgoing strip stays opaque underneath so the fade doesn't dip through black
+        WorkspaceSwitchAnimation::Crossfade => TransitionFrame {
+            outgoing_x: 0.0,
+            outgoing_opacity: 1.0,
+            incoming_x: 0.0,
+            incoming_opacity: t,
+        },
+        WorkspaceSwitchAnimation::Slide => {
+            let direction = if forward { -1.0 } else { 1.0 };
+            TransitionFrame {
+                outgoing_x: direction * t * width,
+                outgoing_opacity: 1.0,
+                incoming_x: -direction * (1.0 - t) * width,
+                incoming_opacity: 1.0,
+            }
+        }
+    }
+}
+
 /// Minimap rectangle for a single terminal.
 #[derive(Debug, Clone, PartialEq)]
 pub struct MinimapRect {
@@ -343,7 +602,7 @@ mod tests {
     #[test]
     fn visible_range_all_visible() {
         let positions = vec![(0.0, 100.0), (100.0, 100.0), (200.0, 100.0)];
-        let visible: Vec<_> = visible_range(&positions, 0.0, 400.0).collect();
+        let visible: Vec<_> = visible_range(&positions, 0.0, 400.0, 0).collect();
         assert_eq!(visible, vec![0, 1, 2]);
     }
 
@@ -351,7 +610,7 @@ mod tests {
     fn visible_range_partial() {
         let positions = vec![(0.0, 100.0), (100.0, 100.0), (200.0, 100.0), (300.0, 100.0)];
         // Viewport from 50 to 250 should see panels 0, 1, 2
-        let visible: Vec<_> = visible_range(&positions, 50.0, 200.0).collect();
+        let visible: Vec<_> = visible_range(&positions, 50.0, 200.0, 0).collect();
         assert_eq!(visible, vec![0, 1, 2]);
     }
 
@@ -359,32 +618,81 @@ mod tests {
     fn visible_range_scrolled() {
         let positions = vec![(0.0, 100.0), (100.0, 100.0), (200.0, 100.0), (300.0, 100.0)];
         // Viewport from 200 to 400 should see panels 2, 3
-        let visible: Vec<_> = visible_range(&positions, 200.0, 200.0).collect();
+        let visible: Vec<_> = visible_range(&positions, 200.0, 200.0, 0).collect();
         assert_eq!(visible, vec![2, 3]);
     }
 
     #[test]
     fn visible_range_empty() {
         let positions: Vec<(f32, f32)> = vec![];
-        let visible: Vec<_> = visible_range(&positions, 0.0, 100.0).collect();
+        let visible: Vec<_> = visible_range(&positions, 0.0, 100.0, 0).collect();
         assert!(visible.is_empty());
     }
 
+    #[test]
+    fn visible_range_overscan() {
+        let positions = vec![(0.0, 100.0), (100.0, 100.0), (200.0, 100.0), (300.0, 100.0), (400.0, 100.0)];
+        // Panel 2 is on screen; one neighbour on each side is pre-rendered
+        assert_eq!(visible_range(&positions, 200.0, 100.0, 1), 1..4);
+        // Clamped at the ends of the strip
+        assert_eq!(visible_range(&positions, 0.0, 150.0, 2), 0..4);
+        assert_eq!(visible_range(&positions, 400.0, 100.0, 3), 1..5);
+    }
+
+    #[test]
+    fn visible_range_nothing_on_screen_ignores_overscan() {
+        let positions = vec![(0.0, 100.0), (100.0, 100.0)];
+        assert!(visible_range(&positions, 500.0, 100.0, 2).is_empty());
+    }
+
+    #[test]
+    fn row_placeholder_only_out_of_view() {
+        let last = Some(RowHeight { height: 40.0, lines: 2 });
+        assert_eq!(row_placeholder(last, 2, true, false, |_| false), Some(40.0));
+        assert_eq!(row_placeholder(last, 2, true, false, |_| true), None);
+        // Visibility is checked at the entry's last height
+        assert_eq!(row_placeholder(last, 2, true, false, |height| height > 50.0), Some(40.0));
+    }
+
+    #[test]
+    fn row_placeholder_needs_a_current_height() {
+        // Never measured
+        assert_eq!(row_placeholder(None, 1, true, false, |_| false), None);
+        // Measured with another number of lines (a description was added since)
+        let last = Some(RowHeight { height: 20.0, lines: 1 });
+        assert_eq!(row_placeholder(last, 2, true, false, |_| false), None);
+    }
+
+    #[test]
+    fn row_placeholder_never_for_the_edited_row_or_few_terminals() {
+        let last = Some(RowHeight { height: 20.0, lines: 1 });
+        assert_eq!(row_placeholder(last, 1, true, true, |_| false), None);
+        assert_eq!(row_placeholder(last, 1, false, false, |_| false), None);
+    }
+
+    #[test]
+    fn follow_targets_stop_reading_counts_at_26() {
+        let mut read = 0;
+        let counts = std::iter::repeat(10).inspect(|_| read += 1).take(1000);
+        assert_eq!(build_follow_targets(counts).len(), 26);
+        assert_eq!(read, 3);
+    }
+
     #[test]
     fn follow_targets_empty() {
-        let targets = build_follow_targets(&[]);
+        let targets = build_follow_targets([]);
         assert!(targets.is_empty());
     }
 
     #[test]
     fn follow_targets_single_workspace() {
-        let targets = build_follow_targets(&[3]);
+        let targets = build_follow_targets([3]);
         assert_eq!(targets, vec![(0, 0), (0, 1), (0, 2)]);
     }
 
     #[test]
     fn follow_targets_multiple_workspaces() {
-        let targets = build_follow_targets(&[2, 3, 1]);
+        let targets = build_follow_targets([2, 3, 1]);
         assert_eq!(
             targets,
             vec![(0, 0), (0, 1), (1, 0), (1, 1), (1, 2), (2, 0)]
@@ -394,7 +702,7 @@ mod tests {
     #[test]
     fn follow_targets_caps_at_26() {
         // 30 terminals should be capped at 26
-        let targets = build_follow_targets(&[30]);
+        let targets = build_follow_targets([30]);
         assert_eq!(targets.len(), 26);
         assert_eq!(targets[0], (0, 0));
         assert_eq!(targets[25], (0, 25));
@@ -403,7 +711,7 @@ mod tests {
     #[test]
     fn follow_targets_caps_across_workspaces() {
         // 10 + 10 + 10 = 30, should cap at 26
-        let targets = build_follow_targets(&[10, 10, 10]);
+        let targets = build_follow_targets([10, 10, 10]);
         assert_eq!(targets.len(), 26);
         // First 10 from ws 0, next 10 from ws 1, only 6 from ws 2
         assert_eq!(targets[9], (0, 9));
@@ -479,6 +787,52 @@ mod tests {
         assert_eq!(prev_ratio(&ratios, 0.34, 0.01), None); // within epsilon of min
     }
 
+    #[test]
+    fn nearest_ratio_snaps_to_closest() {
+        let ratios = [0.25, 0.4, 0.6, 0.75, 1.0];
+        assert_eq!(nearest_ratio(&ratios, 0.3), 0.25);
+        assert_eq!(nearest_ratio(&ratios, 0.52), 0.6);
+        assert_eq!(nearest_ratio(&ratios, 0.1), 0.25);
+        assert_eq!(nearest_ratio(&ratios, 1.4), 1.0);
+    }
+
+    #[test]
+    fn nearest_ratio_empty_keeps_current() {
+        assert_eq!(nearest_ratio(&[], 0.42), 0.42);
+    }
+
+    #[test]
+    fn scrollback_thumb_hidden_at_bottom() {
+        assert_eq!(scrollback_thumb(0, 100, 50), None);
+    }
+
+    #[test]
+    fn scrollback_thumb_tracks_offset() {
+        assert_eq!(scrollback_thumb(100, 100, 50), Some((0.0, 50.0 / 150.0)));
+        assert_eq!(scrollback_thumb(50, 100, 50), Some((50.0 / 150.0, 50.0 / 150.0)));
+    }
+
+    #[test]
+    fn equal_ratio_splits_viewport() {
+        assert_eq!(equal_ratio(1, 0.25), 1.0);
+        assert_eq!(equal_ratio(4, 0.25), 0.25);
+        assert_eq!(equal_ratio(0, 0.25), 1.0);
+    }
+
+    #[test]
+    fn equal_ratio_respects_minimum() {
+        assert_eq!(equal_ratio(10, 0.333), 0.333);
+    }
+
+    #[test]
+    fn font_size_for_ratio_matches_within_epsilon() {
+        let sizes = [(0.333, 12.0), (1.0, 16.0)];
+        assert_eq!(font_size_for_ratio(&sizes, 1.0, 0.01), Some(16.0));
+        assert_eq!(font_size_for_ratio(&sizes, 0.335, 0.01), Some(12.0));
+        assert_eq!(font_size_for_ratio(&sizes, 0.5, 0.01), None);
+        assert_eq!(font_size_for_ratio(&[], 1.0, 0.01), None);
+    }
+
     // Minimap tests
 
     #[test]
@@ -572,4 +926,174 @@ mod tests {
         assert!((viewport.x - 0.5).abs() < 0.001);
         assert!((viewport.width - 0.5).abs() < 0.001);
     }
+
+    // Minimap scrubbing tests
+
+    #[test]
+    fn clamp_scroll_bounds() {
+        let positions = vec![(0.0, 200.0), (200.0, 200.0)];
+        assert_eq!(clamp_scroll(-50.0, &positions, 300.0), 0.0);
+        assert_eq!(clamp_scroll(50.0, &positions, 300.0), 50.0);
+        assert_eq!(clamp_scroll(500.0, &positions, 300.0), 100.0);
+        // Content narrower than viewport never scrolls
+        assert_eq!(clamp_scroll(50.0, &positions, 600.0), 0.0);
+    }
+
+    #[test]
+    fn columns_width_with_chrome() {
+        assert_eq!(columns_width(80, 8.0, 12.0), 652.0);
+        assert_eq!(columns_width(0, 8.0, 12.0), 0.0);
+    }
+
+    #[test]
+    fn split_docked_strip() {
+        let positions = vec![(0.0, 100.0), (100.0, 200.0), (300.0, 300.0)];
+        let (docked, scrolling) = split_docked(&positions, 1);
+        assert_eq!(docked, 100.0);
+        assert_eq!(scrolling, vec![(0.0, 200.0), (200.0, 300.0)]);
+        // Scrolling the last terminal into a 400px viewport beside the docked one
+        assert_eq!(scroll_target_for_visible(&scrolling, 1, 0.0, 400.0 - docked), 200.0);
+
+        assert_eq!(split_docked(&positions, 0), (0.0, positions.clone()));
+        assert_eq!(split_docked(&positions, 5), (600.0, Vec::new()));
+    }
+
+    #[test]
+    fn minimap_fixed_rects_scale_with_ratio() {
+        // Viewport 300: widths are 1/3 and full
+        let positions = vec![(0.0, 100.0), (100.0, 300.0)];
+        let rects = minimap_fixed_rects(&positions, 300.0, 10.0, 1.0 / 3.0, 2.0);
+        assert_eq!(rects.len(), 2);
+        assert!((rects[0].0 - 0.0).abs() < 0.001);
+        assert!((rects[0].1 - 10.0).abs() < 0.001);
+        assert!((rects[1].0 - 12.0).abs() < 0.001);
+        assert!((rects[1].1 - 30.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn minimap_to_strip_inside_rect() {
+        let positions = vec![(0.0, 100.0), (100.0, 300.0)];
+        let rects = vec![(0.0, 10.0), (12.0, 30.0)];
+        assert!((minimap_to_strip_x(5.0, &rects, &positions) - 50.0).abs() < 0.001);
+        assert!((minimap_to_strip_x(27.0, &rects, &positions) - 250.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn minimap_to_strip_gap_and_ends() {
+        let positions = vec![(0.0, 100.0), (100.0, 300.0)];
+        let rects = vec![(0.0, 10.0), (12.0, 30.0)];
+        // Gap between rects maps to the start of the next terminal
+        assert_eq!(minimap_to_strip_x(11.0, &rects, &positions), 100.0);
+        // Before the first rect and past the last rect clamp
+        assert_eq!(minimap_to_strip_x(-5.0, &rects, &positions), 0.0);
+        assert_eq!(minimap_to_strip_x(100.0, &rects, &positions), 400.0);
+    }
+
+    // Overscroll tests
+
+    #[test]
+    fn rubber_band_zero_excess() {
+        assert_eq!(rubber_band(0.0, 48.0), 0.0);
+    }
+
+    #[test]
+    fn rubber_band_preserves_sign() {
+        assert!(rubber_band(30.0, 48.0) > 0.0);
+        assert!(rubber_band(-30.0, 48.0) < 0.0);
+        assert!((rubber_band(30.0, 48.0) + rubber_band(-30.0, 48.0)).abs() < 0.001);
+    }
+
+    #[test]
+    fn rubber_band_resists_and_caps() {
+        // Always less than the raw excess, never beyond max
+        assert!(rubber_band(48.0, 48.0) < 48.0);
+        assert!((rubber_band(48.0, 48.0) - 24.0).abs() < 0.001);
+        assert!(rubber_band(10_000.0, 48.0) < 48.0);
+    }
+
+    #[test]
+    fn rubber_band_zero_max() {
+        assert_eq!(rubber_band(30.0, 0.0), 0.0);
+    }
+
+    // Overlay animation tests
+
+    #[test]
+    fn step_toward_moves_up_and_down() {
+        assert!((step_toward(0.0, 1.0, 0.25) - 0.25).abs() < 0.001);
+        assert!((step_toward(1.0, 0.0, 0.25) - 0.75).abs() < 0.001);
+    }
+
+    #[test]
+    fn step_toward_does_not_overshoot() {
+        assert_eq!(step_toward(0.9, 1.0, 0.25), 1.0);
+        assert_eq!(step_toward(0.1, 0.0, 0.25), 0.0);
+        assert_eq!(step_toward(1.0, 1.0, 0.25), 1.0);
+    }
+
+    #[test]
+    fn ease_out_cubic_endpoints() {
+        assert_eq!(ease_out_cubic(0.0), 0.0);
+        assert_eq!(ease_out_cubic(1.0), 1.0);
+        assert_eq!(ease_out_cubic(-1.0), 0.0);
+        assert_eq!(ease_out_cubic(2.0), 1.0);
+    }
+
+    #[test]
+    fn ease_out_cubic_front_loaded() {
+        assert!(ease_out_cubic(0.5) > 0.5);
+    }
+
+    // Workspace zoom tests
+
+    #[test]
+    fn step_font_scale_steps() {
+        assert!((step_font_scale(1.0, 1) - 1.1).abs() < 0.001);
+        assert!((step_font_scale(1.0, -2) - 0.8).abs() < 0.001);
+    }
+
+    #[test]
+    fn step_font_scale_snaps_drift() {
+        // Repeated float steps must not accumulate error
+        let mut scale = 1.0;
+        for _ in 0..7 {
+            scale = step_font_scale(scale, 1);
+        }
+        for _ in 0..7 {
+            scale = step_font_scale(scale, -1);
+        }
+        assert_eq!(scale, 1.0);
+    }
+
+    #[test]
+    fn step_font_scale_clamps() {
+        assert_eq!(step_font_scale(FONT_SCALE_MIN, -1), FONT_SCALE_MIN);
+        assert_eq!(step_font_scale(FONT_SCALE_MAX, 1), FONT_SCALE_MAX);
+    }
+
+    #[test]
+    fn parse_workspace_switch_animation() {
+        assert_eq!(WorkspaceSwitchAnimation::parse("slide"), Some(WorkspaceSwitchAnimation::Slide));
+        assert_eq!(WorkspaceSwitchAnimation::parse("Crossfade"), Some(WorkspaceSwitchAnimation::Crossfade));
+        assert_eq!(WorkspaceSwitchAnimation::parse("off"), Some(WorkspaceSwitchAnimation::Off));
+        assert_eq!(WorkspaceSwitchAnimation::parse("zoom"), None);
+    }
+
+    #[test]
+    fn slide_moves_toward_later_workspace() {
+        let start = transition_frame(WorkspaceSwitchAnimation::Slide, 0.0, true, 800.0);
+        assert_eq!((start.outgoing_x, start.incoming_x), (0.0, 800.0));
+        let end = transition_frame(WorkspaceSwitchAnimation::Slide, 1.0, true, 800.0);
+        assert_eq!((end.outgoing_x, end.incoming_x), (-800.0, 0.0));
+        let back = transition_frame(WorkspaceSwitchAnimation::Slide, 0.0, false, 800.0);
+        assert_eq!(back.incoming_x, -800.0);
+    }
+
+    #[test]
+    fn crossfade_fades_incoming_in() {
+        let mid = transition_frame(WorkspaceSwitchAnimation::Crossfade, 0.5, true, 800.0);
+        assert_eq!(mid.outgoing_opacity, 1.0);
+        assert!(mid.incoming_opacity > 0.5 && mid.incoming_opacity < 1.0);
+        assert_eq!(mid.incoming_x, 0.0);
+    }
 }
@@ -0,0 +1,84 @@
+//! File descriptor headroom for spawning terminals, and hints for failed spawns.
+
+/// Share of the descriptor limit in use above which spawning warns
+pub const FD_WARN_RATIO: f64 = 0.8;
+/// Descriptors kept free for the app itself (spawning is refused below this headroom)
+pub const FD_RESERVE: u64 = 32;
+
+/// How close the process is to its open file limit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FdPressure {
+    Normal,
+    /// Past `FD_WARN_RATIO` of the limit
+    High,
+    /// Fewer than `FD_RESERVE` descriptors left
+    Exhausted,
+}
+
+/// Pressure with `open` descriptors in use out of `limit`.
+pub fn fd_pressure(open: u64, limit: u64) -> FdPressure {
+    if open.saturating_add(FD_RESERVE) >= limit {
+        FdPressure::Exhausted
+    } else if open as f64 >= limit as f64 * FD_WARN_RATIO {
+        FdPressure::High
+    } else {
+        FdPressure::Normal
+    }
+}
+
+/// What to do about a terminal that failed to start with OS error `errno`
+/// (empty when there is no specific advice).
+pub fn spawn_error_hint(errno: Option<i32>) -> &'static str {
+    match errno {
+        Some(libc::EMFILE) => {
+            "Too many open files: close some terminals or raise the limit (ulimit -n) before starting manse."
+        }
+        Some(libc::ENFILE) => "The system file table is full: close other programs or raise the system limit.",
+        Some(libc::EAGAIN) => "Process limit reached: close some terminals or raise the limit (ulimit -u).",
+        Some(libc::ENOENT) => "Shell not found: check that $SHELL points to an installed shell.",
+        Some(libc::EACCES) => "Shell is not executable: check the permissions of $SHELL.",
+        _ => "",
+    }
+}
+
+/// User-facing message for a terminal that failed to start.
+pub fn spawn_error_message(error: &std::io::Error) -> String {
+    let hint = spawn_error_hint(error.raw_os_error());
+    if hint.is_empty() {
+        format!("Could not start terminal: {}", error)
+    } else {
+        format!("Could not start terminal: {}. {}", error, hint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pressure_thresholds() {
+        assert_eq!(fd_pressure(100, 1024), FdPressure::Normal);
+        assert_eq!(fd_pressure(900, 1024), FdPressure::High);
+        assert_eq!(fd_pressure(1024 - FD_RESERVE, 1024), FdPressure::Exhausted);
+        // A tiny limit is exhausted before it is merely high
+        assert_eq!(fd_pressure(0, 16), FdPressure::Exhausted);
+    }
+
+    #[test]
+    fn hints_for_known_errors() {
+        assert!(spawn_error_hint(Some(libc::EMFILE)).contains("ulimit -n"));
+        assert!(spawn_error_hint(Some(libc::ENOENT)).contains("$SHELL"));
+        assert_eq!(spawn_error_hint(None), "");
+    }
+
+    #[test]
+    fn message_includes_hint() {
+        let error = std::io::Error::from_raw_os_error(libc::EMFILE);
+        let message = spawn_error_message(&error);
+        assert!(message.starts_with("Could not start terminal: "));
+        assert!(message.ends_with("before starting manse."));
+
+        let other = std::io::Error::other("boom");
+        assert_eq!(spawn_error_message(&other), "Could not start terminal: boom");
+    }
+}
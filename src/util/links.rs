@@ -0,0 +1,25 @@
+//! Per-terminal link detection policy.
+
+/// Whether URL detection is turned off while `process` runs in a terminal's foreground.
+/// `exclusions` are process names ("htop"), compared case-insensitively; a leading path
+/// on the process name is ignored.
+pub fn is_excluded(process: &str, exclusions: &[String]) -> bool {
+    let name = process.rsplit('/').next().unwrap_or(process);
+    exclusions.iter().any(|pattern| pattern.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_whole_process_names() {
+        let exclusions = vec!["htop".to_string(), "NVIM".to_string()];
+        assert!(is_excluded("htop", &exclusions));
+        assert!(is_excluded("nvim", &exclusions));
+        assert!(is_excluded("/usr/bin/htop", &exclusions));
+        assert!(!is_excluded("vim", &exclusions));
+        assert!(!is_excluded("htop-wrapper", &exclusions));
+        assert!(!is_excluded("htop", &[]));
+    }
+}
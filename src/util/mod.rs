@@ -1,3 +1,19 @@
+pub mod activity;
+pub mod bytes;
+#[cfg(test)]
+pub mod contrast;
+pub mod fuzzy;
 pub mod icons;
+pub mod labels;
 pub mod ids;
+pub mod keystrokes;
 pub mod layout;
+pub mod links;
+pub mod limits;
+pub mod perf_log;
+pub mod screen_diff;
+pub mod shell;
+pub mod text;
+pub mod time;
+pub mod titles;
+pub mod toasts;
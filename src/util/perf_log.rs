@@ -0,0 +1,161 @@
+//! Rows of the perf log file (`perf_log_file`), one per measurement window.
+
+use std::path::Path;
+
+/// Columns of a CSV perf log, written once at the top of each file
+pub const CSV_HEADER: &str =
+    "timestamp,version,secs,frames,fps,pty,ipc,scroll,minimized,pointer,keyboard,focused,mystery,idle";
+
+/// Perf log file format, picked from the file extension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PerfLogFormat {
+    Csv,
+    /// One JSON object per line (`.json` / `.jsonl`)
+    JsonLines,
+}
+
+impl PerfLogFormat {
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json" | "jsonl") => Self::JsonLines,
+            _ => Self::Csv,
+        }
+    }
+}
+
+/// Counters of one measurement window
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PerfWindow {
+    /// End of the window, in seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Length of the window in seconds
+    pub secs: f64,
+    pub frames: u64,
+    pub pty_events: u64,
+    pub ipc_requests: u64,
+    pub scroll_frames: u64,
+    pub minimized_frames: u64,
+    pub pointer_frames: u64,
+    pub keyboard_frames: u64,
+    pub focused_frames: u64,
+}
+
+impl PerfWindow {
+    pub fn fps(&self) -> f64 {
+        if self.secs > 0.0 {
+            self.frames as f64 / self.secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Frames that none of the counted causes account for
+    pub fn mystery(&self) -> u64 {
+        let explained = self.pty_events
+            + self.scroll_frames
+            + self.minimized_frames
+            + self.pointer_frames
+            + self.keyboard_frames;
+        self.frames.saturating_sub(explained)
+    }
+
+    /// No pointer or keyboard activity during the window, so every frame was drawn
+    /// on the app's own account (the windows that matter for battery use)
+    pub fn is_idle(&self) -> bool {
+        self.pointer_frames == 0 && self.keyboard_frames == 0
+    }
+
+    /// The window as one line of `format` (without the newline), tagged with `version`.
+    pub fn format_row(&self, format: PerfLogFormat, version: &str) -> String {
+        match format {
+            PerfLogFormat::Csv => format!(
+                "{},{},{:.1},{},{:.2},{},{},{},{},{},{},{},{},{}",
+                self.timestamp,
+                version,
+                self.secs,
+                self.frames,
+                self.fps(),
+                self.pty_events,
+                self.ipc_requests,
+                self.scroll_frames,
+                self.minimized_frames,
+                self.pointer_frames,
+                self.keyboard_frames,
+                self.focused_frames,
+                self.mystery(),
+                self.is_idle(),
+            ),
+            PerfLogFormat::JsonLines => self.to_json(version).to_string(),
+        }
+    }
+
+    /// The window as a JSON object (a JSON-lines row, or the perf section of a debug report).
+    pub fn to_json(&self, version: &str) -> serde_json::Value {
+        serde_json::json!({
+            "timestamp": self.timestamp,
+            "version": version,
+            "secs": self.secs,
+            "frames": self.frames,
+            "fps": self.fps(),
+            "pty": self.pty_events,
+            "ipc": self.ipc_requests,
+            "scroll": self.scroll_frames,
+            "minimized": self.minimized_frames,
+            "pointer": self.pointer_frames,
+            "keyboard": self.keyboard_frames,
+            "focused": self.focused_frames,
+            "mystery": self.mystery(),
+            "idle": self.is_idle(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window() -> PerfWindow {
+        PerfWindow {
+            timestamp: 1_700_000_000,
+            secs: 5.0,
+            frames: 20,
+            pty_events: 6,
+            scroll_frames: 2,
+            focused_frames: 20,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn format_from_extension() {
+        assert_eq!(PerfLogFormat::for_path(Path::new("/tmp/perf.jsonl")), PerfLogFormat::JsonLines);
+        assert_eq!(PerfLogFormat::for_path(Path::new("/tmp/perf.csv")), PerfLogFormat::Csv);
+        assert_eq!(PerfLogFormat::for_path(Path::new("perf")), PerfLogFormat::Csv);
+    }
+
+    #[test]
+    fn derived_counts() {
+        let w = window();
+        assert_eq!(w.fps(), 4.0);
+        assert_eq!(w.mystery(), 12);
+        assert!(w.is_idle());
+        assert!(!PerfWindow { keyboard_frames: 1, ..w }.is_idle());
+    }
+
+    #[test]
+    fn csv_row_matches_header() {
+        let row = window().format_row(PerfLogFormat::Csv, "0.1.0");
+        assert_eq!(row, "1700000000,0.1.0,5.0,20,4.00,6,0,2,0,0,0,20,12,true");
+        assert_eq!(row.split(',').count(), CSV_HEADER.split(',').count());
+    }
+
+    #[test]
+    fn json_row() {
+        let row = window().format_row(PerfLogFormat::JsonLines, "0.1.0");
+        let value: serde_json::Value = serde_json::from_str(&row).unwrap();
+        assert_eq!(value["frames"], 20);
+        assert_eq!(value["mystery"], 12);
+        assert_eq!(value["idle"], true);
+        assert!(!row.contains('\n'));
+    }
+}
@@ -0,0 +1,84 @@
+//! What changed on a terminal's screen since it was last looked at, for the
+//! `grid_changed` IPC event: the rows whose text differs and where the cursor is.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Changes to a screen since the last look
+#[derive(Debug, PartialEq)]
+pub struct ScreenChange {
+    /// Rows whose text changed, counted from the top (0)
+    pub lines: Vec<usize>,
+    /// Cursor as (line, column)
+    pub cursor: (usize, usize),
+}
+
+/// Hashes of a screen's rows and its cursor, as last seen
+#[derive(Debug, Default)]
+pub struct ScreenDiff {
+    rows: Vec<u64>,
+    cursor: (usize, usize),
+}
+
+impl ScreenDiff {
+    /// Compare `rows` and `cursor` with the last look and remember them. `None` when
+    /// nothing changed. The first look reports every row.
+    pub fn update(&mut self, rows: &[String], cursor: (usize, usize)) -> Option<ScreenChange> {
+        let hashes: Vec<u64> = rows.iter().map(|row| hash(row)).collect();
+        let lines: Vec<usize> = (0..hashes.len().max(self.rows.len()))
+            .filter(|&line| hashes.get(line) != self.rows.get(line))
+            .collect();
+        let moved = cursor != self.cursor;
+        self.rows = hashes;
+        self.cursor = cursor;
+        (moved || !lines.is_empty()).then_some(ScreenChange { lines, cursor })
+    }
+}
+
+fn hash(row: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    row.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(text: &[&str]) -> Vec<String> {
+        text.iter().map(|row| row.to_string()).collect()
+    }
+
+    #[test]
+    fn reports_changed_rows_and_cursor_moves() {
+        let mut diff = ScreenDiff::default();
+        let change = diff.update(&rows(&["$ ", "", ""]), (0, 2)).unwrap();
+        assert_eq!(change.lines, vec![0, 1, 2]);
+
+        assert_eq!(diff.update(&rows(&["$ ", "", ""]), (0, 2)), None);
+        assert_eq!(
+            diff.update(&rows(&["$ ls", "a  b", ""]), (1, 4)),
+            Some(ScreenChange {
+                lines: vec![0, 1],
+                cursor: (1, 4),
+            })
+        );
+        assert_eq!(
+            diff.update(&rows(&["$ ls", "a  b", ""]), (2, 0)),
+            Some(ScreenChange {
+                lines: vec![],
+                cursor: (2, 0),
+            })
+        );
+    }
+
+    #[test]
+    fn resized_screen_reports_added_and_removed_rows() {
+        let mut diff = ScreenDiff::default();
+        diff.update(&rows(&["a", "b", "c"]), (0, 0));
+        let change = diff.update(&rows(&["a", "b"]), (0, 0)).unwrap();
+        assert_eq!(change.lines, vec![2]);
+        let change = diff.update(&rows(&["a", "b", "", ""]), (0, 0)).unwrap();
+        assert_eq!(change.lines, vec![2, 3]);
+    }
+}
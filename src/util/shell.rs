@@ -0,0 +1,34 @@
+//! POSIX shell command building.
+
+// One quoting rule for the app and manse-ctl's `term-spawn`
+pub use manse_ctl::shell::quote;
+
+/// Shell line running `command` with stdin read from `path`, deleting the file afterwards.
+///
+/// The command is grouped so pipelines (`sort | uniq -c`) all see the redirect.
+pub fn with_stdin_from(command: &str, path: &str) -> String {
+    let command = command.trim().trim_end_matches(';');
+    let path = quote(path);
+    format!("{{ {}; }} < {}; rm -f {}", command, path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stdin_redirect_groups_pipeline() {
+        assert_eq!(
+            with_stdin_from("sort | uniq -c", "/tmp/x"),
+            "{ sort | uniq -c; } < '/tmp/x'; rm -f '/tmp/x'"
+        );
+    }
+
+    #[test]
+    fn stdin_redirect_drops_trailing_semicolon() {
+        assert_eq!(
+            with_stdin_from(" jq . ; ", "/tmp/x"),
+            "{ jq . ; } < '/tmp/x'; rm -f '/tmp/x'"
+        );
+    }
+}
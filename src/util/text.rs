@@ -0,0 +1,34 @@
+//! Plain text helpers for terminal captures.
+
+/// The last `count` lines of `text`, ignoring trailing blank lines (the empty rows
+/// below a prompt). Lines are joined with `\n`, without a trailing newline.
+pub fn tail_lines(text: &str, count: usize) -> String {
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    let end = lines
+        .iter()
+        .rposition(|line| !line.is_empty())
+        .map_or(0, |i| i + 1);
+    let start = end.saturating_sub(count);
+    lines[start..end].join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tail_skips_blank_rows_at_the_end() {
+        assert_eq!(tail_lines("a\nb\nc\n\n   \n", 2), "b\nc");
+    }
+
+    #[test]
+    fn tail_longer_than_text_keeps_everything() {
+        assert_eq!(tail_lines("a\n\nb", 10), "a\n\nb");
+    }
+
+    #[test]
+    fn tail_of_blank_text_is_empty() {
+        assert_eq!(tail_lines("\n\n", 3), "");
+        assert_eq!(tail_lines("a", 0), "");
+    }
+}
@@ -0,0 +1,113 @@
+//! Time formatting and time-of-day helpers.
+
+/// Format an elapsed duration in seconds as a short relative age ("just now", "5m ago").
+pub fn format_age(secs: u64) -> String {
+    match secs {
+        0..=9 => "just now".to_string(),
+        10..=59 => format!("{}s ago", secs),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+/// Minutes in a day
+pub const DAY_MINUTES: u32 = 24 * 60;
+
+/// Parse a 24-hour "HH:MM" clock time into minutes since midnight.
+pub fn parse_clock(text: &str) -> Option<u32> {
+    let (hours, minutes) = text.trim().split_once(':')?;
+    let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+/// Whether minute-of-day `now` falls in the daily window from `start` up to `end`,
+/// which wraps past midnight when `end` comes before `start`.
+pub fn in_daily_window(now: u32, start: u32, end: u32) -> bool {
+    if start <= end {
+        (start..end).contains(&now)
+    } else {
+        now >= start || now < end
+    }
+}
+
+/// Minutes from minute-of-day `now` until the clock next shows `target` (a full day if
+/// it shows it now).
+pub fn minutes_until(now: u32, target: u32) -> u32 {
+    match (target + DAY_MINUTES - now) % DAY_MINUTES {
+        0 => DAY_MINUTES,
+        minutes => minutes,
+    }
+}
+
+/// The current local time, broken down, or `None` if it can't be converted.
+#[cfg(unix)]
+pub fn local_tm() -> Option<libc::tm> {
+    // SAFETY: `time` accepts a null pointer, and `localtime_r` only writes to the `tm`
+    // it is given (no shared buffer, unlike `localtime`)
+    unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        (!libc::localtime_r(&now, &mut tm).is_null()).then_some(tm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_clock_times() {
+        assert_eq!(parse_clock("07:30"), Some(450));
+        assert_eq!(parse_clock(" 0:05 "), Some(5));
+        assert_eq!(parse_clock("23:59"), Some(1439));
+        assert_eq!(parse_clock("24:00"), None);
+        assert_eq!(parse_clock("7"), None);
+        assert_eq!(parse_clock("ab:cd"), None);
+    }
+
+    #[test]
+    fn daily_windows() {
+        // 07:00 to 19:00
+        assert!(in_daily_window(420, 420, 1140));
+        assert!(!in_daily_window(1140, 420, 1140));
+        assert!(!in_daily_window(60, 420, 1140));
+        // 22:00 to 06:00, across midnight
+        assert!(in_daily_window(1380, 1320, 360));
+        assert!(in_daily_window(60, 1320, 360));
+        assert!(!in_daily_window(720, 1320, 360));
+    }
+
+    #[test]
+    fn minutes_until_wraps() {
+        assert_eq!(minutes_until(420, 1140), 720);
+        assert_eq!(minutes_until(1200, 420), 660);
+        assert_eq!(minutes_until(420, 420), DAY_MINUTES);
+    }
+
+    #[test]
+    fn format_age_just_now() {
+        assert_eq!(format_age(0), "just now");
+        assert_eq!(format_age(9), "just now");
+    }
+
+    #[test]
+    fn format_age_seconds() {
+        assert_eq!(format_age(10), "10s ago");
+        assert_eq!(format_age(59), "59s ago");
+    }
+
+    #[test]
+    fn format_age_minutes() {
+        assert_eq!(format_age(60), "1m ago");
+        assert_eq!(format_age(3599), "59m ago");
+    }
+
+    #[test]
+    fn format_age_hours_and_days() {
+        assert_eq!(format_age(3600), "1h ago");
+        assert_eq!(format_age(86399), "23h ago");
+        assert_eq!(format_age(86400), "1d ago");
+        assert_eq!(format_age(3 * 86400 + 5), "3d ago");
+    }
+}
@@ -0,0 +1,207 @@
+//! Disambiguation of duplicate terminal titles.
+
+use std::collections::HashMap;
+
+/// How terminals sharing a title within a workspace are told apart
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TitleDisambiguation {
+    /// Show titles as-is
+    Off,
+    /// Suffix with the last component of the working directory, falling back to an index
+    Cwd,
+    /// Suffix with a 1-based index among the duplicates
+    Index,
+}
+
+impl TitleDisambiguation {
+    /// Parse a config value ("off", "cwd", "index").
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "cwd" => Some(Self::Cwd),
+            "index" => Some(Self::Index),
+            _ => None,
+        }
+    }
+}
+
+/// Last non-empty component of a path ("/home/me/src/app/" -> "app").
+pub fn path_tail(path: &str) -> Option<&str> {
+    path.trim_end_matches('/').rsplit('/').next().filter(|s| !s.is_empty())
+}
+
+/// Compute a suffix for each `(title, cwd)` entry whose title is shared with another entry.
+///
+/// Returns one element per entry, `None` for titles that are already unique.
+/// In `Cwd` mode, a duplicate group falls back to indices when the cwd tails
+/// don't tell every entry apart.
+pub fn disambiguate(
+    entries: &[(&str, Option<&str>)],
+    mode: TitleDisambiguation,
+) -> Vec<Option<String>> {
+    let mut suffixes = vec![None; entries.len()];
+    if mode == TitleDisambiguation::Off {
+        return suffixes;
+    }
+
+    let mut groups: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (idx, (title, _)) in entries.iter().enumerate() {
+        groups.entry(title).or_default().push(idx);
+    }
+
+    for members in groups.values().filter(|m| m.len() > 1) {
+        if mode == TitleDisambiguation::Cwd {
+            let tails: Vec<Option<&str>> = members
+                .iter()
+                .map(|&idx| entries[idx].1.and_then(path_tail))
+                .collect();
+            let all_distinct = tails.iter().all(|t| t.is_some())
+                && tails
+                    .iter()
+                    .enumerate()
+                    .all(|(i, t)| !tails[..i].contains(t));
+            if all_distinct {
+                for (&idx, tail) in members.iter().zip(tails) {
+                    suffixes[idx] = tail.map(str::to_string);
+                }
+                continue;
+            }
+        }
+
+        for (n, &idx) in members.iter().enumerate() {
+            suffixes[idx] = Some((n + 1).to_string());
+        }
+    }
+
+    suffixes
+}
+
+/// What the window title is made of (`window_title` placeholders)
+pub struct WindowTitleParts<'a> {
+    /// `{workspace}`: name of the active workspace
+    pub workspace: &'a str,
+    /// `{title}`: display title of the focused terminal
+    pub title: &'a str,
+    /// `{description}`: the focused terminal's description
+    pub description: &'a str,
+    /// `{cwd}`: the focused terminal's working directory
+    pub cwd: &'a str,
+}
+
+/// `format` with its placeholders filled in. Separators left dangling at the end by an
+/// empty value (no focused terminal) are dropped: "manse — {workspace}: {title}" gives
+/// "manse — api" then.
+pub fn window_title(format: &str, parts: &WindowTitleParts) -> String {
+    let title = format
+        .replace("{workspace}", parts.workspace)
+        .replace("{title}", parts.title)
+        .replace("{description}", parts.description)
+        .replace("{cwd}", parts.cwd);
+    title
+        .trim_end_matches(|c: char| c.is_whitespace() || ":·—-|/".contains(c))
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_modes() {
+        assert_eq!(TitleDisambiguation::parse("off"), Some(TitleDisambiguation::Off));
+        assert_eq!(TitleDisambiguation::parse("cwd"), Some(TitleDisambiguation::Cwd));
+        assert_eq!(TitleDisambiguation::parse("index"), Some(TitleDisambiguation::Index));
+        assert_eq!(TitleDisambiguation::parse(" CWD "), Some(TitleDisambiguation::Cwd));
+        assert_eq!(TitleDisambiguation::parse("path"), None);
+    }
+
+    #[test]
+    fn path_tail_variants() {
+        assert_eq!(path_tail("/home/me/src/app"), Some("app"));
+        assert_eq!(path_tail("/home/me/src/app/"), Some("app"));
+        assert_eq!(path_tail("/"), None);
+        assert_eq!(path_tail(""), None);
+    }
+
+    #[test]
+    fn unique_titles_untouched() {
+        let entries = [("zsh", Some("/a")), ("vim", Some("/b"))];
+        assert_eq!(
+            disambiguate(&entries, TitleDisambiguation::Cwd),
+            vec![None, None]
+        );
+    }
+
+    #[test]
+    fn off_mode_never_suffixes() {
+        let entries = [("zsh", Some("/a")), ("zsh", Some("/b"))];
+        assert_eq!(
+            disambiguate(&entries, TitleDisambiguation::Off),
+            vec![None, None]
+        );
+    }
+
+    #[test]
+    fn cwd_mode_uses_tails() {
+        let entries = [
+            ("zsh", Some("/src/api")),
+            ("vim", Some("/src/api")),
+            ("zsh", Some("/src/web")),
+        ];
+        assert_eq!(
+            disambiguate(&entries, TitleDisambiguation::Cwd),
+            vec![Some("api".into()), None, Some("web".into())]
+        );
+    }
+
+    #[test]
+    fn cwd_mode_falls_back_to_index_on_collision() {
+        let entries = [("zsh", Some("/a/app")), ("zsh", Some("/b/app"))];
+        assert_eq!(
+            disambiguate(&entries, TitleDisambiguation::Cwd),
+            vec![Some("1".into()), Some("2".into())]
+        );
+    }
+
+    #[test]
+    fn cwd_mode_falls_back_to_index_when_cwd_missing() {
+        let entries = [("zsh", Some("/a/app")), ("zsh", None)];
+        assert_eq!(
+            disambiguate(&entries, TitleDisambiguation::Cwd),
+            vec![Some("1".into()), Some("2".into())]
+        );
+    }
+
+    #[test]
+    fn index_mode_numbers_in_order() {
+        let entries = [("zsh", Some("/a")), ("vim", None), ("zsh", Some("/b")), ("zsh", None)];
+        assert_eq!(
+            disambiguate(&entries, TitleDisambiguation::Index),
+            vec![Some("1".into()), None, Some("2".into()), Some("3".into())]
+        );
+    }
+
+    fn parts<'a>(title: &'a str) -> WindowTitleParts<'a> {
+        WindowTitleParts {
+            workspace: "backend",
+            title,
+            description: "",
+            cwd: "/src/api",
+        }
+    }
+
+    #[test]
+    fn window_title_fills_placeholders() {
+        assert_eq!(
+            window_title("manse — {workspace}: {title}", &parts("cargo watch")),
+            "manse — backend: cargo watch"
+        );
+        assert_eq!(window_title("{title} ({cwd})", &parts("zsh")), "zsh (/src/api)");
+    }
+
+    #[test]
+    fn window_title_drops_dangling_separators() {
+        assert_eq!(window_title("manse — {workspace}: {title}", &parts("")), "manse — backend");
+        assert_eq!(window_title("{workspace} · {description}", &parts("zsh")), "backend");
+    }
+}
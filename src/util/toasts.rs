@@ -0,0 +1,103 @@
+//! Short-lived messages shown over the window (spawn failures, resource warnings).
+
+use std::collections::VecDeque;
+
+/// Seconds a toast stays fully visible
+pub const TOAST_SECS: f64 = 8.0;
+/// Seconds a toast takes to fade out after that
+pub const TOAST_FADE_SECS: f64 = 0.5;
+/// Most toasts shown at once (older ones are dropped)
+pub const MAX_TOASTS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToastLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toast {
+    pub level: ToastLevel,
+    pub text: String,
+    /// Time it was (last) raised, in seconds
+    pub time: f64,
+}
+
+/// Opacity of a toast raised `age` seconds ago.
+pub fn opacity(age: f64) -> f32 {
+    if age <= TOAST_SECS {
+        1.0
+    } else {
+        (1.0 - (age - TOAST_SECS) / TOAST_FADE_SECS).clamp(0.0, 1.0) as f32
+    }
+}
+
+/// Toasts currently shown, oldest first
+#[derive(Default)]
+pub struct Toasts {
+    entries: VecDeque<Toast>,
+}
+
+impl Toasts {
+    /// Show `text` from `now`. Raising a message that is already shown restarts its timer
+    /// and moves it to the end instead of stacking a copy.
+    pub fn push(&mut self, level: ToastLevel, text: impl Into<String>, now: f64) {
+        let text = text.into();
+        self.entries.retain(|t| t.text != text);
+        self.entries.push_back(Toast { level, text, time: now });
+        while self.entries.len() > MAX_TOASTS {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Drop toasts that have fully faded by `now`.
+    pub fn prune(&mut self, now: f64) {
+        self.entries.retain(|t| now - t.time < TOAST_SECS + TOAST_FADE_SECS);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Toast> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_message_is_not_stacked() {
+        let mut toasts = Toasts::default();
+        toasts.push(ToastLevel::Error, "a", 0.0);
+        toasts.push(ToastLevel::Warning, "b", 1.0);
+        toasts.push(ToastLevel::Error, "a", 2.0);
+        let texts: Vec<_> = toasts.iter().map(|t| (t.text.as_str(), t.time)).collect();
+        assert_eq!(texts, vec![("b", 1.0), ("a", 2.0)]);
+    }
+
+    #[test]
+    fn keeps_newest_and_prunes_faded() {
+        let mut toasts = Toasts::default();
+        for i in 0..MAX_TOASTS + 1 {
+            toasts.push(ToastLevel::Warning, i.to_string(), i as f64);
+        }
+        assert_eq!(toasts.iter().count(), MAX_TOASTS);
+        assert_eq!(toasts.iter().next().unwrap().text, "1");
+
+        toasts.prune(2.0 + TOAST_SECS + TOAST_FADE_SECS);
+        assert_eq!(toasts.iter().count(), MAX_TOASTS - 2);
+        toasts.prune(1000.0);
+        assert!(toasts.is_empty());
+    }
+
+    #[test]
+    fn opacity_holds_then_fades() {
+        assert_eq!(opacity(1.0), 1.0);
+        assert!((opacity(TOAST_SECS + TOAST_FADE_SECS / 2.0) - 0.5).abs() < 1e-6);
+        assert_eq!(opacity(TOAST_SECS + TOAST_FADE_SECS), 0.0);
+    }
+}
@@ -0,0 +1,214 @@
+//! Restarting when the UI thread hangs (`watchdog_secs`).
+//!
+//! Every frame marks when it started and clears the mark when it ends. A background
+//! thread checks the mark each second: a frame still running after `watchdog_secs` while
+//! terminals are open means the UI is stuck. The thread logs what the last frames looked
+//! like and resumes from the crash rescue snapshot (see `rescue`), the stuck thread being
+//! of no help with a regular restart. Without a rescue (not Unix) the hang is only logged.
+
+#[cfg(unix)]
+use crate::rescue;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Time between checks of the frame in progress
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Milliseconds between diagnostics notes
+const NOTE_INTERVAL_MS: u64 = 1000;
+
+/// State shared by the UI thread and the watchdog thread
+struct Shared {
+    /// Reference point of the millisecond times below
+    epoch: Instant,
+    /// When the frame in progress started (ms after `epoch` + 1), 0 between frames
+    frame_started: AtomicU64,
+    /// Seconds a frame may run before it counts as a hang (0 = never)
+    timeout_secs: AtomicU64,
+    /// Terminals open as of the last frame
+    terminals: AtomicUsize,
+    /// When `diagnostics` was last written (ms after `epoch` + 1), 0 before that
+    noted: AtomicU64,
+    /// What the app last reported about itself, logged with a hang
+    diagnostics: Mutex<String>,
+}
+
+impl Shared {
+    fn new(timeout_secs: u64) -> Self {
+        Self {
+            epoch: Instant::now(),
+            frame_started: AtomicU64::new(0),
+            timeout_secs: AtomicU64::new(timeout_secs),
+            terminals: AtomicUsize::new(0),
+            noted: AtomicU64::new(0),
+            diagnostics: Mutex::new(String::new()),
+        }
+    }
+
+    fn now_ms(&self) -> u64 {
+        self.epoch.elapsed().as_millis() as u64
+    }
+
+    /// How long the frame in progress has been stuck, if it is
+    fn stuck_ms(&self) -> Option<u64> {
+        stuck_ms(
+            self.timeout_secs.load(Ordering::Relaxed),
+            self.frame_started.load(Ordering::Relaxed),
+            self.terminals.load(Ordering::Relaxed),
+            self.now_ms() + 1,
+        )
+    }
+}
+
+/// How long a frame started at `frame_started` (0 between frames) has been running at
+/// `now` (both in ms, as in `Shared`), if that makes it stuck: past `timeout_secs`
+/// (0 = never) with terminals open.
+fn stuck_ms(timeout_secs: u64, frame_started: u64, terminals: usize, now: u64) -> Option<u64> {
+    if timeout_secs == 0 || frame_started == 0 || terminals == 0 {
+        return None;
+    }
+    let running = now.saturating_sub(frame_started);
+    (running >= timeout_secs * 1000).then_some(running)
+}
+
+/// Handle of the watchdog thread, held by the app
+pub struct Watchdog {
+    shared: Arc<Shared>,
+}
+
+/// Marks a frame as in progress until dropped
+pub struct FrameGuard {
+    shared: Arc<Shared>,
+}
+
+impl Drop for FrameGuard {
+    fn drop(&mut self) {
+        self.shared.frame_started.store(0, Ordering::Relaxed);
+    }
+}
+
+impl Watchdog {
+    /// Start the watchdog thread, counting a frame running `timeout_secs` as a hang
+    /// (0 until `set_timeout` gives it one).
+    pub fn start(timeout_secs: u64) -> Self {
+        let shared = Arc::new(Shared::new(timeout_secs));
+        let watched = shared.clone();
+        let spawned = thread::Builder::new()
+            .name("watchdog".to_string())
+            .spawn(move || watch(&watched));
+        if let Err(e) = spawned {
+            log::warn!("Failed to start the watchdog: {}", e);
+        }
+        Self { shared }
+    }
+
+    pub fn set_timeout(&self, timeout_secs: u64) {
+        self.shared.timeout_secs.store(timeout_secs, Ordering::Relaxed);
+    }
+
+    /// Mark a frame as started with `terminals` open; it ends when the guard is dropped.
+    pub fn frame(&self, terminals: usize) -> FrameGuard {
+        let shared = &self.shared;
+        shared.frame_started.store(shared.now_ms() + 1, Ordering::Relaxed);
+        shared.terminals.store(terminals, Ordering::Relaxed);
+        FrameGuard { shared: shared.clone() }
+    }
+
+    /// Run `f` (e.g. a modal file dialog) without it counting toward the frame's time.
+    pub fn outside_frame<T>(&self, f: impl FnOnce() -> T) -> T {
+        let shared = &self.shared;
+        let started = shared.frame_started.swap(0, Ordering::Relaxed);
+        let result = f();
+        if started != 0 {
+            shared.frame_started.store(shared.now_ms() + 1, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Replace the diagnostics logged with a hang with what `describe` gives, at most
+    /// once a second.
+    pub fn note(&self, describe: impl FnOnce() -> String) {
+        let shared = &self.shared;
+        if shared.timeout_secs.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let now = shared.now_ms() + 1;
+        let noted = shared.noted.load(Ordering::Relaxed);
+        if noted != 0 && now - noted < NOTE_INTERVAL_MS {
+            return;
+        }
+        shared.noted.store(now, Ordering::Relaxed);
+        if let Ok(mut diagnostics) = shared.diagnostics.lock() {
+            *diagnostics = describe();
+        }
+    }
+}
+
+/// Watchdog thread: resume once a frame runs past the timeout with terminals open.
+/// Stops watching when resuming fails, so a hang is reported once.
+fn watch(shared: &Shared) {
+    loop {
+        thread::sleep(CHECK_INTERVAL);
+        let Some(stuck_ms) = shared.stuck_ms() else {
+            continue;
+        };
+
+        let diagnostics = shared
+            .diagnostics
+            .try_lock()
+            .map(|diagnostics| diagnostics.clone())
+            .unwrap_or_default();
+        log::error!(
+            "UI thread stuck in a frame for {:.1}s with {} terminals open; last seen: {}",
+            stuck_ms as f64 / 1000.0,
+            shared.terminals.load(Ordering::Relaxed),
+            if diagnostics.is_empty() { "-" } else { &diagnostics },
+        );
+        #[cfg(unix)]
+        rescue::resume("UI hung");
+        log::error!("Not restarting after the hang; the watchdog stops here");
+        return;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stuck_past_the_timeout_with_terminals() {
+        assert_eq!(stuck_ms(5, 1_000, 2, 5_999), None);
+        assert_eq!(stuck_ms(5, 1_000, 2, 6_000), Some(5_000));
+        assert_eq!(stuck_ms(5, 1_000, 2, 9_500), Some(8_500));
+    }
+
+    #[test]
+    fn never_stuck_without_timeout_frame_or_terminals() {
+        // Timeout 0 turns the watchdog off
+        assert_eq!(stuck_ms(0, 1_000, 2, 100_000), None);
+        // Between frames
+        assert_eq!(stuck_ms(5, 0, 2, 100_000), None);
+        // Nothing to rescue
+        assert_eq!(stuck_ms(5, 1_000, 0, 100_000), None);
+    }
+
+    #[test]
+    fn outside_frame_pauses_the_clock() {
+        let mut shared = Shared::new(1);
+        shared.epoch -= Duration::from_secs(10);
+        let watchdog = Watchdog {
+            shared: Arc::new(shared),
+        };
+        let _frame = watchdog.frame(3);
+        // As if the frame had started 10s ago
+        watchdog.shared.frame_started.store(1, Ordering::Relaxed);
+        assert!(watchdog.shared.stuck_ms().is_some());
+
+        watchdog.outside_frame(|| assert_eq!(watchdog.shared.stuck_ms(), None));
+        // The frame goes on, timed from the end of the pause
+        assert_ne!(watchdog.shared.frame_started.load(Ordering::Relaxed), 0);
+        assert_eq!(watchdog.shared.stuck_ms(), None);
+    }
+}
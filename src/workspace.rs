@@ -1,3 +1,8 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Instant, SystemTime};
+
 /// Cached terminal position data
 #[derive(Clone, Default)]
 pub struct TerminalPositions {
@@ -5,8 +10,33 @@ pub struct TerminalPositions {
     pub positions: Vec<(u64, f32, f32)>,
     /// Viewport width used to compute these positions
     pub viewport_width: f32,
+    /// Number of pinned terminals docked at the front of the strip
+    pub pinned: usize,
+}
+
+impl TerminalPositions {
+    /// Whether these positions still describe a strip of `panel_count` terminals
+    /// laid out for `viewport_width`.
+    pub fn is_valid_for(&self, viewport_width: f32, panel_count: usize) -> bool {
+        (self.viewport_width - viewport_width).abs() < 0.1 && self.positions.len() == panel_count
+    }
 }
 
+/// The `.manse.json` project a workspace was named from
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProjectLink {
+    /// Directory holding the project file
+    pub root: PathBuf,
+    /// `workspaceName` as last read from or written to the file
+    pub file_name: String,
+    /// Modification time of the file when it was last read
+    #[serde(skip)]
+    pub modified: Option<SystemTime>,
+}
+
+/// Name of the reserved workspace for throwaway terminals
+pub const SCRATCH_WORKSPACE: &str = "scratch";
+
 /// A workspace containing a horizontal strip of terminals
 pub struct Workspace {
     /// Workspace name
@@ -19,8 +49,32 @@ pub struct Workspace {
     pub scroll_offset: f32,
     /// Target scroll offset
     pub target_offset: f32,
+    /// Elastic overscroll past the strip edges (animated back to 0)
+    pub overscroll: f32,
+    /// Terminal font scale for this workspace (zoom, 1.0 = configured size)
+    pub font_scale: f32,
+    /// Panel that was focused when the user last panned the strip manually.
+    /// While it stays focused, the strip is not auto-scrolled back to it.
+    pub manual_scroll_anchor: Option<u64>,
     /// Cached terminal positions (invalidated when layout changes)
     pub cached_positions: TerminalPositions,
+    /// Suffixes telling apart terminals with the same title (panel_id -> suffix)
+    pub title_suffixes: HashMap<u64, String>,
+    /// Hash of the titles/cwds the suffixes were computed from
+    pub title_suffix_key: u64,
+    /// Project file the workspace was named from, kept in sync on renames
+    pub project: Option<ProjectLink>,
+    /// When the workspace lost its last terminal. It lingers in the sidebar's empty
+    /// section until `empty_workspace_linger_secs` have passed.
+    pub emptied_at: Option<Instant>,
+    /// Named by default rather than by the user: the git repository its first terminal
+    /// starts in names it (`workspace_name_from_git`)
+    pub implicit_name: bool,
+    /// Terminals by when they were last focused, most recent first (⌃Tab)
+    pub focus_history: Vec<u64>,
+    /// Group the workspace is listed under in the sidebar (`ws-group`). Members of a
+    /// group are kept next to each other.
+    pub group: Option<String>,
 }
 
 impl Workspace {
@@ -31,12 +85,280 @@ impl Workspace {
             focused_index: 0,
             scroll_offset: 0.0,
             target_offset: 0.0,
+            overscroll: 0.0,
+            font_scale: 1.0,
+            manual_scroll_anchor: None,
             cached_positions: TerminalPositions::default(),
+            title_suffixes: HashMap::new(),
+            title_suffix_key: 0,
+            project: None,
+            emptied_at: None,
+            implicit_name: false,
+            focus_history: Vec::new(),
+            group: None,
         }
     }
 
+    /// The workspace a window starts with, named "default" until its first terminal's
+    /// repository gives it a name.
+    pub fn implicit() -> Self {
+        Self {
+            implicit_name: true,
+            ..Self::new("default")
+        }
+    }
+
+    /// Whether this is the reserved scratch workspace
+    pub fn is_scratch(&self) -> bool {
+        self.name == SCRATCH_WORKSPACE
+    }
+
+    /// Whether the workspace is empty and waiting to be removed
+    pub fn is_lingering(&self) -> bool {
+        self.emptied_at.is_some() && self.panel_order.is_empty()
+    }
+
+    /// Put `panel_id` at the front of the focus history, forgetting terminals that left
+    /// the workspace.
+    pub fn record_focus(&mut self, panel_id: u64) {
+        let order = &self.panel_order;
+        self.focus_history.retain(|id| *id != panel_id && order.contains(id));
+        self.focus_history.insert(0, panel_id);
+    }
+
+    /// The workspace's terminals, most recently focused first. Ones never focused
+    /// follow in strip order.
+    pub fn mru_order(&self) -> Vec<u64> {
+        let mut order: Vec<u64> = self
+            .focus_history
+            .iter()
+            .copied()
+            .filter(|id| self.panel_order.contains(id))
+            .collect();
+        let unfocused: Vec<u64> = self.panel_order.iter().copied().filter(|id| !order.contains(id)).collect();
+        order.extend(unfocused);
+        order
+    }
+
     /// Invalidate cached positions (call when layout changes)
     pub fn invalidate_positions(&mut self) {
         self.cached_positions.viewport_width = 0.0;
     }
+
+    /// Move the terminals `is_pinned` holds for to the front of the strip, where they
+    /// are docked, keeping the order on either side and the focused terminal focused.
+    /// Returns how many are pinned.
+    pub fn dock_pinned(&mut self, is_pinned: impl Fn(u64) -> bool) -> usize {
+        let focused = self.panel_order.get(self.focused_index).copied();
+        let (mut order, unpinned): (Vec<u64>, Vec<u64>) = self.panel_order.iter().partition(|&&id| is_pinned(id));
+        let pinned = order.len();
+        order.extend(unpinned);
+        if order != self.panel_order {
+            self.panel_order = order;
+            if let Some(index) = focused.and_then(|id| self.panel_order.iter().position(|&other| other == id)) {
+                self.focused_index = index;
+            }
+            self.invalidate_positions();
+        }
+        pinned
+    }
+}
+
+/// Index of the workspace named `spec`, or else at 1-based position `spec` ("2").
+pub fn find_workspace(workspaces: &[Workspace], spec: &str) -> Option<usize> {
+    workspaces.iter().position(|ws| ws.name == spec).or_else(|| {
+        spec.parse::<usize>()
+            .ok()
+            .filter(|&n| n >= 1 && n <= workspaces.len())
+            .map(|n| n - 1)
+    })
+}
+
+/// Put the workspace at `index` in `group` (`None` ungroups it) and move it so each
+/// group's members stay together: after the group's last member, or out of the middle
+/// of the group it left. Returns its new index.
+pub fn set_group(workspaces: &mut Vec<Workspace>, index: usize, group: Option<String>) -> usize {
+    let mut ws = workspaces.remove(index);
+    ws.group = group;
+    let last_member = ws
+        .group
+        .as_ref()
+        .and_then(|group| workspaces.iter().rposition(|other| other.group.as_ref() == Some(group)));
+    let target = match last_member {
+        Some(last) => last + 1,
+        None => {
+            // Leaving a group from its middle would split it in two
+            let before = index.checked_sub(1).and_then(|i| workspaces.get(i)).and_then(|w| w.group.as_ref());
+            let after = workspaces.get(index).and_then(|w| w.group.as_ref());
+            match before.filter(|before| Some(*before) == after) {
+                Some(split) => {
+                    let split = split.clone();
+                    workspaces.iter().rposition(|w| w.group.as_ref() == Some(&split)).map_or(index, |last| last + 1)
+                }
+                None => index,
+            }
+        }
+    };
+    workspaces.insert(target, ws);
+    target
+}
+
+/// Where a new terminal goes when the workspace at `index` already holds `limit`
+/// terminals: the first of `<base>-2`, `<base>-3`, … with room (created if missing),
+/// where `<base>` drops an overflow suffix the workspace may already have. `None` while
+/// the workspace has room or without a limit (0).
+pub fn overflow_workspace(workspaces: &[Workspace], index: usize, limit: usize) -> Option<String> {
+    let ws = &workspaces[index];
+    if limit == 0 || ws.panel_order.len() < limit {
+        return None;
+    }
+    let base = ws
+        .name
+        .rsplit_once('-')
+        .filter(|(base, n)| n.parse::<u32>().is_ok() && workspaces.iter().any(|ws| ws.name == *base))
+        .map_or(ws.name.as_str(), |(base, _)| base);
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|name| {
+            workspaces
+                .iter()
+                .find(|ws| ws.name == *name)
+                .is_none_or(|ws| ws.panel_order.len() < limit)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(ws: &mut Workspace, viewport_width: f32) {
+        ws.cached_positions = TerminalPositions {
+            positions: ws.panel_order.iter().map(|&id| (id, 0.0, 100.0)).collect(),
+            viewport_width,
+            pinned: 0,
+        };
+    }
+
+    #[test]
+    fn empty_cache_is_stale() {
+        let mut ws = Workspace::new("w");
+        ws.panel_order = vec![1];
+        assert!(!ws.cached_positions.is_valid_for(800.0, 1));
+    }
+
+    #[test]
+    fn cache_valid_until_viewport_changes() {
+        let mut ws = Workspace::new("w");
+        ws.panel_order = vec![1, 2];
+        cached(&mut ws, 800.0);
+        assert!(ws.cached_positions.is_valid_for(800.0, 2));
+        assert!(ws.cached_positions.is_valid_for(800.05, 2));
+        assert!(!ws.cached_positions.is_valid_for(801.0, 2));
+    }
+
+    #[test]
+    fn cache_stale_when_panel_count_changes() {
+        let mut ws = Workspace::new("w");
+        ws.panel_order = vec![1, 2];
+        cached(&mut ws, 800.0);
+        assert!(!ws.cached_positions.is_valid_for(800.0, 3));
+    }
+
+    #[test]
+    fn invalidate_marks_stale_but_keeps_positions() {
+        let mut ws = Workspace::new("w");
+        ws.panel_order = vec![1, 2];
+        cached(&mut ws, 800.0);
+        ws.invalidate_positions();
+        assert!(!ws.cached_positions.is_valid_for(800.0, 2));
+        // The last layout stays readable until it is recomputed
+        assert_eq!(ws.cached_positions.positions.len(), 2);
+    }
+
+    #[test]
+    fn find_workspace_by_name_then_position() {
+        let workspaces = vec![Workspace::new("default"), Workspace::new("3"), Workspace::new("api")];
+        assert_eq!(find_workspace(&workspaces, "api"), Some(2));
+        assert_eq!(find_workspace(&workspaces, "2"), Some(1));
+        // A workspace named like a number wins over the position
+        assert_eq!(find_workspace(&workspaces, "3"), Some(1));
+        assert_eq!(find_workspace(&workspaces, "0"), None);
+        assert_eq!(find_workspace(&workspaces, "4"), None);
+        assert_eq!(find_workspace(&workspaces, "web"), None);
+    }
+
+    #[test]
+    fn overflow_goes_to_first_numbered_workspace_with_room() {
+        let with_terminals = |name: &str, count: u64| {
+            let mut ws = Workspace::new(name);
+            ws.panel_order = (0..count).collect();
+            ws
+        };
+        let workspaces = vec![
+            with_terminals("api", 2),
+            with_terminals("api-2", 2),
+            with_terminals("web", 1),
+            with_terminals("v-1", 2),
+        ];
+        assert_eq!(overflow_workspace(&workspaces, 0, 0), None);
+        assert_eq!(overflow_workspace(&workspaces, 2, 2), None);
+        assert_eq!(overflow_workspace(&workspaces, 0, 2).as_deref(), Some("api-3"));
+        // Full overflow workspaces continue the numbering of their base
+        assert_eq!(overflow_workspace(&workspaces, 1, 2).as_deref(), Some("api-3"));
+        // A numeric suffix without a base workspace is part of the name
+        assert_eq!(overflow_workspace(&workspaces, 3, 2).as_deref(), Some("v-1-2"));
+    }
+
+    #[test]
+    fn focus_history_orders_by_recency() {
+        let mut ws = Workspace::new("w");
+        ws.panel_order = vec![1, 2, 3, 4];
+        ws.record_focus(1);
+        ws.record_focus(3);
+        ws.record_focus(1);
+        assert_eq!(ws.mru_order(), vec![1, 3, 2, 4]);
+
+        // Closed terminals drop out
+        ws.panel_order = vec![1, 2, 4];
+        assert_eq!(ws.mru_order(), vec![1, 2, 4]);
+        ws.record_focus(4);
+        assert_eq!(ws.focus_history, vec![4, 1]);
+    }
+
+    #[test]
+    fn dock_pinned_moves_pinned_terminals_first() {
+        let mut ws = Workspace::new("w");
+        ws.panel_order = vec![1, 2, 3, 4];
+        ws.focused_index = 1;
+        assert_eq!(ws.dock_pinned(|id| id == 3 || id == 4), 2);
+        assert_eq!(ws.panel_order, vec![3, 4, 1, 2]);
+        // Terminal 2 stays focused
+        assert_eq!(ws.focused_index, 3);
+
+        // Already docked: nothing moves
+        assert_eq!(ws.dock_pinned(|id| id == 3), 1);
+        assert_eq!(ws.panel_order, vec![3, 4, 1, 2]);
+        assert_eq!(ws.dock_pinned(|_| false), 0);
+    }
+
+    #[test]
+    fn set_group_keeps_members_together() {
+        let names = |workspaces: &[Workspace]| workspaces.iter().map(|ws| ws.name.clone()).collect::<Vec<_>>();
+        let mut workspaces: Vec<Workspace> = ["a", "b", "c", "d"].into_iter().map(Workspace::new).collect();
+
+        // The first member stays in place
+        assert_eq!(set_group(&mut workspaces, 1, Some("client".into())), 1);
+        // Later members follow the last one
+        assert_eq!(set_group(&mut workspaces, 3, Some("client".into())), 2);
+        assert_eq!(set_group(&mut workspaces, 0, Some("client".into())), 2);
+        assert_eq!(names(&workspaces), ["b", "d", "a", "c"]);
+
+        // Leaving from the middle moves past the rest of the group
+        assert_eq!(set_group(&mut workspaces, 1, None), 2);
+        assert_eq!(names(&workspaces), ["b", "a", "d", "c"]);
+        // Leaving from the edge stays put
+        assert_eq!(set_group(&mut workspaces, 1, None), 1);
+        assert_eq!(workspaces[0].group.as_deref(), Some("client"));
+        assert!(workspaces[1..].iter().all(|ws| ws.group.is_none()));
+    }
 }